@@ -0,0 +1,196 @@
+//! HTML annotations pinned to nodes, for the component's `annotations` prop.
+//!
+//! The graph works out where each annotated node is on screen every few
+//! frames, in a worker too, and reports the placements that changed; the
+//! component moves the annotations' elements to match.
+
+use std::collections::{HashMap, HashSet};
+
+use leptos::prelude::*;
+
+use super::state::ForceGraphState;
+
+/// Frames between placements, so annotations don't restyle every frame.
+const PLACE_INTERVAL: u32 = 2;
+
+/// Distance in pixels from the canvas edge of annotations clamped to it.
+const EDGE_INSET: f64 = 16.0;
+
+/// Screen distance in pixels an annotation must move to be placed again.
+const PLACE_EPSILON: f64 = 0.5;
+
+/// Floating HTML content attached to a node, following it as the layout
+/// moves and the view pans and zooms.
+#[derive(Clone)]
+pub struct Annotation {
+	/// Id of the node the annotation follows.
+	pub node: String,
+	/// Content of the annotation.
+	pub view: ViewFn,
+}
+
+impl Annotation {
+	/// Attach `view` to the node with id `node`.
+	pub fn new(node: impl Into<String>, view: impl Into<ViewFn>) -> Self {
+		Self {
+			node: node.into(),
+			view: view.into(),
+		}
+	}
+}
+
+/// What an annotation does while its node is off screen.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OffscreenAnnotations {
+	/// Stay at the nearest edge of the canvas, with an arrow pointing
+	/// toward the node.
+	#[default]
+	Clamp,
+	/// Hide until the node is back in view.
+	Hide,
+}
+
+/// Where an annotation goes, in canvas pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) struct Placement {
+	pub(super) x: f64,
+	pub(super) y: f64,
+	/// Direction in radians from the annotation to its node, when the node
+	/// is off screen and the annotation clamped to the edge
+	pub(super) pointing: Option<f64>,
+}
+
+/// The annotated nodes and where their annotations were last placed.
+#[derive(Clone, Debug, Default)]
+pub(super) struct Annotated {
+	pub(super) ids: Vec<String>,
+	pub(super) offscreen: OffscreenAnnotations,
+	placed: HashMap<String, Placement>,
+	/// Frames since the last placement
+	age: u32,
+}
+
+impl Annotated {
+	pub(super) fn new(ids: Vec<String>, offscreen: OffscreenAnnotations) -> Self {
+		Self {
+			ids,
+			offscreen,
+			..Self::default()
+		}
+	}
+
+	/// Place each annotation if enough frames have passed since the last
+	/// time, returning the placements when any of them changed. Hidden and
+	/// unknown nodes, and off-screen nodes under
+	/// [`OffscreenAnnotations::Hide`], get no placement.
+	pub(super) fn update(&mut self, state: &ForceGraphState) -> Option<HashMap<String, Placement>> {
+		self.age = self.age.saturating_add(1);
+		if self.age < PLACE_INTERVAL {
+			return None;
+		}
+		self.age = 0;
+
+		let wanted: HashSet<&str> = self.ids.iter().map(String::as_str).collect();
+		let (w, h) = (state.width, state.height);
+		let mut placed = HashMap::new();
+		state.graph.visit_nodes(|node| {
+			let info = &node.data.user_data;
+			if info.is_hidden() || !wanted.contains(info.id.as_str()) {
+				return;
+			}
+			let (sx, sy) = state.graph_to_screen(node.x() as f64, node.y() as f64);
+			let on_screen = (0.0..=w).contains(&sx) && (0.0..=h).contains(&sy);
+			let placement = if on_screen {
+				Placement {
+					x: sx,
+					y: sy,
+					pointing: None,
+				}
+			} else if self.offscreen == OffscreenAnnotations::Clamp {
+				let x = sx.clamp(EDGE_INSET, (w - EDGE_INSET).max(EDGE_INSET));
+				let y = sy.clamp(EDGE_INSET, (h - EDGE_INSET).max(EDGE_INSET));
+				Placement {
+					x,
+					y,
+					pointing: Some((sy - y).atan2(sx - x)),
+				}
+			} else {
+				return;
+			};
+			placed.insert(info.id.clone(), placement);
+		});
+
+		let moved = |a: &Placement, b: &Placement| {
+			(a.x - b.x).hypot(a.y - b.y) > PLACE_EPSILON
+				|| a.pointing.is_some() != b.pointing.is_some()
+		};
+		let changed = placed.len() != self.placed.len()
+			|| placed
+				.iter()
+				.any(|(id, p)| self.placed.get(id).is_none_or(|last| moved(p, last)));
+		if !changed {
+			return None;
+		}
+		self.placed = placed;
+		Some(self.placed.clone())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::components::force_graph::seed::SeedStrategy;
+	use crate::components::force_graph::state::ViewTransform;
+	use crate::components::force_graph::theme::Theme;
+	use crate::components::force_graph::types::GraphData;
+	use crate::components::force_graph::types::fixtures::node;
+
+	fn place(annotated: &mut Annotated, state: &ForceGraphState) -> HashMap<String, Placement> {
+		(0..PLACE_INTERVAL)
+			.find_map(|_| annotated.update(state))
+			.unwrap_or_default()
+	}
+
+	#[test]
+	fn off_screen_nodes_clamp_or_hide() {
+		let data = GraphData {
+			nodes: vec![node("a"), node("b")],
+			links: Vec::new(),
+		};
+		let mut state = ForceGraphState::new(
+			&data,
+			800.0,
+			600.0,
+			&Theme::default(),
+			false,
+			SeedStrategy::Circle,
+			None,
+		);
+		// Center the view on "a"
+		let mut at = (0.0, 0.0);
+		state.graph.visit_nodes(|node| {
+			if node.data.user_data.id == "a" {
+				at = (node.x() as f64, node.y() as f64);
+			}
+		});
+		state.transform = ViewTransform {
+			x: 400.0 - at.0,
+			y: 300.0 - at.1,
+			k: 1.0,
+		};
+		let mut annotated = Annotated::new(vec!["a".into()], OffscreenAnnotations::Clamp);
+		let placed = place(&mut annotated, &state);
+		assert_eq!(placed.len(), 1);
+		assert_eq!(placed["a"].pointing, None);
+		// Nothing moved, so nothing to report
+		assert!(place(&mut annotated, &state).is_empty());
+
+		state.transform.x += 5000.0;
+		let placed = place(&mut annotated, &state);
+		assert_eq!(placed["a"].x, 800.0 - EDGE_INSET);
+		assert!(placed["a"].pointing.is_some());
+
+		let mut hidden = Annotated::new(vec!["a".into()], OffscreenAnnotations::Hide);
+		assert!(place(&mut hidden, &state).is_empty());
+	}
+}
@@ -0,0 +1,84 @@
+//! Background image loading for [`BackgroundImage`](super::theme::BackgroundImage).
+//!
+//! The image loads asynchronously; until it arrives, or if it fails to, the
+//! renderer keeps drawing the plain background color or gradient.
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+use web_sys::HtmlImageElement;
+
+/// Loading progress of a [`Backdrop`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Status {
+	Loading,
+	/// Loaded, but no frame has been drawn with it yet
+	Fresh,
+	Ready,
+	Failed,
+}
+
+/// A background image being loaded, shared by every renderer that draws it.
+pub(super) struct Backdrop {
+	image: HtmlImageElement,
+	status: Rc<Cell<Status>>,
+	_on_load: Closure<dyn FnMut()>,
+	_on_error: Closure<dyn FnMut()>,
+}
+
+impl Backdrop {
+	/// Start loading the image at `url`. `None` where there is no DOM to
+	/// create images with, as in a worker.
+	pub(super) fn load(url: &str) -> Option<Rc<Self>> {
+		let image = HtmlImageElement::new().ok()?;
+		let status = Rc::new(Cell::new(Status::Loading));
+		let on_load = {
+			let status = status.clone();
+			Closure::<dyn FnMut()>::new(move || status.set(Status::Fresh))
+		};
+		let on_error = {
+			let status = status.clone();
+			Closure::<dyn FnMut()>::new(move || status.set(Status::Failed))
+		};
+		image.set_onload(Some(on_load.as_ref().unchecked_ref()));
+		image.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+		// Lets an exported canvas stay readable when the server allows it
+		image.set_cross_origin(Some("anonymous"));
+		image.set_src(url);
+		Some(Rc::new(Self {
+			image,
+			status,
+			_on_load: on_load,
+			_on_error: on_error,
+		}))
+	}
+
+	/// The image with its natural size, once loaded.
+	pub(super) fn image(&self) -> Option<(&HtmlImageElement, f64, f64)> {
+		let (w, h) = (
+			self.image.natural_width() as f64,
+			self.image.natural_height() as f64,
+		);
+		(matches!(self.status.get(), Status::Fresh | Status::Ready) && w > 0.0 && h > 0.0)
+			.then_some((&self.image, w, h))
+	}
+
+	/// Whether the image finished loading since the last call, so the frame
+	/// showing it has to be repainted.
+	pub(super) fn take_loaded(&self) -> bool {
+		let fresh = self.status.get() == Status::Fresh;
+		if fresh {
+			self.status.set(Status::Ready);
+		}
+		fresh
+	}
+}
+
+impl Drop for Backdrop {
+	fn drop(&mut self) {
+		// The handlers die with us; an image still loading must not call them
+		self.image.set_onload(None);
+		self.image.set_onerror(None);
+	}
+}
@@ -0,0 +1,360 @@
+//! Force-directed edge bundling.
+//!
+//! Follows Holten and van Wijk's FDEB: every edge is split into subdivision
+//! points that are pulled toward the matching points of nearby, similarly
+//! oriented edges while springs keep each edge smooth. Each cycle doubles the
+//! subdivisions and shortens the step, so bundles form coarse to fine. The
+//! cost is quadratic in the edge count, so [`EdgeBundles`] only reruns it
+//! every few frames while the layout moves, a slice per frame, drawing the
+//! previous bundles until the new ones are done.
+
+use std::collections::HashMap;
+
+use force_graph::DefaultNodeIdx;
+
+/// A straight edge from source to target, in world units.
+pub type Segment = ((f64, f64), (f64, f64));
+
+/// Refinement cycles; the last one leaves `2^(CYCLES-1)` interior points.
+const CYCLES: usize = 4;
+/// Iterations in the first cycle, reduced by [`ITERATION_DECAY`] each cycle.
+const ITERATIONS: f64 = 40.0;
+const ITERATION_DECAY: f64 = 2.0 / 3.0;
+/// First-cycle step as a fraction of each edge's length; halved each cycle.
+const STEP: f64 = 0.04;
+/// Spring stiffness holding subdivision points in line; in the first cycle a
+/// fully attracted edge bows by about `1 / (4 * STIFFNESS)` of its length.
+const STIFFNESS: f64 = 2.0;
+/// Edges less compatible than this never attract each other.
+const MIN_COMPATIBILITY: f64 = 0.6;
+/// Graphs with more edges than this are left unbundled.
+const MAX_BUNDLED_EDGES: usize = 2000;
+/// Point visits one frame's slice of bundling may make, bounding the time
+/// it adds to a frame.
+const SLICE_WORK: usize = 200_000;
+/// Frames between rebundles while the layout is moving.
+const REBUNDLE_INTERVAL: u32 = 30;
+/// Endpoint travel, in screen pixels, that makes the bundles stale.
+const STALE_DISTANCE: f64 = 2.0;
+
+/// Another edge that attracts an edge, with the strength of the pull.
+struct Partner {
+	edge: usize,
+	/// Runs the opposite way, so its subdivision points pair in reverse
+	reversed: bool,
+	compatibility: f64,
+}
+
+/// A bundling run over some edges, advanced a slice at a time so a large
+/// graph doesn't stall a frame: first each edge's partners, then each
+/// edge's move, with every edge's new path swapped in together at the end
+/// of each iteration.
+struct Bundling {
+	edges: Vec<Segment>,
+	lengths: Vec<f64>,
+	/// Partners of the first edges; complete once there's a row per edge
+	partners: Vec<Vec<Partner>>,
+	/// For each edge, the polyline it should be drawn along, from its
+	/// source to its target. Degenerate edges stay straight.
+	paths: Vec<Vec<(f64, f64)>>,
+	/// Paths moved so far in the current iteration
+	next: Vec<Vec<(f64, f64)>>,
+	/// Cycles started so far
+	cycle: usize,
+	/// Iterations still to run in the current cycle
+	iterations_left: usize,
+	step: f64,
+	iterations: f64,
+}
+
+impl Bundling {
+	fn new(edges: Vec<Segment>) -> Self {
+		Self {
+			lengths: edges.iter().map(|&(a, b)| distance(a, b)).collect(),
+			paths: edges.iter().map(|&(a, b)| vec![a, b]).collect(),
+			partners: Vec::with_capacity(edges.len()),
+			next: Vec::with_capacity(edges.len()),
+			edges,
+			cycle: 0,
+			iterations_left: 0,
+			step: STEP,
+			iterations: ITERATIONS,
+		}
+	}
+
+	/// Work until about `budget` point visits are spent. Returns whether
+	/// the paths are finished.
+	fn step(&mut self, budget: usize) -> bool {
+		let n = self.edges.len();
+		if n < 2 {
+			return true;
+		}
+		let mut work = 0;
+		while work < budget {
+			if self.partners.len() < n {
+				let partners = self.partners_of(self.partners.len());
+				self.partners.push(partners);
+				work += n;
+			} else if self.next.is_empty() && self.iterations_left == 0 {
+				if self.cycle == CYCLES {
+					return true;
+				}
+				if self.cycle > 0 {
+					self.step /= 2.0;
+					self.iterations *= ITERATION_DECAY;
+				}
+				self.cycle += 1;
+				for path in self.paths.iter_mut() {
+					subdivide(path);
+				}
+				self.iterations_left = self.iterations.round() as usize;
+				work += n * self.paths[0].len();
+			} else {
+				let p = self.next.len();
+				let moved = self.moved(p);
+				work += (self.partners[p].len() + 1) * moved.len();
+				self.next.push(moved);
+				if self.next.len() == n {
+					self.paths = std::mem::take(&mut self.next);
+					self.iterations_left -= 1;
+				}
+			}
+		}
+		false
+	}
+
+	/// The edges that attract edge `p`.
+	fn partners_of(&self, p: usize) -> Vec<Partner> {
+		let (edges, lengths) = (&self.edges, &self.lengths);
+		(0..edges.len())
+			.filter(|&q| q != p && lengths[p] > f64::EPSILON && lengths[q] > f64::EPSILON)
+			.filter_map(|q| {
+				let compatibility = compatibility(edges[p], edges[q]);
+				(compatibility >= MIN_COMPATIBILITY).then(|| Partner {
+					edge: q,
+					reversed: dot(edges[p], edges[q]) < 0.0,
+					compatibility,
+				})
+			})
+			.collect()
+	}
+
+	/// Path `p` after one iteration's pull from its partners and springs.
+	fn moved(&self, p: usize) -> Vec<(f64, f64)> {
+		let (path, partners, length) = (&self.paths[p], &self.partners[p], self.lengths[p]);
+		if partners.is_empty() {
+			return path.clone();
+		}
+		// Softens as the edge subdivides, letting later cycles tighten
+		// bundles, while keeping each step stable
+		let spring = STIFFNESS * (path.len() - 1) as f64 / length;
+		let total: f64 = partners.iter().map(|q| q.compatibility).sum();
+		let mut moved = path.clone();
+		for i in 1..path.len() - 1 {
+			let (x, y) = path[i];
+			let (prev, next) = (path[i - 1], path[i + 1]);
+			let (mut ex, mut ey) = (0.0, 0.0);
+			for partner in partners {
+				let j = if partner.reversed {
+					path.len() - 1 - i
+				} else {
+					i
+				};
+				let (qx, qy) = self.paths[partner.edge][j];
+				let d = (qx - x).hypot(qy - y);
+				if d > f64::EPSILON {
+					ex += partner.compatibility * (qx - x) / d;
+					ey += partner.compatibility * (qy - y) / d;
+				}
+			}
+			// Many weak partners pull no harder than one strong one
+			let norm = total.max(1.0);
+			let fx = spring * (prev.0 + next.0 - 2.0 * x) + ex / norm;
+			let fy = spring * (prev.1 + next.1 - 2.0 * y) + ey / norm;
+			moved[i] = (x + self.step * length * fx, y + self.step * length * fy);
+		}
+		moved
+	}
+}
+
+/// Insert a point midway between each pair of neighbors.
+fn subdivide(path: &mut Vec<(f64, f64)>) {
+	let mut out = Vec::with_capacity(path.len() * 2 - 1);
+	for pair in path.windows(2) {
+		out.push(pair[0]);
+		out.push(((pair[0].0 + pair[1].0) / 2.0, (pair[0].1 + pair[1].1) / 2.0));
+	}
+	out.extend(path.last());
+	*path = out;
+}
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+	(b.0 - a.0).hypot(b.1 - a.1)
+}
+
+fn dot((p0, p1): Segment, (q0, q1): Segment) -> f64 {
+	(p1.0 - p0.0) * (q1.0 - q0.0) + (p1.1 - p0.1) * (q1.1 - q0.1)
+}
+
+fn midpoint((a, b): Segment) -> (f64, f64) {
+	((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0)
+}
+
+/// How strongly two edges should bundle, in `[0, 1]`: the product of how
+/// parallel, similarly long, close and mutually visible they are.
+fn compatibility(p: Segment, q: Segment) -> f64 {
+	let (lp, lq) = (distance(p.0, p.1), distance(q.0, q.1));
+	let angle = (dot(p, q) / (lp * lq)).abs();
+	let mean = (lp + lq) / 2.0;
+	let scale = 2.0 / (mean / lp.min(lq) + lp.max(lq) / mean);
+	let position = mean / (mean + distance(midpoint(p), midpoint(q)));
+	angle * scale * position * visibility(p, q).min(visibility(q, p))
+}
+
+/// How much of `p`, projected onto the line through `q`, overlaps `q`, in
+/// `[0, 1]`.
+fn visibility(p: Segment, q: Segment) -> f64 {
+	let (q0, q1) = q;
+	let (dx, dy) = (q1.0 - q0.0, q1.1 - q0.1);
+	let len_sq = dx * dx + dy * dy;
+	let project = |(x, y): (f64, f64)| {
+		let t = ((x - q0.0) * dx + (y - q0.1) * dy) / len_sq;
+		(q0.0 + t * dx, q0.1 + t * dy)
+	};
+	let (i0, i1) = (project(p.0), project(p.1));
+	let mid_i = ((i0.0 + i1.0) / 2.0, (i0.1 + i1.1) / 2.0);
+	let span = distance(i0, i1);
+	if span < f64::EPSILON {
+		return 0.0;
+	}
+	(1.0 - 2.0 * distance(midpoint(q), mid_i) / span).max(0.0)
+}
+
+/// Bundled edge paths kept across frames, recomputed only periodically.
+#[derive(Default)]
+pub struct EdgeBundles {
+	paths: HashMap<(DefaultNodeIdx, DefaultNodeIdx), Vec<(f64, f64)>>,
+	/// Edges bundled last time, counting parallel duplicates
+	edge_count: usize,
+	/// Frames drawn since the last rebundle
+	age: u32,
+	/// Rebundle in progress, with the edge each of its paths belongs to
+	pending: Option<(Bundling, Vec<(DefaultNodeIdx, DefaultNodeIdx)>)>,
+}
+
+impl EdgeBundles {
+	/// Rebundle `edges` if the layout has moved them and either enough frames
+	/// have passed or the layout has come to rest. `k` is the zoom, for
+	/// judging movement in screen pixels.
+	pub fn update(
+		&mut self,
+		edges: &[(DefaultNodeIdx, DefaultNodeIdx, Segment)],
+		k: f64,
+		resting: bool,
+	) {
+		self.age = self.age.saturating_add(1);
+		if edges.len() > MAX_BUNDLED_EDGES {
+			self.paths.clear();
+			self.edge_count = 0;
+			self.pending = None;
+			return;
+		}
+		if self.pending.is_some() {
+			self.advance();
+			return;
+		}
+		let stale = edges.len() != self.edge_count
+			|| edges.iter().any(|(a, b, (from, to))| {
+				self.paths.get(&(*a, *b)).is_none_or(|path| {
+					let moved = |p: (f64, f64), q: Option<&(f64, f64)>| {
+						q.is_none_or(|&q| distance(p, q) * k > STALE_DISTANCE)
+					};
+					moved(*from, path.first()) || moved(*to, path.last())
+				})
+			});
+		if !stale || (self.age < REBUNDLE_INTERVAL && !resting && !self.paths.is_empty()) {
+			return;
+		}
+		let segments: Vec<Segment> = edges.iter().map(|&(_, _, segment)| segment).collect();
+		let keys = edges.iter().map(|&(a, b, _)| (a, b)).collect();
+		self.pending = Some((Bundling::new(segments), keys));
+		self.advance();
+	}
+
+	/// Whether a rebundle is still in progress, so frames must keep coming.
+	pub fn is_pending(&self) -> bool {
+		self.pending.is_some()
+	}
+
+	/// Run the next slice of the pending rebundle, swapping its paths in
+	/// once it's done.
+	fn advance(&mut self) {
+		let Some((bundling, _)) = self.pending.as_mut() else {
+			return;
+		};
+		if !bundling.step(SLICE_WORK) {
+			return;
+		}
+		if let Some((bundling, keys)) = self.pending.take() {
+			self.edge_count = keys.len();
+			self.paths = keys.into_iter().zip(bundling.paths).collect();
+			self.age = 0;
+		}
+	}
+
+	/// Path of the edge from `source` to `target`, ending at the given
+	/// current endpoint positions so it stays attached between rebundles.
+	/// `None` when the edge isn't bundled.
+	pub fn path(
+		&self,
+		source: DefaultNodeIdx,
+		target: DefaultNodeIdx,
+		from: (f64, f64),
+		to: (f64, f64),
+	) -> Option<Vec<(f64, f64)>> {
+		let path = self.paths.get(&(source, target))?;
+		if path.len() < 3 {
+			return None;
+		}
+		let mut path = path.clone();
+		let last = path.len() - 1;
+		path[0] = from;
+		path[last] = to;
+		Some(path)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const PARALLEL: [Segment; 2] = [((0.0, 0.0), (100.0, 0.0)), ((0.0, 10.0), (100.0, 10.0))];
+
+	fn bundle_edges(edges: &[Segment]) -> Vec<Vec<(f64, f64)>> {
+		let mut bundling = Bundling::new(edges.to_vec());
+		assert!(bundling.step(usize::MAX));
+		bundling.paths
+	}
+
+	#[test]
+	fn parallel_edges_bow_toward_each_other() {
+		let paths = bundle_edges(&PARALLEL);
+		let mid = |path: &[(f64, f64)]| path[path.len() / 2];
+		assert!(mid(&paths[0]).1 > 1.0);
+		assert!(mid(&paths[1]).1 < 9.0);
+		// Ends stay put
+		assert_eq!(paths[0][0], (0.0, 0.0));
+		assert_eq!(*paths[1].last().unwrap(), (100.0, 10.0));
+	}
+
+	#[test]
+	fn slices_add_up_to_one_run() {
+		let mut sliced = Bundling::new(PARALLEL.to_vec());
+		let mut slices = 1;
+		while !sliced.step(1) {
+			slices += 1;
+		}
+		assert!(slices > 1);
+		assert_eq!(sliced.paths, bundle_edges(&PARALLEL));
+	}
+}
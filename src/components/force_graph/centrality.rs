@@ -0,0 +1,262 @@
+//! Node metrics for heatmap coloring with [`ColorBy`].
+//!
+//! Degree and betweenness treat links as undirected and unweighted.
+//! Betweenness is computed a slice at a time, a few breadth-first searches
+//! per frame, so a large graph draws its first frame while it's still
+//! being scored.
+
+use std::collections::VecDeque;
+
+use super::types::NodeEvent;
+
+/// Largest graph betweenness is computed for; bigger graphs fall back to
+/// degree.
+pub const BETWEENNESS_MAX_NODES: usize = 5000;
+
+/// Edge visits one slice of betweenness may make, bounding the time it adds
+/// to a frame.
+const SLICE_WORK: usize = 200_000;
+
+/// Metric that colors nodes, in place of their `group` and `color`.
+#[derive(Clone, Copy, Debug)]
+pub enum ColorBy {
+	/// Number of distinct neighbors.
+	Degree,
+	/// How many shortest paths between other nodes pass through the node.
+	/// Graphs with more than [`BETWEENNESS_MAX_NODES`] nodes use degree
+	/// instead.
+	Betweenness,
+	/// A value the application computes from each node. Not available in
+	/// worker mode, where nodes keep their own colors.
+	Custom(fn(&NodeEvent) -> f64),
+}
+
+/// The metrics of the earlier centrality overlay, kept for its `centrality`
+/// prop; each colors nodes as the [`ColorBy`] of the same name.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Centrality {
+	/// Number of distinct neighbors.
+	#[default]
+	Degree,
+	/// How many shortest paths between other nodes pass through the node.
+	/// Graphs with more than [`BETWEENNESS_MAX_NODES`] nodes use degree
+	/// instead.
+	Betweenness,
+}
+
+impl Centrality {
+	/// Score each of `node_count` nodes connected by `edges`, from 0 to 1.
+	///
+	/// Edges are pairs of node indices; repeated pairs count once, and
+	/// self-loops and out-of-range indices are ignored.
+	pub fn compute(self, node_count: usize, edges: &[(usize, usize)]) -> Vec<f64> {
+		let neighbors = neighbors(node_count, edges);
+		let scores = match self {
+			Self::Betweenness if node_count <= BETWEENNESS_MAX_NODES => {
+				let mut betweenness = Betweenness::new(neighbors);
+				betweenness.step_within(usize::MAX);
+				betweenness.scores()
+			}
+			_ => degree(&neighbors),
+		};
+		let max = scores.iter().copied().fold(0.0, f64::max);
+		if max > 0.0 {
+			scores.into_iter().map(|s| s / max).collect()
+		} else {
+			scores
+		}
+	}
+}
+
+impl From<Centrality> for ColorBy {
+	fn from(centrality: Centrality) -> Self {
+		match centrality {
+			Centrality::Degree => Self::Degree,
+			Centrality::Betweenness => Self::Betweenness,
+		}
+	}
+}
+
+impl ColorBy {
+	/// Title of the gradient legend.
+	pub(super) fn name(self) -> &'static str {
+		match self {
+			Self::Degree => "Degree",
+			Self::Betweenness => "Betweenness",
+			Self::Custom(_) => "Value",
+		}
+	}
+}
+
+/// Distinct neighbors of each of `node_count` nodes connected by `edges`,
+/// ignoring self-loops and out-of-range indices.
+pub(super) fn neighbors(node_count: usize, edges: &[(usize, usize)]) -> Vec<Vec<usize>> {
+	let mut neighbors: Vec<Vec<usize>> = vec![Vec::new(); node_count];
+	for &(a, b) in edges {
+		if a != b && a < node_count && b < node_count {
+			neighbors[a].push(b);
+			neighbors[b].push(a);
+		}
+	}
+	for list in &mut neighbors {
+		list.sort_unstable();
+		list.dedup();
+	}
+	neighbors
+}
+
+/// Degree of each node, from [`neighbors`].
+pub(super) fn degree(neighbors: &[Vec<usize>]) -> Vec<f64> {
+	neighbors.iter().map(|n| n.len() as f64).collect()
+}
+
+/// Betweenness by Brandes' algorithm, being computed a slice at a time: a
+/// breadth-first search from each node, then dependencies summed back from
+/// the farthest nodes.
+#[derive(Clone, Debug)]
+pub(super) struct Betweenness {
+	neighbors: Vec<Vec<usize>>,
+	scores: Vec<f64>,
+	/// Next node to search from
+	source: usize,
+	order: Vec<usize>,
+	queue: VecDeque<usize>,
+	/// Shortest path count and distance from the source, and the source's
+	/// dependency on each node
+	paths: Vec<f64>,
+	dist: Vec<usize>,
+	dependency: Vec<f64>,
+}
+
+impl Betweenness {
+	pub(super) fn new(neighbors: Vec<Vec<usize>>) -> Self {
+		let n = neighbors.len();
+		Self {
+			neighbors,
+			scores: vec![0.0; n],
+			source: 0,
+			order: Vec::with_capacity(n),
+			queue: VecDeque::new(),
+			paths: vec![0.0; n],
+			dist: vec![usize::MAX; n],
+			dependency: vec![0.0; n],
+		}
+	}
+
+	/// Search from further sources until the slice's work is spent.
+	/// Returns whether every source is done.
+	pub(super) fn step(&mut self) -> bool {
+		self.step_within(SLICE_WORK)
+	}
+
+	/// [`step`](Self::step) with a budget of `budget` edge visits.
+	fn step_within(&mut self, budget: usize) -> bool {
+		let mut work = 0;
+		while self.source < self.neighbors.len() && work < budget {
+			work += self.search(self.source);
+			self.source += 1;
+		}
+		self.source >= self.neighbors.len()
+	}
+
+	/// Share of sources searched so far, from 0 to 1.
+	pub(super) fn progress(&self) -> f64 {
+		self.source as f64 / self.neighbors.len().max(1) as f64
+	}
+
+	/// The finished scores; each path was counted from both ends, so halve
+	/// them.
+	pub(super) fn scores(self) -> Vec<f64> {
+		self.scores.into_iter().map(|s| s / 2.0).collect()
+	}
+
+	/// Add the dependencies of `source`, returning the edge visits taken.
+	fn search(&mut self, source: usize) -> usize {
+		let Self {
+			neighbors,
+			scores,
+			order,
+			queue,
+			paths,
+			dist,
+			dependency,
+			..
+		} = self;
+		order.clear();
+		paths.fill(0.0);
+		dist.fill(usize::MAX);
+		dependency.fill(0.0);
+		paths[source] = 1.0;
+		dist[source] = 0;
+		queue.push_back(source);
+		let mut work = 0;
+		while let Some(v) = queue.pop_front() {
+			order.push(v);
+			for &w in &neighbors[v] {
+				if dist[w] == usize::MAX {
+					dist[w] = dist[v] + 1;
+					queue.push_back(w);
+				}
+				if dist[w] == dist[v] + 1 {
+					paths[w] += paths[v];
+				}
+			}
+			work += neighbors[v].len();
+		}
+		for &w in order.iter().rev() {
+			for &v in &neighbors[w] {
+				if dist[v] != usize::MAX && dist[v] + 1 == dist[w] {
+					dependency[v] += paths[v] / paths[w] * (1.0 + dependency[w]);
+				}
+			}
+			if w != source {
+				scores[w] += dependency[w];
+			}
+		}
+		// The fills cost a pass over every node too
+		work * 2 + neighbors.len()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn the_middle_of_a_path_is_most_between() {
+		let path = [(0, 1), (1, 2)];
+		assert_eq!(
+			Centrality::Betweenness.compute(3, &path),
+			vec![0.0, 1.0, 0.0]
+		);
+		assert_eq!(Centrality::Degree.compute(3, &path), vec![0.5, 1.0, 0.5]);
+	}
+
+	#[test]
+	fn slices_add_up_to_one_pass() {
+		// Two triangles bridged by a path, with a repeated edge and a loop
+		let edges = [
+			(0, 1),
+			(1, 2),
+			(2, 0),
+			(2, 3),
+			(3, 4),
+			(4, 5),
+			(5, 6),
+			(6, 4),
+			(3, 3),
+			(1, 0),
+		];
+		let mut whole = Betweenness::new(neighbors(7, &edges));
+		assert!(whole.step_within(usize::MAX));
+
+		let mut sliced = Betweenness::new(neighbors(7, &edges));
+		let mut slices = 1;
+		while !sliced.step_within(1) {
+			slices += 1;
+			assert!(sliced.progress() < 1.0);
+		}
+		assert_eq!(slices, 7);
+		assert_eq!(sliced.scores(), whole.scores());
+	}
+}
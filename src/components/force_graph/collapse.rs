@@ -0,0 +1,179 @@
+//! Collapsing node groups into single meta-nodes, for
+//! [`GraphController::collapse_group`](super::GraphController::collapse_group).
+//!
+//! Collapsing rewrites the input data rather than the simulation: the full
+//! data is kept, so expanding a group is just building the graph again
+//! without it collapsed.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+
+use super::types::{GraphData, GraphLink, GraphNode};
+
+/// Prefix of meta-node ids; the control character keeps them from clashing
+/// with ids in the data.
+const META_PREFIX: &str = "\u{1}group:";
+
+/// Id of the meta-node standing in for collapsed `group`.
+pub(super) fn meta_id(group: u32) -> String {
+	format!("{META_PREFIX}{group}")
+}
+
+/// The group a meta-node stands in for, if `id` is one.
+pub(super) fn meta_group(id: &str) -> Option<u32> {
+	id.strip_prefix(META_PREFIX)?.parse().ok()
+}
+
+/// Size multiplier of a meta-node with `members` nodes: larger than any
+/// ordinary node, growing with the square root of its membership.
+pub(super) fn meta_size(members: usize) -> f64 {
+	(2.0 + 0.25 * (members as f64).sqrt()).min(6.0)
+}
+
+/// Data with collapsed groups replaced by meta-nodes.
+pub(super) struct Collapsed {
+	pub(super) data: GraphData,
+	/// Member count of each meta-node's group
+	pub(super) members: HashMap<u32, usize>,
+}
+
+/// Replace the nodes of each group in `groups` with one meta-node labeled
+/// with the group's name from `names` and its member count.
+///
+/// Links inside a group are dropped. Links leaving it are rewired to the
+/// meta-node, and links that end up joining the same two nodes merge into
+/// one whose weight is their sum, counting unweighted links as 1. Groups
+/// without nodes are skipped.
+pub(super) fn collapse_groups(
+	data: &GraphData,
+	groups: &HashSet<u32>,
+	names: &[(u32, String)],
+) -> Collapsed {
+	let mut members: HashMap<u32, usize> = HashMap::new();
+	let mut owner: HashMap<&str, u32> = HashMap::new();
+	let mut nodes = Vec::new();
+	let mut first_member: HashMap<u32, usize> = HashMap::new();
+	for node in &data.nodes {
+		match node.group.filter(|g| groups.contains(g)) {
+			Some(group) => {
+				*members.entry(group).or_insert(0) += 1;
+				owner.insert(&node.id, group);
+				// The meta-node takes the place of the group's first member
+				first_member.entry(group).or_insert_with(|| {
+					nodes.push(meta_node(group, node));
+					nodes.len() - 1
+				});
+			}
+			None => nodes.push(node.clone()),
+		}
+	}
+	for (&group, &i) in &first_member {
+		let name = names
+			.iter()
+			.find(|(g, _)| *g == group)
+			.map_or_else(|| format!("Group {group}"), |(_, name)| name.clone());
+		nodes[i].label = Some(format!("{name} ({})", members[&group]));
+	}
+
+	let endpoint = |id: &String| {
+		owner
+			.get(id.as_str())
+			.map_or_else(|| id.clone(), |&g| meta_id(g))
+	};
+	let mut links: Vec<GraphLink> = Vec::new();
+	let mut merged: HashMap<(String, String), usize> = HashMap::new();
+	for link in &data.links {
+		let (source, target) = (endpoint(&link.source), endpoint(&link.target));
+		if source == link.source && target == link.target {
+			links.push(link.clone());
+			continue;
+		}
+		if source == target {
+			continue;
+		}
+		// An undirected link joins the same pair whichever way round it runs
+		let key = if link.directed || source < target {
+			(source, target)
+		} else {
+			(target, source)
+		};
+		match merged.entry(key) {
+			Entry::Occupied(entry) => {
+				let existing = &mut links[*entry.get()];
+				existing.weight = Some(existing.weight.unwrap_or(1.0) + link.weight.unwrap_or(1.0));
+				existing.time = match (existing.time, link.time) {
+					(Some(a), Some(b)) => Some(a.min(b)),
+					(a, b) => a.or(b),
+				};
+			}
+			Entry::Vacant(entry) => {
+				let (source, target) = entry.key().clone();
+				entry.insert(links.len());
+				links.push(GraphLink {
+					source,
+					target,
+					..link.clone()
+				});
+			}
+		}
+	}
+
+	Collapsed {
+		data: GraphData { nodes, links },
+		members,
+	}
+}
+
+/// The meta-node for `group`, seeded from its first member `first`.
+fn meta_node(group: u32, first: &GraphNode) -> GraphNode {
+	GraphNode {
+		id: meta_id(group),
+		group: Some(group),
+		position: first.position,
+		..Default::default()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::components::force_graph::types::fixtures::{link, node};
+
+	fn grouped(id: &str, group: u32) -> GraphNode {
+		GraphNode {
+			group: Some(group),
+			..node(id)
+		}
+	}
+
+	fn undirected(source: &str, target: &str, weight: Option<f64>) -> GraphLink {
+		GraphLink {
+			weight,
+			directed: false,
+			..link(source, target)
+		}
+	}
+
+	#[test]
+	fn collapsing_merges_links_into_the_meta_node() {
+		let data = GraphData {
+			nodes: vec![grouped("a", 1), grouped("b", 1), grouped("c", 2)],
+			links: vec![
+				undirected("a", "b", None),
+				undirected("a", "c", Some(2.0)),
+				undirected("c", "b", None),
+			],
+		};
+		let collapsed = collapse_groups(&data, &HashSet::from([1]), &[(1, "Ones".into())]);
+		assert_eq!(collapsed.members, HashMap::from([(1, 2)]));
+
+		let nodes = &collapsed.data.nodes;
+		assert_eq!(nodes.len(), 2);
+		assert_eq!(meta_group(&nodes[0].id), Some(1));
+		assert_eq!(nodes[0].label.as_deref(), Some("Ones (2)"));
+
+		let links = &collapsed.data.links;
+		assert_eq!(links.len(), 1);
+		assert_eq!(links[0].weight, Some(3.0));
+	}
+}
@@ -0,0 +1,115 @@
+//! Community detection over the link structure.
+//!
+//! Uses asynchronous label propagation: every node starts in its own
+//! community and repeatedly adopts the label most common among its
+//! neighbors until no label changes. Runs in roughly linear time per pass,
+//! so it stays fast on graphs with tens of thousands of edges.
+
+use super::rng::Xorshift;
+
+/// Upper bound on propagation passes; labels usually stabilize in under 10.
+const MAX_PASSES: usize = 50;
+
+/// Detect communities among `node_count` nodes connected by `edges`.
+///
+/// Edges are undirected pairs of node indices; self-loops and out-of-range
+/// indices are ignored. Returns one community id per node, numbered densely
+/// from 0 in order of each community's first node. Isolated nodes and
+/// disconnected components always end up in distinct communities.
+///
+/// Visiting order and tie-breaks come from a fixed-seed generator, so the
+/// result is deterministic for a given input.
+pub fn label_propagation(node_count: usize, edges: &[(usize, usize)]) -> Vec<u32> {
+	let mut neighbors: Vec<Vec<usize>> = vec![Vec::new(); node_count];
+	for &(a, b) in edges {
+		if a != b && a < node_count && b < node_count {
+			neighbors[a].push(b);
+			neighbors[b].push(a);
+		}
+	}
+
+	let mut labels: Vec<usize> = (0..node_count).collect();
+	let mut order: Vec<usize> = (0..node_count).collect();
+	// Scratch tally indexed by label, reset after each node
+	let mut counts = vec![0usize; node_count];
+	let mut rng = Xorshift(0x9e37_79b9_7f4a_7c15);
+	for _ in 0..MAX_PASSES {
+		// A fresh visiting order each pass stops one label from flooding
+		// across bridges before communities have formed
+		for i in (1..order.len()).rev() {
+			order.swap(i, rng.below(i + 1));
+		}
+		let salt = rng.next();
+
+		let mut changed = false;
+		for &node in &order {
+			if neighbors[node].is_empty() {
+				continue;
+			}
+			for &n in &neighbors[node] {
+				counts[labels[n]] += 1;
+			}
+			// Prefer keeping the current label on a tie so labels don't
+			// flip-flop, otherwise break ties pseudo-randomly
+			let current = labels[node];
+			let rank = |label: usize| (counts[label], label == current, mix(label as u64 ^ salt));
+			let mut chosen = current;
+			for &n in &neighbors[node] {
+				if rank(labels[n]) > rank(chosen) {
+					chosen = labels[n];
+				}
+			}
+			for &n in &neighbors[node] {
+				counts[labels[n]] = 0;
+			}
+
+			if chosen != current {
+				labels[node] = chosen;
+				changed = true;
+			}
+		}
+		if !changed {
+			break;
+		}
+	}
+
+	// Renumber densely in order of first appearance
+	let mut remap = vec![u32::MAX; node_count];
+	let mut next = 0;
+	labels
+		.into_iter()
+		.map(|label| {
+			if remap[label] == u32::MAX {
+				remap[label] = next;
+				next += 1;
+			}
+			remap[label]
+		})
+		.collect()
+}
+
+/// Scramble a label so tie-breaks don't favor low indices.
+fn mix(mut x: u64) -> u64 {
+	x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+	x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+	x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn bridged_cliques_split_into_two_communities() {
+		// Two triangles joined by the 2–3 bridge, and isolated node 6
+		let edges = [(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3), (2, 3)];
+		let labels = label_propagation(7, &edges);
+		assert_eq!(labels[0], labels[1]);
+		assert_eq!(labels[1], labels[2]);
+		assert_eq!(labels[3], labels[4]);
+		assert_eq!(labels[4], labels[5]);
+		assert_ne!(labels[0], labels[3]);
+		assert_eq!(labels, label_propagation(7, &edges));
+		assert_eq!(labels[6], 2);
+	}
+}
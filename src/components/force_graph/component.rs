@@ -3,27 +3,617 @@
 //! The component creates an HTML canvas element and wires up mouse/wheel event
 //! handlers for node dragging, panning, and zooming. An animation loop runs via
 //! `requestAnimationFrame`, calling the physics simulation and renderer each frame.
+//! The loop stops while the page is hidden, or while the browser has taken
+//! the canvas's rendering context away, and resumes where it left off.
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 
 use leptos::prelude::*;
 use wasm_bindgen::prelude::*;
-use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, MouseEvent, WheelEvent, Window};
+use web_sys::{
+	CanvasRenderingContext2d, HtmlCanvasElement, KeyboardEvent, MouseEvent, WheelEvent, Window,
+};
 
+use super::annotations::{Annotated, Annotation, OffscreenAnnotations, Placement};
+use super::centrality::{Centrality, ColorBy};
+use super::collapse::{collapse_groups, meta_group, meta_size};
+use super::controller::GraphController;
+use super::input::{Input, InputOutcome};
+use super::minimap::Minimap;
 use super::particles::ParticleSystem;
-use super::render;
+use super::render::{self, FrameStats, Legend, RenderCache, TextWidths};
+use super::sampling::{
+	MAX_RENDER_NODES, NodeSampling, SampleStrategy, SamplingInfo, UnsampledNodes,
+};
 use super::scale::ScaleConfig;
-use super::state::ForceGraphState;
-use super::theme::Theme;
-use super::types::GraphData;
+use super::seed::SeedStrategy;
+use super::state::{Bounds, DegreeSizing, Fisheye, ForceGraphState, ViewTransform};
+use super::theme::{Colormap, Theme};
+use super::timeline::apply_window;
+use super::types::{
+	ButtonBinding, Corner, Filter, GraphData, MouseBindings, NodeEvent, PointerAction, ViewportInfo,
+};
+use super::webgl::{GlRenderer, RendererKind};
+use super::worker::{self, Command, Reply, WorkerHost};
+
+/// Shared slot for a JS callback closure that must outlive the effect that creates it.
+pub(super) type ClosureSlot<F = dyn FnMut()> = Rc<RefCell<Option<Closure<F>>>>;
+
+/// Draws beneath the graph each frame, given the 2D context, the camera and
+/// the canvas size in CSS pixels. See [`ForceGraphCanvas`]'s
+/// `background_renderer`.
+pub type BackgroundRenderer = Rc<dyn Fn(&CanvasRenderingContext2d, &ViewTransform, f64, f64)>;
+
+/// Step used for the first frame after (re)starting the loop, in seconds.
+const NOMINAL_DT: f64 = 1.0 / 60.0;
+/// Longest step fed to the simulation, in seconds, so a stalled or
+/// throttled frame doesn't make the layout explode.
+const MAX_DT: f64 = 0.05;
+/// Most simulation steps taken to settle the layout before a static render.
+const STATIC_SETTLE_TICKS: usize = 600;
 
 /// Bundles graph simulation state with visual configuration (scaling, theme, particles).
-struct GraphContext {
-	state: ForceGraphState,
-	scale: ScaleConfig,
-	theme: Theme,
-	particles: Option<ParticleSystem>,
+pub(super) struct GraphContext {
+	pub(super) state: ForceGraphState,
+	pub(super) scale: ScaleConfig,
+	pub(super) theme: Theme,
+	pub(super) particles: Option<ParticleSystem>,
+	pub(super) legend: Option<Legend>,
+	pub(super) minimap: Option<Minimap>,
+	pub(super) stats: Option<FrameStats>,
+	pub(super) cache: RenderCache,
+	/// App drawing under the graph; never set in a worker
+	pub(super) background: Option<BackgroundRenderer>,
+	/// Held still for a static render; see [`GraphContext::freeze`]
+	pub(super) frozen: bool,
+	/// Nodes with HTML annotations, placed as they move
+	pub(super) annotated: Annotated,
+}
+
+/// Graph context shared between the component, its handlers and a controller.
+pub(super) type SharedContext = Rc<RefCell<Option<GraphContext>>>;
+
+/// Plain configuration a [`GraphContext`] is built from, taken from the
+/// component's props.
+#[derive(Clone, Debug)]
+pub(super) struct GraphOptions {
+	pub(super) theme: Theme,
+	pub(super) bounded: bool,
+	pub(super) bound_margin: f64,
+	pub(super) color_by_community: bool,
+	pub(super) seed_strategy: SeedStrategy,
+	pub(super) layout_seed: Option<u64>,
+	pub(super) snap_grid: Option<f64>,
+	pub(super) draggable: bool,
+	pub(super) mouse_bindings: MouseBindings,
+	pub(super) rest_speed: f64,
+	pub(super) settle_threshold: f64,
+	pub(super) settle_delay: f64,
+	pub(super) align_guides: bool,
+	pub(super) undo_limit: usize,
+	/// Ids of nodes with annotations
+	pub(super) annotated: Vec<String>,
+	pub(super) offscreen_annotations: OffscreenAnnotations,
+	pub(super) highlighted_group: Option<u32>,
+	pub(super) groups: Vec<(u32, String)>,
+	pub(super) legend: bool,
+	pub(super) legend_corner: Corner,
+	pub(super) minimap: bool,
+	pub(super) minimap_corner: Corner,
+	pub(super) minimap_size: f64,
+	pub(super) show_stats: bool,
+	pub(super) edge_bundling: bool,
+	pub(super) group_labels: bool,
+	/// Starting camera; ignored unless its zoom is positive.
+	pub(super) transform: Option<ViewTransform>,
+	pub(super) filter: Option<Filter>,
+	pub(super) current_time: Option<f64>,
+	/// Groups drawn as a single meta-node each
+	pub(super) collapsed: HashSet<u32>,
+	/// Time range outside which links leave the graph
+	pub(super) time_window: Option<(f64, f64)>,
+	pub(super) size_by_degree: Option<DegreeSizing>,
+	pub(super) color_by: Option<ColorBy>,
+	/// Heatmap ramp; the theme's colormap if unset
+	pub(super) color_ramp: Option<Colormap>,
+	pub(super) heat_legend_corner: Corner,
+	pub(super) fisheye: Option<Fisheye>,
+	pub(super) fisheye_on: bool,
+	pub(super) scale: ScaleConfig,
+	/// Physics held still from a controller
+	pub(super) paused: bool,
+	/// Limit on nodes drawn, and how to pick and draw them beyond it
+	pub(super) sampling: Option<NodeSampling>,
+}
+
+/// What a graph is drawn on.
+pub(super) enum Surface {
+	/// A Canvas 2D context.
+	Canvas2d(CanvasRenderingContext2d),
+	/// A WebGL2 renderer with its text overlay.
+	WebGl2(Box<GlRenderer>),
+}
+
+/// What a frame changed, reported once the graph is no longer borrowed.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(super) struct FrameOutcome {
+	/// The layout settled this frame.
+	pub(super) settled: bool,
+	/// The simulation woke from settled this frame.
+	pub(super) woke: bool,
+	/// The camera came to rest this frame.
+	pub(super) viewport: Option<ViewportInfo>,
+	/// Where each annotation goes, when any of them moved.
+	pub(super) annotations: Option<HashMap<String, Placement>>,
+	/// The frame was repainted, so nodes or the view may have moved.
+	pub(super) repainted: bool,
+	/// How much of the graph is drawn, when sampling started, changed or
+	/// stopped.
+	pub(super) sampling: Option<Option<SamplingInfo>>,
+}
+
+impl GraphContext {
+	/// Lay out `data` on a `width`×`height` canvas.
+	pub(super) fn new(data: &GraphData, width: f64, height: f64, opts: &GraphOptions) -> Self {
+		let windowed = opts.time_window.map(|window| apply_window(data, window));
+		let data = windowed.as_ref().map_or(data, |w| &w.data);
+		let collapsed = (!opts.collapsed.is_empty())
+			.then(|| collapse_groups(data, &opts.collapsed, &opts.groups));
+		let data = collapsed.as_ref().map_or(data, |c| &c.data);
+		let theme = opts.theme.clone();
+		let particles = theme
+			.particles
+			.enabled
+			.then(|| ParticleSystem::new(&theme.particles, width, height));
+
+		let mut state = ForceGraphState::new(
+			data,
+			width,
+			height,
+			&theme,
+			opts.color_by_community,
+			opts.seed_strategy,
+			opts.layout_seed,
+		);
+		state.group_names = opts.groups.iter().cloned().collect();
+		if let Some(sizing) = opts.size_by_degree {
+			state.size_by_degree(sizing);
+		}
+		if let Some(ref collapsed) = collapsed {
+			state.graph.visit_nodes_mut(|node| {
+				let members = meta_group(&node.data.user_data.id)
+					.and_then(|group| collapsed.members.get(&group));
+				if let Some(&members) = members {
+					node.data.user_data.size = meta_size(members);
+					node.data.user_data.target_size = meta_size(members);
+				}
+			});
+		}
+		state.bounds = Bounds {
+			bounded: opts.bounded,
+			margin: opts.bound_margin,
+			..Bounds::default()
+		};
+		state.snap_grid = opts.snap_grid;
+		state.draggable = opts.draggable;
+		state.mouse = opts.mouse_bindings;
+		state.stability.rest_speed = opts.rest_speed;
+		state.stability.threshold = opts.settle_threshold;
+		state.stability.settle_delay = opts.settle_delay;
+		state.align_guides = opts.align_guides;
+		state.history.limit = opts.undo_limit;
+		state.highlight_group(opts.highlighted_group);
+		state.set_filter(opts.filter.clone());
+		state.set_time(opts.current_time);
+		state.set_sampling(opts.sampling);
+		if let Some(ref windowed) = windowed {
+			state.graph.visit_nodes_mut(|node| {
+				let info = &mut node.data.user_data;
+				info.ghost = windowed.ghosts.contains(&info.id);
+			});
+		}
+		state.finish_fades();
+		if let Some(ref ramp) = opts.color_ramp {
+			state.heat_ramp = ramp.clone();
+		}
+		state.heat_legend = opts.heat_legend_corner;
+		state.fisheye = opts.fisheye;
+		state.fisheye_on = opts.fisheye_on;
+		state.paused = opts.paused;
+		if opts.color_by.is_some() {
+			state.set_color_by(opts.color_by);
+		}
+		if let Some(ref initial) = opts.transform
+			&& initial.k > 0.0
+		{
+			state.transform = initial.clone();
+		}
+
+		let mut cache = RenderCache::default();
+		cache.set_bundling(opts.edge_bundling);
+		cache.set_group_labels(opts.group_labels);
+		cache.load_background_image(theme.background.image.as_ref());
+
+		Self {
+			state,
+			scale: opts.scale.clone(),
+			theme,
+			particles,
+			legend: opts.legend.then(|| Legend {
+				entries: opts.groups.clone(),
+				corner: opts.legend_corner,
+				widths: TextWidths::default(),
+			}),
+			minimap: opts
+				.minimap
+				.then(|| {
+					Minimap::new(
+						opts.minimap_corner,
+						opts.minimap_size,
+						opts.minimap_size * 0.75,
+					)
+				})
+				.flatten(),
+			stats: opts.show_stats.then(FrameStats::default),
+			cache,
+			background: None,
+			frozen: false,
+			annotated: Annotated::new(opts.annotated.clone(), opts.offscreen_annotations),
+		}
+	}
+
+	/// Settle the layout and hold the graph still for a static render: the
+	/// simulation stops, nodes are anchored, ambient particles and pulsing
+	/// are dropped and input is ignored. Nodes that all have positions under
+	/// [`SeedStrategy::FromData`] stay where the data put them.
+	pub(super) fn freeze(&mut self, data: &GraphData, seeding: SeedStrategy) {
+		let placed = seeding == SeedStrategy::FromData
+			&& data.nodes.iter().all(|node| node.position.is_some());
+		if !placed {
+			for _ in 0..STATIC_SETTLE_TICKS {
+				if self.state.stability.is_settled() {
+					break;
+				}
+				self.state.tick(NOMINAL_DT as f32);
+			}
+		}
+		self.state.animation_running = false;
+		self.state.graph.visit_nodes_mut(|node| {
+			let info = &mut node.data.user_data;
+			node.data.is_anchor = true;
+			info.appear_t = 1.0;
+			info.size = info.target_size;
+		});
+		self.state.finish_fades();
+		self.state.finish_heatmap();
+		self.state.departing.clear();
+		self.state.departing_edges.clear();
+		self.state.flow_time = 0.0;
+		self.state.mark_dirty();
+		self.particles = None;
+		self.frozen = true;
+	}
+
+	/// Pick up drawing on a rendering context restored after a loss. The
+	/// simulation and camera carry on where they were.
+	pub(super) fn restore_context(&mut self) {
+		self.cache.forget_context();
+		self.state.mark_dirty();
+	}
+
+	/// Resize the simulation area and particle field.
+	pub(super) fn resize(&mut self, width: f64, height: f64) {
+		self.state.resize(width, height);
+		if let Some(ref mut ps) = self.particles {
+			ps.resize(width, height);
+		}
+	}
+
+	/// Advance the simulation by `dt` seconds and repaint `surface` if
+	/// anything visible changed.
+	pub(super) fn frame(&mut self, surface: &mut Surface, dt: f64) -> FrameOutcome {
+		let frame_start = now_ms();
+		if let Some(ref mut stats) = self.stats {
+			stats.record_frame(frame_start);
+		}
+		let dt = if self.frozen { 0.0 } else { dt };
+		let ticked = self.state.tick(dt as f32);
+		if self.cache.take_background_loaded() {
+			self.state.mark_dirty();
+		}
+		let mut outcome = FrameOutcome {
+			settled: self.state.take_settled_event(),
+			woke: self.state.take_wake_event(),
+			viewport: self.state.take_viewport_event(),
+			annotations: self.annotated.update(&self.state),
+			repainted: false,
+			sampling: self.state.take_sampling_event(),
+		};
+		if let Some(ref mut ps) = self.particles {
+			ps.update(dt);
+		}
+		if let Some(ref mut stats) = self.stats {
+			stats.tick_ms = now_ms() - frame_start;
+		}
+		let animated = render::is_time_animated(
+			&self.state,
+			&self.scale,
+			&self.theme,
+			self.particles.as_ref(),
+		);
+		// Skip repainting a static frame; live stats and pending bundles
+		// always repaint
+		if self.state.take_redraw()
+			|| ticked || animated
+			|| self.stats.is_some()
+			|| self.cache.is_bundling()
+		{
+			outcome.repainted = true;
+			let true_positions = self.state.apply_lens();
+			let ctx = match surface {
+				Surface::Canvas2d(ctx) => {
+					render::render(
+						&self.state,
+						ctx,
+						&self.scale,
+						&self.theme,
+						self.particles.as_ref(),
+						self.legend.as_ref(),
+						self.stats.as_ref(),
+						&mut self.cache,
+						self.background.as_deref(),
+					);
+					&*ctx
+				}
+				Surface::WebGl2(gl) => {
+					gl.render(
+						&self.state,
+						&self.scale,
+						&self.theme,
+						self.legend.as_ref(),
+						self.stats.as_ref(),
+					);
+					gl.overlay()
+				}
+			};
+			self.state.restore_positions(true_positions);
+			if let Some(ref mut m) = self.minimap {
+				m.draw(&self.state, ctx, &self.theme);
+			}
+		}
+		if let Some(ref mut stats) = self.stats {
+			stats.work_ms = now_ms() - frame_start;
+			stats.render_ms = stats.work_ms - stats.tick_ms;
+		}
+		outcome
+	}
+}
+
+/// Spinner shown while `is_loading`, animated without a stylesheet.
+const SPINNER_SVG: &str = r#"<svg width="32" height="32" viewBox="0 0 32 32" aria-hidden="true"><circle cx="16" cy="16" r="12" fill="none" stroke="currentColor" stroke-width="3" stroke-linecap="round" stroke-dasharray="56 20"><animateTransform attributeName="transform" type="rotate" from="0 16 16" to="360 16 16" dur="1s" repeatCount="indefinite"/></circle></svg>"#;
+
+/// Message centered over the canvas while `is_loading`, or while the graph
+/// has no nodes: `empty_view`, or "No data" without one.
+fn status_overlay(
+	empty: Memo<bool>,
+	is_loading: Option<Signal<bool>>,
+	empty_view: Option<ViewFn>,
+	text_color: String,
+	canvas_ref: NodeRef<leptos::html::Canvas>,
+) -> impl IntoView {
+	let loading = move || is_loading.is_some_and(|l| l.get());
+	let style = move || {
+		if !loading() && !empty.get() {
+			return "display: none;".to_string();
+		}
+		let (x, y) = canvas_ref.get_untracked().map_or((0.0, 0.0), |c| {
+			(
+				f64::from(c.offset_left()) + f64::from(c.client_width()) / 2.0,
+				f64::from(c.offset_top()) + f64::from(c.client_height()) / 2.0,
+			)
+		});
+		format!(
+			"position: absolute; left: {x}px; top: {y}px; transform: translate(-50%, -50%); display: flex; flex-direction: column; align-items: center; gap: 8px; color: {text_color}; pointer-events: none;"
+		)
+	};
+	let content = move || {
+		if loading() {
+			view! {
+				<span class="force-graph-spinner" inner_html=SPINNER_SVG></span>
+				<span>"Loading…"</span>
+			}
+			.into_any()
+		} else if empty.get() {
+			match empty_view {
+				Some(ref view) => view.run(),
+				None => view! { <span>"No data"</span> }.into_any(),
+			}
+		} else {
+			().into_any()
+		}
+	};
+	view! {
+		<div class="force-graph-status" role="status" style=style>
+			{content}
+		</div>
+	}
+}
+
+/// Note at the top of the canvas while only a sample of the nodes is drawn.
+fn sampling_notice(
+	sampled: RwSignal<Option<SamplingInfo>>,
+	text_color: String,
+	canvas_ref: NodeRef<leptos::html::Canvas>,
+) -> impl IntoView {
+	let style = move || {
+		if sampled.get().is_none() {
+			return "display: none;".to_string();
+		}
+		let (x, y) = canvas_ref.get_untracked().map_or((0.0, 0.0), |c| {
+			(
+				f64::from(c.offset_left()) + f64::from(c.client_width()) / 2.0,
+				f64::from(c.offset_top()) + 8.0,
+			)
+		});
+		format!(
+			"position: absolute; left: {x}px; top: {y}px; transform: translateX(-50%); color: {text_color}; font-size: 12px; opacity: 0.8; pointer-events: none;"
+		)
+	};
+	let text = move || {
+		sampled
+			.get()
+			.map(|s| format!("Showing {} of {} nodes", s.drawn, s.total))
+	};
+	view! {
+		<div class="force-graph-sampling" role="status" style=style>
+			{text}
+		</div>
+	}
+}
+
+/// Ids of the nodes `annotations` are attached to.
+fn annotated_ids(annotations: &[Annotation]) -> Vec<String> {
+	annotations.iter().map(|a| a.node.clone()).collect()
+}
+
+/// Overlay holding the annotations, each positioned above its node at its
+/// latest placement. It sits at the canvas's offset within their shared
+/// positioned parent, and lets the pointer through except over the
+/// annotations themselves.
+fn annotation_layer(
+	annotations: Signal<Vec<Annotation>>,
+	placements: RwSignal<HashMap<String, Placement>>,
+	canvas_ref: NodeRef<leptos::html::Canvas>,
+) -> impl IntoView {
+	let entries = move || {
+		annotations
+			.get()
+			.into_iter()
+			.enumerate()
+			.collect::<Vec<_>>()
+	};
+	view! {
+		<div
+			class="force-graph-annotations"
+			style="position: absolute; left: 0; top: 0; width: 0; height: 0; overflow: visible; pointer-events: none;"
+		>
+			<For
+				each=entries
+				key=|(i, annotation)| (*i, annotation.node.clone())
+				children=move |(_, annotation)| {
+					let id = annotation.node.clone();
+					let placement = Memo::new(move |_| placements.with(|p| p.get(&id).copied()));
+					let style = move || {
+						let Some(p) = placement.get() else {
+							return "display: none;".to_string();
+						};
+						let (left, top) = canvas_ref
+							.get_untracked()
+							.map_or((0.0, 0.0), |c| (f64::from(c.offset_left()), f64::from(c.offset_top())));
+						format!(
+							"position: absolute; left: {}px; top: {}px; transform: translate(-50%, calc(-100% - 10px)); pointer-events: auto; white-space: nowrap;",
+							left + p.x,
+							top + p.y,
+						)
+					};
+					// Off-screen nodes get an arrow pointing their way
+					let arrow = move || {
+						placement.get().and_then(|p| p.pointing).map(|angle| {
+							view! {
+								<span
+									class="force-graph-annotation-arrow"
+									style=format!("display: inline-block; margin-right: 4px; transform: rotate({angle}rad);")
+								>
+									"➤"
+								</span>
+							}
+						})
+					};
+					view! {
+						<div class="force-graph-annotation" style=style>
+							{arrow}
+							{annotation.view.run()}
+						</div>
+					}
+				}
+			/>
+		</div>
+	}
+}
+
+/// Apply a CSS cursor to the canvas element.
+fn set_cursor(canvas: &HtmlCanvasElement, cursor: &str) {
+	// Go through `HtmlElement` explicitly; leptos' `ElementExt::style` shadows it
+	let element: &web_sys::HtmlElement = canvas;
+	let _ = element.style().set_property("cursor", cursor);
+}
+
+/// Request an animation frame running `animate`, unless one is pending,
+/// keeping its id in `pending`.
+fn schedule_frame(animate: &ClosureSlot<dyn FnMut(f64)>, pending: &Cell<Option<i32>>) {
+	if pending.get().is_some() {
+		return;
+	}
+	if let Some(ref cb) = *animate.borrow() {
+		pending.set(request_frame(cb));
+	}
+}
+
+/// Request an animation frame running `cb`, returning its id.
+fn request_frame(cb: &Closure<dyn FnMut(f64)>) -> Option<i32> {
+	web_sys::window()
+		.unwrap()
+		.request_animation_frame(cb.as_ref().unchecked_ref())
+		.ok()
+}
+
+/// Whether the canvas can be drawn on; the browser may take its rendering
+/// context away under GPU pressure and later give it back.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ContextHealth {
+	Ok,
+	Lost,
+	/// Given back, but the surface hasn't been rebuilt on it yet
+	Restored,
+}
+
+/// Get the rendering context `renderer` asks for on `canvas`, falling back
+/// from WebGL2 to Canvas 2D. `None` if neither is available, as while the
+/// context is lost.
+fn acquire_surface(canvas: &HtmlCanvasElement, renderer: RendererKind) -> Option<Surface> {
+	let gl = match renderer {
+		RendererKind::WebGl2 => GlRenderer::new(canvas),
+		RendererKind::Canvas2d => None,
+	};
+	Some(match gl {
+		Some(gl) => Surface::WebGl2(Box::new(gl)),
+		None => Surface::Canvas2d(canvas.get_context("2d").ok()??.dyn_into().ok()?),
+	})
+}
+
+/// Whether the page is in a background tab or otherwise not visible.
+fn is_hidden() -> bool {
+	web_sys::window()
+		.and_then(|w| w.document())
+		.is_some_and(|d| d.hidden())
+}
+
+/// Simulation step in seconds for an animation frame at `timestamp`, given
+/// the previous frame's timestamp (both in ms).
+pub(super) fn frame_dt(previous: Option<f64>, timestamp: f64) -> f64 {
+	previous.map_or(NOMINAL_DT, |last| {
+		((timestamp - last) / 1000.0).clamp(0.0, MAX_DT)
+	})
+}
+
+/// High-resolution timestamp in milliseconds, for frame timing.
+fn now_ms() -> f64 {
+	web_sys::window()
+		.and_then(|w| w.performance())
+		.map(|p| p.now())
+		.unwrap_or_else(js_sys::Date::now)
 }
 
 /// Renders an interactive force-directed graph on a canvas element.
@@ -32,25 +622,434 @@ struct GraphContext {
 /// to its parent container by default; set `fullscreen = true` to fill the
 /// viewport and resize automatically with the window. Explicit `width`/`height`
 /// override automatic sizing.
+///
+/// When `data` changes, nodes with the same IDs keep their positions and the
+/// camera stays put; new nodes grow in beside their existing neighbors
+/// and removed ones shrink away.
+///
+/// Pass a `theme` to customize colors and effects, including the
+/// [`NodePalette`](super::theme::NodePalette) that group indices cycle through.
+///
+/// Pass a `scale_config` to tune how node radii, edge widths, arrows,
+/// labels and detail levels respond to zoom, e.g.
+/// [`ScaleConfig::screen_sized`] to keep nodes a constant size on screen.
+/// See [`ScaleConfig`](super::scale::ScaleConfig) for each setting; a
+/// signal's changes apply from the next frame.
+///
+/// Set `bounded = true` to keep free nodes inside the canvas; `bound_margin`
+/// is the width in pixels of the soft spring band along each wall.
+///
+/// Set `legend = true` to draw a key in `legend_corner` listing each
+/// `(group, name)` pair from `groups` next to its palette color. Pass the
+/// same `group_names` as a [`GraphLegend`](crate::GraphLegend) to name the
+/// groups `groups` doesn't.
+///
+/// Set `group_labels = true` to draw each group's name large and faint at
+/// the center of its nodes, sized to how far they spread. The labels fade
+/// out as you zoom in far enough for node labels to show, and ignore the
+/// pointer. Unnamed groups read `Group <n>`.
+///
+/// The layout settles once its nodes' mean squared speed has stayed below
+/// `settle_threshold` (in px²/s²) for `settle_delay` seconds; the
+/// simulation then stops stepping until a drag, a data change or
+/// [`GraphController::reheat`] wakes it, easing back up to speed.
+/// `on_stable` fires once when the layout first settles, and again after
+/// each wake; [`GraphController::is_settled`] follows it too. Free nodes
+/// moving slower than `rest_speed` pixels per second are held still, so a
+/// settling layout stops instead of creeping; set it to `0.0` to let the
+/// simulation run unchecked until it settles.
+///
+/// Set `static_render = true` for thumbnails and print: the layout is
+/// settled up front (or taken from the data's positions under
+/// [`SeedStrategy::FromData`]) and drawn once, without particles, pulsing,
+/// pan, zoom, drag or hover, and without a running animation loop. It's
+/// redrawn only when a fullscreen canvas is resized, and never runs in a
+/// worker. [`GraphController`] exports still work.
+///
+/// Set `draggable = false` to stop nodes from being dragged, for read-only
+/// embeds: pressing a node pans the view instead, while hover, clicks and
+/// zoom work as usual.
+///
+/// Pass `mouse_bindings` to choose what dragging with each mouse button
+/// does, such as middle-drag panning while left-drag on the background
+/// selects nodes with a rubber band; see [`MouseBindings`].
+///
+/// Scrolling pans the view, in both directions on a trackpad, and a pinch
+/// (or scrolling with Ctrl or Cmd held) zooms around the pointer, as web
+/// maps do. Set `wheel_zoom = true` to zoom on plain scrolling instead, for
+/// mouse wheels.
+///
+/// Pass a `fisheye` signal to magnify the area under the pointer, spreading
+/// out dense regions while the rest of the graph stays put; `fisheye_lens`
+/// sets its radius and strength. The F key toggles the lens too. It only
+/// moves where nodes are drawn, and clicks and hover hit what's drawn. The
+/// lens lets go during drags and pans.
+///
+/// Each drag can be undone with Ctrl+Z (Cmd+Z on a Mac) while the canvas
+/// has focus and redone with Ctrl+Shift+Z or Ctrl+Y, or through
+/// [`GraphController::undo`] and [`GraphController::redo`]: the nodes go
+/// back where they were and lose the pin the drag gave them. Only drags are
+/// recorded, never the simulation's own movement, and at most `undo_limit`
+/// of them are kept.
+///
+/// Pass `annotations` to pin HTML content (an [`Annotation`]) to nodes: each
+/// follows its node as the layout settles and the view pans and zooms.
+/// While a node is off screen its annotation waits at the nearest canvas
+/// edge with an arrow pointing toward it, or hides, per
+/// `offscreen_annotations`. Annotations are placed every other frame, so
+/// they may trail fast motion slightly. To lay out your own overlay
+/// instead, read [`GraphController::node_screen_positions`], which updates
+/// every frame the graph repaints.
+///
+/// Set `align_guides = true` to show guide lines and snap a dragged node
+/// when it comes within a few pixels of another node's x or y coordinate.
+///
+/// Shift-click nodes to select them; dragging any selected node moves the
+/// whole selection together. Clicking empty canvas clears the selection.
+///
+/// The canvas is keyboard-focusable: arrow keys move a focus ring to the
+/// neighboring node in that direction and center the view on it, Enter
+/// fires `on_node_click` for it, and Escape clears it. The focused node is
+/// announced to screen readers through a live region.
+///
+/// `on_node_click` fires when a node is pressed and released without being
+/// dragged; `on_node_hover` fires with the newly hovered node, or `None` when
+/// the pointer leaves all nodes.
+///
+/// Set `minimap = true` to show an overview of the whole graph in
+/// `minimap_corner`, `minimap_size` pixels wide; click or drag inside it to
+/// move the main view.
+///
+/// Pass a [`GraphController`] to drive the graph imperatively from outside,
+/// e.g. to export images.
+///
+/// Pass a `transform` signal to control the camera from outside: the view
+/// follows the signal, and panning or zooming writes back to it. Use it to
+/// sync several graphs or restore a saved view. If the signal's zoom `k` is
+/// zero on mount (as with `ViewTransform::default()`), it is first set to the
+/// component's initial centered view.
+///
+/// `on_viewport_change` fires once the camera has stayed still for a moment
+/// after a pan, zoom or resize (and once on mount), with the transform and
+/// the visible world-space rectangle.
+///
+/// Set `show_stats = true` to overlay FPS, frame time split into simulation
+/// and rendering, graph size, zoom and simulation state for performance
+/// tuning. Timing is only measured while the overlay is shown.
+///
+/// Set `snap_grid` to a spacing in world units to snap dragged nodes to a
+/// grid; they stay put where dropped.
+///
+/// Pass a `highlighted_group` signal to highlight every node of that group
+/// as if hovered; [`GraphLegend`](crate::GraphLegend)
+/// toggles it when an entry is clicked.
+///
+/// Pass a `filter` signal to hide nodes by id or group. Hidden nodes and
+/// their links fade to the filter's residual alpha and ignore the pointer,
+/// but keep their place in the layout unless the filter removes them from
+/// the simulation, in which case they fade out and the rest close up.
+///
+/// Set `size_by_degree = true` to size nodes by their number of links
+/// instead of by label and links, through the curve and size range of
+/// `degree_sizing`. Hubs also repel their surroundings harder, so they anchor
+/// the layout. When a data update changes a node's links, it eases to its
+/// new size.
+///
+/// Groups collapsed with [`GraphController::collapse_group`] draw as one
+/// meta-node each; double-click a meta-node to expand its group again.
+///
+/// For a temporal graph, give links (and optionally nodes) a `time` and
+/// pass a `current_time` signal: only what's timed at or before it shows,
+/// fading in as playback reaches it and out when scrubbed back. Nodes
+/// without a time appear with their earliest link. Hidden nodes keep their
+/// place in the layout and ignore the pointer.
+///
+/// Pass a `time_window` signal to show a temporal graph between two times:
+/// links timed outside it leave the layout, fading out, and nodes left
+/// without any of their links, or timed outside it themselves, fade to
+/// ghosts. Moving the window fades links in and out, so scrubbing it reads
+/// as the graph evolving; [`TimeSlider`](crate::TimeSlider) drives it from
+/// the data's time range.
+///
+/// Pass a `color_by` signal to color nodes by a metric instead of their
+/// `group` or `color`, such as their degree or betweenness; see [`ColorBy`].
+/// Values map onto `color_ramp` (the theme's colormap if unset; a two- or
+/// three-stop ramp reads best) and a gradient legend in
+/// `heat_legend_corner` shows the range. The metric is computed when the
+/// signal changes and when the data does, not every frame, and each
+/// node's value is reported in its [`NodeEvent`]. Betweenness on a large
+/// graph is computed over several frames, with the legend showing its
+/// progress, so it doesn't hold up the first frame. A `centrality` signal
+/// does the same with a [`Centrality`], unless `color_by` is also given.
+///
+/// Set `edge_bundling = true` to draw edges along force-directed bundles,
+/// gathering nearby parallel edges into shared curves to untangle dense
+/// graphs. Bundles are recomputed every few frames while the layout moves
+/// and once it rests, spread over as many frames as a large graph needs;
+/// graphs with more than 2000 edges stay unbundled.
+///
+/// `seed_strategy` picks where nodes start before the simulation runs; see
+/// [`SeedStrategy`]. Placement is reproducible: the same data, canvas size
+/// and `layout_seed` (a fixed default if unset) give the same layout.
+///
+/// Set `color_by_community = true` to detect communities from the links and
+/// color nodes by community instead of `group`; the detected id is reported
+/// in each [`NodeEvent`].
+///
+/// Set `renderer` to [`RendererKind::WebGl2`] to draw with WebGL2, which
+/// keeps large graphs at 60fps. It draws nodes, straight edges, arrows,
+/// labels and hover highlighting, but skips the 2D renderer's glows,
+/// gradients, shadows, curves, dashes and decorations. It falls back to
+/// Canvas 2D when WebGL2 is unavailable, and isn't used in worker mode.
+///
+/// Set `background_renderer` to draw beneath the graph, such as a map or
+/// floor plan the nodes are placed over. It runs each frame after the theme
+/// background and before particles and edges, in screen space with the
+/// context saved; apply the [`ViewTransform`] it's given
+/// (`translate(x, y)` then `scale(k, k)`) to draw in world coordinates. It
+/// isn't called with the WebGL2 renderer or in worker mode.
+///
+/// While `data` has no nodes, "No data" (or `empty_view`, if given) is
+/// shown centered over the canvas, and a spinner while an `is_loading`
+/// signal is true, e.g. while the data is fetched. Both go as soon as
+/// there are nodes and loading is done.
+///
+/// A graph of more than `max_render_nodes` nodes ([`MAX_RENDER_NODES`] by
+/// default; `None` for no limit) draws only a sample of them, picked by
+/// `sample_strategy`, and lays out only those, so an oversized input slows
+/// down instead of locking the tab. The rest hold still where they were
+/// placed and are drawn as a faint point cloud, or not at all, per
+/// `unsampled_nodes`, and can't be hovered or clicked. A note over the canvas says how many nodes are
+/// shown; pass `on_sampling` to be told instead, with `None` once the
+/// whole graph is drawn again.
+///
+/// Set `worker` to run the simulation and rendering off the main thread, in
+/// the module worker script at `worker_url`, which must call
+/// [`run_graph_worker`](super::run_graph_worker) (see there for building
+/// it). Once the script has loaded the canvas is handed to it with
+/// `transferControlToOffscreen`; input, data updates and resizes are posted
+/// to it, and callbacks fire from its replies. Without a `worker_url`, in
+/// browsers without `OffscreenCanvas` or workers, or when the script fails
+/// to load, the graph renders on the main thread as usual. So does a graph
+/// whose theme isn't an unmodified built-in preset, or that starts with
+/// [`ColorBy::Custom`] or a `background_renderer`, none of which can be
+/// sent to a worker. In worker mode the minimap and [`GraphController`] are
+/// unavailable.
 #[component]
 pub fn ForceGraphCanvas(
 	#[prop(into)] data: Signal<GraphData>,
 	#[prop(default = false)] fullscreen: bool,
 	#[prop(default = None)] width: Option<f64>,
 	#[prop(default = None)] height: Option<f64>,
+	#[prop(optional)] theme: Option<Theme>,
+	#[prop(optional, into)] scale_config: Option<Signal<ScaleConfig>>,
+	#[prop(default = false)] bounded: bool,
+	#[prop(default = 40.0)] bound_margin: f64,
+	#[prop(optional)] on_stable: Option<Callback<()>>,
+	#[prop(default = false)] legend: bool,
+	#[prop(optional)] groups: Vec<(u32, String)>,
+	#[prop(optional)] group_names: HashMap<u32, String>,
+	#[prop(default = false)] group_labels: bool,
+	#[prop(default = Corner::TopRight)] legend_corner: Corner,
+	#[prop(optional)] on_node_click: Option<Callback<NodeEvent>>,
+	#[prop(optional)] on_node_hover: Option<Callback<Option<NodeEvent>>>,
+	#[prop(default = false)] color_by_community: bool,
+	#[prop(default = false)] minimap: bool,
+	#[prop(default = Corner::BottomRight)] minimap_corner: Corner,
+	#[prop(default = 160.0)] minimap_size: f64,
+	#[prop(optional)] controller: Option<GraphController>,
+	#[prop(optional)] transform: Option<RwSignal<ViewTransform>>,
+	#[prop(optional)] on_viewport_change: Option<Callback<ViewportInfo>>,
+	#[prop(default = false)] show_stats: bool,
+	#[prop(optional)] snap_grid: Option<f64>,
+	#[prop(optional)] highlighted_group: Option<RwSignal<Option<u32>>>,
+	#[prop(optional, into)] filter: Option<Signal<Option<Filter>>>,
+	#[prop(optional, into)] current_time: Option<Signal<f64>>,
+	#[prop(optional, into)] time_window: Option<Signal<Option<(f64, f64)>>>,
+	#[prop(default = false)] size_by_degree: bool,
+	#[prop(optional)] degree_sizing: DegreeSizing,
+	#[prop(optional, into)] color_by: Option<Signal<Option<ColorBy>>>,
+	#[prop(optional, into)] centrality: Option<Signal<Option<Centrality>>>,
+	#[prop(optional)] color_ramp: Option<Colormap>,
+	#[prop(default = Corner::BottomLeft)] heat_legend_corner: Corner,
+	#[prop(default = false)] wheel_zoom: bool,
+	#[prop(optional, into)] fisheye: Option<Signal<bool>>,
+	#[prop(optional)] fisheye_lens: Fisheye,
+	#[prop(default = false)] align_guides: bool,
+	#[prop(default = 100)] undo_limit: usize,
+	#[prop(optional, into)] annotations: Option<Signal<Vec<Annotation>>>,
+	#[prop(optional)] offscreen_annotations: OffscreenAnnotations,
+	#[prop(default = true)] draggable: bool,
+	#[prop(optional)] mouse_bindings: MouseBindings,
+	#[prop(default = 5.0)] rest_speed: f64,
+	#[prop(default = 50.0)] settle_threshold: f64,
+	#[prop(default = 1.0)] settle_delay: f64,
+	#[prop(optional)] seed_strategy: SeedStrategy,
+	#[prop(optional)] layout_seed: Option<u64>,
+	#[prop(default = false)] worker: bool,
+	#[prop(optional, into)] worker_url: Option<String>,
+	#[prop(optional)] renderer: RendererKind,
+	#[prop(default = false)] edge_bundling: bool,
+	#[prop(optional)] background_renderer: Option<BackgroundRenderer>,
+	#[prop(default = false)] static_render: bool,
+	#[prop(optional, into)] empty_view: Option<ViewFn>,
+	#[prop(optional, into)] is_loading: Option<Signal<bool>>,
+	#[prop(into, default = Some(MAX_RENDER_NODES))] max_render_nodes: Option<usize>,
+	#[prop(optional)] sample_strategy: SampleStrategy,
+	#[prop(optional)] unsampled_nodes: UnsampledNodes,
+	#[prop(optional)] on_sampling: Option<Callback<Option<SamplingInfo>>>,
 ) -> impl IntoView {
+	// Names shared with a legend fill in the groups `groups` leaves unnamed
+	let mut groups = groups;
+	let mut unlisted: Vec<_> = group_names
+		.into_iter()
+		.filter(|(group, _)| groups.iter().all(|(g, _)| g != group))
+		.collect();
+	unlisted.sort_by_key(|(group, _)| *group);
+	groups.extend(unlisted);
 	let canvas_ref = NodeRef::<leptos::html::Canvas>::new();
-	let context: Rc<RefCell<Option<GraphContext>>> = Rc::new(RefCell::new(None));
-	let animate: Rc<RefCell<Option<Closure<dyn FnMut()>>>> = Rc::new(RefCell::new(None));
-	let resize_cb: Rc<RefCell<Option<Closure<dyn FnMut()>>>> = Rc::new(RefCell::new(None));
-	let (context_init, animate_init, resize_cb_init) =
-		(context.clone(), animate.clone(), resize_cb.clone());
+	// The loading and empty messages take the label color
+	let text_color = theme
+		.as_ref()
+		.map_or_else(Theme::default, Theme::clone)
+		.node
+		.label_color
+		.to_css_rgb();
+	let context: SharedContext = controller
+		.map(|c| c.context())
+		.unwrap_or_else(|| Rc::new(RefCell::new(None)));
+	// Groups collapsed into meta-nodes, shared with the controller
+	let collapsed = controller
+		.map(|c| c.collapsed())
+		.unwrap_or_else(|| RwSignal::new(HashSet::new()));
+	// Whether the controller paused the simulation
+	let paused = controller.map(|c| c.paused_signal());
+	let color_by = color_by.or_else(|| {
+		centrality.map(|metric| Signal::derive(move || metric.get().map(ColorBy::from)))
+	});
+	// Whether the layout has settled, shown through the controller
+	let settled = controller.map(|c| c.settled_signal());
+	let animate: ClosureSlot<dyn FnMut(f64)> = Rc::new(RefCell::new(None));
+	let resize_cb: ClosureSlot = Rc::new(RefCell::new(None));
+	let visibility_cb: ClosureSlot = Rc::new(RefCell::new(None));
+	let context_lost_cb: ClosureSlot<dyn FnMut(web_sys::Event)> = Rc::new(RefCell::new(None));
+	let context_restored_cb: ClosureSlot = Rc::new(RefCell::new(None));
+	let health = Rc::new(Cell::new(ContextHealth::Ok));
+	// Id of the scheduled animation frame, if any; cleared while the tab is hidden
+	let frame_pending: Rc<Cell<Option<i32>>> = Rc::new(Cell::new(None));
+	// `requestAnimationFrame` timestamp of the previous frame, in ms
+	let last_frame: Rc<Cell<Option<f64>>> = Rc::new(Cell::new(None));
+	// Set while a worker owns the canvas; see `worker`
+	let host: Rc<RefCell<Option<WorkerHost>>> = Rc::new(RefCell::new(None));
+	// Set when the worker failed to start, to render on the main thread instead
+	let worker_failed = RwSignal::new(false);
+	let (context_init, animate_init, resize_cb_init, visibility_cb_init, host_init) = (
+		context.clone(),
+		animate.clone(),
+		resize_cb.clone(),
+		visibility_cb.clone(),
+		host.clone(),
+	);
+	// Screen reader text describing the focused node
+	let announcement = RwSignal::new(String::new());
+	// Report what an input or frame changed, outside the graph borrow so callbacks
+	// may safely touch the graph
+	let report = move |out: InputOutcome| {
+		if let (Some(t), Some(camera)) = (out.moved, transform) {
+			camera.set(t);
+		}
+		if let (Some(cursor), Some(canvas)) = (out.cursor, canvas_ref.get_untracked()) {
+			set_cursor(&canvas, cursor);
+		}
+		if let Some(text) = out.announce {
+			announcement.set(text);
+		}
+		if let (Some(event), Some(cb)) = (out.hovered, on_node_hover) {
+			cb.run(event);
+		}
+		if let (Some(event), Some(cb)) = (out.clicked, on_node_click) {
+			cb.run(event);
+		}
+		if let Some(group) = out.expand {
+			collapsed.update(|groups| {
+				groups.remove(&group);
+			});
+		}
+	};
+	// Where each annotation goes, from the latest frame that moved one
+	let placements = RwSignal::new(HashMap::<String, Placement>::new());
+	// How much of the graph is drawn, while sampling
+	let sampled = RwSignal::new(None::<SamplingInfo>);
+	let report_frame = move |out: FrameOutcome| {
+		if let Some(placed) = out.annotations {
+			placements.set(placed);
+		}
+		if let Some(info) = out.sampling {
+			sampled.set(info);
+			if let Some(cb) = on_sampling {
+				cb.run(info);
+			}
+		}
+		if out.repainted
+			&& let Some(c) = controller
+		{
+			c.notify_repainted();
+		}
+		if let Some(settled) = settled {
+			if out.woke {
+				settled.set(false);
+			}
+			if out.settled {
+				settled.set(true);
+			}
+		}
+		if out.settled
+			&& let Some(cb) = on_stable
+		{
+			cb.run(());
+		}
+		if let Some(info) = out.viewport {
+			// Catch a controlled camera up with moves it didn't make, such
+			// as an animated fit
+			if let Some(camera) = transform
+				&& camera.get_untracked() != info.transform
+			{
+				camera.set(info.transform.clone());
+			}
+			if let Some(cb) = on_viewport_change {
+				cb.run(info);
+			}
+		}
+	};
+
+	// The data without nodes a filter takes out of the simulation
+	let removed = Memo::new(move |_| {
+		filter
+			.and_then(|f| f.get())
+			.filter(|f| f.remove_from_simulation)
+	});
+	let visible_data = move || {
+		let data = data.get();
+		match removed.get() {
+			Some(filter) => filter.apply(&data),
+			None => data,
+		}
+	};
 
 	Effect::new(move |_| {
 		let Some(canvas) = canvas_ref.get() else {
 			return;
 		};
-		let canvas: HtmlCanvasElement = canvas.into();
+		if worker_failed.get() {
+			host_init.borrow_mut().take();
+		}
+		// A transferred canvas belongs to the worker; hand it the new data
+		if let Some(ref h) = *host_init.borrow() {
+			h.send(&Command::Collapse(collapsed.get()));
+			h.send(&Command::TimeWindow(time_window.and_then(|w| w.get())));
+			h.send(&Command::Data(visible_data()));
+			return;
+		}
 		let window: Window = web_sys::window().unwrap();
 
 		let (w, h) = if fullscreen {
@@ -77,42 +1076,118 @@ pub fn ForceGraphCanvas(
 		canvas.set_width(w as u32);
 		canvas.set_height(h as u32);
 
-		let ctx: CanvasRenderingContext2d = canvas
-			.get_context("2d")
-			.unwrap()
-			.unwrap()
-			.dyn_into()
-			.unwrap();
-
-		let theme = Theme::default();
-		let particles = if theme.particles.enabled {
-			Some(ParticleSystem::new(&theme.particles, w, h))
-		} else {
-			None
+		let opts = GraphOptions {
+			theme: theme.clone().unwrap_or_default(),
+			bounded,
+			bound_margin,
+			color_by_community,
+			seed_strategy,
+			layout_seed,
+			snap_grid,
+			draggable,
+			mouse_bindings,
+			rest_speed,
+			settle_threshold,
+			settle_delay,
+			align_guides,
+			undo_limit,
+			annotated: annotations
+				.map(|a| annotated_ids(&a.get_untracked()))
+				.unwrap_or_default(),
+			offscreen_annotations,
+			highlighted_group: highlighted_group.and_then(|g| g.get_untracked()),
+			groups: groups.clone(),
+			legend,
+			legend_corner,
+			minimap,
+			minimap_corner,
+			minimap_size,
+			show_stats,
+			edge_bundling,
+			group_labels,
+			transform: transform.map(|camera| camera.get_untracked()),
+			filter: filter.and_then(|f| f.get_untracked()),
+			current_time: current_time.map(|t| t.get_untracked()),
+			collapsed: collapsed.get(),
+			time_window: time_window.and_then(|w| w.get()),
+			size_by_degree: size_by_degree.then_some(degree_sizing),
+			color_by: color_by.and_then(|c| c.get_untracked()),
+			color_ramp: color_ramp.clone(),
+			heat_legend_corner,
+			fisheye: fisheye.is_some().then_some(fisheye_lens),
+			fisheye_on: fisheye.is_some_and(|on| on.get_untracked()),
+			scale: scale_config
+				.map(|config| config.get_untracked())
+				.unwrap_or_default(),
+			paused: paused.is_some_and(|p| p.get_untracked()),
+			sampling: max_render_nodes.map(|max_nodes| NodeSampling {
+				max_nodes,
+				strategy: sample_strategy,
+				unsampled: unsampled_nodes,
+			}),
 		};
+		let graph_data = visible_data();
 
-		*context_init.borrow_mut() = Some(GraphContext {
-			state: ForceGraphState::new(&data.get(), w, h, &theme),
-			scale: ScaleConfig::default(),
-			theme,
-			particles,
-		});
+		// Options that can't be posted to the worker would be silently lost there
+		let portable = background_renderer.is_none() && worker::can_send(&opts);
+		let worker_url = worker_url
+			.as_deref()
+			.filter(|_| worker && !static_render && !worker_failed.get_untracked());
+		if worker && worker_url.is_none() {
+			log::warn!("graph worker mode needs a worker_url, rendering on the main thread");
+		} else if worker && !portable {
+			log::warn!(
+				"custom theme, color_by or background_renderer can't run in a graph worker, \
+				 rendering on the main thread"
+			);
+		}
+		if let Some(url) = worker_url.filter(|_| portable && worker::supported()) {
+			let on_reply = move |reply| match reply {
+				Reply::Input(out) => report(*out),
+				Reply::Frame(out) => report_frame(out),
+				Reply::Ready => {}
+			};
+			match WorkerHost::spawn(
+				url,
+				&canvas,
+				graph_data.clone(),
+				(w, h),
+				opts.clone(),
+				on_reply,
+				move || worker_failed.set(true),
+			) {
+				Ok(h) => *host_init.borrow_mut() = Some(h),
+				Err(err) => {
+					log::warn!("graph worker unavailable, rendering on the main thread: {err:?}")
+				}
+			}
+		}
 
 		if fullscreen {
-			let (context_resize, canvas_resize) = (context_init.clone(), canvas.clone());
+			let (context_resize, canvas_resize, host_resize) =
+				(context_init.clone(), canvas.clone(), host_init.clone());
+			let (animate_resize, pending_resize) = (animate_init.clone(), frame_pending.clone());
 			*resize_cb_init.borrow_mut() = Some(Closure::new(move || {
 				let win: Window = web_sys::window().unwrap();
 				let (nw, nh) = (
 					win.inner_width().unwrap().as_f64().unwrap(),
 					win.inner_height().unwrap().as_f64().unwrap(),
 				);
+				if let Some(ref h) = *host_resize.borrow() {
+					h.send(&Command::Resize {
+						width: nw,
+						height: nh,
+					});
+					return;
+				}
 				canvas_resize.set_width(nw as u32);
 				canvas_resize.set_height(nh as u32);
 				if let Some(ref mut c) = *context_resize.borrow_mut() {
-					c.state.resize(nw, nh);
-					if let Some(ref mut ps) = c.particles {
-						ps.resize(nw, nh);
-					}
+					c.resize(nw, nh);
+				}
+				// Resizing clears the canvas, and a static render has no loop
+				if static_render {
+					schedule_frame(&animate_resize, &pending_resize);
 				}
 			}));
 			if let Some(ref cb) = *resize_cb_init.borrow() {
@@ -121,148 +1196,361 @@ pub fn ForceGraphCanvas(
 			}
 		}
 
-		let (context_anim, animate_inner) = (context_init.clone(), animate_init.clone());
-		*animate_init.borrow_mut() = Some(Closure::new(move || {
-			if let Some(ref mut c) = *context_anim.borrow_mut() {
-				let dt = 0.016;
-				if c.state.animation_running {
-					c.state.tick(dt as f32);
-				}
-				if let Some(ref mut ps) = c.particles {
-					ps.update(dt);
-				}
-				render::render(&c.state, &ctx, &c.scale, &c.theme, c.particles.as_ref());
-			}
-			if let Some(ref cb) = *animate_inner.borrow() {
-				let _ = web_sys::window()
-					.unwrap()
-					.request_animation_frame(cb.as_ref().unchecked_ref());
-			}
-		}));
-		if let Some(ref cb) = *animate_init.borrow() {
-			let _ = window.request_animation_frame(cb.as_ref().unchecked_ref());
+		// The worker runs its own loop
+		if host_init.borrow().is_some() {
+			return;
+		}
+		if let Some(controller) = controller {
+			controller.set_canvas(canvas.clone());
 		}
-	});
-
-	let context_md = context.clone();
-	let on_mousedown = move |ev: MouseEvent| {
-		let canvas: HtmlCanvasElement = canvas_ref.get().unwrap().into();
-		let rect = canvas.get_bounding_client_rect();
-		let (x, y) = (
-			ev.client_x() as f64 - rect.left(),
-			ev.client_y() as f64 - rect.top(),
-		);
 
-		if let Some(ref mut c) = *context_md.borrow_mut() {
-			if let Some(idx) = c.state.node_at_position(x, y, &c.scale) {
-				c.state.drag.active = true;
-				c.state.drag.node_idx = Some(idx);
-				c.state.drag.start_x = x;
-				c.state.drag.start_y = y;
-				c.state.graph.visit_nodes(|node| {
-					if node.index() == idx {
-						c.state.drag.node_start_x = node.x();
-						c.state.drag.node_start_y = node.y();
-					}
-				});
-			} else {
-				c.state.pan.active = true;
-				c.state.pan.start_x = x;
-				c.state.pan.start_y = y;
-				c.state.pan.transform_start_x = c.state.transform.x;
-				c.state.pan.transform_start_y = c.state.transform.y;
-			}
+		let Some(mut surface) = acquire_surface(&canvas, renderer) else {
+			log::error!("graph canvas has no rendering context");
+			return;
+		};
+		let mut graph = GraphContext::new(&graph_data, w, h, &opts);
+		graph.background = background_renderer.clone();
+		// A data update continues from the graph it replaces
+		if let Some(ref previous) = *context_init.borrow() {
+			graph.state.carry_over(&previous.state);
+			graph.cache.share_background_image(&previous.cache);
 		}
-	};
+		if static_render {
+			graph.freeze(&graph_data, seed_strategy);
+		}
+		if let Some(camera) = transform
+			&& camera.get_untracked().k <= 0.0
+		{
+			camera.set(graph.state.transform.clone());
+		}
+		*context_init.borrow_mut() = Some(graph);
 
-	let context_mm = context.clone();
-	let on_mousemove = move |ev: MouseEvent| {
-		let canvas: HtmlCanvasElement = canvas_ref.get().unwrap().into();
-		let rect = canvas.get_bounding_client_rect();
-		let (x, y) = (
-			ev.client_x() as f64 - rect.left(),
-			ev.client_y() as f64 - rect.top(),
+		let (context_anim, animate_inner, pending_anim) = (
+			context_init.clone(),
+			animate_init.clone(),
+			frame_pending.clone(),
 		);
-
-		if let Some(ref mut c) = *context_mm.borrow_mut() {
-			// Update hover state when not dragging
-			if !c.state.drag.active {
-				let hovered = c.state.node_at_position(x, y, &c.scale);
-				c.state.set_hover(hovered);
+		let last_frame_anim = last_frame.clone();
+		let (health_anim, canvas_anim) = (health.clone(), canvas.clone());
+		// A frame queued for the loop being replaced would run a dropped
+		// closure, so cancel it and start the new loop afresh
+		if let Some(id) = frame_pending.take() {
+			let _ = window.cancel_animation_frame(id);
+		}
+		*animate_init.borrow_mut() = Some(Closure::new(move |timestamp: f64| {
+			match health_anim.get() {
+				// Stop the loop until the context comes back; the graph keeps
+				// its layout and camera meanwhile
+				ContextHealth::Lost => {
+					pending_anim.set(None);
+					last_frame_anim.set(None);
+					return;
+				}
+				ContextHealth::Restored => match acquire_surface(&canvas_anim, renderer) {
+					Some(restored) => {
+						surface = restored;
+						if let Some(ref mut c) = *context_anim.borrow_mut() {
+							c.restore_context();
+						}
+						health_anim.set(ContextHealth::Ok);
+					}
+					None => {
+						log::warn!("graph canvas context was restored but can't be acquired");
+						health_anim.set(ContextHealth::Lost);
+						pending_anim.set(None);
+						return;
+					}
+				},
+				ContextHealth::Ok => {}
 			}
+			let dt = frame_dt(last_frame_anim.replace(Some(timestamp)), timestamp);
+			let outcome = context_anim
+				.borrow_mut()
+				.as_mut()
+				.map(|c| c.frame(&mut surface, dt))
+				.unwrap_or_default();
+			report_frame(outcome);
+			// A static render draws once, and again only when resized
+			if static_render {
+				pending_anim.set(None);
+			// Stop the loop in a background tab; `visibilitychange` restarts it
+			} else if is_hidden() {
+				pending_anim.set(None);
+				// Resume with a nominal step rather than the time spent hidden
+				last_frame_anim.set(None);
+			} else if let Some(ref cb) = *animate_inner.borrow() {
+				pending_anim.set(request_frame(cb));
+			}
+		}));
+		schedule_frame(&animate_init, &frame_pending);
 
-			if c.state.drag.active {
-				if let Some(idx) = c.state.drag.node_idx {
-					let (dx, dy) = (
-						(x - c.state.drag.start_x) / c.state.transform.k,
-						(y - c.state.drag.start_y) / c.state.transform.k,
-					);
-					let (nx, ny) = (
-						c.state.drag.node_start_x + dx as f32,
-						c.state.drag.node_start_y + dy as f32,
-					);
-					c.state.graph.visit_nodes_mut(|node| {
-						if node.index() == idx {
-							node.data.x = nx;
-							node.data.y = ny;
-							node.data.is_anchor = true;
-						}
-					});
+		// The loop's slot outlives data updates, so the tab is watched once
+		if visibility_cb_init.borrow().is_none() {
+			let (animate_resume, pending_resume) = (animate_init.clone(), frame_pending.clone());
+			*visibility_cb_init.borrow_mut() = Some(Closure::new(move || {
+				if !is_hidden() && !static_render {
+					schedule_frame(&animate_resume, &pending_resume);
 				}
-			} else if c.state.pan.active {
-				c.state.transform.x = c.state.pan.transform_start_x + (x - c.state.pan.start_x);
-				c.state.transform.y = c.state.pan.transform_start_y + (y - c.state.pan.start_y);
+			}));
+			if let (Some(cb), Some(document)) = (&*visibility_cb_init.borrow(), window.document()) {
+				let _ = document.add_event_listener_with_callback(
+					"visibilitychange",
+					cb.as_ref().unchecked_ref(),
+				);
 			}
 		}
-	};
 
-	let context_mu = context.clone();
-	let on_mouseup = move |_: MouseEvent| {
-		if let Some(ref mut c) = *context_mu.borrow_mut() {
-			if c.state.drag.active {
-				if let Some(idx) = c.state.drag.node_idx {
-					c.state.graph.visit_nodes_mut(|node| {
-						if node.index() == idx {
-							node.data.is_anchor = true;
-						}
-					});
-				}
+		// The canvas outlives data updates, so it's watched once
+		if context_lost_cb.borrow().is_some() {
+			return;
+		}
+		let (health_lost, losses) = (health.clone(), Cell::new(0u32));
+		*context_lost_cb.borrow_mut() = Some(Closure::new(move |ev: web_sys::Event| {
+			// Without this the browser never gives a WebGL context back
+			ev.prevent_default();
+			losses.set(losses.get() + 1);
+			log::warn!(
+				"graph canvas lost its rendering context (loss {} on this canvas)",
+				losses.get()
+			);
+			health_lost.set(ContextHealth::Lost);
+		}));
+		let (health_restored, animate_restored, pending_restored) =
+			(health.clone(), animate_init.clone(), frame_pending.clone());
+		*context_restored_cb.borrow_mut() = Some(Closure::new(move || {
+			health_restored.set(ContextHealth::Restored);
+			schedule_frame(&animate_restored, &pending_restored);
+		}));
+		if let Some(ref cb) = *context_lost_cb.borrow() {
+			for name in ["contextlost", "webglcontextlost"] {
+				let _ = canvas.add_event_listener_with_callback(name, cb.as_ref().unchecked_ref());
 			}
-			c.state.drag.active = false;
-			c.state.drag.node_idx = None;
-			c.state.pan.active = false;
 		}
-	};
+		if let Some(ref cb) = *context_restored_cb.borrow() {
+			for name in ["contextrestored", "webglcontextrestored"] {
+				let _ = canvas.add_event_listener_with_callback(name, cb.as_ref().unchecked_ref());
+			}
+		}
+	});
 
-	let context_ml = context.clone();
-	let on_mouseleave = move |_: MouseEvent| {
-		if let Some(ref mut c) = *context_ml.borrow_mut() {
-			c.state.drag.active = false;
-			c.state.drag.node_idx = None;
-			c.state.pan.active = false;
-			c.state.set_hover(None);
+	// Follow external camera changes in controlled mode
+	let (context_camera, host_camera) = (context.clone(), host.clone());
+	Effect::new(move |_| {
+		let Some(camera) = transform else {
+			return;
+		};
+		let next = camera.get();
+		if let Some(ref h) = *host_camera.borrow() {
+			h.send(&Command::Camera(next));
+		} else if let Some(ref mut c) = *context_camera.borrow_mut()
+			&& next.k > 0.0
+			&& c.state.transform != next
+		{
+			c.state.transform = next;
+			c.state.mark_dirty();
+		}
+	});
+	// Follow the filter; one that removes nodes rebuilds the graph instead
+	let (context_filter, host_filter) = (context.clone(), host.clone());
+	Effect::new(move |_| {
+		let Some(filter) = filter else {
+			return;
+		};
+		let filter = filter.get();
+		if let Some(ref h) = *host_filter.borrow() {
+			h.send(&Command::Filter(filter));
+		} else if let Some(ref mut c) = *context_filter.borrow_mut() {
+			c.state.set_filter(filter);
+		}
+	});
+	// Follow playback of a temporal graph
+	let (context_time, host_time) = (context.clone(), host.clone());
+	Effect::new(move |_| {
+		let Some(time) = current_time else {
+			return;
+		};
+		let time = Some(time.get());
+		if let Some(ref h) = *host_time.borrow() {
+			h.send(&Command::Time(time));
+		} else if let Some(ref mut c) = *context_time.borrow_mut() {
+			c.state.set_time(time);
+		}
+	});
+	// Follow the heatmap metric
+	let (context_color, host_color) = (context.clone(), host.clone());
+	Effect::new(move |_| {
+		let Some(color_by) = color_by else {
+			return;
+		};
+		let color_by = color_by.get();
+		if let Some(ref h) = *host_color.borrow() {
+			h.send(&Command::ColorBy(color_by));
+		} else if let Some(ref mut c) = *context_color.borrow_mut() {
+			c.state.set_color_by(color_by);
+		}
+	});
+	// Follow the annotated nodes
+	let (context_notes, host_notes) = (context.clone(), host.clone());
+	Effect::new(move |_| {
+		let Some(annotations) = annotations else {
+			return;
+		};
+		let ids = annotations.with(|a| annotated_ids(a));
+		if let Some(ref h) = *host_notes.borrow() {
+			h.send(&Command::Annotate(ids));
+		} else if let Some(ref mut c) = *context_notes.borrow_mut() {
+			c.annotated.ids = ids;
+		}
+	});
+	// Follow the controller's pause
+	let (context_pause, host_pause) = (context.clone(), host.clone());
+	Effect::new(move |_| {
+		let Some(paused) = paused else {
+			return;
+		};
+		let paused = paused.get();
+		if let Some(ref h) = *host_pause.borrow() {
+			h.send(&Command::Pause(paused));
+		} else if let Some(ref mut c) = *context_pause.borrow_mut() {
+			c.state.paused = paused;
+		}
+	});
+	// Follow the zoom scaling
+	let (context_scale, host_scale) = (context.clone(), host.clone());
+	Effect::new(move |_| {
+		let Some(scale_config) = scale_config else {
+			return;
+		};
+		let config = scale_config.get();
+		if let Some(ref h) = *host_scale.borrow() {
+			h.send(&Command::Scale(Box::new(config)));
+		} else if let Some(ref mut c) = *context_scale.borrow_mut() {
+			c.scale = config;
+			c.state.mark_dirty();
+		}
+	});
+	// Follow the fisheye toggle
+	let (context_lens, host_lens) = (context.clone(), host.clone());
+	Effect::new(move |_| {
+		let Some(fisheye) = fisheye else {
+			return;
+		};
+		let on = fisheye.get();
+		if let Some(ref h) = *host_lens.borrow() {
+			h.send(&Command::Fisheye(on));
+		} else if let Some(ref mut c) = *context_lens.borrow_mut() {
+			c.state.fisheye_on = on;
+			c.state.mark_dirty();
+		}
+	});
+	// Follow the highlighted group, e.g. from a legend
+	let (context_group, host_group) = (context.clone(), host.clone());
+	Effect::new(move |_| {
+		let Some(group) = highlighted_group else {
+			return;
+		};
+		let group = group.get();
+		if let Some(ref h) = *host_group.borrow() {
+			h.send(&Command::Group(group));
+		} else if let Some(ref mut c) = *context_group.borrow_mut() {
+			c.state.highlight_group(group);
+		}
+	});
+	let (context_input, host_input) = (context.clone(), host.clone());
+	let dispatch = move |input: Input| {
+		if let Some(ref h) = *host_input.borrow() {
+			h.send(&Command::Input(input));
+			return;
+		}
+		let out = context_input.borrow_mut().as_mut().map(|c| c.handle(input));
+		if let Some(out) = out {
+			report(out);
 		}
 	};
-
-	let context_wh = context.clone();
-	let on_wheel = move |ev: WheelEvent| {
-		ev.prevent_default();
-		let canvas: HtmlCanvasElement = canvas_ref.get().unwrap().into();
-		let rect = canvas.get_bounding_client_rect();
-		let (x, y) = (
+	// Pointer position relative to the canvas
+	let local = move |ev: &MouseEvent| {
+		let rect = canvas_ref
+			.get_untracked()
+			.unwrap()
+			.get_bounding_client_rect();
+		(
 			ev.client_x() as f64 - rect.left(),
 			ev.client_y() as f64 - rect.top(),
-		);
+		)
+	};
 
-		if let Some(ref mut c) = *context_wh.borrow_mut() {
-			let factor = if ev.delta_y() > 0.0 { 0.9 } else { 1.1 };
-			let new_k = (c.state.transform.k * factor).clamp(0.1, 10.0);
-			let ratio = new_k / c.state.transform.k;
-			c.state.transform.x = x - (x - c.state.transform.x) * ratio;
-			c.state.transform.y = y - (y - c.state.transform.y) * ratio;
-			c.state.transform.k = new_k;
+	let dispatch_md = dispatch.clone();
+	let on_mousedown = move |ev: MouseEvent| {
+		// A bound middle button mustn't start the browser's autoscroll
+		if ev.button() == 1 && mouse_bindings.middle != ButtonBinding::always(PointerAction::Ignore)
+		{
+			ev.prevent_default();
+		}
+		let (x, y) = local(&ev);
+		dispatch_md(Input::Down {
+			x,
+			y,
+			shift: ev.shift_key(),
+			button: ev.button(),
+		});
+	};
+	// A bound right button replaces the context menu
+	let on_contextmenu = move |ev: MouseEvent| {
+		if mouse_bindings.right != ButtonBinding::always(PointerAction::Ignore) {
+			ev.prevent_default();
 		}
 	};
+	let dispatch_mm = dispatch.clone();
+	let on_mousemove = move |ev: MouseEvent| {
+		let (x, y) = local(&ev);
+		dispatch_mm(Input::Move { x, y });
+	};
+	let dispatch_mu = dispatch.clone();
+	let on_mouseup = move |ev: MouseEvent| {
+		let (x, y) = local(&ev);
+		dispatch_mu(Input::Up { x, y });
+	};
+	let dispatch_dc = dispatch.clone();
+	let on_dblclick = move |ev: MouseEvent| {
+		let (x, y) = local(&ev);
+		dispatch_dc(Input::DoubleClick { x, y });
+	};
+	let dispatch_ml = dispatch.clone();
+	let on_mouseleave = move |_: MouseEvent| dispatch_ml(Input::Leave);
+	let dispatch_wh = dispatch.clone();
+	let on_wheel = move |ev: WheelEvent| {
+		ev.prevent_default();
+		let (x, y) = local(&ev);
+		// Deltas may come in lines or pages rather than pixels
+		let unit = match ev.delta_mode() {
+			WheelEvent::DOM_DELTA_LINE => 16.0,
+			WheelEvent::DOM_DELTA_PAGE => canvas_ref
+				.get_untracked()
+				.map_or(600.0, |c| f64::from(c.client_height())),
+			_ => 1.0,
+		};
+		// Trackpads report a pinch as a wheel with ctrl held
+		dispatch_wh(Input::Wheel {
+			x,
+			y,
+			delta_x: ev.delta_x() * unit,
+			delta_y: ev.delta_y() * unit,
+			zoom: wheel_zoom || ev.ctrl_key() || ev.meta_key(),
+		});
+	};
+	let on_keydown = move |ev: KeyboardEvent| {
+		let input = if ev.ctrl_key() || ev.meta_key() {
+			Input::from_shortcut(&ev.key(), ev.shift_key())
+		} else {
+			Input::from_key(&ev.key())
+		};
+		if let Some(input) = input {
+			ev.prevent_default();
+			dispatch(input);
+		}
+	};
+
+	let empty = Memo::new(move |_| data.with(|d| d.nodes.is_empty()));
 
 	view! {
 		<canvas
@@ -272,8 +1560,25 @@ pub fn ForceGraphCanvas(
 			on:mousemove=on_mousemove
 			on:mouseup=on_mouseup
 			on:mouseleave=on_mouseleave
+			on:dblclick=on_dblclick
+			on:contextmenu=on_contextmenu
 			on:wheel=on_wheel
-			style="display: block; cursor: grab;"
+			on:keydown=on_keydown
+			tabindex="0"
+			role="application"
+			aria-label="Graph. Use arrow keys to move between nodes and Enter to select."
+			style=if static_render { "display: block;" } else { "display: block; cursor: grab;" }
 		/>
+		{annotations.map(|annotations| annotation_layer(annotations, placements, canvas_ref))}
+		{on_sampling
+			.is_none()
+			.then(|| sampling_notice(sampled, text_color.clone(), canvas_ref))}
+		{status_overlay(empty, is_loading, empty_view, text_color, canvas_ref)}
+		<div
+			aria-live="polite"
+			style="position: absolute; width: 1px; height: 1px; overflow: hidden; clip: rect(0 0 0 0); white-space: nowrap;"
+		>
+			{move || announcement.get()}
+		</div>
 	}
 }
@@ -0,0 +1,635 @@
+//! Imperative handle for driving a mounted graph from outside the component.
+//!
+//! Create a [`GraphController`] in the parent, pass it to the `controller`
+//! prop, and call its methods from event handlers. Methods are no-ops (or
+//! return an error) until the component has mounted.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use js_sys::{Array, Object, Promise, Reflect};
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+	Blob, BlobEvent, BlobPropertyBag, CanvasRenderingContext2d, ClipboardItem, HtmlAnchorElement,
+	HtmlCanvasElement, MediaRecorder, MediaRecorderOptions, MediaStreamTrack, RecordingState, Url,
+};
+
+use super::component::SharedContext;
+use super::render::{self, RenderCache};
+use super::state::ViewTransform;
+
+/// Frame rate recordings capture the canvas at.
+const RECORD_FPS: f64 = 30.0;
+
+/// Zoom factor of one [`GraphController::zoom_in`] step.
+const ZOOM_STEP: f64 = 1.25;
+
+/// Pixels [`GraphController::fit`] leaves around the graph.
+const FIT_PADDING: f64 = 40.0;
+
+/// Milliseconds a [`GraphController::download`] URL outlives its click.
+const DOWNLOAD_REVOKE_MS: i32 = 10_000;
+
+/// Container formats tried for recordings, best first.
+const RECORD_TYPES: [&str; 3] = [
+	"video/webm;codecs=vp9",
+	"video/webm;codecs=vp8",
+	"video/webm",
+];
+
+/// Which part of the graph an export captures.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExportRegion {
+	/// Exactly what's on screen, at the canvas's size.
+	Viewport,
+	/// The whole graph's bounding box, fitted into an image of this size.
+	Fit {
+		/// Image width in CSS pixels, before supersampling.
+		width: u32,
+		/// Image height in CSS pixels, before supersampling.
+		height: u32,
+	},
+}
+
+/// Options for [`GraphController::export_png`].
+#[derive(Clone, Debug)]
+pub struct ExportOptions {
+	/// Download file name.
+	pub filename: String,
+	/// Supersampling factor, clamped to `1.0..=4.0`.
+	pub scale: f64,
+	/// Leave the background transparent instead of using the theme fill.
+	pub transparent: bool,
+	/// Include the ambient particle layer.
+	pub include_particles: bool,
+	/// Include the vignette overlay.
+	pub include_vignette: bool,
+	/// Area of the graph to capture.
+	pub region: ExportRegion,
+}
+
+impl Default for ExportOptions {
+	fn default() -> Self {
+		Self {
+			filename: "graph.png".into(),
+			scale: 2.0,
+			transparent: false,
+			include_particles: true,
+			include_vignette: true,
+			region: ExportRegion::Viewport,
+		}
+	}
+}
+
+/// Imperative handle to a [`ForceGraphCanvas`](super::ForceGraphCanvas).
+///
+/// Cheap to copy; every copy refers to the same graph.
+#[derive(Clone, Copy)]
+pub struct GraphController {
+	context: StoredValue<SharedContext, LocalStorage>,
+	collapsed: RwSignal<HashSet<u32>>,
+	paused: RwSignal<bool>,
+	settled: RwSignal<bool>,
+	/// Notified on each frame that repaints, for position queries to track
+	repainted: Trigger,
+	/// The on-screen canvas, set once the graph renders on the main thread
+	canvas: StoredValue<Option<HtmlCanvasElement>, LocalStorage>,
+	/// Set while a recording runs
+	recorder: StoredValue<Option<MediaRecorder>, LocalStorage>,
+}
+
+impl Default for GraphController {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl GraphController {
+	/// Create a controller to pass to the component's `controller` prop.
+	pub fn new() -> Self {
+		Self {
+			context: StoredValue::new_local(Rc::new(RefCell::new(None))),
+			collapsed: RwSignal::new(HashSet::new()),
+			paused: RwSignal::new(false),
+			settled: RwSignal::new(false),
+			repainted: Trigger::new(),
+			canvas: StoredValue::new_local(None),
+			recorder: StoredValue::new_local(None),
+		}
+	}
+
+	/// Graph context slot shared with the component.
+	pub(super) fn context(&self) -> SharedContext {
+		self.context.get_value()
+	}
+
+	/// Groups collapsed into meta-nodes, shared with the component.
+	pub(super) fn collapsed(&self) -> RwSignal<HashSet<u32>> {
+		self.collapsed
+	}
+
+	/// Whether the simulation is paused, shared with the component.
+	pub(super) fn paused_signal(&self) -> RwSignal<bool> {
+		self.paused
+	}
+
+	/// Whether the layout has settled, set by the component.
+	pub(super) fn settled_signal(&self) -> RwSignal<bool> {
+		self.settled
+	}
+
+	/// Wake views tracking node positions after a frame repainted.
+	pub(super) fn notify_repainted(&self) {
+		self.repainted.notify();
+	}
+
+	/// The canvas the component draws on, for recording.
+	pub(super) fn set_canvas(&self, canvas: HtmlCanvasElement) {
+		self.canvas.set_value(Some(canvas));
+	}
+
+	/// Replace the nodes of `group` with a single meta-node, labeled with
+	/// the group's name from the `groups` prop and its member count and
+	/// sized by that count. Links leaving the group are rewired to the
+	/// meta-node, summing the weights of links that merge. Works before
+	/// mounting too; the graph starts out collapsed.
+	pub fn collapse_group(&self, group: u32) {
+		self.collapsed.update(|groups| {
+			groups.insert(group);
+		});
+	}
+
+	/// Restore the nodes of a collapsed `group` around its meta-node, as
+	/// double-clicking the meta-node does.
+	pub fn expand_group(&self, group: u32) {
+		self.collapsed.update(|groups| {
+			groups.remove(&group);
+		});
+	}
+
+	/// Animate the camera to frame the nodes with the given ids, leaving
+	/// `padding` pixels around them, e.g. to zoom into search results.
+	/// Unknown ids are skipped; does nothing if none match or the graph
+	/// hasn't mounted.
+	pub fn fit_to_nodes<S: AsRef<str>>(&self, ids: &[S], padding: f64) {
+		if let Some(ref mut c) = *self.context().borrow_mut() {
+			let indices = c.state.indices_of(ids);
+			c.state.fit_to_nodes(&indices, padding);
+		}
+	}
+
+	/// Animate the camera in by one step around the center of the view.
+	pub fn zoom_in(&self) {
+		self.zoom_by(ZOOM_STEP);
+	}
+
+	/// Animate the camera out by one step around the center of the view.
+	pub fn zoom_out(&self) {
+		self.zoom_by(1.0 / ZOOM_STEP);
+	}
+
+	/// Animate the camera to zoom by `factor` around the center of the view.
+	pub fn zoom_by(&self, factor: f64) {
+		if let Some(ref mut c) = *self.context().borrow_mut() {
+			c.state.zoom_by(factor);
+		}
+	}
+
+	/// Animate the camera to frame the whole graph.
+	pub fn fit(&self) {
+		if let Some(ref mut c) = *self.context().borrow_mut() {
+			let mut indices = Vec::new();
+			c.state.graph.visit_nodes(|node| indices.push(node.index()));
+			c.state.fit_to_nodes(&indices, FIT_PADDING);
+		}
+	}
+
+	/// Animate the camera back to the view the graph started with.
+	pub fn reset_view(&self) {
+		if let Some(ref mut c) = *self.context().borrow_mut() {
+			c.state.reset_view();
+		}
+	}
+
+	/// Hold the simulation still, or let it run again. Nodes can still be
+	/// dragged while paused. Works before mounting and in worker mode too.
+	pub fn set_paused(&self, paused: bool) {
+		self.paused.set(paused);
+	}
+
+	/// Pause the simulation if it's running, or resume it.
+	pub fn toggle_paused(&self) {
+		self.paused.update(|paused| *paused = !*paused);
+	}
+
+	/// Whether the simulation is paused; reactive, so views reading it
+	/// follow [`Self::set_paused`].
+	pub fn is_paused(&self) -> bool {
+		self.paused.get()
+	}
+
+	/// The current pan and zoom; `None` before mounting and in worker
+	/// mode. Reactive like [`Self::node_screen_position`].
+	pub fn transform(&self) -> Option<ViewTransform> {
+		self.repainted.track();
+		self.context()
+			.borrow()
+			.as_ref()
+			.map(|c| c.state.transform.clone())
+	}
+
+	/// World point under the canvas point `(sx, sy)`, in pixels from its
+	/// top left corner, as the graph's own input handling converts it.
+	/// `None` before mounting and in worker mode; reactive like
+	/// [`Self::node_screen_position`].
+	pub fn screen_to_graph(&self, sx: f64, sy: f64) -> Option<(f64, f64)> {
+		self.transform().map(|t| t.screen_to_graph(sx, sy))
+	}
+
+	/// Canvas point, in pixels from its top left corner, of the world
+	/// point `(x, y)`; the inverse of [`Self::screen_to_graph`].
+	pub fn graph_to_screen(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+		self.transform().map(|t| t.graph_to_screen(x, y))
+	}
+
+	/// Where the node with `id` is on the canvas, in pixels from its top
+	/// left corner, for positioning HTML over it. `None` for unknown and
+	/// hidden nodes, before mounting and in worker mode, where the
+	/// component's `annotations` prop pins HTML to nodes instead.
+	///
+	/// Reactive: views and effects reading it run again on every frame the
+	/// graph repaints, as the layout moves and the view pans and zooms.
+	pub fn node_screen_position(&self, id: &str) -> Option<(f64, f64)> {
+		self.repainted.track();
+		self.context()
+			.borrow()
+			.as_ref()
+			.and_then(|c| c.state.node_screen_position(id))
+	}
+
+	/// [`Self::node_screen_position`] of every visible node, by id; empty
+	/// before mounting and in worker mode.
+	pub fn node_screen_positions(&self) -> HashMap<String, (f64, f64)> {
+		self.repainted.track();
+		self.context()
+			.borrow()
+			.as_ref()
+			.map(|c| c.state.node_screen_positions())
+			.unwrap_or_default()
+	}
+
+	/// Whether the layout has settled and the simulation stopped stepping;
+	/// reactive, for a status indicator. Works in worker mode too.
+	pub fn is_settled(&self) -> bool {
+		self.settled.get()
+	}
+
+	/// Wake a settled simulation, easing it back up to speed, as dragging a
+	/// node does. Does nothing before mounting or in worker mode.
+	pub fn reheat(&self) {
+		if let Some(ref mut c) = *self.context().borrow_mut() {
+			c.state.reheat();
+		}
+	}
+
+	/// [`Self::fit_to_nodes`] on the shift-click selection.
+	pub fn fit_to_selection(&self, padding: f64) {
+		if let Some(ref mut c) = *self.context().borrow_mut() {
+			let indices: Vec<_> = c.state.selection.iter().copied().collect();
+			c.state.fit_to_nodes(&indices, padding);
+		}
+	}
+
+	/// Undo the latest drag, putting the nodes it moved back and unpinning
+	/// those it pinned, as Ctrl+Z does on the focused canvas. Returns
+	/// whether there was anything to undo; always `false` in worker mode.
+	pub fn undo(&self) -> bool {
+		self.context()
+			.borrow_mut()
+			.as_mut()
+			.is_some_and(|c| c.state.undo())
+	}
+
+	/// Redo the latest undone drag, as Ctrl+Shift+Z or Ctrl+Y does. Returns
+	/// whether there was anything to redo; always `false` in worker mode.
+	pub fn redo(&self) -> bool {
+		self.context()
+			.borrow_mut()
+			.as_mut()
+			.is_some_and(|c| c.state.redo())
+	}
+
+	/// Whether there's a drag to undo.
+	pub fn can_undo(&self) -> bool {
+		self.context()
+			.borrow()
+			.as_ref()
+			.is_some_and(|c| c.state.history.can_undo())
+	}
+
+	/// Whether there's an undone drag to redo.
+	pub fn can_redo(&self) -> bool {
+		self.context()
+			.borrow()
+			.as_ref()
+			.is_some_and(|c| c.state.history.can_redo())
+	}
+
+	/// Render the current frame off-screen and download it as a PNG.
+	///
+	/// The download starts asynchronously once the browser has encoded the
+	/// image. Fails if the graph hasn't mounted yet or the browser can't
+	/// create the export canvas.
+	pub fn export_png(&self, options: &ExportOptions) -> Result<(), JsValue> {
+		let canvas = self.render_offscreen(options)?;
+		let filename = options.filename.clone();
+		let on_blob = Closure::once_into_js(move |blob: Option<Blob>| {
+			if let Some(blob) = blob {
+				Self::download(&blob, &filename);
+			}
+		});
+		canvas.to_blob(on_blob.unchecked_ref())
+	}
+
+	/// Save `blob` through the browser's downloads as `filename`, e.g. a
+	/// clip from [`Self::record`].
+	pub fn download(blob: &Blob, filename: &str) {
+		let Ok(url) = Url::create_object_url_with_blob(blob) else {
+			return;
+		};
+		let Some(window) = web_sys::window() else {
+			let _ = Url::revoke_object_url(&url);
+			return;
+		};
+		let anchor = window
+			.document()
+			.and_then(|d| d.create_element("a").ok())
+			.and_then(|a| a.dyn_into::<HtmlAnchorElement>().ok());
+		if let Some(anchor) = anchor {
+			anchor.set_href(&url);
+			anchor.set_download(filename);
+			anchor.click();
+		}
+		// The download reads the URL after the click returns, so it's revoked
+		// only once the browser has had time to start it
+		let revoke = Closure::once_into_js(move || {
+			let _ = Url::revoke_object_url(&url);
+		});
+		let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(
+			revoke.unchecked_ref(),
+			DOWNLOAD_REVOKE_MS,
+		);
+	}
+
+	/// Record the live canvas, animation and interaction included, as a
+	/// WebM clip for `seconds` or until [`Self::stop_recording`]; a
+	/// duration that isn't a positive number records until stopped.
+	///
+	/// `on_done` receives the clip once the recording stops, e.g. to pass to
+	/// [`Self::download`], or an error message if the browser lacks
+	/// `MediaRecorder`, canvas capture or WebM encoding, a recording is
+	/// already running, or the graph hasn't mounted or renders in a worker.
+	/// The frame loop carries on untouched; the recording only watches the
+	/// canvas.
+	pub fn record(&self, seconds: f64, on_done: Callback<Result<Blob, String>>) {
+		if let Err(err) = self.start_recording(seconds, on_done) {
+			on_done.run(Err(describe_error(&err)));
+		}
+	}
+
+	/// Stop the running recording early, handing its clip to `on_done` as
+	/// if the duration had run out. Does nothing if nothing is recording.
+	pub fn stop_recording(&self) {
+		if let Some(recorder) = self.recorder.get_value() {
+			let _ = recorder.stop();
+		}
+	}
+
+	/// Whether a recording is running.
+	pub fn is_recording(&self) -> bool {
+		self.recorder.with_value(Option::is_some)
+	}
+
+	/// Start the recording, reporting the finished clip via `on_done`.
+	fn start_recording(
+		&self,
+		seconds: f64,
+		on_done: Callback<Result<Blob, String>>,
+	) -> Result<(), JsValue> {
+		if self.is_recording() {
+			return Err("a recording is already running".into());
+		}
+		let canvas = self
+			.canvas
+			.get_value()
+			.ok_or_else(|| JsValue::from_str("graph is not mounted on the main thread"))?;
+		let window = web_sys::window().ok_or_else(|| JsValue::from_str("no window"))?;
+		// Feature-detect rather than let the bindings throw on `undefined`
+		if Reflect::get(&window, &"MediaRecorder".into())?.is_undefined() {
+			return Err("MediaRecorder is not supported by this browser".into());
+		}
+		if Reflect::get(&canvas, &"captureStream".into())?.is_undefined() {
+			return Err("canvas capture is not supported by this browser".into());
+		}
+		let mime = RECORD_TYPES
+			.into_iter()
+			.find(|mime| MediaRecorder::is_type_supported(mime))
+			.ok_or_else(|| JsValue::from_str("WebM recording is not supported by this browser"))?;
+
+		let stream = canvas.capture_stream_with_frame_request_rate(RECORD_FPS)?;
+		let options = MediaRecorderOptions::new();
+		options.set_mime_type(mime);
+		let recorder =
+			MediaRecorder::new_with_media_stream_and_media_recorder_options(&stream, &options)?;
+
+		let chunks = Array::new();
+		let chunks_data = chunks.clone();
+		let on_data = Closure::<dyn FnMut(BlobEvent)>::new(move |event: BlobEvent| {
+			if let Some(data) = event.data().filter(|data| data.size() > 0.0) {
+				chunks_data.push(&data);
+			}
+		});
+		recorder.set_ondataavailable(Some(on_data.as_ref().unchecked_ref()));
+		let (slot, recorder_stop) = (self.recorder, recorder.clone());
+		let on_stop = Closure::once_into_js(move || {
+			// Release the capture so the canvas stops feeding it frames
+			for track in stream.get_tracks() {
+				track.unchecked_into::<MediaStreamTrack>().stop();
+			}
+			slot.set_value(None);
+			recorder_stop.set_ondataavailable(None);
+			drop(on_data);
+			let bag = BlobPropertyBag::new();
+			bag.set_type(mime);
+			on_done.run(
+				Blob::new_with_blob_sequence_and_options(&chunks, &bag)
+					.map_err(|err| describe_error(&err)),
+			);
+		});
+		recorder.set_onstop(Some(on_stop.unchecked_ref()));
+		recorder.start()?;
+		self.recorder.set_value(Some(recorder.clone()));
+
+		if seconds.is_finite() && seconds > 0.0 {
+			// Stops this recording only, not a later one started after it
+			let stop = Closure::once_into_js(move || {
+				if recorder.state() == RecordingState::Recording {
+					let _ = recorder.stop();
+				}
+			});
+			window.set_timeout_with_callback_and_timeout_and_arguments_0(
+				stop.unchecked_ref(),
+				(seconds * 1000.0).round() as i32,
+			)?;
+		}
+		Ok(())
+	}
+
+	/// Render the current view and put it on the clipboard as a PNG.
+	///
+	/// `on_done` receives `Ok` once the browser accepts the image, or an
+	/// error message if the Clipboard API is missing (insecure context,
+	/// partial `ClipboardItem` support), permission is denied, or the graph
+	/// hasn't mounted. Call this from a user gesture such as a click; most
+	/// browsers reject clipboard writes otherwise.
+	pub fn copy_to_clipboard(&self, on_done: Callback<Result<(), String>>) {
+		if let Err(err) = self.write_clipboard(on_done) {
+			on_done.run(Err(describe_error(&err)));
+		}
+	}
+
+	/// Start the clipboard write, reporting async failures via `on_done`.
+	fn write_clipboard(&self, on_done: Callback<Result<(), String>>) -> Result<(), JsValue> {
+		let window = web_sys::window().ok_or_else(|| JsValue::from_str("no window"))?;
+		let navigator = window.navigator();
+		// Feature-detect rather than let the bindings throw on `undefined`
+		let clipboard = Reflect::get(&navigator, &"clipboard".into())?;
+		if clipboard.is_undefined() {
+			return Err("Clipboard API unavailable (requires a secure context)".into());
+		}
+		if Reflect::get(&window, &"ClipboardItem".into())?.is_undefined() {
+			return Err("ClipboardItem is not supported by this browser".into());
+		}
+
+		let canvas = self.render_offscreen(&ExportOptions {
+			scale: 1.0,
+			..ExportOptions::default()
+		})?;
+		// Hand the clipboard a pending blob so the write starts within the
+		// user gesture; Safari rejects writes that begin after an await
+		let png = Promise::new(&mut |resolve, reject| {
+			let reject_encode = reject.clone();
+			let on_blob = Closure::once_into_js(move |blob: Option<Blob>| {
+				let _ = match blob {
+					Some(blob) => resolve.call1(&JsValue::NULL, &blob),
+					None => reject_encode.call1(&JsValue::NULL, &"failed to encode PNG".into()),
+				};
+			});
+			if let Err(err) = canvas.to_blob(on_blob.unchecked_ref()) {
+				let _ = reject.call1(&JsValue::NULL, &err);
+			}
+		});
+		let record = Object::new();
+		Reflect::set(&record, &"image/png".into(), &png)?;
+		let item = ClipboardItem::new_with_record_from_str_to_blob_promise(&record)?;
+
+		let written = JsFuture::from(navigator.clipboard().write(&js_sys::Array::of1(&item)));
+		spawn_local(async move {
+			on_done.run(
+				written
+					.await
+					.map(|_| ())
+					.map_err(|err| describe_error(&err)),
+			);
+		});
+		Ok(())
+	}
+
+	/// Draw a frame into a new canvas according to `options`.
+	fn render_offscreen(&self, options: &ExportOptions) -> Result<HtmlCanvasElement, JsValue> {
+		let context = self.context();
+		let mut guard = context.borrow_mut();
+		let c = guard
+			.as_mut()
+			.ok_or_else(|| JsValue::from_str("graph is not mounted"))?;
+
+		let (width, height) = match options.region {
+			ExportRegion::Viewport => (c.state.width, c.state.height),
+			ExportRegion::Fit { width, height } => (width as f64, height as f64),
+		};
+		let scale = options.scale.clamp(1.0, 4.0);
+
+		let canvas: HtmlCanvasElement = web_sys::window()
+			.and_then(|w| w.document())
+			.ok_or_else(|| JsValue::from_str("no document"))?
+			.create_element("canvas")?
+			.dyn_into()?;
+		canvas.set_width((width * scale).round() as u32);
+		canvas.set_height((height * scale).round() as u32);
+		let ctx: CanvasRenderingContext2d = canvas
+			.get_context("2d")?
+			.ok_or_else(|| JsValue::from_str("2d context unavailable"))?
+			.dyn_into()?;
+		let _ = ctx.scale(scale, scale);
+
+		let mut theme = c.theme.clone();
+		if options.transparent {
+			// Keep the hue so overlays derived from the background still match
+			theme.background.color = theme.background.color.with_alpha(0.0);
+			theme.background.color_secondary = theme.background.color_secondary.with_alpha(0.0);
+		}
+		if !options.include_vignette {
+			theme.background.vignette = 0.0;
+		}
+		// Nothing points at an export
+		theme.background.spotlight.enabled = false;
+		let particles = c.particles.as_ref().filter(|_| options.include_particles);
+		let mut cache = RenderCache::default();
+		cache.set_group_labels(c.cache.has_group_labels());
+		if !options.transparent {
+			cache.share_background_image(&c.cache);
+		}
+
+		// Borrow the live state with the export's size and camera, then put
+		// the originals back so the on-screen view is untouched
+		let saved = (c.state.width, c.state.height, c.state.transform.clone());
+		c.state.width = width;
+		c.state.height = height;
+		if let ExportRegion::Fit { .. } = options.region
+			&& let Some((x0, y0, x1, y1)) = c.state.bounding_box()
+		{
+			// Leave room for node radii and labels around the outermost nodes
+			const PADDING: f64 = 40.0;
+			c.state.transform = ViewTransform::fit((x0, y0, x1, y1), width, height, PADDING);
+		}
+		render::render(
+			&c.state,
+			&ctx,
+			&c.scale,
+			&theme,
+			particles,
+			c.legend.as_ref(),
+			None,
+			&mut cache,
+			c.background.as_deref(),
+		);
+		(c.state.width, c.state.height, c.state.transform) = saved;
+
+		Ok(canvas)
+	}
+}
+
+/// Readable message for a JS error or rejection value.
+fn describe_error(err: &JsValue) -> String {
+	err.as_string()
+		.or_else(|| {
+			Reflect::get(err, &"message".into())
+				.ok()
+				.and_then(|m| m.as_string())
+		})
+		.unwrap_or_else(|| format!("{err:?}"))
+}
@@ -0,0 +1,396 @@
+//! Graphviz DOT import for [`GraphData`].
+
+use std::collections::HashMap;
+
+use super::types::{GraphData, GraphLink, GraphNode, LinkStyle};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+	Id(String),
+	/// `->` (directed) or `--` (undirected)
+	Edge(bool),
+	LBracket,
+	RBracket,
+	LBrace,
+	RBrace,
+	Equals,
+	Colon,
+	Separator,
+}
+
+/// Split DOT source into tokens, dropping comments and whitespace.
+fn tokenize(input: &str) -> Vec<Token> {
+	let mut tokens = Vec::new();
+	let mut chars = input.chars().peekable();
+	let mut line_start = true;
+
+	while let Some(c) = chars.next() {
+		let at_line_start = line_start;
+		line_start = c == '\n' || (line_start && c.is_whitespace());
+		match c {
+			c if c.is_whitespace() => {}
+			// Preprocessor-style lines are comments in DOT
+			'#' if at_line_start => {
+				for c in chars.by_ref() {
+					if c == '\n' {
+						line_start = true;
+						break;
+					}
+				}
+			}
+			'/' if chars.peek() == Some(&'/') => {
+				for c in chars.by_ref() {
+					if c == '\n' {
+						line_start = true;
+						break;
+					}
+				}
+			}
+			'/' if chars.peek() == Some(&'*') => {
+				chars.next();
+				let mut prev = ' ';
+				for c in chars.by_ref() {
+					if prev == '*' && c == '/' {
+						break;
+					}
+					prev = c;
+				}
+			}
+			'-' if matches!(chars.peek(), Some('>') | Some('-')) => {
+				tokens.push(Token::Edge(chars.next() == Some('>')));
+			}
+			'[' => tokens.push(Token::LBracket),
+			']' => tokens.push(Token::RBracket),
+			'{' => tokens.push(Token::LBrace),
+			'}' => tokens.push(Token::RBrace),
+			'=' => tokens.push(Token::Equals),
+			':' => tokens.push(Token::Colon),
+			';' | ',' => tokens.push(Token::Separator),
+			'"' => {
+				let mut id = String::new();
+				while let Some(c) = chars.next() {
+					match c {
+						'"' => break,
+						'\\' if chars.peek() == Some(&'"') => id.push(chars.next().unwrap()),
+						// Line continuation
+						'\\' if chars.peek() == Some(&'\n') => {
+							chars.next();
+						}
+						c => id.push(c),
+					}
+				}
+				tokens.push(Token::Id(id));
+			}
+			'<' => {
+				// HTML label: keep the raw markup, balancing nested tags
+				let mut id = String::new();
+				let mut depth = 1;
+				for c in chars.by_ref() {
+					match c {
+						'<' => depth += 1,
+						'>' => depth -= 1,
+						_ => {}
+					}
+					if depth == 0 {
+						break;
+					}
+					id.push(c);
+				}
+				tokens.push(Token::Id(id));
+			}
+			// Bare words and numerals, including negative numbers
+			c if is_id_char(c)
+				|| (c == '-' && chars.peek().is_some_and(|c| c.is_ascii_digit())) =>
+			{
+				let mut id = String::from(c);
+				while let Some(&c) = chars.peek() {
+					if !is_id_char(c) {
+						break;
+					}
+					id.push(c);
+					chars.next();
+				}
+				tokens.push(Token::Id(id));
+			}
+			_ => {}
+		}
+	}
+
+	tokens
+}
+
+fn is_id_char(c: char) -> bool {
+	c.is_alphanumeric() || c == '_' || c == '.' || !c.is_ascii()
+}
+
+fn is_keyword(id: &str, keyword: &str) -> bool {
+	id.eq_ignore_ascii_case(keyword)
+}
+
+/// Single-pass scanner over the token stream; nesting is ignored.
+struct Parser {
+	tokens: Vec<Token>,
+	pos: usize,
+	data: GraphData,
+	index: HashMap<String, usize>,
+}
+
+impl Parser {
+	fn peek(&self) -> Option<&Token> {
+		self.tokens.get(self.pos)
+	}
+
+	fn peek_at(&self, offset: usize) -> Option<&Token> {
+		self.tokens.get(self.pos + offset)
+	}
+
+	/// Consume zero or more `[k=v, ...]` lists, returning their pairs.
+	fn attr_lists(&mut self) -> Vec<(String, String)> {
+		let mut attrs = Vec::new();
+		while self.peek() == Some(&Token::LBracket) {
+			self.pos += 1;
+			while let Some(token) = self.peek().cloned() {
+				self.pos += 1;
+				match token {
+					Token::RBracket => break,
+					Token::Id(key) if self.peek() == Some(&Token::Equals) => {
+						self.pos += 1;
+						if let Some(Token::Id(value)) = self.peek().cloned() {
+							self.pos += 1;
+							attrs.push((key, value));
+						}
+					}
+					_ => {}
+				}
+			}
+		}
+		attrs
+	}
+
+	/// Consume a node ID and any `:port[:compass]` suffix.
+	fn node_id(&mut self) -> Option<String> {
+		let Some(Token::Id(id)) = self.peek().cloned() else {
+			return None;
+		};
+		self.pos += 1;
+		while self.peek() == Some(&Token::Colon) && matches!(self.peek_at(1), Some(Token::Id(_))) {
+			self.pos += 2;
+		}
+		Some(id)
+	}
+
+	fn node(&mut self, id: &str) -> &mut GraphNode {
+		let i = *self.index.entry(id.to_string()).or_insert_with(|| {
+			self.data.nodes.push(GraphNode {
+				id: id.to_string(),
+				..Default::default()
+			});
+			self.data.nodes.len() - 1
+		});
+		&mut self.data.nodes[i]
+	}
+
+	fn parse(mut self) -> GraphData {
+		while let Some(token) = self.peek().cloned() {
+			let Token::Id(id) = token else {
+				self.pos += 1;
+				continue;
+			};
+
+			if is_keyword(&id, "strict") {
+				self.pos += 1;
+			} else if ["graph", "digraph", "subgraph"]
+				.iter()
+				.any(|k| is_keyword(&id, k))
+				&& self.peek_at(1) != Some(&Token::LBracket)
+			{
+				// Header or subgraph: skip the optional name; braces are flattened
+				self.pos += 1;
+				if matches!(self.peek(), Some(Token::Id(_))) {
+					self.pos += 1;
+				}
+			} else if ["graph", "node", "edge"].iter().any(|k| is_keyword(&id, k)) {
+				// Default attribute statement
+				self.pos += 1;
+				self.attr_lists();
+			} else if self.peek_at(1) == Some(&Token::Equals) {
+				// Graph attribute assignment
+				self.pos += 3;
+			} else {
+				self.statement();
+			}
+		}
+		self.data
+	}
+
+	/// Parse a node statement or an edge chain starting at a node ID.
+	fn statement(&mut self) {
+		let Some(first) = self.node_id() else {
+			return;
+		};
+		let mut chain = vec![(first, true)];
+		while let Some(&Token::Edge(directed)) = self.peek() {
+			self.pos += 1;
+			match self.node_id() {
+				Some(id) => chain.push((id, directed)),
+				None => break,
+			}
+		}
+		let attrs = self.attr_lists();
+
+		if chain.len() == 1 {
+			let node = self.node(&chain[0].0);
+			for (key, value) in attrs {
+				match key.as_str() {
+					"label" => node.label = Some(value),
+					// HSV triples and color lists aren't valid CSS
+					"color" if !value.contains([' ', ',', ':']) => node.color = Some(value),
+					_ => {}
+				}
+			}
+			return;
+		}
+
+		let mut style = None;
+		let mut weight = None;
+		for (key, value) in &attrs {
+			match key.as_str() {
+				"style" => {
+					style = match value.as_str() {
+						"solid" => Some(LinkStyle::Solid),
+						"dashed" => Some(LinkStyle::Dashed),
+						"dotted" => Some(LinkStyle::Dotted),
+						_ => style,
+					}
+				}
+				"weight" => weight = value.parse().ok(),
+				_ => {}
+			}
+		}
+		for (id, _) in &chain {
+			self.node(id);
+		}
+		for pair in chain.windows(2) {
+			self.data.links.push(GraphLink {
+				source: pair[0].0.clone(),
+				target: pair[1].0.clone(),
+				style,
+				weight,
+				directed: pair[1].1,
+				..Default::default()
+			});
+		}
+	}
+}
+
+impl GraphData {
+	/// Parse a Graphviz DOT document.
+	///
+	/// Supports the subset of DOT needed to recover graph structure:
+	///
+	/// - `graph`/`digraph` bodies, optionally `strict` and named
+	/// - Node statements: `a [label="A", color=red]`
+	/// - Edge chains: `a -> b -> c [style=dashed]`; `--` makes the links
+	///   undirected
+	/// - Node attributes `label` and `color`; edge attributes `style`
+	///   (`solid`, `dashed`, `dotted`) and `weight`
+	/// - IDs as bare words, numerals, or double-quoted strings
+	/// - `//`, `/* */` and `#` comments
+	///
+	/// Everything else is skipped rather than rejected: `graph`/`node`/`edge`
+	/// default attribute statements, `key = value` graph attributes, ports,
+	/// and HTML labels. Subgraph braces are flattened, so their contents are
+	/// kept, but subgraphs used as edge endpoints (`a -> {b c}`) are not
+	/// expanded. Nodes are created in order of first mention.
+	pub fn from_dot(input: &str) -> GraphData {
+		Parser {
+			tokens: tokenize(input),
+			pos: 0,
+			data: GraphData::default(),
+			index: HashMap::new(),
+		}
+		.parse()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn ids(data: &GraphData) -> Vec<&str> {
+		data.nodes.iter().map(|n| n.id.as_str()).collect()
+	}
+
+	fn links(data: &GraphData) -> Vec<(&str, &str, bool)> {
+		data.links
+			.iter()
+			.map(|l| (l.source.as_str(), l.target.as_str(), l.directed))
+			.collect()
+	}
+
+	#[test]
+	fn edge_chains_link_each_step() {
+		let data = GraphData::from_dot("digraph { a -> b -> c [style=dashed, weight=2]; d }");
+		assert_eq!(ids(&data), ["a", "b", "c", "d"]);
+		assert_eq!(links(&data), [("a", "b", true), ("b", "c", true)]);
+		for link in &data.links {
+			assert_eq!(link.style, Some(LinkStyle::Dashed));
+			assert_eq!(link.weight, Some(2.0));
+		}
+	}
+
+	#[test]
+	fn dashes_make_links_undirected() {
+		let data = GraphData::from_dot("graph { a -- b; b -> c -- a }");
+		assert_eq!(
+			links(&data),
+			[("a", "b", false), ("b", "c", true), ("c", "a", false)]
+		);
+	}
+
+	#[test]
+	fn comments_are_dropped() {
+		let data = GraphData::from_dot(
+			"# generated\ndigraph {\n  a -> b // not -> c\n  /* d -> e */ b -> f\n}",
+		);
+		assert_eq!(ids(&data), ["a", "b", "f"]);
+		assert_eq!(links(&data), [("a", "b", true), ("b", "f", true)]);
+	}
+
+	#[test]
+	fn quoted_ids_keep_their_text() {
+		let data = GraphData::from_dot(
+			r#"digraph { "two words" -> "say \"hi\"" ["label"="x"]; "a" [label="A"] }"#,
+		);
+		assert_eq!(ids(&data), ["two words", "say \"hi\"", "a"]);
+		assert_eq!(data.nodes[2].label.as_deref(), Some("A"));
+	}
+
+	#[test]
+	fn unsupported_statements_are_skipped() {
+		let data = GraphData::from_dot(
+			"strict digraph G {\n\
+			 rankdir = LR;\n\
+			 node [shape=box];\n\
+			 edge [color=gray];\n\
+			 subgraph cluster_0 { a:n -> b:s:e }\n\
+			 c [label=<<b>C</b>>, color=\"0.5 0.5 0.5\", shape=circle]\n\
+			 }",
+		);
+		assert_eq!(ids(&data), ["a", "b", "c"]);
+		assert_eq!(links(&data), [("a", "b", true)]);
+		assert_eq!(data.nodes[2].label.as_deref(), Some("<b>C</b>"));
+		assert_eq!(data.nodes[2].color, None);
+	}
+
+	#[test]
+	fn malformed_input_keeps_what_parses() {
+		let data = GraphData::from_dot("digraph { a -> ; b -> c [weight=heavy, style=wavy");
+		assert_eq!(ids(&data), ["a", "b", "c"]);
+		assert_eq!(links(&data), [("b", "c", true)]);
+		assert_eq!(data.links[0].weight, None);
+		assert_eq!(data.links[0].style, None);
+		assert!(GraphData::from_dot("").nodes.is_empty());
+		assert!(GraphData::from_dot("}}} -> [[ =").links.is_empty());
+	}
+}
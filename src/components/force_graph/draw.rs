@@ -0,0 +1,499 @@
+//! Drawing surface abstraction for the renderer.
+//!
+//! [`DrawContext`] is the subset of the Canvas 2D API the renderer uses. The
+//! browser path implements it for [`CanvasRenderingContext2d`];
+//! [`RecordingContext`] logs every call instead, so a frame can be rendered
+//! without a browser and compared against a stored snapshot.
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use web_sys::{CanvasGradient, CanvasRenderingContext2d, HtmlImageElement};
+
+use super::render::{self, RenderCache};
+use super::scale::ScaleConfig;
+use super::seed::SeedStrategy;
+use super::state::ForceGraphState;
+use super::theme::Theme;
+use super::types::GraphData;
+
+/// A color gradient created by a [`DrawContext`].
+pub trait DrawGradient {
+	/// Add a CSS `color` at `offset` (0.0 to 1.0) along the gradient.
+	fn add_color_stop(&self, offset: f64, color: &str);
+}
+
+/// The drawing operations the renderer needs, mirroring the Canvas 2D API.
+///
+/// Coordinates and angles follow `CanvasRenderingContext2D`; errors from the
+/// underlying canvas are ignored, as the renderer has no way to recover.
+#[allow(
+	missing_docs,
+	reason = "each method mirrors the Canvas 2D method of the same name"
+)]
+pub trait DrawContext {
+	/// Gradient type this context creates and fills or strokes with.
+	type Gradient: DrawGradient;
+
+	fn save(&self);
+	fn restore(&self);
+	fn translate(&self, x: f64, y: f64);
+	fn scale(&self, x: f64, y: f64);
+
+	fn begin_path(&self);
+	fn close_path(&self);
+	fn move_to(&self, x: f64, y: f64);
+	fn line_to(&self, x: f64, y: f64);
+	fn quadratic_curve_to(&self, cpx: f64, cpy: f64, x: f64, y: f64);
+	fn arc(&self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64);
+	fn fill(&self);
+	fn stroke(&self);
+	fn fill_rect(&self, x: f64, y: f64, w: f64, h: f64);
+	fn clear_rect(&self, x: f64, y: f64, w: f64, h: f64);
+	fn stroke_rect(&self, x: f64, y: f64, w: f64, h: f64);
+	fn draw_image(&self, image: &HtmlImageElement, x: f64, y: f64, w: f64, h: f64);
+
+	fn set_fill_style_str(&self, style: &str);
+	fn set_stroke_style_str(&self, style: &str);
+	fn set_fill_gradient(&self, gradient: &Self::Gradient);
+	fn set_stroke_gradient(&self, gradient: &Self::Gradient);
+	fn set_global_alpha(&self, alpha: f64);
+	fn set_line_width(&self, width: f64);
+	fn set_line_dash(&self, segments: &[f64]);
+	fn set_line_dash_offset(&self, offset: f64);
+	fn set_shadow_color(&self, color: &str);
+	fn set_shadow_blur(&self, blur: f64);
+	fn set_shadow_offset_x(&self, offset: f64);
+	fn set_shadow_offset_y(&self, offset: f64);
+
+	fn set_font(&self, font: &str);
+	fn set_text_align(&self, align: &str);
+	fn fill_text(&self, text: &str, x: f64, y: f64);
+	/// Width of `text` in the current font, in pixels.
+	fn measure_text(&self, text: &str) -> f64;
+
+	fn create_linear_gradient(&self, x0: f64, y0: f64, x1: f64, y1: f64) -> Self::Gradient;
+	fn create_radial_gradient(
+		&self,
+		x0: f64,
+		y0: f64,
+		r0: f64,
+		x1: f64,
+		y1: f64,
+		r1: f64,
+	) -> Self::Gradient;
+}
+
+impl DrawGradient for CanvasGradient {
+	fn add_color_stop(&self, offset: f64, color: &str) {
+		let _ = CanvasGradient::add_color_stop(self, offset as f32, color);
+	}
+}
+
+impl DrawContext for CanvasRenderingContext2d {
+	type Gradient = CanvasGradient;
+
+	fn save(&self) {
+		CanvasRenderingContext2d::save(self);
+	}
+	fn restore(&self) {
+		CanvasRenderingContext2d::restore(self);
+	}
+	fn translate(&self, x: f64, y: f64) {
+		let _ = CanvasRenderingContext2d::translate(self, x, y);
+	}
+	fn scale(&self, x: f64, y: f64) {
+		let _ = CanvasRenderingContext2d::scale(self, x, y);
+	}
+
+	fn begin_path(&self) {
+		CanvasRenderingContext2d::begin_path(self);
+	}
+	fn close_path(&self) {
+		CanvasRenderingContext2d::close_path(self);
+	}
+	fn move_to(&self, x: f64, y: f64) {
+		CanvasRenderingContext2d::move_to(self, x, y);
+	}
+	fn line_to(&self, x: f64, y: f64) {
+		CanvasRenderingContext2d::line_to(self, x, y);
+	}
+	fn quadratic_curve_to(&self, cpx: f64, cpy: f64, x: f64, y: f64) {
+		CanvasRenderingContext2d::quadratic_curve_to(self, cpx, cpy, x, y);
+	}
+	fn arc(&self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) {
+		let _ = CanvasRenderingContext2d::arc(self, x, y, radius, start_angle, end_angle);
+	}
+	fn fill(&self) {
+		CanvasRenderingContext2d::fill(self);
+	}
+	fn stroke(&self) {
+		CanvasRenderingContext2d::stroke(self);
+	}
+	fn fill_rect(&self, x: f64, y: f64, w: f64, h: f64) {
+		CanvasRenderingContext2d::fill_rect(self, x, y, w, h);
+	}
+	fn clear_rect(&self, x: f64, y: f64, w: f64, h: f64) {
+		CanvasRenderingContext2d::clear_rect(self, x, y, w, h);
+	}
+	fn stroke_rect(&self, x: f64, y: f64, w: f64, h: f64) {
+		CanvasRenderingContext2d::stroke_rect(self, x, y, w, h);
+	}
+	fn draw_image(&self, image: &HtmlImageElement, x: f64, y: f64, w: f64, h: f64) {
+		let _ = self.draw_image_with_html_image_element_and_dw_and_dh(image, x, y, w, h);
+	}
+
+	fn set_fill_style_str(&self, style: &str) {
+		CanvasRenderingContext2d::set_fill_style_str(self, style);
+	}
+	fn set_stroke_style_str(&self, style: &str) {
+		CanvasRenderingContext2d::set_stroke_style_str(self, style);
+	}
+	fn set_fill_gradient(&self, gradient: &CanvasGradient) {
+		CanvasRenderingContext2d::set_fill_style_canvas_gradient(self, gradient);
+	}
+	fn set_stroke_gradient(&self, gradient: &CanvasGradient) {
+		CanvasRenderingContext2d::set_stroke_style_canvas_gradient(self, gradient);
+	}
+	fn set_global_alpha(&self, alpha: f64) {
+		CanvasRenderingContext2d::set_global_alpha(self, alpha);
+	}
+	fn set_line_width(&self, width: f64) {
+		CanvasRenderingContext2d::set_line_width(self, width);
+	}
+	fn set_line_dash(&self, segments: &[f64]) {
+		let segments: js_sys::Array = segments
+			.iter()
+			.copied()
+			.map(wasm_bindgen::JsValue::from)
+			.collect();
+		let _ = CanvasRenderingContext2d::set_line_dash(self, &segments);
+	}
+	fn set_line_dash_offset(&self, offset: f64) {
+		CanvasRenderingContext2d::set_line_dash_offset(self, offset);
+	}
+	fn set_shadow_color(&self, color: &str) {
+		CanvasRenderingContext2d::set_shadow_color(self, color);
+	}
+	fn set_shadow_blur(&self, blur: f64) {
+		CanvasRenderingContext2d::set_shadow_blur(self, blur);
+	}
+	fn set_shadow_offset_x(&self, offset: f64) {
+		CanvasRenderingContext2d::set_shadow_offset_x(self, offset);
+	}
+	fn set_shadow_offset_y(&self, offset: f64) {
+		CanvasRenderingContext2d::set_shadow_offset_y(self, offset);
+	}
+
+	fn set_font(&self, font: &str) {
+		CanvasRenderingContext2d::set_font(self, font);
+	}
+	fn set_text_align(&self, align: &str) {
+		CanvasRenderingContext2d::set_text_align(self, align);
+	}
+	fn fill_text(&self, text: &str, x: f64, y: f64) {
+		let _ = CanvasRenderingContext2d::fill_text(self, text, x, y);
+	}
+	fn measure_text(&self, text: &str) -> f64 {
+		CanvasRenderingContext2d::measure_text(self, text).map_or(0.0, |m| m.width())
+	}
+
+	fn create_linear_gradient(&self, x0: f64, y0: f64, x1: f64, y1: f64) -> CanvasGradient {
+		CanvasRenderingContext2d::create_linear_gradient(self, x0, y0, x1, y1)
+	}
+	fn create_radial_gradient(
+		&self,
+		x0: f64,
+		y0: f64,
+		r0: f64,
+		x1: f64,
+		y1: f64,
+		r1: f64,
+	) -> CanvasGradient {
+		// Only fails for negative radii, which the renderer never passes
+		CanvasRenderingContext2d::create_radial_gradient(self, x0, y0, r0, x1, y1, r1).unwrap()
+	}
+}
+
+/// A [`DrawContext`] that records each call as a line of text instead of
+/// drawing, for snapshot tests.
+///
+/// Numbers are formatted to two decimals so tiny float differences don't
+/// churn snapshots. Text is measured at a fixed 6 pixels per character.
+#[derive(Clone, Debug, Default)]
+pub struct RecordingContext {
+	log: Rc<RefCell<Vec<String>>>,
+	gradients: Rc<Cell<usize>>,
+}
+
+/// A gradient created by a [`RecordingContext`], logged by number.
+#[derive(Clone, Debug)]
+pub struct RecordedGradient {
+	id: usize,
+	log: Rc<RefCell<Vec<String>>>,
+}
+
+impl RecordingContext {
+	/// An empty recording.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// The calls recorded so far, one per line.
+	pub fn calls(&self) -> Vec<String> {
+		self.log.borrow().clone()
+	}
+
+	/// The recorded calls joined into a single snapshot string.
+	pub fn snapshot(&self) -> String {
+		self.log.borrow().join("\n")
+	}
+
+	fn record(&self, call: String) {
+		self.log.borrow_mut().push(call);
+	}
+
+	fn record_nums(&self, name: &str, nums: &[f64]) {
+		let args: Vec<String> = nums.iter().map(|n| format!("{n:.2}")).collect();
+		self.record(format!("{name}({})", args.join(", ")));
+	}
+
+	fn gradient(&self, kind: &str, nums: &[f64]) -> RecordedGradient {
+		let id = self.gradients.get() + 1;
+		self.gradients.set(id);
+		self.record_nums(&format!("{kind}#{id} = create_{kind}_gradient"), nums);
+		RecordedGradient {
+			id,
+			log: self.log.clone(),
+		}
+	}
+}
+
+impl DrawGradient for RecordedGradient {
+	fn add_color_stop(&self, offset: f64, color: &str) {
+		self.log.borrow_mut().push(format!(
+			"gradient#{}.add_color_stop({offset:.2}, {color})",
+			self.id
+		));
+	}
+}
+
+impl DrawContext for RecordingContext {
+	type Gradient = RecordedGradient;
+
+	fn save(&self) {
+		self.record("save()".into());
+	}
+	fn restore(&self) {
+		self.record("restore()".into());
+	}
+	fn translate(&self, x: f64, y: f64) {
+		self.record_nums("translate", &[x, y]);
+	}
+	fn scale(&self, x: f64, y: f64) {
+		self.record_nums("scale", &[x, y]);
+	}
+
+	fn begin_path(&self) {
+		self.record("begin_path()".into());
+	}
+	fn close_path(&self) {
+		self.record("close_path()".into());
+	}
+	fn move_to(&self, x: f64, y: f64) {
+		self.record_nums("move_to", &[x, y]);
+	}
+	fn line_to(&self, x: f64, y: f64) {
+		self.record_nums("line_to", &[x, y]);
+	}
+	fn quadratic_curve_to(&self, cpx: f64, cpy: f64, x: f64, y: f64) {
+		self.record_nums("quadratic_curve_to", &[cpx, cpy, x, y]);
+	}
+	fn arc(&self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) {
+		self.record_nums("arc", &[x, y, radius, start_angle, end_angle]);
+	}
+	fn fill(&self) {
+		self.record("fill()".into());
+	}
+	fn stroke(&self) {
+		self.record("stroke()".into());
+	}
+	fn fill_rect(&self, x: f64, y: f64, w: f64, h: f64) {
+		self.record_nums("fill_rect", &[x, y, w, h]);
+	}
+	fn clear_rect(&self, x: f64, y: f64, w: f64, h: f64) {
+		self.record_nums("clear_rect", &[x, y, w, h]);
+	}
+	fn stroke_rect(&self, x: f64, y: f64, w: f64, h: f64) {
+		self.record_nums("stroke_rect", &[x, y, w, h]);
+	}
+	fn draw_image(&self, _image: &HtmlImageElement, x: f64, y: f64, w: f64, h: f64) {
+		self.record_nums("draw_image", &[x, y, w, h]);
+	}
+
+	fn set_fill_style_str(&self, style: &str) {
+		self.record(format!("fill_style = {style}"));
+	}
+	fn set_stroke_style_str(&self, style: &str) {
+		self.record(format!("stroke_style = {style}"));
+	}
+	fn set_fill_gradient(&self, gradient: &RecordedGradient) {
+		self.record(format!("fill_style = gradient#{}", gradient.id));
+	}
+	fn set_stroke_gradient(&self, gradient: &RecordedGradient) {
+		self.record(format!("stroke_style = gradient#{}", gradient.id));
+	}
+	fn set_global_alpha(&self, alpha: f64) {
+		self.record(format!("global_alpha = {alpha:.2}"));
+	}
+	fn set_line_width(&self, width: f64) {
+		self.record(format!("line_width = {width:.2}"));
+	}
+	fn set_line_dash(&self, segments: &[f64]) {
+		self.record_nums("set_line_dash", segments);
+	}
+	fn set_line_dash_offset(&self, offset: f64) {
+		self.record(format!("line_dash_offset = {offset:.2}"));
+	}
+	fn set_shadow_color(&self, color: &str) {
+		self.record(format!("shadow_color = {color}"));
+	}
+	fn set_shadow_blur(&self, blur: f64) {
+		self.record(format!("shadow_blur = {blur:.2}"));
+	}
+	fn set_shadow_offset_x(&self, offset: f64) {
+		self.record(format!("shadow_offset_x = {offset:.2}"));
+	}
+	fn set_shadow_offset_y(&self, offset: f64) {
+		self.record(format!("shadow_offset_y = {offset:.2}"));
+	}
+
+	fn set_font(&self, font: &str) {
+		self.record(format!("font = {font}"));
+	}
+	fn set_text_align(&self, align: &str) {
+		self.record(format!("text_align = {align}"));
+	}
+	fn fill_text(&self, text: &str, x: f64, y: f64) {
+		self.record(format!("fill_text({text:?}, {x:.2}, {y:.2})"));
+	}
+	fn measure_text(&self, text: &str) -> f64 {
+		text.chars().count() as f64 * 6.0
+	}
+
+	fn create_linear_gradient(&self, x0: f64, y0: f64, x1: f64, y1: f64) -> RecordedGradient {
+		self.gradient("linear", &[x0, y0, x1, y1])
+	}
+	fn create_radial_gradient(
+		&self,
+		x0: f64,
+		y0: f64,
+		r0: f64,
+		x1: f64,
+		y1: f64,
+		r1: f64,
+	) -> RecordedGradient {
+		self.gradient("radial", &[x0, y0, r0, x1, y1, r1])
+	}
+}
+
+/// Lay out `data` on a `width`×`height` canvas, run `ticks` simulation steps
+/// of 1/60 s and draw one frame to `ctx`, without particles or overlays.
+///
+/// Layout is seeded from `seed` (or the default seed), so the same inputs
+/// always produce the same draw calls.
+pub fn render_headless<C: DrawContext>(
+	data: &GraphData,
+	(width, height): (f64, f64),
+	theme: &Theme,
+	seed: Option<u64>,
+	ticks: usize,
+	ctx: &C,
+) {
+	let mut state = ForceGraphState::new(
+		data,
+		width,
+		height,
+		theme,
+		false,
+		SeedStrategy::default(),
+		seed,
+	);
+	for _ in 0..ticks {
+		state.tick(1.0 / 60.0);
+	}
+	render::render(
+		&state,
+		ctx,
+		&ScaleConfig::default(),
+		theme,
+		None,
+		None,
+		None,
+		&mut RenderCache::default(),
+		None,
+	);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::components::force_graph::types::GraphNode;
+	use crate::components::force_graph::types::fixtures::{link, node};
+
+	/// Two labeled nodes joined by a directed link.
+	fn pair() -> GraphData {
+		let node = |id: &str| GraphNode {
+			label: Some(id.to_uppercase()),
+			group: Some(0),
+			..node(id)
+		};
+		GraphData {
+			nodes: vec![node("a"), node("b")],
+			links: vec![link("a", "b")],
+		}
+	}
+
+	fn record(data: &GraphData, theme: &Theme) -> String {
+		let ctx = RecordingContext::new();
+		render_headless(data, (400.0, 300.0), theme, None, 60, &ctx);
+		ctx.snapshot()
+	}
+
+	#[test]
+	fn headless_render_is_deterministic() {
+		let theme = Theme::default();
+		let first = record(&pair(), &theme);
+		assert!(!first.is_empty());
+		assert_eq!(first, record(&pair(), &theme));
+	}
+
+	#[test]
+	fn transparent_background_clears_instead_of_filling() {
+		let mut theme = Theme::default();
+		theme.background.transparent = true;
+		let calls = record(&pair(), &theme);
+		assert!(calls.starts_with("clear_rect(0.00, 0.00, 400.00, 300.00)"));
+		// Neither the background nor the vignette covers the canvas
+		assert!(!calls.contains("fill_rect(0.00, 0.00, 400.00, 300.00)"));
+	}
+
+	/// Compare against `snapshots/headless_pair.txt`; run with
+	/// `UPDATE_SNAPSHOTS=1` to accept an intended visual change.
+	#[test]
+	fn headless_render_matches_snapshot() {
+		let path = concat!(
+			env!("CARGO_MANIFEST_DIR"),
+			"/src/components/force_graph/snapshots/headless_pair.txt"
+		);
+		let actual = record(&pair(), &Theme::minimal());
+		if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+			std::fs::write(path, &actual).unwrap();
+		}
+		let expected = std::fs::read_to_string(path).unwrap_or_default();
+		assert!(
+			expected == actual,
+			"render output differs from {path}; rerun with UPDATE_SNAPSHOTS=1 if intended"
+		);
+	}
+}
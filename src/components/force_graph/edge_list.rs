@@ -0,0 +1,116 @@
+//! CSV edge-list import for [`GraphData`].
+
+use std::collections::HashSet;
+
+use log::warn;
+
+use super::types::{GraphData, GraphLink, GraphNode};
+
+impl GraphData {
+	/// Parse a CSV edge list of `source,target[,weight]` lines.
+	///
+	/// A node is created for every ID seen, in order of first appearance,
+	/// with no label or color so the theme decides how it looks. Fields are
+	/// trimmed; blank lines and lines starting with `#` are ignored. Set
+	/// `has_header` to skip the first non-blank line.
+	///
+	/// Malformed lines (wrong column count, empty IDs, or a weight that isn't
+	/// a number) are skipped and logged as warnings.
+	pub fn from_edge_list(input: &str, has_header: bool) -> GraphData {
+		let mut data = GraphData::default();
+		let mut seen: HashSet<String> = HashSet::new();
+		let mut skip_header = has_header;
+
+		for (line_no, line) in input.lines().enumerate() {
+			let line = line.trim();
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+			if skip_header {
+				skip_header = false;
+				continue;
+			}
+
+			let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+			let (source, target, weight) = match fields.as_slice() {
+				[s, t] | [s, t, ""] => (*s, *t, None),
+				[s, t, w] => match w.parse::<f64>() {
+					Ok(w) => (*s, *t, Some(w)),
+					Err(_) => {
+						warn!("edge list line {}: invalid weight {w:?}", line_no + 1);
+						continue;
+					}
+				},
+				_ => {
+					warn!(
+						"edge list line {}: expected 2 or 3 columns, found {}",
+						line_no + 1,
+						fields.len()
+					);
+					continue;
+				}
+			};
+			if source.is_empty() || target.is_empty() {
+				warn!("edge list line {}: empty node id", line_no + 1);
+				continue;
+			}
+
+			for id in [source, target] {
+				if seen.insert(id.to_string()) {
+					data.nodes.push(GraphNode {
+						id: id.to_string(),
+						..Default::default()
+					});
+				}
+			}
+			data.links.push(GraphLink {
+				source: source.to_string(),
+				target: target.to_string(),
+				weight,
+				..Default::default()
+			});
+		}
+
+		data
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn links(data: &GraphData) -> Vec<(&str, &str, Option<f64>)> {
+		data.links
+			.iter()
+			.map(|l| (l.source.as_str(), l.target.as_str(), l.weight))
+			.collect()
+	}
+
+	#[test]
+	fn header_row_is_skipped_when_asked() {
+		let input = "\n# exported\nfrom,to\na,b\n";
+		let with = GraphData::from_edge_list(input, true);
+		assert_eq!(links(&with), [("a", "b", None)]);
+		let without = GraphData::from_edge_list(input, false);
+		assert_eq!(links(&without), [("from", "to", None), ("a", "b", None)]);
+	}
+
+	#[test]
+	fn weights_come_from_the_third_column() {
+		let data = GraphData::from_edge_list(" a , b , 2.5\nb,c,\nc,a", false);
+		assert_eq!(
+			links(&data),
+			[("a", "b", Some(2.5)), ("b", "c", None), ("c", "a", None)]
+		);
+		let ids: Vec<_> = data.nodes.iter().map(|n| n.id.as_str()).collect();
+		assert_eq!(ids, ["a", "b", "c"]);
+		assert!(data.links.iter().all(|l| l.directed));
+	}
+
+	#[test]
+	fn malformed_lines_are_skipped() {
+		let data = GraphData::from_edge_list("a\na,b,c,d\na,b,heavy\n,b\na,b\n", false);
+		assert_eq!(links(&data), [("a", "b", None)]);
+		assert_eq!(data.nodes.len(), 2);
+	}
+}
@@ -0,0 +1,264 @@
+//! GraphML import for [`GraphData`].
+
+use std::collections::HashMap;
+
+use roxmltree::{Document, Node};
+
+use super::types::{GraphData, GraphLink, GraphNode};
+
+/// Field a `<data>` value is mapped onto, resolved from its key's name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Field {
+	Label,
+	Color,
+	Group,
+	Weight,
+	Red,
+	Green,
+	Blue,
+	X,
+	Y,
+}
+
+impl Field {
+	fn from_name(name: &str) -> Option<Self> {
+		match name.to_ascii_lowercase().as_str() {
+			"label" | "name" => Some(Field::Label),
+			"color" | "colour" => Some(Field::Color),
+			"group" | "community" | "modularity_class" => Some(Field::Group),
+			"weight" => Some(Field::Weight),
+			// Gephi stores node colors as separate channels
+			"r" => Some(Field::Red),
+			"g" => Some(Field::Green),
+			"b" => Some(Field::Blue),
+			"x" => Some(Field::X),
+			"y" => Some(Field::Y),
+			_ => None,
+		}
+	}
+}
+
+/// Yield each `<data>` child of `element` as `(name, value)`, naming it by
+/// its `<key>` declaration's `attr.name` or, failing that, the raw key id.
+fn data_fields<'a>(
+	element: Node<'a, '_>,
+	key_names: &HashMap<&'a str, &'a str>,
+) -> impl Iterator<Item = (&'a str, &'a str)> {
+	element
+		.children()
+		.filter(|c| c.has_tag_name("data"))
+		.filter_map(move |data| {
+			let key = data.attribute("key")?;
+			let name = key_names.get(key).copied().unwrap_or(key);
+			Some((name, data.text().unwrap_or("").trim()))
+		})
+}
+
+impl GraphData {
+	/// Parse a GraphML document, as exported by Gephi, NetworkX or yEd.
+	///
+	/// Reads every `<node>` and `<edge>` in the first `<graph>`, including
+	/// nested subgraphs. `<data>` values are mapped by their key's
+	/// `attr.name`, case-insensitively:
+	///
+	/// - `label`/`name` → [`GraphNode::label`]
+	/// - `color`, or Gephi's `r`/`g`/`b` channels → [`GraphNode::color`]
+	/// - `group`/`community`/`modularity_class` → [`GraphNode::group`]
+	/// - `x`/`y` → [`GraphNode::position`], when both are numbers
+	/// - `weight` → [`GraphLink::weight`]
+	///
+	/// Other node keys are kept in [`GraphNode::metadata`] under their name;
+	/// other edge keys are dropped. Edges follow the graph's `edgedefault`
+	/// unless they set `directed` themselves, and edges referencing undeclared
+	/// nodes create them. Fails only if the input isn't well-formed XML.
+	pub fn from_graphml(input: &str) -> Result<GraphData, roxmltree::Error> {
+		let doc = Document::parse(input)?;
+		let root = doc.root_element();
+
+		let key_names: HashMap<&str, &str> = root
+			.children()
+			.filter(|c| c.has_tag_name("key"))
+			.filter_map(|key| Some((key.attribute("id")?, key.attribute("attr.name")?)))
+			.collect();
+
+		let mut data = GraphData::default();
+		let Some(graph) = root.children().find(|c| c.has_tag_name("graph")) else {
+			return Ok(data);
+		};
+		let directed_default = graph.attribute("edgedefault") != Some("undirected");
+
+		let mut index: HashMap<String, usize> = HashMap::new();
+		let mut edges = Vec::new();
+		for element in graph.descendants() {
+			if element.has_tag_name("edge") {
+				edges.push(element);
+				continue;
+			}
+			if !element.has_tag_name("node") {
+				continue;
+			}
+			let Some(id) = element.attribute("id") else {
+				continue;
+			};
+
+			let mut node = GraphNode {
+				id: id.to_string(),
+				..Default::default()
+			};
+			let mut rgb = [None; 3];
+			let mut xy = [None; 2];
+			for (name, value) in data_fields(element, &key_names) {
+				match Field::from_name(name) {
+					Some(Field::Label) => node.label = Some(value.to_string()),
+					Some(Field::Color) => node.color = Some(value.to_string()),
+					Some(Field::Group) => node.group = value.parse().ok(),
+					Some(Field::Red) => rgb[0] = value.parse::<u8>().ok(),
+					Some(Field::Green) => rgb[1] = value.parse::<u8>().ok(),
+					Some(Field::Blue) => rgb[2] = value.parse::<u8>().ok(),
+					Some(Field::X) => xy[0] = value.parse::<f64>().ok(),
+					Some(Field::Y) => xy[1] = value.parse::<f64>().ok(),
+					Some(Field::Weight) | None => {
+						node.metadata.insert(name.to_string(), value.to_string());
+					}
+				}
+			}
+			if node.color.is_none()
+				&& let [Some(r), Some(g), Some(b)] = rgb
+			{
+				node.color = Some(format!("rgb({r}, {g}, {b})"));
+			}
+			if let [Some(x), Some(y)] = xy {
+				node.position = Some((x, y));
+			}
+
+			if let Some(&i) = index.get(id) {
+				data.nodes[i] = node;
+			} else {
+				index.insert(id.to_string(), data.nodes.len());
+				data.nodes.push(node);
+			}
+		}
+
+		for edge in edges {
+			let (Some(source), Some(target)) = (edge.attribute("source"), edge.attribute("target"))
+			else {
+				continue;
+			};
+			for id in [source, target] {
+				if !index.contains_key(id) {
+					index.insert(id.to_string(), data.nodes.len());
+					data.nodes.push(GraphNode {
+						id: id.to_string(),
+						..Default::default()
+					});
+				}
+			}
+
+			let weight = data_fields(edge, &key_names)
+				.find(|(name, _)| Field::from_name(name) == Some(Field::Weight))
+				.and_then(|(_, value)| value.parse().ok());
+			let directed = match edge.attribute("directed") {
+				Some(d) => d == "true",
+				None => directed_default,
+			};
+			data.links.push(GraphLink {
+				source: source.to_string(),
+				target: target.to_string(),
+				weight,
+				directed,
+				..Default::default()
+			});
+		}
+
+		Ok(data)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// A document declaring Gephi-style keys around `graph`.
+	fn document(graph: &str) -> String {
+		format!(
+			r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="d0" for="node" attr.name="label" attr.type="string"/>
+  <key id="d1" for="node" attr.name="modularity_class" attr.type="int"/>
+  <key id="d2" for="node" attr.name="r" attr.type="int"/>
+  <key id="d3" for="node" attr.name="g" attr.type="int"/>
+  <key id="d4" for="node" attr.name="b" attr.type="int"/>
+  <key id="d5" for="node" attr.name="url" attr.type="string"/>
+  <key id="d6" for="edge" attr.name="Weight" attr.type="double"/>
+  {graph}
+</graphml>"#
+		)
+	}
+
+	#[test]
+	fn keys_resolve_by_attribute_name() {
+		let data = GraphData::from_graphml(&document(
+			r#"<graph edgedefault="directed">
+    <node id="a">
+      <data key="d0">Alpha</data>
+      <data key="d1">3</data>
+      <data key="d2">255</data><data key="d3">128</data><data key="d4">0</data>
+    </node>
+    <node id="b"><data key="d2">255</data></node>
+    <edge source="a" target="b"><data key="d6">2.5</data></edge>
+  </graph>"#,
+		))
+		.unwrap();
+		let a = &data.nodes[0];
+		assert_eq!(a.label.as_deref(), Some("Alpha"));
+		assert_eq!(a.group, Some(3));
+		assert_eq!(a.color.as_deref(), Some("rgb(255, 128, 0)"));
+		// One channel alone isn't a color
+		assert_eq!(data.nodes[1].color, None);
+		assert_eq!(data.links[0].weight, Some(2.5));
+	}
+
+	#[test]
+	fn edges_follow_the_default_direction() {
+		let graph = |default: &str| {
+			GraphData::from_graphml(&document(&format!(
+				r#"<graph edgedefault="{default}">
+    <edge source="a" target="b"/>
+    <edge source="b" target="c" directed="true"/>
+    <edge source="c" target="a" directed="false"/>
+  </graph>"#
+			)))
+			.unwrap()
+		};
+		let directed = |data: &GraphData| data.links.iter().map(|l| l.directed).collect::<Vec<_>>();
+		assert_eq!(directed(&graph("undirected")), [false, true, false]);
+		assert_eq!(directed(&graph("directed")), [true, true, false]);
+		// Edges to undeclared nodes create them
+		let ids: Vec<_> = graph("directed").nodes.into_iter().map(|n| n.id).collect();
+		assert_eq!(ids, ["a", "b", "c"]);
+	}
+
+	#[test]
+	fn other_node_keys_land_in_metadata() {
+		let data = GraphData::from_graphml(&document(
+			r#"<graph>
+    <node id="a">
+      <data key="d5">https://example.com/a</data>
+      <data key="undeclared">raw</data>
+    </node>
+  </graph>"#,
+		))
+		.unwrap();
+		let metadata = &data.nodes[0].metadata;
+		assert_eq!(metadata["url"], "https://example.com/a");
+		assert_eq!(metadata["undeclared"], "raw");
+		assert_eq!(metadata.len(), 2);
+	}
+
+	#[test]
+	fn only_malformed_xml_fails() {
+		assert!(GraphData::from_graphml("<graphml><graph>").is_err());
+		let empty = GraphData::from_graphml("<graphml/>").unwrap();
+		assert!(empty.nodes.is_empty() && empty.links.is_empty());
+	}
+}
@@ -0,0 +1,72 @@
+//! Convex hull geometry for group overlays.
+
+use std::f64::consts::TAU;
+
+/// Number of points used to approximate the padding circle around each member.
+const PAD_SEGMENTS: usize = 12;
+
+/// Convex hull of a point set in counter-clockwise order (Andrew's monotone chain).
+///
+/// Collinear points are dropped. Returns the input unchanged for fewer than
+/// three points.
+pub fn convex_hull(points: &[(f64, f64)]) -> Vec<(f64, f64)> {
+	if points.len() < 3 {
+		return points.to_vec();
+	}
+
+	let mut sorted = points.to_vec();
+	sorted.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+
+	let cross = |o: (f64, f64), a: (f64, f64), b: (f64, f64)| {
+		(a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+	};
+
+	let mut hull: Vec<(f64, f64)> = Vec::with_capacity(sorted.len() * 2);
+	// Lower hull, then upper hull
+	for pass in [
+		sorted.as_slice(),
+		&sorted.iter().rev().copied().collect::<Vec<_>>(),
+	] {
+		let start = hull.len();
+		for &p in pass {
+			while hull.len() >= start + 2
+				&& cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0
+			{
+				hull.pop();
+			}
+			hull.push(p);
+		}
+		// Last point of each pass is the first point of the next
+		hull.pop();
+	}
+	hull
+}
+
+/// Convex hull of the given points, each expanded to a circle of radius `padding`.
+///
+/// This approximates the Minkowski sum of the hull with a disc, giving a
+/// rounded outline that keeps `padding` distance from every member.
+pub fn padded_hull(points: &[(f64, f64)], padding: f64) -> Vec<(f64, f64)> {
+	let expanded: Vec<(f64, f64)> = points
+		.iter()
+		.flat_map(|&(x, y)| {
+			(0..PAD_SEGMENTS).map(move |i| {
+				let angle = i as f64 * TAU / PAD_SEGMENTS as f64;
+				(x + padding * angle.cos(), y + padding * angle.sin())
+			})
+		})
+		.collect();
+	convex_hull(&expanded)
+}
+
+/// Arithmetic mean of a point set.
+pub fn centroid(points: &[(f64, f64)]) -> Option<(f64, f64)> {
+	if points.is_empty() {
+		return None;
+	}
+	let n = points.len() as f64;
+	let (sx, sy) = points
+		.iter()
+		.fold((0.0, 0.0), |(sx, sy), &(x, y)| (sx + x, sy + y));
+	Some((sx / n, sy / n))
+}
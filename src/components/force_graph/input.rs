@@ -0,0 +1,406 @@
+//! Pointer and keyboard input applied to a [`GraphContext`].
+//!
+//! DOM handlers translate browser events into canvas-relative [`Input`]s so
+//! the same handling runs on the main thread or, in worker mode, inside the
+//! worker that owns the graph.
+
+use super::collapse::meta_group;
+use super::component::GraphContext;
+use super::state::{MAX_ZOOM, MIN_ZOOM, SelectBox, ViewTransform};
+use super::types::{NodeEvent, PointerAction};
+
+/// Maximum pointer travel in pixels between press and release for a click.
+const CLICK_TOLERANCE: f64 = 4.0;
+
+/// Zoom per pixel of wheel delta, as a power of e.
+const WHEEL_ZOOM_RATE: f64 = 0.002;
+
+/// A user interaction, in canvas pixel coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) enum Input {
+	/// Mouse `button` pressed, numbered as in `MouseEvent.button`; acts by
+	/// its [`MouseBindings`](super::MouseBindings). A left press with
+	/// `shift` toggles the node under it in the selection.
+	Down {
+		x: f64,
+		y: f64,
+		shift: bool,
+		button: i16,
+	},
+	/// Pointer moved.
+	Move { x: f64, y: f64 },
+	/// Pointer released.
+	Up { x: f64, y: f64 },
+	/// Pointer left the canvas.
+	Leave,
+	/// Double click; expands a collapsed group's meta-node.
+	DoubleClick { x: f64, y: f64 },
+	/// Wheel or trackpad scrolled by `(delta_x, delta_y)` pixels; zooms
+	/// around the pointer when `zoom` is set and pans otherwise.
+	Wheel {
+		x: f64,
+		y: f64,
+		delta_x: f64,
+		delta_y: f64,
+		zoom: bool,
+	},
+	/// Move keyboard focus to the neighbor in direction `(dx, dy)`.
+	Focus { dx: f64, dy: f64 },
+	/// Click the focused node.
+	Activate,
+	/// Clear keyboard focus.
+	Blur,
+	/// Turn the fisheye lens on or off, if it's enabled.
+	ToggleFisheye,
+	/// Undo the latest layout edit.
+	Undo,
+	/// Redo the latest undone layout edit.
+	Redo,
+}
+
+impl Input {
+	/// The keyboard input for a `KeyboardEvent.key` value, if the canvas
+	/// handles that key.
+	pub(super) fn from_key(key: &str) -> Option<Self> {
+		Some(match key {
+			"ArrowLeft" => Self::Focus { dx: -1.0, dy: 0.0 },
+			"ArrowRight" => Self::Focus { dx: 1.0, dy: 0.0 },
+			"ArrowUp" => Self::Focus { dx: 0.0, dy: -1.0 },
+			"ArrowDown" => Self::Focus { dx: 0.0, dy: 1.0 },
+			"Enter" => Self::Activate,
+			"Escape" => Self::Blur,
+			"f" | "F" => Self::ToggleFisheye,
+			_ => return None,
+		})
+	}
+
+	/// The input for a `KeyboardEvent.key` value pressed with Ctrl or Cmd,
+	/// if the canvas handles that shortcut.
+	pub(super) fn from_shortcut(key: &str, shift: bool) -> Option<Self> {
+		Some(match key {
+			"z" | "Z" if shift => Self::Redo,
+			"z" | "Z" => Self::Undo,
+			"y" | "Y" => Self::Redo,
+			_ => return None,
+		})
+	}
+}
+
+/// What an [`Input`] changed, reported once the graph is no longer borrowed
+/// so callbacks may safely touch it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(super) struct InputOutcome {
+	/// New camera, when the input panned or zoomed the view.
+	pub(super) moved: Option<ViewTransform>,
+	/// Newly hovered node, or `Some(None)` when the pointer left all nodes.
+	pub(super) hovered: Option<Option<NodeEvent>>,
+	/// Node clicked, or activated from the keyboard.
+	pub(super) clicked: Option<NodeEvent>,
+	/// Screen reader text for a focus change; empty when focus was cleared.
+	pub(super) announce: Option<String>,
+	/// CSS cursor to show over the canvas, after pointer input.
+	pub(super) cursor: Option<&'static str>,
+	/// Collapsed group whose meta-node was double-clicked, to expand.
+	pub(super) expand: Option<u32>,
+}
+
+impl GraphContext {
+	/// Apply `input` to the graph.
+	pub(super) fn handle(&mut self, input: Input) -> InputOutcome {
+		let mut out = InputOutcome::default();
+		if self.frozen {
+			return out;
+		}
+		// Taking hold of the camera stops any animated move
+		if matches!(
+			input,
+			Input::Down { .. } | Input::Wheel { .. } | Input::Focus { .. }
+		) {
+			self.state.camera_tween = None;
+		}
+		match input {
+			Input::Down {
+				x,
+				y,
+				shift,
+				button,
+			} => {
+				if let Some(ref mut m) = self.minimap
+					&& button == 0 && m.contains(&self.state, x, y)
+				{
+					m.dragging = true;
+					m.center_view_at(&mut self.state, x, y);
+					out.moved = Some(self.state.transform.clone());
+				} else if let Some(binding) = self.state.mouse.for_button(button) {
+					let node = self.state.node_at_position(x, y, &self.scale);
+					let action = match node {
+						Some(_) => binding.node,
+						None => binding.background,
+					};
+					match (node, action) {
+						(_, PointerAction::Ignore) | (None, PointerAction::MoveNode) => {}
+						(Some(idx), _) if shift && button == 0 => self.state.toggle_selected(idx),
+						(Some(idx), PointerAction::MoveNode) if self.state.draggable => {
+							self.state.reheat();
+							self.state.begin_drag(idx, x, y);
+						}
+						(_, PointerAction::Select) => {
+							self.state.select_box = Some(SelectBox {
+								start_x: x,
+								start_y: y,
+								x,
+								y,
+								additive: shift,
+								node,
+							});
+						}
+						_ => {
+							self.state.pan.active = true;
+							self.state.pan.start_x = x;
+							self.state.pan.start_y = y;
+							self.state.pan.transform_start_x = self.state.transform.x;
+							self.state.pan.transform_start_y = self.state.transform.y;
+							self.state.pan.node = node;
+						}
+					}
+				}
+			}
+			Input::Move { x, y } => {
+				self.state.pointer = Some((x, y));
+				if let Some(ref mut m) = self.minimap
+					&& m.dragging
+				{
+					m.center_view_at(&mut self.state, x, y);
+					out.moved = Some(self.state.transform.clone());
+				}
+				let over_minimap = self
+					.minimap
+					.as_ref()
+					.is_some_and(|m| m.dragging || m.contains(&self.state, x, y));
+
+				// Update hover state when not dragging; the minimap hides nodes
+				// beneath it from hit-testing
+				if !self.state.drag.active {
+					let hovered = if over_minimap {
+						None
+					} else {
+						self.state.node_at_position(x, y, &self.scale)
+					};
+					if self.state.set_hover(hovered) {
+						out.hovered = Some(hovered.and_then(|idx| self.state.node_event(idx)));
+					}
+				}
+
+				if self.state.drag.active {
+					self.state.drag_to(x, y);
+				} else if let Some(ref mut select_box) = self.state.select_box {
+					select_box.x = x;
+					select_box.y = y;
+				} else if self.state.pan.active {
+					let pan = &self.state.pan;
+					self.state.transform.x = pan.transform_start_x + (x - pan.start_x);
+					self.state.transform.y = pan.transform_start_y + (y - pan.start_y);
+					out.moved = Some(self.state.transform.clone());
+				}
+			}
+			Input::Up { x, y } => {
+				if self.state.drag.active
+					&& let Some(idx) = self.state.drag.node_idx
+				{
+					let travel = (x - self.state.drag.start_x).hypot(y - self.state.drag.start_y);
+					if travel <= CLICK_TOLERANCE {
+						out.clicked = self.state.node_event(idx);
+					}
+				}
+				// A click on empty canvas clears the selection; on an undraggable
+				// node it's a node click
+				if self.state.pan.active
+					&& (x - self.state.pan.start_x).hypot(y - self.state.pan.start_y)
+						<= CLICK_TOLERANCE
+				{
+					match self.state.pan.node {
+						Some(idx) => out.clicked = self.state.node_event(idx),
+						None => self.state.selection.clear(),
+					}
+				}
+				if let Some(select_box) = self.state.select_box.take() {
+					let travel = (x - select_box.start_x).hypot(y - select_box.start_y);
+					match select_box.node {
+						Some(idx) if travel <= CLICK_TOLERANCE => {
+							out.clicked = self.state.node_event(idx)
+						}
+						_ => self.state.select_in_box(&select_box),
+					}
+				}
+				self.end_pointer();
+			}
+			Input::Leave => {
+				self.end_pointer();
+				self.state.pointer = None;
+				if self.state.set_hover(None) {
+					out.hovered = Some(None);
+				}
+			}
+			Input::DoubleClick { x, y } => {
+				out.expand = self
+					.state
+					.node_at_position(x, y, &self.scale)
+					.and_then(|idx| self.state.node_event(idx))
+					.and_then(|event| meta_group(&event.id));
+				return out;
+			}
+			Input::Wheel {
+				x,
+				y,
+				delta_x,
+				delta_y,
+				zoom,
+			} => {
+				let t = &mut self.state.transform;
+				if zoom {
+					// In proportion to the delta, so a pinch's stream of small
+					// deltas zooms smoothly, capped at a notch of a mouse wheel
+					let factor = (-delta_y * WHEEL_ZOOM_RATE).exp().clamp(0.9, 1.1);
+					let new_k = (t.k * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+					let ratio = new_k / t.k;
+					t.x = x - (x - t.x) * ratio;
+					t.y = y - (y - t.y) * ratio;
+					t.k = new_k;
+				} else {
+					t.x -= delta_x;
+					t.y -= delta_y;
+				}
+				self.state.mark_dirty();
+				out.moved = Some(self.state.transform.clone());
+				return out;
+			}
+			Input::Focus { dx, dy } => {
+				if let Some(idx) = self.state.move_focus(dx, dy) {
+					self.state.center_on(idx);
+					out.moved = Some(self.state.transform.clone());
+					out.announce = self
+						.state
+						.node_event(idx)
+						.map(|event| event.label.unwrap_or(event.id));
+				}
+				return out;
+			}
+			Input::Activate => {
+				out.clicked = self
+					.state
+					.focused
+					.and_then(|idx| self.state.node_event(idx));
+				return out;
+			}
+			Input::Blur => {
+				self.state.focused = None;
+				self.state.mark_dirty();
+				out.announce = Some(String::new());
+				return out;
+			}
+			Input::Undo => {
+				self.state.undo();
+				return out;
+			}
+			Input::Redo => {
+				self.state.redo();
+				return out;
+			}
+			Input::ToggleFisheye => {
+				if self.state.fisheye.is_some() {
+					self.state.fisheye_on = !self.state.fisheye_on;
+					self.state.mark_dirty();
+				}
+				return out;
+			}
+		}
+		self.state.mark_dirty();
+		out.cursor = Some(self.state.cursor());
+		out
+	}
+
+	/// Drop any drag, pan or minimap drag in progress, recording a node drag
+	/// so it can be undone.
+	fn end_pointer(&mut self) {
+		if self.state.drag.active {
+			self.state.end_drag();
+		}
+		self.state.drag.active = false;
+		self.state.drag.node_idx = None;
+		self.state.drag.followers.clear();
+		self.state.guides.clear();
+		self.state.pan.active = false;
+		self.state.select_box = None;
+		if let Some(ref mut m) = self.minimap {
+			m.dragging = false;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::components::force_graph::annotations::Annotated;
+	use crate::components::force_graph::render::RenderCache;
+	use crate::components::force_graph::scale::ScaleConfig;
+	use crate::components::force_graph::seed::SeedStrategy;
+	use crate::components::force_graph::state::ForceGraphState;
+	use crate::components::force_graph::theme::Theme;
+	use crate::components::force_graph::types::GraphData;
+	use crate::components::force_graph::types::fixtures::{link, node};
+
+	fn context() -> GraphContext {
+		let data = GraphData {
+			nodes: ["a", "b"].map(node).to_vec(),
+			links: vec![link("a", "b")],
+		};
+		let theme = Theme::default();
+		GraphContext {
+			state: ForceGraphState::new(
+				&data,
+				800.0,
+				600.0,
+				&theme,
+				false,
+				SeedStrategy::Circle,
+				None,
+			),
+			scale: ScaleConfig::screen_sized(),
+			theme,
+			particles: None,
+			legend: None,
+			minimap: None,
+			stats: None,
+			cache: RenderCache::default(),
+			background: None,
+			frozen: false,
+			annotated: Annotated::default(),
+		}
+	}
+
+	#[test]
+	fn drag_leaving_the_canvas_is_undoable() {
+		let mut context = context();
+		let state = &mut context.state;
+		state.animation_running = false;
+		let (mut idx, mut start) = (None, (0.0, 0.0));
+		state.graph.visit_nodes(|n| {
+			if n.data.user_data.id == "a" {
+				idx = Some(n.index());
+				start = (n.x(), n.y());
+			}
+		});
+		state.begin_drag(idx.unwrap(), 0.0, 0.0);
+
+		context.handle(Input::Move { x: 40.0, y: 30.0 });
+		context.handle(Input::Leave);
+		let state = &mut context.state;
+		assert!(!state.drag.active && state.drag.followers.is_empty());
+		let a = &state.graph.get_graph()[idx.unwrap()];
+		assert_ne!((a.x(), a.y()), start);
+
+		assert!(state.undo());
+		let a = &state.graph.get_graph()[idx.unwrap()];
+		assert_eq!((a.x(), a.y()), start);
+	}
+}
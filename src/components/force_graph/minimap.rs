@@ -0,0 +1,170 @@
+//! Minimap overlay showing the whole graph and the current viewport.
+//!
+//! Node dots are drawn into a small offscreen canvas that is refreshed only
+//! every few frames; each visible frame blits that cache and overlays the
+//! viewport rectangle, which is cheap enough to keep exact.
+
+use std::f64::consts::PI;
+
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+
+use super::state::ForceGraphState;
+use super::theme::Theme;
+use super::types::Corner;
+
+/// Gap between the minimap and the canvas edges, in pixels.
+const MARGIN: f64 = 12.0;
+/// Inner padding around the graph's bounding box, in minimap pixels.
+const PADDING: f64 = 6.0;
+/// Frames between refreshes of the cached node layer.
+const REFRESH_FRAMES: u32 = 10;
+
+/// Maps world coordinates onto the minimap's local pixel space.
+#[derive(Clone, Copy, Debug)]
+struct Fit {
+	scale: f64,
+	offset_x: f64,
+	offset_y: f64,
+}
+
+impl Fit {
+	fn to_local(self, wx: f64, wy: f64) -> (f64, f64) {
+		(
+			self.offset_x + wx * self.scale,
+			self.offset_y + wy * self.scale,
+		)
+	}
+
+	fn to_world(self, lx: f64, ly: f64) -> (f64, f64) {
+		(
+			(lx - self.offset_x) / self.scale,
+			(ly - self.offset_y) / self.scale,
+		)
+	}
+}
+
+/// Cached minimap renderer and its drag interaction state.
+pub struct Minimap {
+	corner: Corner,
+	width: f64,
+	height: f64,
+	cache: HtmlCanvasElement,
+	cache_ctx: CanvasRenderingContext2d,
+	fit: Fit,
+	frames_until_refresh: u32,
+	/// Whether a drag that started inside the minimap is in progress
+	pub dragging: bool,
+}
+
+impl Minimap {
+	/// Create a `width`×`height` minimap in `corner`, or `None` if the
+	/// offscreen canvas can't be created.
+	pub fn new(corner: Corner, width: f64, height: f64) -> Option<Self> {
+		let cache: HtmlCanvasElement = web_sys::window()?
+			.document()?
+			.create_element("canvas")
+			.ok()?
+			.dyn_into()
+			.ok()?;
+		cache.set_width(width as u32);
+		cache.set_height(height as u32);
+		let cache_ctx = cache.get_context("2d").ok()??.dyn_into().ok()?;
+		Some(Self {
+			corner,
+			width,
+			height,
+			cache,
+			cache_ctx,
+			fit: Fit {
+				scale: 1.0,
+				offset_x: width / 2.0,
+				offset_y: height / 2.0,
+			},
+			frames_until_refresh: 0,
+			dragging: false,
+		})
+	}
+
+	/// Screen-space top-left corner of the minimap.
+	fn origin(&self, state: &ForceGraphState) -> (f64, f64) {
+		self.corner
+			.origin(state.width, state.height, self.width, self.height, MARGIN)
+	}
+
+	/// Whether the screen point lies inside the minimap.
+	pub fn contains(&self, state: &ForceGraphState, sx: f64, sy: f64) -> bool {
+		let (x, y) = self.origin(state);
+		sx >= x && sx <= x + self.width && sy >= y && sy <= y + self.height
+	}
+
+	/// Pan the main view so the world point under the screen position is
+	/// centered on the canvas.
+	pub fn center_view_at(&self, state: &mut ForceGraphState, sx: f64, sy: f64) {
+		let (x, y) = self.origin(state);
+		let (wx, wy) = self.fit.to_world(sx - x, sy - y);
+		state.transform.x = state.width / 2.0 - wx * state.transform.k;
+		state.transform.y = state.height / 2.0 - wy * state.transform.k;
+		state.mark_dirty();
+	}
+
+	/// Draw the minimap onto the main canvas, refreshing the cached node
+	/// layer if it's due.
+	pub fn draw(&mut self, state: &ForceGraphState, ctx: &CanvasRenderingContext2d, theme: &Theme) {
+		if self.frames_until_refresh == 0 {
+			self.refresh(state, theme);
+			self.frames_until_refresh = REFRESH_FRAMES;
+		}
+		self.frames_until_refresh -= 1;
+
+		let (x, y) = self.origin(state);
+		let _ = ctx.draw_image_with_html_canvas_element(&self.cache, x, y);
+
+		// Visible world rect, clipped to the minimap so it stays readable
+		// when zoomed far out
+		let t = &state.transform;
+		let (left, top) = self.fit.to_local(-t.x / t.k, -t.y / t.k);
+		let (right, bottom) = self
+			.fit
+			.to_local((state.width - t.x) / t.k, (state.height - t.y) / t.k);
+		let (l, tp) = (left.max(0.0), top.max(0.0));
+		let (r, b) = (right.min(self.width), bottom.min(self.height));
+		if r > l && b > tp {
+			ctx.set_fill_style_str(&theme.edge.glow_color.with_alpha(0.1).to_css());
+			ctx.fill_rect(x + l, y + tp, r - l, b - tp);
+			ctx.set_stroke_style_str(&theme.edge.glow_color.with_alpha(0.8).to_css());
+			ctx.set_line_width(1.0);
+			ctx.stroke_rect(x + l, y + tp, r - l, b - tp);
+		}
+
+		ctx.set_stroke_style_str(&theme.edge.color.to_css());
+		ctx.set_line_width(1.0);
+		ctx.stroke_rect(x + 0.5, y + 0.5, self.width - 1.0, self.height - 1.0);
+	}
+
+	/// Re-fit the graph's bounding box and redraw the node dots.
+	fn refresh(&mut self, state: &ForceGraphState, theme: &Theme) {
+		if let Some((min_x, min_y, max_x, max_y)) = state.bounding_box() {
+			let (bw, bh) = ((max_x - min_x).max(1.0), (max_y - min_y).max(1.0));
+			let scale = ((self.width - 2.0 * PADDING) / bw).min((self.height - 2.0 * PADDING) / bh);
+			self.fit = Fit {
+				scale,
+				offset_x: self.width / 2.0 - (min_x + bw / 2.0) * scale,
+				offset_y: self.height / 2.0 - (min_y + bh / 2.0) * scale,
+			};
+		}
+
+		let ctx = &self.cache_ctx;
+		ctx.clear_rect(0.0, 0.0, self.width, self.height);
+		ctx.set_fill_style_str(&theme.background.color.with_alpha(0.85).to_css());
+		ctx.fill_rect(0.0, 0.0, self.width, self.height);
+
+		state.graph.visit_nodes(|node| {
+			let (lx, ly) = self.fit.to_local(node.x() as f64, node.y() as f64);
+			ctx.begin_path();
+			let _ = ctx.arc(lx, ly, 1.5, 0.0, 2.0 * PI);
+			ctx.set_fill_style_str(&node.data.user_data.color);
+			ctx.fill();
+		});
+	}
+}
@@ -13,25 +13,67 @@
 //!
 //! let data = GraphData {
 //!     nodes: vec![
-//!         GraphNode { id: "a".into(), label: Some("Node A".into()), .. },
-//!         GraphNode { id: "b".into(), label: Some("Node B".into()), .. },
+//!         GraphNode { id: "a".into(), label: Some("Node A".into()), ..Default::default() },
+//!         GraphNode { id: "b".into(), label: Some("Node B".into()), ..Default::default() },
 //!     ],
 //!     links: vec![
-//!         GraphLink { source: "a".into(), target: "b".into() },
+//!         GraphLink { source: "a".into(), target: "b".into(), ..Default::default() },
 //!     ],
 //! };
 //!
 //! view! { <ForceGraphCanvas data=data.into() fullscreen=true /> }
 //! ```
 
+mod annotations;
+mod backdrop;
+mod bundling;
+mod centrality;
+mod collapse;
+mod community;
 mod component;
+mod controller;
+#[cfg(feature = "dot")]
+mod dot;
+mod draw;
+#[cfg(feature = "edge-list")]
+mod edge_list;
+#[cfg(feature = "graphml")]
+mod graphml;
+mod hull;
+mod input;
+mod minimap;
 mod particles;
 mod render;
+mod rng;
+mod sampling;
 pub mod scale;
+mod seed;
 mod state;
 pub mod theme;
+mod timeline;
 mod types;
+mod webgl;
+mod worker;
 
-pub use component::ForceGraphCanvas;
-pub use theme::Theme;
-pub use types::{GraphData, GraphLink, GraphNode};
+pub use annotations::{Annotation, OffscreenAnnotations};
+pub use centrality::{BETWEENNESS_MAX_NODES, Centrality, ColorBy};
+pub use component::{BackgroundRenderer, ForceGraphCanvas};
+pub use controller::{ExportOptions, ExportRegion, GraphController};
+pub use draw::{DrawContext, DrawGradient, RecordedGradient, RecordingContext, render_headless};
+pub use sampling::{MAX_RENDER_NODES, SampleStrategy, SamplingInfo, UnsampledNodes};
+pub use scale::{
+	AlphaBehavior, ArrowScaleConfig, DetailLevel, EdgeScaleConfig, GlowScaleConfig, LodConfig,
+	NodeScaleConfig, ScaleBehavior, ScaleConfig,
+};
+pub use seed::SeedStrategy;
+pub use state::{DegreeCurve, DegreeSizing, Fisheye, ViewTransform};
+pub use theme::{
+	BackgroundImage, BackgroundPattern, Color, Colormap, HighlightConfig, NodePalette, RingColor,
+	SelectionRing, SelectionStyle, ShadowConfig, SpotlightStyle, Theme, WeightStyle,
+};
+pub use types::{
+	ButtonBinding, Corner, Filter, GraphData, GraphLink, GraphNode, LinkStyle, MouseBindings,
+	NodeEvent, PointerAction, ViewportInfo,
+};
+pub use webgl::RendererKind;
+pub use worker::run_graph_worker;
@@ -2,112 +2,784 @@
 //!
 //! Handles all drawing operations: background, edges, nodes, labels, and effects.
 //! Rendering uses multiple passes for correct z-ordering:
-//! 1. Background and particles (screen space)
+//! 1. Background fill and image, then particles (screen space), then the background pattern
+//!    (world space)
 //! 2. Edge glows, then edge lines (world space)
 //! 3. Node glows, non-highlighted nodes, then highlighted nodes on top
+//!
+//! All drawing goes through [`DrawContext`], so a frame can also be recorded
+//! without a browser.
 
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
 use std::f64::consts::PI;
+use std::rc::Rc;
 
-use wasm_bindgen::JsValue;
-use web_sys::CanvasRenderingContext2d;
+use force_graph::DefaultNodeIdx;
+use web_sys::CanvasGradient;
 
+use super::backdrop::Backdrop;
+use super::bundling::EdgeBundles;
+use super::draw::{DrawContext, DrawGradient};
+use super::hull;
 use super::particles::ParticleSystem;
-use super::scale::{ScaleConfig, ScaledValues};
-use super::state::{ForceGraphState, NodeInfo};
-use super::theme::{Color, Theme};
+use super::sampling::density_cell;
+use super::scale::{AlphaBehavior, DetailLevel, ScaleConfig, ScaledValues};
+use super::state::{
+	DepartingNode, EdgeInfo, ForceGraphState, Heatmap, NodeInfo, SelectBox, ViewTransform,
+};
+use super::theme::{
+	ArrowStyle, BackgroundImage, BackgroundPattern, Color, SelectionRing, SpotlightStyle, Theme,
+	parse_color,
+};
+use super::types::{Corner, LinkStyle};
+
+/// Frames between placements of group labels.
+const GROUP_LABEL_INTERVAL: u32 = 10;
+
+/// Opacity of group labels while no node labels show.
+const GROUP_LABEL_ALPHA: f64 = 0.25;
+
+/// Smallest group label font size, in screen pixels.
+const GROUP_LABEL_MIN_PX: f64 = 16.0;
+
+/// Radius of the hole in a donut node, as a fraction of the node radius.
+const DONUT_HOLE: f64 = 0.55;
+
+/// Minimum curve tension of reciprocal edges, enough to separate the two
+/// directions even under themes with straight edges.
+const RECIPROCAL_TENSION: f64 = 0.5;
+
+/// Smallest on-screen radius of a node badge, in pixels, so it stays legible
+/// when zoomed out.
+const BADGE_MIN_RADIUS: f64 = 6.0;
+
+/// Border width in screen pixels for a node with a border color override
+/// when the theme draws no borders.
+const OVERRIDE_BORDER_WIDTH: f64 = 1.5;
+
+/// Fonts [`TextWidths`] keeps widths for before starting over, bounding its
+/// memory as zooming changes the label font.
+const MAX_MEASURED_FONTS: usize = 8;
+
+/// Screen-space key mapping group colors to names.
+#[derive(Clone, Debug, Default)]
+pub struct Legend {
+	/// `(group, name)` pairs, drawn in order.
+	pub entries: Vec<(u32, String)>,
+	/// Canvas corner the legend box is anchored to.
+	pub corner: Corner,
+	/// Measured entry names
+	pub widths: TextWidths,
+}
+
+/// Text widths keyed by font and text, so text drawn every frame is
+/// measured once.
+#[derive(Clone, Debug, Default)]
+pub struct TextWidths {
+	fonts: RefCell<HashMap<String, HashMap<String, f64>>>,
+}
+
+impl TextWidths {
+	/// Width of `text` in `font`, which `ctx` must already be set to.
+	pub fn measure<C: DrawContext>(&self, ctx: &C, font: &str, text: &str) -> f64 {
+		let mut fonts = self.fonts.borrow_mut();
+		if let Some(&width) = fonts.get(font).and_then(|widths| widths.get(text)) {
+			return width;
+		}
+		if !fonts.contains_key(font) && fonts.len() >= MAX_MEASURED_FONTS {
+			fonts.clear();
+		}
+		let width = ctx.measure_text(text);
+		fonts
+			.entry(font.to_string())
+			.or_default()
+			.insert(text.to_string(), width);
+		width
+	}
+}
+
+/// CSS strings for colors at the alphas drawn, reused across frames.
+///
+/// Alphas are quantized to the 256 levels a canvas can show, so fades reuse
+/// a bounded set of strings.
+#[derive(Clone, Debug, Default)]
+pub struct CssCache {
+	strings: HashMap<[u8; 4], String>,
+}
+
+impl CssCache {
+	/// CSS for `color` with its alpha multiplied by `alpha`.
+	pub fn rgba(&mut self, color: Color, alpha: f64) -> &str {
+		let level = ((color.a * alpha).clamp(0.0, 1.0) * 255.0).round() as u8;
+		self.strings
+			.entry([color.r, color.g, color.b, level])
+			.or_insert_with(|| color.with_alpha(f64::from(level) / 255.0).to_css())
+	}
+}
+
+/// Rolling frame timing shown by the stats overlay.
+#[derive(Clone, Debug, Default)]
+pub struct FrameStats {
+	/// Start times (ms) of the frames within the last second.
+	frames: VecDeque<f64>,
+	/// Time spent ticking and rendering the previous frame, in ms.
+	pub work_ms: f64,
+	/// Part of `work_ms` spent advancing physics, highlights and particles.
+	pub tick_ms: f64,
+	/// Part of `work_ms` spent drawing the canvas and minimap.
+	pub render_ms: f64,
+}
+
+impl FrameStats {
+	/// Record the start of a frame at `now` milliseconds.
+	pub fn record_frame(&mut self, now: f64) {
+		self.frames.push_back(now);
+		while self.frames.front().is_some_and(|&t| t < now - 1000.0) {
+			self.frames.pop_front();
+		}
+	}
+
+	/// Frames per second, averaged over the last second.
+	pub fn fps(&self) -> f64 {
+		match (self.frames.front(), self.frames.back()) {
+			(Some(first), Some(last)) if last > first => {
+				(self.frames.len() - 1) as f64 * 1000.0 / (last - first)
+			}
+			_ => 0.0,
+		}
+	}
+}
+
+/// Canvas objects that are expensive to create, reused across frames.
+pub struct RenderCache<G = CanvasGradient> {
+	edge_gradients: HashMap<(DefaultNodeIdx, DefaultNodeIdx), CachedGradient<G>>,
+	/// Detail tier of the previous frame, for hysteresis
+	detail: DetailLevel,
+	/// Bundled edge paths, when edge bundling is on
+	bundles: Option<EdgeBundles>,
+	/// Where group labels go, when they're drawn
+	group_labels: Option<GroupLabels>,
+	css: CssCache,
+	text: TextWidths,
+	/// Theme background image, shared with exports of the same graph
+	backdrop: Option<Rc<Backdrop>>,
+}
+
+/// A source-to-target edge gradient and the inputs it was built from.
+struct CachedGradient<G> {
+	from: (f64, f64),
+	to: (f64, f64),
+	colors: (String, String),
+	gradient: G,
+}
+
+impl<G> Default for RenderCache<G> {
+	fn default() -> Self {
+		Self {
+			edge_gradients: HashMap::new(),
+			detail: DetailLevel::default(),
+			bundles: None,
+			group_labels: None,
+			css: CssCache::default(),
+			text: TextWidths::default(),
+			backdrop: None,
+		}
+	}
+}
+
+impl<G> RenderCache<G> {
+	/// Turn force-directed edge bundling on or off.
+	pub fn set_bundling(&mut self, enabled: bool) {
+		self.bundles = enabled.then(EdgeBundles::default);
+	}
+
+	/// Whether edge bundles are being recomputed a slice per frame, so
+	/// frames must keep painting until they're done.
+	pub fn is_bundling(&self) -> bool {
+		self.bundles.as_ref().is_some_and(EdgeBundles::is_pending)
+	}
+
+	/// Turn group labels at cluster centroids on or off.
+	pub fn set_group_labels(&mut self, enabled: bool) {
+		self.group_labels = enabled.then(GroupLabels::default);
+	}
+
+	/// Whether group labels are drawn.
+	pub fn has_group_labels(&self) -> bool {
+		self.group_labels.is_some()
+	}
+
+	/// Start loading the theme's background image, if it has one.
+	pub fn load_background_image(&mut self, image: Option<&BackgroundImage>) {
+		self.backdrop = image.and_then(|image| Backdrop::load(&image.url));
+	}
+
+	/// Draw the same background image as `other`, without loading it again.
+	pub fn share_background_image<H>(&mut self, other: &RenderCache<H>) {
+		self.backdrop = other.backdrop.clone();
+	}
+
+	/// Drop the gradients and text measurements made with a rendering
+	/// context that was since lost. The background image is an HTML image,
+	/// not a context resource, so it's kept.
+	pub fn forget_context(&mut self) {
+		self.edge_gradients.clear();
+		self.text = TextWidths::default();
+	}
+
+	/// Whether the background image arrived since the last call, so the
+	/// frame needs repainting.
+	pub fn take_background_loaded(&self) -> bool {
+		self.backdrop.as_ref().is_some_and(|b| b.take_loaded())
+	}
+}
+
+impl<G: DrawGradient> RenderCache<G> {
+	/// Current bundled path of an edge, or `None` when it is drawn unbundled.
+	fn bundle_path(
+		&self,
+		n1: &force_graph::Node<NodeInfo>,
+		n2: &force_graph::Node<NodeInfo>,
+	) -> Option<Vec<(f64, f64)>> {
+		self.bundles.as_ref()?.path(
+			n1.index(),
+			n2.index(),
+			(n1.x() as f64, n1.y() as f64),
+			(n2.x() as f64, n2.y() as f64),
+		)
+	}
+
+	/// Get the gradient for an edge, rebuilding it only when an endpoint has
+	/// moved more than a screen pixel or a node color has changed.
+	fn edge_gradient<C: DrawContext<Gradient = G>>(
+		&mut self,
+		ctx: &C,
+		n1: &force_graph::Node<NodeInfo>,
+		n2: &force_graph::Node<NodeInfo>,
+		k: f64,
+	) -> &G {
+		let from = (n1.x() as f64, n1.y() as f64);
+		let to = (n2.x() as f64, n2.y() as f64);
+		let (c1, c2) = (&n1.data.user_data.color, &n2.data.user_data.color);
+		let moved = |a: (f64, f64), b: (f64, f64)| (a.0 - b.0).hypot(a.1 - b.1) * k > 1.0;
+
+		let key = (n1.index(), n2.index());
+		let stale = self.edge_gradients.get(&key).is_none_or(|cached| {
+			moved(cached.from, from)
+				|| moved(cached.to, to)
+				|| cached.colors.0 != *c1
+				|| cached.colors.1 != *c2
+		});
+
+		if stale {
+			let gradient = ctx.create_linear_gradient(from.0, from.1, to.0, to.1);
+			let tint = |css: &str| parse_color(css).desaturate(0.35).with_alpha(1.0).to_css();
+			gradient.add_color_stop(0.0, &tint(c1));
+			gradient.add_color_stop(1.0, &tint(c2));
+			self.edge_gradients.insert(
+				key,
+				CachedGradient {
+					from,
+					to,
+					colors: (c1.clone(), c2.clone()),
+					gradient,
+				},
+			);
+		}
+
+		&self.edge_gradients[&key].gradient
+	}
+}
 
 /// Attempt to smooth values that would otherwise cause abrupt visual changes.
-fn smooth_step(t: f64) -> f64 {
+pub(super) fn smooth_step(t: f64) -> f64 {
 	t * t * (3.0 - 2.0 * t)
 }
 
+/// Whether the current configuration draws anything that changes every frame
+/// on its own (flowing dashes, particles, pulsing), forcing continuous redraws.
+pub fn is_time_animated(
+	state: &ForceGraphState,
+	config: &ScaleConfig,
+	theme: &Theme,
+	particles: Option<&ParticleSystem>,
+) -> bool {
+	if particles.is_some() || theme.node.pulse_intensity > 0.0 {
+		return true;
+	}
+	let flow = &theme.edge.flow_particles;
+	if flow.enabled
+		&& flow.count > 0
+		&& (!flow.highlighted_only || state.highlight.max_intensity() > 0.01)
+	{
+		return true;
+	}
+	if config.edge.flow_speed == 0.0 {
+		return false;
+	}
+
+	// Flowing dashes: unstyled edges only dash when zoomed in far enough,
+	// explicitly dashed or dotted links always do
+	if state.has_dashed_edges {
+		return true;
+	}
+	let scale = ScaledValues::new(config, state.transform.k);
+	state.has_unstyled_edges && scale.dash_pattern.1 * scale.dash_alpha > 0.1
+}
+
+/// App drawing under the graph, given the camera and canvas size.
+pub(super) type BackgroundHook<'a, C> = &'a dyn Fn(&C, &ViewTransform, f64, f64);
+
 /// Renders the complete graph to the canvas.
-pub fn render(
+///
+/// `background` is called with the camera and canvas size once the theme
+/// background is filled, in screen space and with the context saved.
+#[allow(clippy::too_many_arguments)]
+pub fn render<C: DrawContext>(
 	state: &ForceGraphState,
-	ctx: &CanvasRenderingContext2d,
+	ctx: &C,
 	config: &ScaleConfig,
 	theme: &Theme,
 	particles: Option<&ParticleSystem>,
+	legend: Option<&Legend>,
+	stats: Option<&FrameStats>,
+	cache: &mut RenderCache<C::Gradient>,
+	background: Option<BackgroundHook<'_, C>>,
 ) {
-	let scale = ScaledValues::new(config, state.transform.k);
+	let k = state.transform.k;
+	cache.detail = config.lod.level(cache.detail, k, state.drawn_node_count());
+	let mut scale = ScaledValues::new(config, k).with_detail(cache.detail);
+	scale.shadows = config.lod.shadows(cache.detail, state.drawn_node_count());
 
 	draw_background(state, ctx, theme);
+	if let Some(style) = &theme.background.image
+		&& let Some((image, w, h)) = cache.backdrop.as_deref().and_then(Backdrop::image)
+	{
+		draw_background_image(state, ctx, style, image, (w, h));
+	}
+	if let Some(draw) = background {
+		ctx.save();
+		draw(ctx, &state.transform, state.width, state.height);
+		ctx.restore();
+	}
 
 	if let Some(ps) = particles {
 		draw_particles(state, ctx, theme, ps);
 	}
 
 	ctx.save();
-	let _ = ctx.translate(state.transform.x, state.transform.y);
-	let _ = ctx.scale(state.transform.k, state.transform.k);
+	ctx.translate(state.transform.x, state.transform.y);
+	ctx.scale(state.transform.k, state.transform.k);
 
-	draw_edges(state, ctx, config, &scale, theme);
-	draw_nodes(state, ctx, config, &scale, theme);
+	if theme.background.pattern != BackgroundPattern::None {
+		draw_background_pattern(state, ctx, theme);
+	}
+	if theme.hulls.enabled {
+		draw_hulls(state, ctx, &scale, theme);
+	}
+	if let Some(ref mut labels) = cache.group_labels {
+		draw_group_labels(state, ctx, &scale, theme, labels);
+	}
+	draw_unsampled(state, ctx, theme);
+	draw_edges(state, ctx, config, &scale, theme, cache);
+	draw_nodes(state, ctx, config, &scale, theme, &mut cache.css);
+	if theme.edge.weighted.is_some_and(|w| w.labels) && scale.full_detail() {
+		draw_weight_labels(state, ctx, &scale, theme, &mut cache.css, &cache.text);
+	}
+	draw_align_guides(state, ctx, &scale, theme);
 
 	ctx.restore();
 
-	if theme.background.vignette > 0.0 {
+	if theme.background.vignette > 0.0 && !theme.background.transparent {
 		draw_vignette(state, ctx, theme);
 	}
-}
+	if theme.background.spotlight.enabled
+		&& let Some(center) = state.spotlight.center
+	{
+		draw_spotlight(state, ctx, &theme.background.spotlight, center);
+	}
+	if let Some(ref select_box) = state.select_box {
+		draw_select_box(ctx, theme, select_box);
+	}
 
-fn draw_background(state: &ForceGraphState, ctx: &CanvasRenderingContext2d, theme: &Theme) {
-	if theme.background.use_gradient {
-		let gradient = ctx
-			.create_radial_gradient(
-				state.width / 2.0,
-				state.height / 2.0,
-				0.0,
-				state.width / 2.0,
-				state.height / 2.0,
-				(state.width.max(state.height)) * 0.8,
-			)
-			.unwrap();
+	if let Some(legend) = legend.filter(|l| !l.entries.is_empty()) {
+		draw_legend(state, ctx, theme, legend);
+	}
+	if let Some(heatmap) = state.heatmap() {
+		draw_heat_legend(state, ctx, theme, heatmap);
+	}
+
+	if let Some(stats) = stats {
+		draw_stats(state, ctx, stats);
+	}
+}
 
-		gradient
-			.add_color_stop(0.0, &theme.background.color_secondary.to_css())
-			.unwrap();
-		gradient
-			.add_color_stop(1.0, &theme.background.color.to_css())
-			.unwrap();
+/// Draw the debug stats panel in the top-left corner, in screen space.
+pub(super) fn draw_stats<C: DrawContext>(state: &ForceGraphState, ctx: &C, stats: &FrameStats) {
+	const PADDING: f64 = 8.0;
+	const LINE_HEIGHT: f64 = 15.0;
+	const MARGIN: f64 = 12.0;
 
-		#[allow(deprecated)]
-		ctx.set_fill_style(&gradient);
+	let sim = if !state.animation_running {
+		"paused"
+	} else if state.stability.is_settled() {
+		"settled"
 	} else {
-		ctx.set_fill_style_str(&theme.background.color.to_css());
+		"running"
+	};
+	let lines = [
+		format!("fps    {:>7.1}", stats.fps()),
+		format!("frame  {:>5.2} ms", stats.work_ms),
+		format!(" tick  {:>5.2} ms", stats.tick_ms),
+		format!(" draw  {:>5.2} ms", stats.render_ms),
+		format!("nodes  {:>7}", state.node_count()),
+		format!("edges  {:>7}", state.edge_count()),
+		format!("zoom   {:>6.2}x", state.transform.k),
+		format!("sim    {sim:>7}"),
+	];
+
+	ctx.set_font("11px monospace");
+	let text_width = lines
+		.iter()
+		.map(|line| ctx.measure_text(line))
+		.fold(0.0, f64::max);
+	let (w, h) = (
+		text_width + PADDING * 2.0,
+		LINE_HEIGHT * lines.len() as f64 + PADDING * 2.0,
+	);
+	let (x, y) = Corner::TopLeft.origin(state.width, state.height, w, h, MARGIN);
+
+	ctx.set_fill_style_str("rgba(0, 0, 0, 0.65)");
+	ctx.fill_rect(x, y, w, h);
+	ctx.set_fill_style_str("rgba(160, 255, 160, 0.9)");
+	for (i, line) in lines.iter().enumerate() {
+		let baseline = y + PADDING + LINE_HEIGHT * (i as f64 + 1.0) - 4.0;
+		ctx.fill_text(line, x + PADDING, baseline);
 	}
+}
 
-	ctx.fill_rect(0.0, 0.0, state.width, state.height);
+pub(super) fn draw_legend<C: DrawContext>(
+	state: &ForceGraphState,
+	ctx: &C,
+	theme: &Theme,
+	legend: &Legend,
+) {
+	const PADDING: f64 = 10.0;
+	const ROW_HEIGHT: f64 = 18.0;
+	const SWATCH_RADIUS: f64 = 5.0;
+	const MARGIN: f64 = 12.0;
+
+	const FONT: &str = "12px sans-serif";
+	ctx.set_font(FONT);
+	let text_width = legend
+		.entries
+		.iter()
+		.map(|(_, name)| legend.widths.measure(ctx, FONT, name))
+		.fold(0.0, f64::max);
+
+	let w = PADDING * 3.0 + SWATCH_RADIUS * 2.0 + text_width;
+	let h = PADDING * 2.0 + ROW_HEIGHT * legend.entries.len() as f64;
+	let (x, y) = legend
+		.corner
+		.origin(state.width, state.height, w, h, MARGIN);
+
+	ctx.set_fill_style_str(&theme.background.color.with_alpha(0.85).to_css());
+	ctx.fill_rect(x, y, w, h);
+	ctx.set_stroke_style_str(&theme.edge.color.to_css());
+	ctx.set_line_width(1.0);
+	ctx.stroke_rect(x + 0.5, y + 0.5, w - 1.0, h - 1.0);
+
+	for (i, (group, name)) in legend.entries.iter().enumerate() {
+		let row_y = y + PADDING + ROW_HEIGHT * (i as f64 + 0.5);
+		let swatch_x = x + PADDING + SWATCH_RADIUS;
+
+		ctx.begin_path();
+		ctx.arc(swatch_x, row_y, SWATCH_RADIUS, 0.0, 2.0 * PI);
+		ctx.set_fill_style_str(&theme.group_color(*group).to_css());
+		ctx.fill();
+
+		ctx.set_fill_style_str(&theme.node.label_color.with_alpha(0.85).to_css());
+		ctx.fill_text(name, swatch_x + SWATCH_RADIUS + PADDING, row_y + 4.0);
+	}
+}
+
+/// Draw a rubber-band selection's rectangle, in screen space.
+pub(super) fn draw_select_box<C: DrawContext>(ctx: &C, theme: &Theme, select_box: &SelectBox) {
+	let b = select_box;
+	let (x, y) = (b.start_x.min(b.x), b.start_y.min(b.y));
+	let (w, h) = ((b.x - b.start_x).abs(), (b.y - b.start_y).abs());
+	let color = theme.node.label_color;
+	ctx.set_fill_style_str(&color.with_alpha(0.08).to_css());
+	ctx.fill_rect(x, y, w, h);
+	ctx.set_stroke_style_str(&color.with_alpha(0.6).to_css());
+	ctx.set_line_width(1.0);
+	ctx.set_line_dash(&[4.0, 3.0]);
+	ctx.stroke_rect(x + 0.5, y + 0.5, w, h);
+	ctx.set_line_dash(&[]);
+}
+
+/// Draw the heatmap's gradient legend in its corner: the metric's name over
+/// the ramp, labeled with the lowest and highest values, or its progress
+/// while it's still being computed.
+pub(super) fn draw_heat_legend<C: DrawContext>(
+	state: &ForceGraphState,
+	ctx: &C,
+	theme: &Theme,
+	heatmap: &Heatmap,
+) {
+	const PADDING: f64 = 10.0;
+	const BAR_WIDTH: f64 = 120.0;
+	const BAR_HEIGHT: f64 = 8.0;
+	const LINE_HEIGHT: f64 = 16.0;
+	const MARGIN: f64 = 12.0;
+
+	let w = PADDING * 2.0 + BAR_WIDTH;
+	let h = PADDING * 2.0 + LINE_HEIGHT * 2.0 + BAR_HEIGHT;
+	let (x, y) = state
+		.heat_legend
+		.origin(state.width, state.height, w, h, MARGIN);
+
+	ctx.set_fill_style_str(&theme.background.color.with_alpha(0.85).to_css());
+	ctx.fill_rect(x, y, w, h);
+	ctx.set_stroke_style_str(&theme.edge.color.to_css());
+	ctx.set_line_width(1.0);
+	ctx.stroke_rect(x + 0.5, y + 0.5, w - 1.0, h - 1.0);
+
+	ctx.set_font("12px sans-serif");
+	let text = theme.node.label_color.with_alpha(0.85).to_css();
+	ctx.set_fill_style_str(&text);
+	ctx.fill_text(heatmap.color_by.name(), x + PADDING, y + PADDING + 12.0);
+
+	let (bar_x, bar_y) = (x + PADDING, y + PADDING + LINE_HEIGHT + 2.0);
+	let label_y = bar_y + BAR_HEIGHT + LINE_HEIGHT - 2.0;
+	if let Some(progress) = heatmap.progress() {
+		ctx.set_fill_style_str(&theme.edge.color.to_css());
+		ctx.fill_rect(bar_x, bar_y, BAR_WIDTH * progress, BAR_HEIGHT);
+		ctx.set_fill_style_str(&text);
+		ctx.fill_text(
+			&format!("computing… {:.0}%", progress * 100.0),
+			bar_x,
+			label_y,
+		);
+		return;
+	}
+
+	let stops = &state.heat_ramp.stops;
+	let gradient = ctx.create_linear_gradient(bar_x, 0.0, bar_x + BAR_WIDTH, 0.0);
+	for (i, stop) in stops.iter().enumerate() {
+		let offset = i as f64 / (stops.len() - 1).max(1) as f64;
+		gradient.add_color_stop(offset, &stop.to_css());
+	}
+	ctx.set_fill_gradient(&gradient);
+	ctx.fill_rect(bar_x, bar_y, BAR_WIDTH, BAR_HEIGHT);
+
+	if let Some((lo, hi)) = heatmap.range {
+		ctx.set_fill_style_str(&text);
+		ctx.fill_text(&format_metric(lo), bar_x, label_y);
+		ctx.set_text_align("end");
+		ctx.fill_text(&format_metric(hi), bar_x + BAR_WIDTH, label_y);
+		ctx.set_text_align("start");
+	}
+}
+
+/// A metric value for the heat legend: whole numbers as such, others to
+/// two decimal places.
+fn format_metric(value: f64) -> String {
+	if value.fract() == 0.0 {
+		format!("{value:.0}")
+	} else {
+		format!("{value:.2}")
+	}
 }
 
-fn draw_vignette(state: &ForceGraphState, ctx: &CanvasRenderingContext2d, theme: &Theme) {
-	let gradient = ctx
-		.create_radial_gradient(
+fn draw_background<C: DrawContext>(state: &ForceGraphState, ctx: &C, theme: &Theme) {
+	if theme.background.transparent {
+		ctx.clear_rect(0.0, 0.0, state.width, state.height);
+		return;
+	}
+	if theme.background.use_gradient {
+		let gradient = ctx.create_radial_gradient(
 			state.width / 2.0,
 			state.height / 2.0,
-			state.width.min(state.height) * 0.3,
+			0.0,
 			state.width / 2.0,
 			state.height / 2.0,
-			state.width.max(state.height) * 0.7,
-		)
-		.unwrap();
+			(state.width.max(state.height)) * 0.8,
+		);
 
-	gradient.add_color_stop(0.0, "rgba(0, 0, 0, 0)").unwrap();
-	gradient
-		.add_color_stop(
-			1.0,
-			&format!("rgba(0, 0, 0, {})", theme.background.vignette),
-		)
-		.unwrap();
+		gradient.add_color_stop(0.0, &theme.background.color_secondary.to_css());
+		gradient.add_color_stop(1.0, &theme.background.color.to_css());
 
-	#[allow(deprecated)]
-	ctx.set_fill_style(&gradient);
+		ctx.set_fill_gradient(&gradient);
+	} else {
+		ctx.set_fill_style_str(&theme.background.color.to_css());
+	}
+
+	ctx.fill_rect(0.0, 0.0, state.width, state.height);
+}
+
+/// Draw the background image scaled to cover the canvas, shifted by the
+/// camera's pan from its starting position times the image's parallax.
+fn draw_background_image<C: DrawContext>(
+	state: &ForceGraphState,
+	ctx: &C,
+	style: &BackgroundImage,
+	image: &web_sys::HtmlImageElement,
+	(image_w, image_h): (f64, f64),
+) {
+	let cover = (state.width / image_w).max(state.height / image_h);
+	let (w, h) = (image_w * cover, image_h * cover);
+	let t = &state.transform;
+	let x = (state.width - w) / 2.0 + (t.x - state.width / 2.0) * style.parallax;
+	let y = (state.height - h) / 2.0 + (t.y - state.height / 2.0) * style.parallax;
+	ctx.set_global_alpha(style.opacity.clamp(0.0, 1.0));
+	ctx.draw_image(image, x, y, w, h);
+	ctx.set_global_alpha(1.0);
+}
+
+/// Draw the world-space dot or line grid over the visible area.
+///
+/// Must be called with the view transform applied. Marks stay hairline at
+/// any zoom. The spacing doubles or halves with the zoom so the grid never
+/// turns into moiré nor thins out, and each subdivision fades in between
+/// the marks of the level above as it grows legible.
+fn draw_background_pattern<C: DrawContext>(state: &ForceGraphState, ctx: &C, theme: &Theme) {
+	/// On-screen spacing (px) of the coarsest level drawn; its subdivision
+	/// fades in as its own spacing grows from half this to this.
+	const VISIBLE_SPACING: f64 = 24.0;
+
+	let bg = &theme.background;
+	if bg.pattern_spacing <= 0.0 {
+		return;
+	}
+	let k = state.transform.k;
+	let doublings = (VISIBLE_SPACING / (bg.pattern_spacing * k)).log2().ceil();
+	let spacing = bg.pattern_spacing * doublings.exp2();
+	let fine_alpha = AlphaBehavior::Fade {
+		zero_alpha_k: VISIBLE_SPACING / spacing,
+		full_alpha_k: 2.0 * VISIBLE_SPACING / spacing,
+	}
+	.apply(k);
+
+	let (left, top) = state.screen_to_graph(0.0, 0.0);
+	let (right, bottom) = state.screen_to_graph(state.width, state.height);
+	let view = (left, top, right, bottom);
+	let color = |alpha: f64| {
+		bg.pattern_color
+			.with_alpha(bg.pattern_color.a * alpha)
+			.to_css()
+	};
+	draw_grid_level(ctx, bg.pattern, spacing, view, k, &color(1.0), false);
+	if fine_alpha > 0.0 {
+		draw_grid_level(
+			ctx,
+			bg.pattern,
+			spacing / 2.0,
+			view,
+			k,
+			&color(fine_alpha),
+			true,
+		);
+	}
+}
+
+/// Draw one level of the background grid across `view` (left, top, right,
+/// bottom in world units). A `subdivision` level leaves out the marks it
+/// shares with the level twice as coarse.
+fn draw_grid_level<C: DrawContext>(
+	ctx: &C,
+	pattern: BackgroundPattern,
+	spacing: f64,
+	(left, top, right, bottom): (f64, f64, f64, f64),
+	k: f64,
+	color: &str,
+	subdivision: bool,
+) {
+	let columns = (left / spacing).floor() as i64..=(right / spacing).ceil() as i64;
+	let rows = (top / spacing).floor() as i64..=(bottom / spacing).ceil() as i64;
+	let shared = |i: i64| subdivision && i % 2 == 0;
+
+	ctx.begin_path();
+	match pattern {
+		BackgroundPattern::None => {}
+		BackgroundPattern::Dots => {
+			let radius = 1.2 / k;
+			for row in rows {
+				for column in columns.clone() {
+					if shared(row) && shared(column) {
+						continue;
+					}
+					let (x, y) = (column as f64 * spacing, row as f64 * spacing);
+					ctx.move_to(x + radius, y);
+					ctx.arc(x, y, radius, 0.0, 2.0 * PI);
+				}
+			}
+			ctx.set_fill_style_str(color);
+			ctx.fill();
+		}
+		BackgroundPattern::Lines => {
+			for column in columns.filter(|&c| !shared(c)) {
+				let x = column as f64 * spacing;
+				ctx.move_to(x, top);
+				ctx.line_to(x, bottom);
+			}
+			for row in rows.filter(|&r| !shared(r)) {
+				let y = row as f64 * spacing;
+				ctx.move_to(left, y);
+				ctx.line_to(right, y);
+			}
+			ctx.set_line_dash(&[]);
+			ctx.set_line_width(1.0 / k);
+			ctx.set_stroke_style_str(color);
+			ctx.stroke();
+		}
+	}
+}
+
+fn draw_vignette<C: DrawContext>(state: &ForceGraphState, ctx: &C, theme: &Theme) {
+	let gradient = ctx.create_radial_gradient(
+		state.width / 2.0,
+		state.height / 2.0,
+		state.width.min(state.height) * 0.3,
+		state.width / 2.0,
+		state.height / 2.0,
+		state.width.max(state.height) * 0.7,
+	);
+
+	gradient.add_color_stop(0.0, "rgba(0, 0, 0, 0)");
+	gradient.add_color_stop(
+		1.0,
+		&format!("rgba(0, 0, 0, {})", theme.background.vignette),
+	);
+
+	ctx.set_fill_gradient(&gradient);
+	ctx.fill_rect(0.0, 0.0, state.width, state.height);
+}
+
+/// Darken the canvas outside the spotlight at `(cx, cy)`, less so as hover
+/// or group highlighting takes over the dimming.
+fn draw_spotlight<C: DrawContext>(
+	state: &ForceGraphState,
+	ctx: &C,
+	style: &SpotlightStyle,
+	(cx, cy): (f64, f64),
+) {
+	let darkness = style.intensity * (1.0 - state.highlight.max_intensity());
+	if darkness <= 0.005 {
+		return;
+	}
+	// Past the outer circle the gradient keeps its last stop
+	let gradient = ctx.create_radial_gradient(cx, cy, style.radius * 0.5, cx, cy, style.radius);
+	gradient.add_color_stop(0.0, "rgba(0, 0, 0, 0)");
+	gradient.add_color_stop(1.0, &format!("rgba(0, 0, 0, {darkness})"));
+	ctx.set_fill_gradient(&gradient);
 	ctx.fill_rect(0.0, 0.0, state.width, state.height);
 }
 
-fn draw_particles(
+fn draw_particles<C: DrawContext>(
 	state: &ForceGraphState,
-	ctx: &CanvasRenderingContext2d,
+	ctx: &C,
 	theme: &Theme,
 	particles: &ParticleSystem,
 ) {
@@ -121,41 +793,332 @@ fn draw_particles(
 		));
 
 		ctx.begin_path();
-		let _ = ctx.arc(p.x, p.y, p.size, 0.0, PI * 2.0);
+		ctx.arc(p.x, p.y, p.size, 0.0, PI * 2.0);
 		ctx.fill();
 	}
 }
 
-fn draw_edges(
+fn draw_hulls<C: DrawContext>(
 	state: &ForceGraphState,
-	ctx: &CanvasRenderingContext2d,
+	ctx: &C,
+	scale: &ScaledValues,
+	theme: &Theme,
+) {
+	let style = &theme.hulls;
+	let max_t = smooth_step(state.highlight.max_intensity());
+	let dim = 1.0 - state.highlight.config.dim_hulls * max_t;
+	if dim < 0.01 {
+		return;
+	}
+
+	let mut members: HashMap<u32, Vec<(f64, f64)>> = HashMap::new();
+	state.graph.visit_nodes(|node| {
+		if let Some(group) = node.data.user_data.group {
+			members
+				.entry(group)
+				.or_default()
+				.push((node.x() as f64, node.y() as f64));
+		}
+	});
+
+	let mut groups: Vec<_> = members.into_iter().collect();
+	groups.sort_by_key(|(group, _)| *group);
+
+	for (group, points) in &groups {
+		let color = theme.group_color(*group);
+		ctx.set_fill_style_str(&color.with_alpha(style.fill_alpha * dim).to_css());
+		ctx.set_stroke_style_str(&color.with_alpha(style.stroke_alpha * dim).to_css());
+		ctx.set_line_width(scale.edge_line_width);
+
+		ctx.begin_path();
+		if let [(x, y)] = points.as_slice() {
+			ctx.arc(*x, *y, style.padding, 0.0, 2.0 * PI);
+		} else {
+			let outline = hull::padded_hull(points, style.padding);
+			if let Some(&(x0, y0)) = outline.first() {
+				ctx.move_to(x0, y0);
+				for &(x, y) in &outline[1..] {
+					ctx.line_to(x, y);
+				}
+				ctx.close_path();
+			}
+		}
+		ctx.fill();
+		ctx.stroke();
+
+		if style.show_labels
+			&& let Some(name) = state.group_names.get(group)
+			&& let Some((cx, cy)) = hull::centroid(points)
+		{
+			ctx.set_font(&format!("bold {}px sans-serif", 13.0 / scale.k));
+			ctx.set_text_align("center");
+			ctx.set_fill_style_str(&color.lighten(0.4).with_alpha(0.6 * dim).to_css());
+			ctx.fill_text(name, cx, cy);
+			ctx.set_text_align("start");
+		}
+	}
+}
+
+/// Group label placements kept across frames, recomputed periodically.
+#[derive(Default)]
+struct GroupLabels {
+	/// Each group's centroid, and its members' farthest distance from it
+	placed: Vec<(u32, (f64, f64), f64)>,
+	/// Frames drawn since the last placement
+	age: u32,
+}
+
+impl GroupLabels {
+	/// Place a label at the centroid of each group's shown members, if
+	/// enough frames have passed since the last time.
+	fn update(&mut self, state: &ForceGraphState) {
+		self.age = self.age.saturating_add(1);
+		if self.age < GROUP_LABEL_INTERVAL && !self.placed.is_empty() {
+			return;
+		}
+		self.age = 0;
+		let mut members: HashMap<u32, Vec<(f64, f64)>> = HashMap::new();
+		state.graph.visit_nodes(|node| {
+			let info = &node.data.user_data;
+			if let Some(group) = info.group
+				&& !info.is_hidden()
+			{
+				members
+					.entry(group)
+					.or_default()
+					.push((node.x() as f64, node.y() as f64));
+			}
+		});
+		self.placed = members
+			.into_iter()
+			.filter_map(|(group, points)| {
+				let (cx, cy) = hull::centroid(&points)?;
+				let extent = points
+					.iter()
+					.map(|&(x, y)| (x - cx).hypot(y - cy))
+					.fold(0.0, f64::max);
+				Some((group, (cx, cy), extent))
+			})
+			.collect();
+		self.placed.sort_by_key(|&(group, ..)| group);
+	}
+}
+
+/// Draw each group's name large and faint at its centroid, sized to the
+/// group's spread and fading out as node labels fade in.
+fn draw_group_labels<C: DrawContext>(
+	state: &ForceGraphState,
+	ctx: &C,
+	scale: &ScaledValues,
+	theme: &Theme,
+	labels: &mut GroupLabels,
+) {
+	let alpha = GROUP_LABEL_ALPHA * (1.0 - scale.label_alpha);
+	if alpha < 0.01 {
+		return;
+	}
+	labels.update(state);
+	ctx.set_text_align("center");
+	for &(group, (cx, cy), extent) in &labels.placed {
+		let name = state
+			.group_names
+			.get(&group)
+			.cloned()
+			.unwrap_or_else(|| format!("Group {group}"));
+		let size = (extent * 0.5).max(GROUP_LABEL_MIN_PX / scale.k);
+		ctx.set_font(&format!("bold {size}px sans-serif"));
+		ctx.set_fill_style_str(&theme.group_color(group).with_alpha(alpha).to_css());
+		// Centered on the baseline, roughly
+		ctx.fill_text(&name, cx, cy + size * 0.35);
+	}
+	ctx.set_text_align("start");
+}
+
+fn draw_edges<C: DrawContext>(
+	state: &ForceGraphState,
+	ctx: &C,
 	config: &ScaleConfig,
 	scale: &ScaledValues,
 	theme: &Theme,
+	cache: &mut RenderCache<C::Gradient>,
 ) {
 	let dash_offset = scale.dash_offset(state.flow_time, config.edge.flow_speed);
 	let k = scale.k;
 
-	if theme.edge.glow_intensity > 0.0 {
+	if let Some(ref mut bundles) = cache.bundles {
+		let mut edges = Vec::new();
 		state.graph.visit_edges(|n1, n2, _| {
-			draw_edge_glow(state, ctx, scale, theme, n1, n2);
+			let segment = (
+				(n1.x() as f64, n1.y() as f64),
+				(n2.x() as f64, n2.y() as f64),
+			);
+			edges.push((n1.index(), n2.index(), segment));
+		});
+		let resting = !state.animation_running || state.stability.is_settled();
+		bundles.update(&edges, k, resting);
+	}
+
+	if theme.edge.glow_intensity > 0.0 && scale.full_detail() {
+		state.graph.visit_edges(|n1, n2, edge| {
+			for (from, to) in directions(n1, n2, &edge.user_data) {
+				let bundle = cache.bundle_path(from, to);
+				let edge = &edge.user_data;
+				draw_edge_glow(state, ctx, scale, theme, from, to, edge, bundle.as_deref());
+			}
 		});
 	}
 
-	state.graph.visit_edges(|n1, n2, _| {
-		draw_edge_main(state, ctx, config, scale, theme, n1, n2, dash_offset, k);
+	state.graph.visit_edges(|n1, n2, edge| {
+		for (from, to) in directions(n1, n2, &edge.user_data) {
+			draw_edge_main(
+				state,
+				ctx,
+				config,
+				scale,
+				theme,
+				from,
+				to,
+				&edge.user_data,
+				cache,
+				dash_offset,
+				k,
+			);
+		}
 	});
 
-	let _ = ctx.set_line_dash(&js_sys::Array::new());
+	ctx.set_line_dash(&[]);
+	let color = theme.edge.color;
+	for (x1, y1, x2, y2, alpha) in state.departing_edge_lines() {
+		ctx.set_stroke_style_str(&color.with_alpha(0.7 * alpha * color.a).to_css());
+		ctx.set_line_width(scale.edge_line_width);
+		ctx.begin_path();
+		ctx.move_to(x1, y1);
+		ctx.line_to(x2, y2);
+		ctx.stroke();
+	}
 }
 
-fn draw_edge_glow(
+/// Label the hovered node's edges with their weights at their midpoints,
+/// fading with the hover.
+fn draw_weight_labels<C: DrawContext>(
+	state: &ForceGraphState,
+	ctx: &C,
+	scale: &ScaledValues,
+	theme: &Theme,
+	css: &mut CssCache,
+	text_widths: &TextWidths,
+) {
+	let padding = scale.label_size * 0.3;
+	let height = scale.label_size * 1.4;
+	ctx.set_font(&scale.label_font);
+	ctx.set_text_align("center");
+	state.graph.visit_edges(|n1, n2, edge| {
+		let Some(weight) = edge.user_data.weight else {
+			return;
+		};
+		let t = smooth_step(
+			state
+				.highlight
+				.hover_ring_intensity(n1.index())
+				.max(state.highlight.hover_ring_intensity(n2.index())),
+		);
+		if t < 0.01 {
+			return;
+		}
+		let (x1, y1, x2, y2) = (n1.x() as f64, n1.y() as f64, n2.x() as f64, n2.y() as f64);
+		// A quadratic curve's midpoint is halfway to its control point
+		let (x, y) = match edge_curve(theme, &edge.user_data, (x1, y1), (x2, y2), scale) {
+			Some((cx, cy)) => (
+				0.25 * x1 + 0.5 * cx + 0.25 * x2,
+				0.25 * y1 + 0.5 * cy + 0.25 * y2,
+			),
+			None => ((x1 + x2) / 2.0, (y1 + y2) / 2.0),
+		};
+		let t = t * edge_shown(state, n1, n2);
+		if t < 0.01 {
+			return;
+		}
+		let text = format!("{weight:.2}");
+		let text = text.trim_end_matches('0').trim_end_matches('.');
+		let width = text_widths.measure(ctx, &scale.label_font, text) + padding * 2.0;
+		ctx.set_fill_style_str(css.rgba(theme.background.color.with_alpha(1.0), 0.8 * t));
+		ctx.fill_rect(x - width / 2.0, y - height / 2.0, width, height);
+		ctx.set_fill_style_str(css.rgba(theme.node.label_color, t));
+		ctx.fill_text(text, x, y + scale.label_size * 0.35);
+	});
+	ctx.set_text_align("start");
+}
+
+/// Source and target of each direction an edge is drawn in: once, or both
+/// ways for a reciprocal edge.
+fn directions<'a, T>(
+	n1: &'a T,
+	n2: &'a T,
+	edge: &EdgeInfo,
+) -> impl Iterator<Item = (&'a T, &'a T)> {
+	std::iter::once((n1, n2)).chain(edge.reciprocal.then_some((n2, n1)))
+}
+
+/// Line alpha, arrow alpha and line width of an edge with highlight
+/// intensity `edge_t`, brightened when highlighted and dimmed while another
+/// part of the graph is. Line alpha and width are scaled by the edge's
+/// weight when the theme asks for it.
+pub(super) fn edge_emphasis(
+	state: &ForceGraphState,
+	scale: &ScaledValues,
+	theme: &Theme,
+	edge: &EdgeInfo,
+	edge_t: f64,
+) -> (f64, f64, f64) {
+	let (alpha, arrow_alpha, width) = highlight_emphasis(state, scale, edge_t);
+	match (theme.edge.weighted, edge.weight) {
+		(Some(style), Some(weight)) => {
+			let t = state.weight_t(weight);
+			let lerp = |(from, to): (f64, f64)| from + (to - from) * t;
+			(
+				alpha * lerp(style.alpha),
+				arrow_alpha,
+				width * lerp(style.width),
+			)
+		}
+		_ => (alpha, arrow_alpha, width),
+	}
+}
+
+fn highlight_emphasis(
+	state: &ForceGraphState,
+	scale: &ScaledValues,
+	edge_t: f64,
+) -> (f64, f64, f64) {
+	let config = &state.highlight.config;
+	let max_t = smooth_step(state.highlight.max_intensity());
+	if edge_t > 0.01 {
+		(
+			0.7 + 0.3 * edge_t,
+			0.9 + 0.1 * edge_t,
+			scale.edge_line_width * (1.0 + config.hovered_growth * edge_t),
+		)
+	} else if max_t > 0.01 {
+		(
+			0.7 - config.dim_edge_alpha * max_t,
+			0.9 - 0.6 * max_t,
+			scale.edge_line_width * (1.0 - config.dim_edge_width * max_t),
+		)
+	} else {
+		(0.7, 0.9, scale.edge_line_width)
+	}
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_edge_glow<C: DrawContext>(
 	state: &ForceGraphState,
-	ctx: &CanvasRenderingContext2d,
+	ctx: &C,
 	scale: &ScaledValues,
 	theme: &Theme,
 	n1: &force_graph::Node<NodeInfo>,
 	n2: &force_graph::Node<NodeInfo>,
+	edge: &EdgeInfo,
+	bundle: Option<&[(f64, f64)]>,
 ) {
 	let (x1, y1, x2, y2) = (n1.x() as f64, n1.y() as f64, n2.x() as f64, n2.y() as f64);
 	let (dx, dy) = (x2 - x1, y2 - y1);
@@ -173,7 +1136,7 @@ fn draw_edge_glow(
 		theme.edge.glow_intensity * (0.6 - 0.4 * smooth_step(max_t))
 	} else {
 		theme.edge.glow_intensity * 0.6
-	};
+	} * edge_shown(state, n1, n2);
 
 	if glow_alpha < 0.01 {
 		return;
@@ -190,21 +1153,23 @@ fn draw_edge_glow(
 		glow_alpha * glow_color.a
 	));
 	ctx.set_line_width(glow_width);
-	let _ = ctx.set_line_dash(&js_sys::Array::new());
+	ctx.set_line_dash(&[]);
 
 	let (ux, uy) = (dx / dist, dy / dist);
 
-	if theme.edge.curved && dist > scale.node_radius * 4.0 {
+	if let Some(points) = bundle {
+		draw_bundled_edge(ctx, points, scale.node_radius, scale.node_radius);
+	} else if let Some((cx, cy)) = edge_curve(theme, edge, (x1, y1), (x2, y2), scale) {
 		draw_curved_edge(
 			ctx,
 			x1,
 			y1,
 			x2,
 			y2,
-			ux,
-			uy,
+			cx,
+			cy,
+			scale.node_radius,
 			scale.node_radius,
-			theme.edge.curve_tension,
 		);
 	} else {
 		ctx.begin_path();
@@ -215,14 +1180,16 @@ fn draw_edge_glow(
 }
 
 #[allow(clippy::too_many_arguments)]
-fn draw_edge_main(
+fn draw_edge_main<C: DrawContext>(
 	state: &ForceGraphState,
-	ctx: &CanvasRenderingContext2d,
+	ctx: &C,
 	_config: &ScaleConfig,
 	scale: &ScaledValues,
 	theme: &Theme,
 	n1: &force_graph::Node<NodeInfo>,
 	n2: &force_graph::Node<NodeInfo>,
+	edge: &EdgeInfo,
+	cache: &mut RenderCache<C::Gradient>,
 	dash_offset: f64,
 	_k: f64,
 ) {
@@ -233,143 +1200,451 @@ fn draw_edge_main(
 		return;
 	}
 
+	let shown = edge_shown(state, n1, n2);
+	if shown < 0.01 {
+		return;
+	}
 	let edge_t = smooth_step(state.highlight.edge_intensity(n1.index(), n2.index()));
-	let max_t = smooth_step(state.highlight.max_intensity());
-
-	let (edge_alpha, base_arrow_alpha, base_width) = if edge_t > 0.01 {
-		(
-			0.7 + 0.3 * edge_t,
-			0.9 + 0.1 * edge_t,
-			scale.edge_line_width * (1.0 + 0.4 * edge_t),
-		)
-	} else if max_t > 0.01 {
-		(
-			0.7 - 0.5 * max_t,
-			0.9 - 0.6 * max_t,
-			scale.edge_line_width * (1.0 - 0.3 * max_t),
-		)
-	} else {
-		(0.7, 0.9, scale.edge_line_width)
-	};
+	let (edge_alpha, base_arrow_alpha, base_width) =
+		edge_emphasis(state, scale, theme, edge, edge_t);
+	let edge_alpha = edge_alpha * shown;
 
 	// Compensate for dash pattern fading to solid
 	let width = base_width * (1.0 + 0.3 * (1.0 - scale.dash_alpha));
-	let arrow_alpha = base_arrow_alpha * scale.arrow_alpha;
+	let arrow_alpha = base_arrow_alpha * scale.arrow_alpha * shown;
 
 	let edge_color = &theme.edge.color;
-	ctx.set_stroke_style_str(&format!(
-		"rgba({}, {}, {}, {})",
-		edge_color.r,
-		edge_color.g,
-		edge_color.b,
-		edge_alpha * edge_color.a
-	));
+	if theme.edge.use_node_gradient && scale.full_detail() {
+		// Gradient stops are opaque so the cache survives highlight changes;
+		// alpha is applied globally for the line stroke only
+		let gradient = cache.edge_gradient(ctx, n1, n2, scale.k);
+		ctx.set_stroke_gradient(gradient);
+		ctx.set_global_alpha(edge_alpha * edge_color.a);
+	} else {
+		ctx.set_stroke_style_str(cache.css.rgba(*edge_color, edge_alpha));
+	}
 	ctx.set_line_width(width);
 
-	// Fade dash pattern to solid when zoomed out
-	let effective_gap = scale.dash_pattern.1 * scale.dash_alpha;
-	if effective_gap > 0.1 {
-		let _ = ctx.set_line_dash(&js_sys::Array::of2(
-			&JsValue::from_f64(scale.dash_pattern.0),
-			&JsValue::from_f64(effective_gap),
-		));
-		ctx.set_line_dash_offset(dash_offset);
+	// Explicit link styles are semantic and hold at every zoom level; unstyled
+	// edges fade the flow dash to solid when zoomed out. Only dashes flow
+	let dash = match edge.style {
+		Some(LinkStyle::Solid) => None,
+		Some(LinkStyle::Dashed) => Some((scale.dash_pattern, dash_offset)),
+		Some(LinkStyle::Dotted) => {
+			let (dot, gap) = scale.dot_pattern;
+			Some(((width * dot, width * gap), 0.0))
+		}
+		None => {
+			let effective_gap = scale.dash_pattern.1 * scale.dash_alpha;
+			(effective_gap > 0.1).then_some(((scale.dash_pattern.0, effective_gap), dash_offset))
+		}
+	};
+	if let Some(((dash_len, gap), offset)) = dash {
+		ctx.set_line_dash(&[dash_len, gap]);
+		ctx.set_line_dash_offset(offset);
 	} else {
-		let _ = ctx.set_line_dash(&js_sys::Array::new());
+		ctx.set_line_dash(&[]);
 	}
 
 	let (ux, uy) = (dx / dist, dy / dist);
+	let arrow_style = if edge.directed {
+		theme.edge.arrow_style
+	} else {
+		ArrowStyle::None
+	};
+	let placement = theme.edge.arrow_placement;
+	let arrow_size = scale.arrow_size * theme.edge.arrow_scale;
 
-	if theme.edge.curved && dist > scale.node_radius * 4.0 {
-		draw_curved_edge(
-			ctx,
-			x1,
-			y1,
-			x2,
-			y2,
-			ux,
-			uy,
-			scale.node_radius + scale.arrow_size,
-			theme.edge.curve_tension,
-		);
+	// Leave room for a solid target arrow; open chevrons sit on top of the line
+	let end_offset = if arrow_style.is_solid() && placement.at_target() {
+		scale.node_radius + arrow_size
+	} else {
+		scale.node_radius
+	};
+	// Bundling takes over from the theme's curves
+	let bundle = cache.bundle_path(n1, n2);
+	let curve = bundle
+		.is_none()
+		.then(|| edge_curve(theme, edge, (x1, y1), (x2, y2), scale))
+		.flatten();
+
+	if let Some(ref points) = bundle {
+		draw_bundled_edge(ctx, points, scale.node_radius, end_offset);
+	} else if let Some((cx, cy)) = curve {
+		draw_curved_edge(ctx, x1, y1, x2, y2, cx, cy, scale.node_radius, end_offset);
 	} else {
 		ctx.begin_path();
 		ctx.move_to(x1 + ux * scale.node_radius, y1 + uy * scale.node_radius);
-		ctx.line_to(
-			x2 - ux * (scale.node_radius + scale.arrow_size),
-			y2 - uy * (scale.node_radius + scale.arrow_size),
-		);
+		ctx.line_to(x2 - ux * end_offset, y2 - uy * end_offset);
 		ctx.stroke();
 	}
+	ctx.set_global_alpha(1.0);
 
-	if !scale.cull_arrows && arrow_alpha > 0.0 {
-		let _ = ctx.set_line_dash(&js_sys::Array::new());
-		ctx.set_fill_style_str(&format!(
-			"rgba({}, {}, {}, {})",
-			edge_color.r,
-			edge_color.g,
-			edge_color.b,
-			arrow_alpha * edge_color.a
-		));
+	let flow = &theme.edge.flow_particles;
+	if flow.enabled
+		&& edge.directed
+		&& !scale.cull_arrows
+		&& (!flow.highlighted_only || edge_t > 0.01)
+	{
+		let visibility = if flow.highlighted_only { edge_t } else { 1.0 };
+		draw_flow_particles(
+			state,
+			ctx,
+			scale,
+			theme,
+			(x1, y1),
+			(x2, y2),
+			curve,
+			bundle.as_deref(),
+			edge.phase,
+			arrow_alpha * visibility,
+		);
+	}
+
+	if arrow_style != ArrowStyle::None && !scale.cull_arrows && arrow_alpha > 0.0 {
+		ctx.set_line_dash(&[]);
+		let arrow_color = theme.edge.arrow_color.unwrap_or(*edge_color);
+		let arrow_css = cache.css.rgba(arrow_color, arrow_alpha);
+		ctx.set_fill_style_str(arrow_css);
+		ctx.set_stroke_style_str(arrow_css);
+
+		if placement.at_target() {
+			// Orient along the curve's end tangent rather than the chord
+			let (tx, ty) = match (&bundle, curve) {
+				(Some(points), _) => {
+					let (px, py) = points[points.len() - 2];
+					unit(x2 - px, y2 - py)
+				}
+				(None, Some((cx, cy))) => unit(x2 - cx, y2 - cy),
+				(None, None) => (ux, uy),
+			};
+			let (tip_x, tip_y) = (x2 - tx * scale.node_radius, y2 - ty * scale.node_radius);
+			draw_arrow(ctx, arrow_style, tip_x, tip_y, tx, ty, arrow_size, width);
+		}
 
-		let (tip_x, tip_y) = (x2 - ux * scale.node_radius, y2 - uy * scale.node_radius);
-		let (back_x, back_y) = (tip_x - ux * scale.arrow_size, tip_y - uy * scale.arrow_size);
-		let (px, py) = (-uy * scale.arrow_size * 0.5, ux * scale.arrow_size * 0.5);
+		if placement.at_midpoint() {
+			// A quadratic curve's tangent at t = 0.5 is parallel to its chord
+			let ((mid_x, mid_y), (mx, my)) = match (&bundle, curve) {
+				(Some(points), _) => polyline_at(points, 0.5),
+				(None, Some((cx, cy))) => (
+					(
+						0.25 * x1 + 0.5 * cx + 0.25 * x2,
+						0.25 * y1 + 0.5 * cy + 0.25 * y2,
+					),
+					(ux, uy),
+				),
+				(None, None) => (((x1 + x2) / 2.0, (y1 + y2) / 2.0), (ux, uy)),
+			};
+			let (tip_x, tip_y) = (mid_x + mx * arrow_size / 2.0, mid_y + my * arrow_size / 2.0);
+			draw_arrow(ctx, arrow_style, tip_x, tip_y, mx, my, arrow_size, width);
+		}
+	}
+}
+
+/// Draws particles traveling from source to target, following the curve or
+/// bundle if any.
+#[allow(clippy::too_many_arguments)]
+fn draw_flow_particles<C: DrawContext>(
+	state: &ForceGraphState,
+	ctx: &C,
+	scale: &ScaledValues,
+	theme: &Theme,
+	(x1, y1): (f64, f64),
+	(x2, y2): (f64, f64),
+	curve: Option<(f64, f64)>,
+	bundle: Option<&[(f64, f64)]>,
+	phase: f64,
+	alpha: f64,
+) {
+	let flow = &theme.edge.flow_particles;
+	if flow.count == 0 || alpha < 0.01 {
+		return;
+	}
+
+	let color = theme.edge.color.lighten(0.5);
+	ctx.set_fill_style_str(&format!(
+		"rgba({}, {}, {}, {})",
+		color.r, color.g, color.b, alpha
+	));
+	let radius = flow.size / scale.k;
+
+	for i in 0..flow.count {
+		let t = (state.flow_time * flow.speed + phase + i as f64 / flow.count as f64).fract();
+		let (px, py) = match (bundle, curve) {
+			(Some(points), _) => polyline_at(points, t).0,
+			(None, Some((cx, cy))) => {
+				let u = 1.0 - t;
+				(
+					u * u * x1 + 2.0 * u * t * cx + t * t * x2,
+					u * u * y1 + 2.0 * u * t * cy + t * t * y2,
+				)
+			}
+			(None, None) => (x1 + (x2 - x1) * t, y1 + (y2 - y1) * t),
+		};
 
 		ctx.begin_path();
-		ctx.move_to(tip_x, tip_y);
-		ctx.line_to(back_x + px, back_y + py);
-		ctx.line_to(back_x - px, back_y - py);
-		ctx.close_path();
+		ctx.arc(px, py, radius, 0.0, 2.0 * PI);
 		ctx.fill();
 	}
 }
 
+/// Normalizes a vector, returning zero for degenerate input.
+fn unit(dx: f64, dy: f64) -> (f64, f64) {
+	let len = (dx * dx + dy * dy).sqrt();
+	if len < 1e-9 {
+		(0.0, 0.0)
+	} else {
+		(dx / len, dy / len)
+	}
+}
+
+/// Control point of an edge's curve from `(x1, y1)` to `(x2, y2)`, or `None`
+/// to draw it straight.
+///
+/// A link's own curvature comes first. Otherwise edges curve when the theme
+/// asks for it, and reciprocal edges always do (at least at
+/// [`RECIPROCAL_TENSION`]) so their two directions separate.
+fn edge_curve(
+	theme: &Theme,
+	edge: &EdgeInfo,
+	(x1, y1): (f64, f64),
+	(x2, y2): (f64, f64),
+	scale: &ScaledValues,
+) -> Option<(f64, f64)> {
+	if (x2 - x1).hypot(y2 - y1) <= scale.node_radius * 4.0 {
+		return None;
+	}
+	let tension = match (edge.curvature, edge.reciprocal, theme.edge.curved) {
+		(Some(curvature), _, _) => curvature,
+		(None, true, _) => theme.edge.curve_tension.max(RECIPROCAL_TENSION),
+		(None, false, true) => theme.edge.curve_tension,
+		(None, false, false) => return None,
+	};
+	(tension != 0.0).then(|| curve_control(x1, y1, x2, y2, tension))
+}
+
+/// Control point of the quadratic curve used for curved edges.
+///
+/// Bows the edge perpendicular to its chord by an amount proportional to its
+/// length and `tension`, always to the same side of the direction of
+/// travel, so the two directions of a reciprocal edge bow apart. Negative
+/// tension bows to the other side.
+fn curve_control(x1: f64, y1: f64, x2: f64, y2: f64, tension: f64) -> (f64, f64) {
+	let (dx, dy) = (x2 - x1, y2 - y1);
+	let dist = (dx * dx + dy * dy).sqrt();
+	let (ux, uy) = unit(dx, dy);
+	let curve_offset = dist * tension * 0.3;
+	(
+		(x1 + x2) / 2.0 - uy * curve_offset,
+		(y1 + y2) / 2.0 + ux * curve_offset,
+	)
+}
+
+/// Draws one arrowhead with its tip at `(tip_x, tip_y)` pointing along `(ux, uy)`.
+///
+/// Expects the fill and stroke styles to already be set.
+#[allow(clippy::too_many_arguments)]
+fn draw_arrow<C: DrawContext>(
+	ctx: &C,
+	style: ArrowStyle,
+	tip_x: f64,
+	tip_y: f64,
+	ux: f64,
+	uy: f64,
+	size: f64,
+	line_width: f64,
+) {
+	let (back_x, back_y) = (tip_x - ux * size, tip_y - uy * size);
+	let (px, py) = (-uy * size * 0.5, ux * size * 0.5);
+
+	match style {
+		ArrowStyle::Filled => {
+			ctx.begin_path();
+			ctx.move_to(tip_x, tip_y);
+			ctx.line_to(back_x + px, back_y + py);
+			ctx.line_to(back_x - px, back_y - py);
+			ctx.close_path();
+			ctx.fill();
+		}
+		ArrowStyle::Open => {
+			ctx.set_line_width(line_width);
+			ctx.begin_path();
+			ctx.move_to(back_x + px, back_y + py);
+			ctx.line_to(tip_x, tip_y);
+			ctx.line_to(back_x - px, back_y - py);
+			ctx.stroke();
+		}
+		ArrowStyle::Diamond => {
+			let (mid_x, mid_y) = (tip_x - ux * size / 2.0, tip_y - uy * size / 2.0);
+			ctx.begin_path();
+			ctx.move_to(tip_x, tip_y);
+			ctx.line_to(mid_x + px * 0.8, mid_y + py * 0.8);
+			ctx.line_to(back_x, back_y);
+			ctx.line_to(mid_x - px * 0.8, mid_y - py * 0.8);
+			ctx.close_path();
+			ctx.fill();
+		}
+		ArrowStyle::None => {}
+	}
+}
+
+/// Strokes a quadratic curve from `(x1, y1)` to `(x2, y2)` through control point
+/// `(cx, cy)`, trimmed along the end tangents by the given offsets.
 #[allow(clippy::too_many_arguments)]
-fn draw_curved_edge(
-	ctx: &CanvasRenderingContext2d,
+fn draw_curved_edge<C: DrawContext>(
+	ctx: &C,
 	x1: f64,
 	y1: f64,
 	x2: f64,
 	y2: f64,
-	ux: f64,
-	uy: f64,
-	offset: f64,
-	tension: f64,
+	cx: f64,
+	cy: f64,
+	start_offset: f64,
+	end_offset: f64,
 ) {
-	let (dx, dy) = (x2 - x1, y2 - y1);
-	let dist = (dx * dx + dy * dy).sqrt();
+	let (sx, sy) = unit(cx - x1, cy - y1);
+	let (ex, ey) = unit(x2 - cx, y2 - cy);
 
-	let curve_offset = dist * tension * 0.3;
-	let (px, py) = (-uy * curve_offset, ux * curve_offset);
+	ctx.begin_path();
+	ctx.move_to(x1 + sx * start_offset, y1 + sy * start_offset);
+	ctx.quadratic_curve_to(cx, cy, x2 - ex * end_offset, y2 - ey * end_offset);
+	ctx.stroke();
+}
 
-	let (start_x, start_y) = (x1 + ux * offset, y1 + uy * offset);
-	let (end_x, end_y) = (x2 - ux * offset, y2 - uy * offset);
-	let (mid_x, mid_y) = ((x1 + x2) / 2.0 + px, (y1 + y2) / 2.0 + py);
+/// Strokes a smooth curve along a bundled edge's `points`, trimmed along the
+/// end segments by the given offsets.
+///
+/// Runs through the midpoints between points, using each point as a
+/// quadratic control, so the bundle reads as one curve rather than a
+/// polyline.
+fn draw_bundled_edge<C: DrawContext>(
+	ctx: &C,
+	points: &[(f64, f64)],
+	start_offset: f64,
+	end_offset: f64,
+) {
+	let n = points.len();
+	let ((x1, y1), (x2, y2)) = (points[0], points[n - 1]);
+	let (sx, sy) = unit(points[1].0 - x1, points[1].1 - y1);
+	let (ex, ey) = unit(x2 - points[n - 2].0, y2 - points[n - 2].1);
 
 	ctx.begin_path();
-	ctx.move_to(start_x, start_y);
-	let _ = ctx.quadratic_curve_to(mid_x, mid_y, end_x, end_y);
+	ctx.move_to(x1 + sx * start_offset, y1 + sy * start_offset);
+	for pair in points[1..n - 1].windows(2) {
+		let (cx, cy) = pair[0];
+		ctx.quadratic_curve_to(cx, cy, (cx + pair[1].0) / 2.0, (cy + pair[1].1) / 2.0);
+	}
+	let (cx, cy) = points[n - 2];
+	ctx.quadratic_curve_to(cx, cy, x2 - ex * end_offset, y2 - ey * end_offset);
 	ctx.stroke();
 }
 
-fn draw_nodes(
+/// Point a fraction `t` of the way along a polyline by length, and the unit
+/// direction of the segment it falls on.
+fn polyline_at(points: &[(f64, f64)], t: f64) -> ((f64, f64), (f64, f64)) {
+	let lengths: Vec<f64> = points
+		.windows(2)
+		.map(|p| (p[1].0 - p[0].0).hypot(p[1].1 - p[0].1))
+		.collect();
+	let mut remaining = t * lengths.iter().sum::<f64>();
+	for (i, &len) in lengths.iter().enumerate() {
+		let ((x1, y1), (x2, y2)) = (points[i], points[i + 1]);
+		if remaining <= len || i == lengths.len() - 1 {
+			let f = if len > 0.0 {
+				(remaining / len).min(1.0)
+			} else {
+				0.0
+			};
+			return (
+				(x1 + (x2 - x1) * f, y1 + (y2 - y1) * f),
+				unit(x2 - x1, y2 - y1),
+			);
+		}
+		remaining -= len;
+	}
+	(points[0], (0.0, 0.0))
+}
+
+/// Radius offset from the theme's pulse animation, shared by all nodes.
+pub(super) fn node_pulse(state: &ForceGraphState, theme: &Theme) -> f64 {
+	if theme.node.pulse_intensity > 0.0 {
+		(state.flow_time * theme.node.pulse_speed).sin() * theme.node.pulse_intensity
+	} else {
+		0.0
+	}
+}
+
+/// Opacity and radius multiplier of a node under the current highlight,
+/// before selection: highlighted nodes grow toward full opacity while the
+/// rest dim and shrink.
+pub(super) fn node_emphasis(state: &ForceGraphState, idx: DefaultNodeIdx) -> (f64, f64) {
+	let config = &state.highlight.config;
+	let max_t = smooth_step(state.highlight.max_intensity());
+	let (dim_alpha, dim_radius) = if max_t > 0.01 {
+		(
+			1.0 - config.dim_node_alpha * max_t,
+			1.0 - config.dim_node_radius * max_t,
+		)
+	} else {
+		(1.0, 1.0)
+	};
+	let eased_t = smooth_step(state.highlight.node_intensity(idx));
+	let hover_t = smooth_step(state.highlight.hover_ring_intensity(idx));
+
+	let neighbor_radius = 1.0 + config.neighbor_growth * eased_t;
+	let hovered_radius = 1.0 + config.hovered_growth * eased_t;
+	let highlight_radius = neighbor_radius + (hovered_radius - neighbor_radius) * hover_t;
+
+	(
+		dim_alpha + (1.0 - dim_alpha) * eased_t,
+		dim_radius + (highlight_radius - dim_radius) * eased_t,
+	)
+}
+
+/// Draw the nodes left out of the sample as a point cloud, each cell's
+/// opacity growing with the nodes in it.
+fn draw_unsampled<C: DrawContext>(state: &ForceGraphState, ctx: &C, theme: &Theme) {
+	let cells = state.unsampled_density();
+	if cells.is_empty() {
+		return;
+	}
+	let side = density_cell(state.transform.k);
+	let color = theme.node.label_color;
+	for (x, y, count) in cells {
+		let alpha = density_alpha(count);
+		ctx.set_fill_style_str(&color.with_alpha(color.a * alpha).to_css());
+		ctx.fill_rect(x - side / 2.0, y - side / 2.0, side, side);
+	}
+}
+
+/// Opacity of a point cloud cell holding `count` nodes.
+pub(super) fn density_alpha(count: u32) -> f64 {
+	(0.15 + 0.1 * f64::from(count).ln()).min(0.6)
+}
+
+fn draw_nodes<C: DrawContext>(
 	state: &ForceGraphState,
-	ctx: &CanvasRenderingContext2d,
+	ctx: &C,
 	_config: &ScaleConfig,
 	scale: &ScaledValues,
 	theme: &Theme,
+	css: &mut CssCache,
 ) {
 	let max_t = smooth_step(state.highlight.max_intensity());
 	let has_highlight = max_t > 0.01;
-	let pulse = if theme.node.pulse_intensity > 0.0 {
-		(state.flow_time * theme.node.pulse_speed).sin() * theme.node.pulse_intensity
-	} else {
-		0.0
+	let pulse = node_pulse(state, theme);
+	// Selected nodes scale their radius and glow on top of highlighting
+	let selection = &theme.node.selection;
+	let selected = |idx, mult: f64| {
+		if state.selection.contains(&idx) {
+			mult
+		} else {
+			1.0
+		}
 	};
 
-	// Pass 1: node glows
-	if theme.node.glow_intensity > 0.0 {
+	// Pass 1: node glows, including nodes that override a glowless theme
+	if scale.full_detail() {
 		state.graph.visit_nodes(|node| {
 			let idx = node.index();
 			let node_t = smooth_step(state.highlight.node_intensity(idx));
@@ -380,13 +1655,22 @@ fn draw_nodes(
 				let hovered_glow = 1.5 + 0.5 * node_t;
 				neighbor_glow + (hovered_glow - neighbor_glow) * hover_t
 			} else if has_highlight {
-				1.0 - 0.7 * max_t
+				1.0 - state.highlight.config.dim_node_glow * max_t
 			} else {
 				1.0
 			};
 
-			draw_node_glow(ctx, node, scale, theme, glow_mult, pulse);
+			let glow_mult = glow_mult * selected(idx, selection.glow_mult);
+			draw_node_glow(ctx, node.into(), scale, theme, glow_mult, pulse);
 		});
+		for node in &state.departing {
+			draw_node_glow(ctx, node.into(), scale, theme, 1.0, pulse);
+		}
+	}
+
+	// Removed nodes fade out beneath the rest
+	for node in &state.departing {
+		draw_node(ctx, node.into(), scale, theme, 1.0, 1.0, pulse, css);
 	}
 
 	// Pass 2: non-highlighted nodes
@@ -396,12 +1680,18 @@ fn draw_nodes(
 		if node_t > 0.001 {
 			return;
 		}
-		let (alpha, radius_mult) = if has_highlight {
-			(1.0 - 0.7 * max_t, 1.0 - 0.15 * max_t)
-		} else {
-			(1.0, 1.0)
-		};
-		draw_node(ctx, node, scale, theme, alpha, radius_mult, pulse);
+		let (alpha, radius_mult) = node_emphasis(state, idx);
+		let radius_mult = radius_mult * selected(idx, selection.radius_mult);
+		draw_node(
+			ctx,
+			node.into(),
+			scale,
+			theme,
+			alpha,
+			radius_mult,
+			pulse,
+			css,
+		);
 	});
 
 	// Pass 3: highlighted/transitioning nodes on top
@@ -412,194 +1702,448 @@ fn draw_nodes(
 			return;
 		}
 
-		let eased_t = smooth_step(node_t);
 		let hover_t = smooth_step(state.highlight.hover_ring_intensity(idx));
 		let (x, y) = (node.x() as f64, node.y() as f64);
+		let (alpha, radius_mult) = node_emphasis(state, idx);
+		let radius_mult = radius_mult * selected(idx, selection.radius_mult);
 
-		let dim_alpha = if has_highlight {
-			1.0 - 0.7 * max_t
-		} else {
-			1.0
-		};
-		let dim_radius = if has_highlight {
-			1.0 - 0.15 * max_t
-		} else {
-			1.0
-		};
-
-		let neighbor_radius = 1.0 + 0.25 * eased_t;
-		let hovered_radius = 1.0 + 0.4 * eased_t;
-		let highlight_radius = neighbor_radius + (hovered_radius - neighbor_radius) * hover_t;
-
-		let alpha = dim_alpha + (1.0 - dim_alpha) * eased_t;
-		let radius_mult = dim_radius + (highlight_radius - dim_radius) * eased_t;
-
-		draw_node(ctx, node, scale, theme, alpha, radius_mult, pulse);
+		draw_node(
+			ctx,
+			node.into(),
+			scale,
+			theme,
+			alpha,
+			radius_mult,
+			pulse,
+			css,
+		);
 
 		let ring_t = smooth_step(state.highlight.hover_ring_intensity(idx));
 		if ring_t > 0.01 {
 			let node_size = node.data.user_data.size;
-			let radius = scale.node_radius * radius_mult * node_size * (1.0 + pulse);
+			let radius = scale.node_radius_for(node_size) * radius_mult * (1.0 + pulse);
+			let ring_color = hover_ring_color(theme, &node.data.user_data);
 			ctx.begin_path();
-			let _ = ctx.arc(x, y, radius + scale.ring_offset, 0.0, 2.0 * PI);
-			ctx.set_stroke_style_str(&format!("rgba(255, 255, 255, {})", 0.8 * ring_t));
-			ctx.set_line_width(scale.ring_width);
+			ctx.arc(x, y, radius + scale.ring_offset, 0.0, 2.0 * PI);
+			ctx.set_stroke_style_str(css.rgba(ring_color, theme.node.ring_alpha * ring_t));
+			ctx.set_line_width(scale.ring_width * theme.node.ring_width_mult);
 			ctx.stroke();
 
 			ctx.begin_path();
-			let _ = ctx.arc(x, y, radius + scale.ring_offset * 2.5, 0.0, 2.0 * PI);
-			ctx.set_stroke_style_str(&format!("rgba(255, 255, 255, {})", 0.3 * ring_t));
-			ctx.set_line_width(scale.ring_width * 0.5);
+			ctx.arc(x, y, radius + scale.ring_offset * 2.5, 0.0, 2.0 * PI);
+			ctx.set_stroke_style_str(
+				css.rgba(ring_color, theme.node.ring_secondary_alpha * ring_t),
+			);
+			ctx.set_line_width(scale.ring_width * theme.node.ring_secondary_width_mult);
 			ctx.stroke();
 		}
 
 		if let Some(label) = &node.data.user_data.label {
 			let node_size = node.data.user_data.size;
-			let radius = scale.node_radius * radius_mult * node_size * (1.0 + pulse);
-			ctx.set_fill_style_str(&format!("rgba(255, 255, 255, {})", 0.95 * alpha));
+			// The hovered node always shows its label, regardless of zoom tier
+			let tier_alpha = scale.label_alpha_for(node_size);
+			let label_alpha = tier_alpha + (1.0 - tier_alpha) * hover_t;
+			if label_alpha < 0.01 {
+				return;
+			}
+			let radius = scale.node_radius_for(node_size) * radius_mult * (1.0 + pulse);
+			let label_alpha = alpha * label_alpha * node_opacity(&node.data.user_data);
+			ctx.set_fill_style_str(css.rgba(theme.node.label_color, label_alpha));
 			ctx.set_font(&scale.label_font);
-			let _ = ctx.fill_text(label, x + radius + 4.0, y + 3.0);
+			ctx.fill_text(label, x + radius + 4.0, y + 3.0);
 		}
 	});
+
+	// Pass 4: selection rings, pushed outside the hover rings while hovered
+	if !state.selection.is_empty() {
+		let style = selection;
+		let width = scale.ring_width * style.width_mult;
+		if style.ring == SelectionRing::Dashed {
+			let dash = [3.0 * width, 2.0 * width];
+			ctx.set_line_dash(&dash);
+		}
+		ctx.set_line_width(width);
+		state.graph.visit_nodes(|node| {
+			let idx = node.index();
+			if !state.selection.contains(&idx) {
+				return;
+			}
+			let color = style.color.resolve(node.data.user_data.fill);
+			let alpha = style.alpha * node.data.user_data.shown;
+			ctx.set_stroke_style_str(&color.with_alpha(alpha).to_css());
+			let node_t = smooth_step(state.highlight.node_intensity(idx));
+			let hover_t = smooth_step(state.highlight.hover_ring_intensity(idx));
+			let radius = scale.node_radius_for(node.data.user_data.size)
+				* style.radius_mult
+				* (1.0 + state.highlight.config.hovered_growth * node_t)
+				* (1.0 + pulse);
+			let offset = scale.ring_offset * (1.5 + 2.5 * hover_t);
+			let (x, y) = (node.x() as f64, node.y() as f64);
+			ctx.begin_path();
+			ctx.arc(x, y, radius + offset, 0.0, 2.0 * PI);
+			ctx.stroke();
+			if style.ring == SelectionRing::Double {
+				ctx.begin_path();
+				ctx.arc(x, y, radius + offset + 2.0 * width, 0.0, 2.0 * PI);
+				ctx.stroke();
+			}
+		});
+		ctx.set_line_dash(&[]);
+	}
+
+	// Pass 5: keyboard focus ring, a solid label-colored ring outside the
+	// hover and selection rings
+	if let Some(idx) = state.focused {
+		state.graph.visit_nodes(|node| {
+			if node.index() != idx {
+				return;
+			}
+			let radius = scale.node_radius_for(node.data.user_data.size)
+				* selected(idx, selection.radius_mult)
+				* (1.0
+					+ state.highlight.config.hovered_growth
+						* smooth_step(state.highlight.node_intensity(idx)))
+				* (1.0 + pulse);
+			ctx.begin_path();
+			ctx.arc(
+				node.x() as f64,
+				node.y() as f64,
+				radius + scale.ring_offset * 5.5,
+				0.0,
+				2.0 * PI,
+			);
+			ctx.set_stroke_style_str(&theme.node.label_color.with_alpha(0.9).to_css());
+			ctx.set_line_width(scale.ring_width * 1.5);
+			ctx.stroke();
+		});
+	}
 }
 
-fn draw_node_glow(
-	ctx: &CanvasRenderingContext2d,
-	node: &force_graph::Node<NodeInfo>,
+/// Draw the alignment guides of the node being dragged, in world space.
+fn draw_align_guides<C: DrawContext>(
+	state: &ForceGraphState,
+	ctx: &C,
+	scale: &ScaledValues,
+	theme: &Theme,
+) {
+	if state.guides.is_empty() {
+		return;
+	}
+	// Extend past both nodes so short guides stay visible
+	let overshoot = 3.0 * scale.node_radius;
+	let dash = [4.0 / scale.k, 4.0 / scale.k];
+	ctx.set_line_dash(&dash);
+	ctx.set_stroke_style_str(&theme.node.label_color.with_alpha(0.35).to_css());
+	ctx.set_line_width(1.0 / scale.k);
+	ctx.begin_path();
+	for g in &state.guides {
+		let len = (g.x1 - g.x0).hypot(g.y1 - g.y0).max(f64::EPSILON);
+		let (ux, uy) = ((g.x1 - g.x0) / len, (g.y1 - g.y0) / len);
+		ctx.move_to(g.x0 - ux * overshoot, g.y0 - uy * overshoot);
+		ctx.line_to(g.x1 + ux * overshoot, g.y1 + uy * overshoot);
+	}
+	ctx.stroke();
+	ctx.set_line_dash(&[]);
+}
+
+/// What [`draw_node`] and [`draw_node_glow`] need of a node, which may be
+/// one a data update removed and that is fading out.
+#[derive(Clone, Copy)]
+struct NodeSprite<'a> {
+	x: f64,
+	y: f64,
+	info: &'a NodeInfo,
+	anchored: bool,
+}
+
+impl<'a> From<&'a force_graph::Node<NodeInfo>> for NodeSprite<'a> {
+	fn from(node: &'a force_graph::Node<NodeInfo>) -> Self {
+		Self {
+			x: node.x() as f64,
+			y: node.y() as f64,
+			info: &node.data.user_data,
+			anchored: node.data.is_anchor,
+		}
+	}
+}
+
+impl<'a> From<&'a DepartingNode> for NodeSprite<'a> {
+	fn from(node: &'a DepartingNode) -> Self {
+		Self {
+			x: node.x,
+			y: node.y,
+			info: &node.info,
+			anchored: false,
+		}
+	}
+}
+
+/// Radius multiplier of a node entering or leaving, growing from nothing
+/// with its fade.
+pub(super) fn appear_scale(info: &NodeInfo) -> f64 {
+	smooth_step(info.appear_t)
+}
+
+/// Opacity of a node as it fades in or out with data updates and filters;
+/// 0 when left out of the sample.
+pub(super) fn node_opacity(info: &NodeInfo) -> f64 {
+	if info.sampled_out {
+		return 0.0;
+	}
+	info.appear_t * info.shown
+}
+
+/// Opacity of the edge from `a` to `b` under the filter and playback: as
+/// faded as the more hidden of the two, and fading in itself once playback
+/// reaches its time. 0 when either is left out of the sample.
+pub(super) fn edge_shown(
+	state: &ForceGraphState,
+	a: &force_graph::Node<NodeInfo>,
+	b: &force_graph::Node<NodeInfo>,
+) -> f64 {
+	let (a_info, b_info) = (&a.data.user_data, &b.data.user_data);
+	if a_info.sampled_out || b_info.sampled_out {
+		return 0.0;
+	}
+	a_info.shown.min(b_info.shown) * state.edge_fade(a.index(), b.index())
+}
+
+fn draw_node_glow<C: DrawContext>(
+	ctx: &C,
+	node: NodeSprite<'_>,
 	scale: &ScaledValues,
 	theme: &Theme,
 	intensity_mult: f64,
 	pulse: f64,
 ) {
-	let (x, y) = (node.x() as f64, node.y() as f64);
-	let node_size = node.data.user_data.size;
-	let radius = scale.node_radius * node_size * (1.0 + pulse);
+	let (x, y, info) = (node.x, node.y, node.info);
+	let radius = scale.node_radius_for(info.size) * (1.0 + pulse) * appear_scale(info);
 	let glow_radius = radius * 3.0 * intensity_mult;
-	let alpha = theme.node.glow_intensity * intensity_mult * 0.4;
+	let alpha =
+		info.glow.unwrap_or(theme.node.glow_intensity) * intensity_mult * 0.4 * node_opacity(info);
 
-	if alpha < 0.01 {
+	if alpha < 0.01 || glow_radius <= 0.0 {
 		return;
 	}
 
-	let node_color = parse_color(&node.data.user_data.color);
+	// An explicit glow color shows at full strength rather than as a tint
+	let (tint, saturation) = match info.glow_color {
+		Some(color) => (color, 1.0),
+		None => (info.fill, theme.node.glow_saturation),
+	};
 
-	let gradient = ctx
-		.create_radial_gradient(x, y, radius * 0.5, x, y, glow_radius)
-		.unwrap();
+	let gradient = ctx.create_radial_gradient(x, y, radius * 0.5, x, y, glow_radius);
 
-	let glow_color = node_color.with_alpha(alpha * theme.node.glow_saturation);
+	let glow_color = tint.with_alpha(alpha * saturation);
 	let white_glow = Color::rgba(255, 255, 255, alpha * 0.3);
 
-	gradient
-		.add_color_stop(0.0, &white_glow.lerp(glow_color, 0.5).to_css())
-		.unwrap();
-	gradient
-		.add_color_stop(0.4, &glow_color.with_alpha(alpha * 0.5).to_css())
-		.unwrap();
-	gradient.add_color_stop(1.0, "rgba(0, 0, 0, 0)").unwrap();
+	gradient.add_color_stop(0.0, &white_glow.lerp(glow_color, 0.5).to_css());
+	gradient.add_color_stop(0.4, &glow_color.with_alpha(alpha * 0.5).to_css());
+	gradient.add_color_stop(1.0, "rgba(0, 0, 0, 0)");
 
 	ctx.begin_path();
-	let _ = ctx.arc(x, y, glow_radius, 0.0, 2.0 * PI);
-	#[allow(deprecated)]
-	ctx.set_fill_style(&gradient);
+	ctx.arc(x, y, glow_radius, 0.0, 2.0 * PI);
+	ctx.set_fill_gradient(&gradient);
 	ctx.fill();
 }
 
-fn draw_node(
-	ctx: &CanvasRenderingContext2d,
-	node: &force_graph::Node<NodeInfo>,
+#[allow(clippy::too_many_arguments)]
+fn draw_node<C: DrawContext>(
+	ctx: &C,
+	node: NodeSprite<'_>,
 	scale: &ScaledValues,
 	theme: &Theme,
 	alpha: f64,
 	radius_mult: f64,
 	pulse: f64,
+	css: &mut CssCache,
 ) {
-	let (x, y) = (node.x() as f64, node.y() as f64);
-	let node_size = node.data.user_data.size;
-	let radius = scale.node_radius * radius_mult * node_size * (1.0 + pulse);
-	let color = &node.data.user_data.color;
+	let (x, y, info) = (node.x, node.y, node.info);
+	if info.sampled_out {
+		return;
+	}
+	let node_size = info.size;
+	// Nodes added or removed by a data update grow in or shrink away
+	let radius =
+		scale.node_radius_for(node_size) * radius_mult * (1.0 + pulse) * appear_scale(info);
+	let alpha = alpha * node_opacity(info);
 
 	ctx.set_global_alpha(alpha);
 
-	if theme.node.use_gradient {
-		let gradient = ctx
-			.create_radial_gradient(x - radius * 0.3, y - radius * 0.3, 0.0, x, y, radius)
-			.unwrap();
+	// Only the fill casts a shadow; the border, pin and label would double it.
+	// Canvas shadows ignore the transform, so scale them with the zoom here
+	let shadow = theme.node.shadow.filter(|_| scale.shadows);
+	if let Some(s) = shadow {
+		ctx.set_shadow_color(&s.color.to_css());
+		ctx.set_shadow_blur(s.blur * scale.k);
+		ctx.set_shadow_offset_x(s.offset.0 * scale.k);
+		ctx.set_shadow_offset_y(s.offset.1 * scale.k);
+	}
 
-		let base_color = parse_color(color);
-		let highlight = base_color.lighten(0.4);
-		let shadow = base_color.darken(0.2);
+	if theme.node.use_gradient && scale.full_detail() {
+		let gradient =
+			ctx.create_radial_gradient(x - radius * 0.3, y - radius * 0.3, 0.0, x, y, radius);
 
-		gradient.add_color_stop(0.0, &highlight.to_css()).unwrap();
-		gradient.add_color_stop(0.7, &base_color.to_css()).unwrap();
-		gradient.add_color_stop(1.0, &shadow.to_css()).unwrap();
+		let [highlight, base, shadow] = &info.fill_stops;
+		gradient.add_color_stop(0.0, highlight);
+		gradient.add_color_stop(0.7, base);
+		gradient.add_color_stop(1.0, shadow);
 
 		ctx.begin_path();
-		let _ = ctx.arc(x, y, radius, 0.0, 2.0 * PI);
-		#[allow(deprecated)]
-		ctx.set_fill_style(&gradient);
+		ctx.arc(x, y, radius, 0.0, 2.0 * PI);
+		ctx.set_fill_gradient(&gradient);
 		ctx.fill();
 	} else {
 		ctx.begin_path();
-		let _ = ctx.arc(x, y, radius, 0.0, 2.0 * PI);
-		ctx.set_fill_style_str(color);
+		ctx.arc(x, y, radius, 0.0, 2.0 * PI);
+		ctx.set_fill_style_str(&info.color);
 		ctx.fill();
 	}
 
-	if theme.node.border_width > 0.0 {
+	if shadow.is_some() {
+		ctx.set_shadow_color("transparent");
+	}
+
+	if let Some(ref segments) = info.segments {
+		draw_donut(ctx, x, y, radius, segments);
+	}
+
+	let border_width = node_border_width(theme, info);
+	if border_width > 0.0 {
 		ctx.begin_path();
-		let _ = ctx.arc(x, y, radius, 0.0, 2.0 * PI);
-		ctx.set_stroke_style_str(&theme.node.border_color.to_css());
-		ctx.set_line_width(theme.node.border_width / scale.k);
+		ctx.arc(x, y, radius, 0.0, 2.0 * PI);
+		let color = info.border_color.unwrap_or(theme.node.border_color);
+		ctx.set_stroke_style_str(css.rgba(color, 1.0));
+		ctx.set_line_width(border_width / scale.k);
 		ctx.stroke();
 	}
 
+	if node.anchored && theme.node.pin_indicator {
+		draw_pin(ctx, x, y, radius, scale, theme);
+	}
+	if let Some(ref badge) = info.badge
+		&& scale.badges
+	{
+		draw_badge(ctx, x, y, radius, badge, scale, theme, css);
+	}
+
 	ctx.set_global_alpha(1.0);
 
-	if let Some(label) = &node.data.user_data.label {
-		if alpha > 0.5 {
-			ctx.set_global_alpha(alpha * 0.8);
-			ctx.set_fill_style_str("rgba(255, 255, 255, 0.85)");
-			ctx.set_font(&scale.label_font);
-			let _ = ctx.fill_text(label, x + radius + 4.0, y + 3.0);
-			ctx.set_global_alpha(1.0);
-		}
-	}
-}
-
-/// Parses a CSS color string into a [`Color`].
-/// Supports hex (`#RRGGBB`) and `rgb()`/`rgba()` functional notation.
-fn parse_color(color_str: &str) -> Color {
-	if color_str.starts_with('#') && color_str.len() == 7 {
-		let r = u8::from_str_radix(&color_str[1..3], 16).unwrap_or(128);
-		let g = u8::from_str_radix(&color_str[3..5], 16).unwrap_or(128);
-		let b = u8::from_str_radix(&color_str[5..7], 16).unwrap_or(128);
-		Color::rgb(r, g, b)
-	} else if color_str.starts_with("rgb") {
-		let nums: Vec<&str> = color_str
-			.trim_start_matches("rgba(")
-			.trim_start_matches("rgb(")
-			.trim_end_matches(')')
-			.split(',')
-			.collect();
-		let r = nums
-			.first()
-			.and_then(|s| s.trim().parse().ok())
-			.unwrap_or(128);
-		let g = nums
-			.get(1)
-			.and_then(|s| s.trim().parse().ok())
-			.unwrap_or(128);
-		let b = nums
-			.get(2)
-			.and_then(|s| s.trim().parse().ok())
-			.unwrap_or(128);
-		let a = nums
-			.get(3)
-			.and_then(|s| s.trim().parse().ok())
-			.unwrap_or(1.0);
-		Color::rgba(r, g, b, a)
-	} else {
-		Color::rgb(128, 128, 128)
+	let label_alpha = scale.label_alpha_for(node_size);
+	if let Some(label) = &info.label
+		&& alpha > 0.5
+		&& label_alpha > 0.01
+	{
+		ctx.set_global_alpha(alpha * 0.8 * label_alpha);
+		// Resting labels sit slightly below the hovered label's opacity
+		ctx.set_fill_style_str(css.rgba(theme.node.label_color, 0.9));
+		ctx.set_font(&scale.label_font);
+		ctx.fill_text(label, x + radius + 4.0, y + 3.0);
+		ctx.set_global_alpha(1.0);
 	}
 }
+
+/// Draw a node's segments as a ring of arcs, each spanning its share of the
+/// total clockwise from the top, leaving the inner [`DONUT_HOLE`] of the node
+/// showing its fill. Draws nothing when the segments sum to zero.
+fn draw_donut<C: DrawContext>(ctx: &C, x: f64, y: f64, radius: f64, segments: &[(f64, String)]) {
+	let total: f64 = segments.iter().map(|(value, _)| value.max(0.0)).sum();
+	if total <= 0.0 {
+		return;
+	}
+	// Stroke each arc along the middle of the ring, as wide as the ring
+	let inner = radius * DONUT_HOLE;
+	ctx.set_line_width(radius - inner);
+	let mut start = -PI / 2.0;
+	for (value, color) in segments.iter().filter(|(value, _)| *value > 0.0) {
+		let end = start + value / total * 2.0 * PI;
+		ctx.begin_path();
+		ctx.arc(x, y, (radius + inner) / 2.0, start, end);
+		ctx.set_stroke_style_str(color);
+		ctx.stroke();
+		start = end;
+	}
+}
+
+/// Opaque color of a node's hover rings: its border override if it has one,
+/// so the emphasis carries through hover, otherwise the theme's ring color.
+pub(super) fn hover_ring_color(theme: &Theme, info: &NodeInfo) -> Color {
+	match info.border_color {
+		Some(color) => color.with_alpha(1.0),
+		None => theme.node.ring_color.resolve(info.fill),
+	}
+}
+
+/// Border width of a node in screen pixels: its own override, else the
+/// theme's, with a border color override shown even under a borderless
+/// theme.
+pub(super) fn node_border_width(theme: &Theme, info: &NodeInfo) -> f64 {
+	info.border_width.unwrap_or(
+		if info.border_color.is_some() && theme.node.border_width <= 0.0 {
+			OVERRIDE_BORDER_WIDTH
+		} else {
+			theme.node.border_width
+		},
+	)
+}
+
+/// Draw a node's badge as a bubble centered on its upper right edge, widening
+/// into a pill for longer text. The bubble scales with the node but never
+/// drops below [`BADGE_MIN_RADIUS`] on screen. Inherits the caller's global
+/// alpha so it dims with its node.
+#[allow(clippy::too_many_arguments)]
+fn draw_badge<C: DrawContext>(
+	ctx: &C,
+	x: f64,
+	y: f64,
+	radius: f64,
+	text: &str,
+	scale: &ScaledValues,
+	theme: &Theme,
+	css: &mut CssCache,
+) {
+	let r = (radius * 0.45).max(BADGE_MIN_RADIUS / scale.k);
+	let (cx, cy) = (x + radius * 0.7, y - radius * 0.7);
+	let font_size = r * 1.3;
+	ctx.set_font(&format!("bold {font_size}px sans-serif"));
+	let half = ((ctx.measure_text(text) + r) / 2.0 - r).max(0.0);
+
+	ctx.begin_path();
+	ctx.arc(cx - half, cy, r, PI / 2.0, 3.0 * PI / 2.0);
+	ctx.arc(cx + half, cy, r, -PI / 2.0, PI / 2.0);
+	ctx.close_path();
+	ctx.set_fill_style_str(css.rgba(theme.node.badge_color, 1.0));
+	ctx.fill();
+
+	ctx.set_fill_style_str(css.rgba(theme.node.badge_text_color, 1.0));
+	ctx.set_text_align("center");
+	ctx.fill_text(text, cx, cy + font_size * 0.35);
+	ctx.set_text_align("start");
+}
+
+/// Draw a small pushpin on the upper right of a node, at a constant screen
+/// size. Inherits the caller's global alpha so it dims with its node.
+fn draw_pin<C: DrawContext>(
+	ctx: &C,
+	x: f64,
+	y: f64,
+	radius: f64,
+	scale: &ScaledValues,
+	theme: &Theme,
+) {
+	let head = 2.5 / scale.k;
+	let (hx, hy) = (x + radius * 0.7 + head, y - radius * 0.7 - head);
+	let color = theme.node.pin_color.to_css();
+
+	ctx.begin_path();
+	ctx.move_to(hx, hy);
+	ctx.line_to(hx - head * 1.6, hy + head * 1.6);
+	ctx.set_stroke_style_str(&color);
+	ctx.set_line_width(1.0 / scale.k);
+	ctx.stroke();
+
+	ctx.begin_path();
+	ctx.arc(hx, hy, head, 0.0, 2.0 * PI);
+	ctx.set_fill_style_str(&color);
+	ctx.fill();
+}
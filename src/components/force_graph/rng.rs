@@ -0,0 +1,39 @@
+//! Small seeded pseudo-random generator for reproducible layouts.
+
+/// Layout seed used when the caller doesn't pick one.
+pub const DEFAULT_SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+/// Xorshift64 generator; fast, deterministic, and plenty for shuffles and
+/// scattering nodes. Not suitable for anything security-related.
+#[derive(Clone, Debug)]
+pub struct Xorshift(pub u64);
+
+impl Xorshift {
+	/// Generator for `seed`. Nearby seeds give unrelated sequences, and any
+	/// seed is valid, including zero.
+	pub fn new(seed: u64) -> Self {
+		// SplitMix64 finalizer; xorshift must never start from zero
+		let mut x = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+		x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+		x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+		Self((x ^ (x >> 31)).max(1))
+	}
+
+	/// Next raw 64-bit value.
+	pub fn next(&mut self) -> u64 {
+		self.0 ^= self.0 << 13;
+		self.0 ^= self.0 >> 7;
+		self.0 ^= self.0 << 17;
+		self.0
+	}
+
+	/// Value in `0..n`; the modulo bias is irrelevant for shuffling.
+	pub fn below(&mut self, n: usize) -> usize {
+		(self.next() % n as u64) as usize
+	}
+
+	/// Uniform value in `[0, 1)`.
+	pub fn unit(&mut self) -> f64 {
+		(self.next() >> 11) as f64 / (1u64 << 53) as f64
+	}
+}
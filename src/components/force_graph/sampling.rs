@@ -0,0 +1,141 @@
+//! Drawing a sample of graphs too large to draw whole, for the component's
+//! `max_render_nodes` prop.
+//!
+//! Only the chosen sample is simulated, drawn and reachable by the pointer.
+//! The rest hold still where they were placed, and are left out or drawn as
+//! a coarse point cloud binned on screen so their mass stays visible.
+
+use std::collections::HashMap;
+
+/// Node count above which the component samples unless told otherwise.
+pub const MAX_RENDER_NODES: usize = 20_000;
+
+/// Side of a point cloud cell, in screen pixels.
+const DENSITY_CELL_PX: f64 = 4.0;
+
+/// Which nodes make the sample when a graph has more than
+/// `max_render_nodes`. Both are deterministic: the same data always draws
+/// the same nodes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SampleStrategy {
+	/// Labeled nodes first, then those with the most links, ties going to
+	/// the earlier node in the data.
+	#[default]
+	Important,
+	/// Nodes evenly spaced through the data's order.
+	Stride,
+}
+
+/// How nodes left out of the sample are drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnsampledNodes {
+	/// As a faint point cloud, denser where more of them gather.
+	#[default]
+	Density,
+	/// Not at all, along with their links.
+	Skip,
+}
+
+/// Sampling settings, as given to the component's props.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) struct NodeSampling {
+	pub(super) max_nodes: usize,
+	pub(super) strategy: SampleStrategy,
+	pub(super) unsampled: UnsampledNodes,
+}
+
+/// How much of the graph a sample draws, reported to the component's
+/// `on_sampling` callback.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SamplingInfo {
+	/// Nodes drawn individually.
+	pub drawn: usize,
+	/// Nodes in the graph.
+	pub total: usize,
+}
+
+/// A node competing for a place in the sample.
+pub(super) struct Candidate {
+	pub(super) labeled: bool,
+	pub(super) degree: usize,
+}
+
+/// Positions in `candidates` of the at most `max` nodes to draw, in
+/// ascending order.
+pub(super) fn pick(candidates: &[Candidate], max: usize, strategy: SampleStrategy) -> Vec<usize> {
+	let n = candidates.len();
+	if n <= max {
+		return (0..n).collect();
+	}
+	let mut picked: Vec<usize> = match strategy {
+		SampleStrategy::Important => {
+			let mut order: Vec<usize> = (0..n).collect();
+			order.sort_by_key(|&i| {
+				let c = &candidates[i];
+				(!c.labeled, std::cmp::Reverse(c.degree), i)
+			});
+			order.truncate(max);
+			order
+		}
+		// Node `i` starts a new stride when `i * max / n` steps up
+		SampleStrategy::Stride => (0..n)
+			.filter(|&i| i == 0 || (i * max) / n != ((i - 1) * max) / n)
+			.collect(),
+	};
+	picked.sort_unstable();
+	picked
+}
+
+/// Bin world `points` into square cells `DENSITY_CELL_PX` screen pixels
+/// across at zoom `k`, returning each occupied cell's world center and the
+/// number of points in it.
+pub(super) fn density(points: impl Iterator<Item = (f64, f64)>, k: f64) -> Vec<(f64, f64, u32)> {
+	let cell = density_cell(k);
+	let mut bins: HashMap<(i64, i64), u32> = HashMap::new();
+	for (x, y) in points {
+		let key = ((x / cell).floor() as i64, (y / cell).floor() as i64);
+		*bins.entry(key).or_insert(0) += 1;
+	}
+	bins.into_iter()
+		.map(|((cx, cy), count)| ((cx as f64 + 0.5) * cell, (cy as f64 + 0.5) * cell, count))
+		.collect()
+}
+
+/// World side of a [`density`] cell at zoom `k`.
+pub(super) fn density_cell(k: f64) -> f64 {
+	DENSITY_CELL_PX / k
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn candidates(specs: &[(bool, usize)]) -> Vec<Candidate> {
+		specs
+			.iter()
+			.map(|&(labeled, degree)| Candidate { labeled, degree })
+			.collect()
+	}
+
+	#[test]
+	fn important_nodes_win_places() {
+		let nodes = candidates(&[(false, 1), (false, 9), (true, 0), (false, 9), (false, 3)]);
+		assert_eq!(pick(&nodes, 3, SampleStrategy::Important), vec![1, 2, 3]);
+		assert_eq!(pick(&nodes, 10, SampleStrategy::Important).len(), 5);
+	}
+
+	#[test]
+	fn stride_spreads_through_the_data() {
+		let nodes = candidates(&[(false, 0); 10]);
+		assert_eq!(pick(&nodes, 5, SampleStrategy::Stride), vec![0, 2, 4, 6, 8]);
+		assert_eq!(pick(&nodes, 3, SampleStrategy::Stride).len(), 3);
+	}
+
+	#[test]
+	fn density_bins_by_screen_cell() {
+		let cells = density([(0.5, 0.5), (1.0, 1.0), (30.0, 0.0)].into_iter(), 2.0);
+		let mut counts: Vec<u32> = cells.iter().map(|&(_, _, n)| n).collect();
+		counts.sort_unstable();
+		assert_eq!(counts, vec![1, 2]);
+	}
+}
@@ -23,18 +23,19 @@
 
 /// Defines how a visual property scales with zoom level.
 #[derive(Clone, Debug)]
-#[allow(
-	dead_code,
-	reason = "World/Screen variants complete the API for users customizing ScaleConfig"
-)]
 pub enum ScaleBehavior {
 	/// Constant world-space size. Appears larger when zoomed in.
 	World,
 	/// Constant screen-space size (pixels). Unaffected by zoom.
 	Screen,
 	/// World-space scaling, clamped to min/max screen-space bounds.
-	/// `(min_screen_px, max_screen_px)` - use `f64::NEG_INFINITY` or `f64::INFINITY` for unbounded.
-	Clamped { min_screen: f64, max_screen: f64 },
+	/// Use `f64::NEG_INFINITY` or `f64::INFINITY` for unbounded.
+	Clamped {
+		/// Smallest on-screen size in pixels.
+		min_screen: f64,
+		/// Largest on-screen size in pixels.
+		max_screen: f64,
+	},
 }
 
 impl ScaleBehavior {
@@ -63,10 +64,6 @@ impl ScaleBehavior {
 
 /// Defines how alpha/opacity scales with zoom level.
 #[derive(Clone, Debug)]
-#[allow(
-	dead_code,
-	reason = "Constant/Fade variants available for custom alpha behaviors"
-)]
 pub enum AlphaBehavior {
 	/// Constant alpha regardless of zoom.
 	Constant,
@@ -76,7 +73,9 @@ pub enum AlphaBehavior {
 	/// Alpha fades based on zoom thresholds.
 	/// Fully visible at `full_alpha_k`, fades to zero at `zero_alpha_k`.
 	Fade {
+		/// Zoom level at and beyond which alpha is zero.
 		zero_alpha_k: f64,
+		/// Zoom level at and beyond which alpha is full.
 		full_alpha_k: f64,
 	},
 }
@@ -108,14 +107,26 @@ pub struct NodeScaleConfig {
 	pub radius: f64,
 	/// How the node radius scales with zoom.
 	pub radius_behavior: ScaleBehavior,
-	/// Hit detection radius in world units.
-	pub hit_radius: f64,
-	/// How hit radius scales with zoom.
-	pub hit_behavior: ScaleBehavior,
+	/// Smallest radius in screen pixels any node is drawn at, whatever its
+	/// size and the zoom, so nodes don't shrink to specks when zoomed far
+	/// out. Hit areas and edge ends follow the clamped radius.
+	pub min_px: f64,
+	/// Screen pixels the hit area extends past a node's drawn radius, so
+	/// hit areas follow node sizes while small nodes stay easy to grab.
+	pub hit_padding: f64,
 	/// Label font size in screen pixels.
 	pub label_size: f64,
 	/// Minimum zoom level for label font scaling.
 	pub label_min_k: f64,
+	/// Zoom level at which labels of important nodes become visible.
+	pub label_important_k: f64,
+	/// Zoom level at which all labels become visible.
+	pub label_all_k: f64,
+	/// Width of the zoom band over which each label tier fades in.
+	/// Centered on the tier threshold.
+	pub label_fade_width: f64,
+	/// Minimum node size multiplier for a labeled node to count as important.
+	pub label_important_size: f64,
 }
 
 /// Configuration for edge visual scaling.
@@ -123,9 +134,15 @@ pub struct NodeScaleConfig {
 pub struct EdgeScaleConfig {
 	/// Base line width in screen pixels.
 	pub line_width: f64,
-	/// Dash pattern (dash, gap) in world units.
+	/// Dash pattern (dash, gap) in world units, for
+	/// [`LinkStyle::Dashed`](crate::LinkStyle::Dashed) links and the flow
+	/// dash of unstyled ones.
 	pub dash_pattern: (f64, f64),
-	/// Flow animation speed (world units per second).
+	/// Dot pattern (dot, gap) of
+	/// [`LinkStyle::Dotted`](crate::LinkStyle::Dotted) links, in multiples
+	/// of the line width.
+	pub dot_pattern: (f64, f64),
+	/// Flow animation speed of dashed and unstyled links (world units per second).
 	pub flow_speed: f64,
 	/// How dash pattern alpha/visibility scales with zoom.
 	/// When faded out, edges become solid lines.
@@ -148,9 +165,11 @@ pub struct ArrowScaleConfig {
 /// Configuration for hover glow effects.
 #[derive(Clone, Debug)]
 pub struct GlowScaleConfig {
-	/// Glow radius multiplier for hovered nodes.
+	/// Glow radius multiplier for hovered nodes. Reserved: the built-in
+	/// renderers brighten hovered nodes' glow by a fixed amount.
 	pub hovered_radius: f64,
-	/// Glow radius multiplier for neighbor nodes.
+	/// Glow radius multiplier for neighbor nodes. Reserved, like
+	/// `hovered_radius`.
 	pub neighbor_radius: f64,
 	/// Stroke width for hover ring in screen pixels.
 	pub ring_width: f64,
@@ -158,13 +177,68 @@ pub struct GlowScaleConfig {
 	pub ring_offset: f64,
 }
 
-/// Complete scale configuration for all graph elements.
+/// Rendering detail tier, traded against frame time on large or distant views.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DetailLevel {
+	/// Every effect the theme enables.
+	#[default]
+	Full,
+	/// No node or edge glow, flat node and edge fills, and no labels except
+	/// on the hovered node.
+	Low,
+}
+
+/// Thresholds for switching between [`DetailLevel`]s.
+#[derive(Clone, Debug)]
+pub struct LodConfig {
+	/// Graphs with more nodes than this always render at [`DetailLevel::Low`].
+	pub max_full_nodes: usize,
+	/// Zoom level below which rendering drops to [`DetailLevel::Low`].
+	pub min_full_k: f64,
+	/// Fraction of `min_full_k` the zoom must pass beyond it before the tier
+	/// switches back, so zooming across the boundary doesn't flicker.
+	pub hysteresis: f64,
+	/// Graphs with more nodes than this draw no node shadows, which are slow
+	/// on canvas.
+	pub max_shadow_nodes: usize,
+	/// Zoom level below which node badges are skipped.
+	pub min_badge_k: f64,
+}
+
+impl LodConfig {
+	/// Detail level for this frame, given the previous frame's level.
+	pub fn level(&self, previous: DetailLevel, k: f64, node_count: usize) -> DetailLevel {
+		if node_count > self.max_full_nodes {
+			return DetailLevel::Low;
+		}
+		let band = self.min_full_k * self.hysteresis;
+		match previous {
+			DetailLevel::Full if k < self.min_full_k - band => DetailLevel::Low,
+			DetailLevel::Low if k > self.min_full_k + band => DetailLevel::Full,
+			other => other,
+		}
+	}
+
+	/// Whether node shadows are drawn at `detail` with `node_count` nodes.
+	pub fn shadows(&self, detail: DetailLevel, node_count: usize) -> bool {
+		detail == DetailLevel::Full && node_count <= self.max_shadow_nodes
+	}
+}
+
+/// Complete scale configuration for all graph elements, passed to
+/// [`ForceGraphCanvas`](super::ForceGraphCanvas) as `scale_config`.
 #[derive(Clone, Debug)]
 pub struct ScaleConfig {
+	/// Node radii, hit areas and label sizes and visibility.
 	pub node: NodeScaleConfig,
+	/// Edge widths and dash patterns.
 	pub edge: EdgeScaleConfig,
+	/// Arrowhead sizes and fading.
 	pub arrow: ArrowScaleConfig,
+	/// Hover glow and ring.
 	pub glow: GlowScaleConfig,
+	/// When rendering drops to lower detail.
+	pub lod: LodConfig,
 }
 
 impl Default for ScaleConfig {
@@ -176,17 +250,19 @@ impl Default for ScaleConfig {
 					min_screen: 5.0,
 					max_screen: f64::INFINITY,
 				},
-				hit_radius: 12.0,
-				hit_behavior: ScaleBehavior::Clamped {
-					min_screen: 5.0,
-					max_screen: f64::INFINITY,
-				},
+				min_px: 2.0,
+				hit_padding: 5.0,
 				label_size: 10.0,
 				label_min_k: 0.5,
+				label_important_k: 0.5,
+				label_all_k: 1.5,
+				label_fade_width: 0.2,
+				label_important_size: 1.7,
 			},
 			edge: EdgeScaleConfig {
 				line_width: 1.5,
 				dash_pattern: (8.0, 4.0),
+				dot_pattern: (1.0, 2.5),
 				flow_speed: 12.0,
 				dash_alpha_behavior: AlphaBehavior::Fade {
 					zero_alpha_k: 0.4,
@@ -208,10 +284,31 @@ impl Default for ScaleConfig {
 				ring_width: 1.5,
 				ring_offset: 2.0,
 			},
+			lod: LodConfig {
+				max_full_nodes: 3000,
+				min_full_k: 0.3,
+				hysteresis: 0.15,
+				max_shadow_nodes: 500,
+				min_badge_k: 0.5,
+			},
 		}
 	}
 }
 
+impl ScaleConfig {
+	/// The default configuration with nodes and arrows a constant size on
+	/// screen, so they stay visible and clickable when zoomed far out, as
+	/// for overview navigation of large graphs. Edge widths are already
+	/// constant on screen. Zooming in no longer enlarges nodes either, so
+	/// the layout's spacing grows around them instead.
+	pub fn screen_sized() -> Self {
+		let mut config = Self::default();
+		config.node.radius_behavior = ScaleBehavior::Screen;
+		config.arrow.size_behavior = ScaleBehavior::Screen;
+		config
+	}
+}
+
 /// Pre-computed scale values for a specific zoom level.
 ///
 /// Create this once per frame and pass it to rendering functions.
@@ -224,18 +321,31 @@ impl Default for ScaleConfig {
 pub struct ScaledValues {
 	/// Current zoom level.
 	pub k: f64,
-	/// Node radius in world-space.
+	/// Node radius in world-space, at least `min_node_radius`.
 	pub node_radius: f64,
-	/// Hit detection radius in world-space.
-	pub hit_radius: f64,
+	/// Smallest radius of any node in world-space; see
+	/// [`NodeScaleConfig::min_px`].
+	pub min_node_radius: f64,
+	/// Hit area padding past the drawn radius, in world-space.
+	pub hit_padding: f64,
+	/// Label font size in world-space.
+	pub label_size: f64,
 	/// Label font size string (e.g., "10px sans-serif").
 	pub label_font: String,
+	/// Label visibility [0, 1] for ordinary labeled nodes.
+	pub label_alpha: f64,
+	/// Label visibility [0, 1] for important labeled nodes.
+	pub important_label_alpha: f64,
+	/// Size multiplier at or above which a node uses `important_label_alpha`.
+	pub important_size: f64,
 	/// Edge line width in world-space.
 	pub edge_line_width: f64,
 	/// Dash pattern in world-space.
 	pub dash_pattern: (f64, f64),
 	/// Dash pattern visibility [0, 1]. At 0, edges are solid lines.
 	pub dash_alpha: f64,
+	/// Dot pattern in multiples of the line width.
+	pub dot_pattern: (f64, f64),
 	/// Arrow size in world-space.
 	pub arrow_size: f64,
 	/// Arrow alpha multiplier [0, 1].
@@ -246,30 +356,96 @@ pub struct ScaledValues {
 	pub ring_width: f64,
 	/// Hover ring offset in world-space.
 	pub ring_offset: f64,
+	/// Detail tier; [`Self::new`] assumes [`DetailLevel::Full`], see
+	/// [`Self::with_detail`].
+	pub detail: DetailLevel,
+	/// Whether node shadows are drawn; see [`LodConfig::shadows`].
+	pub shadows: bool,
+	/// Whether node badges are drawn; see [`LodConfig::min_badge_k`].
+	pub badges: bool,
 }
 
 impl ScaledValues {
 	/// Compute scaled values from configuration and current zoom level.
 	pub fn new(config: &ScaleConfig, k: f64) -> Self {
-		let node_radius = config.node.radius_behavior.apply(config.node.radius, k);
-		let hit_radius = config.node.hit_behavior.apply(config.node.hit_radius, k);
+		let min_node_radius = config.node.min_px / k;
+		let node_radius = config
+			.node
+			.radius_behavior
+			.apply(config.node.radius, k)
+			.max(min_node_radius);
 		let label_font_size = config.node.label_size / k.max(config.node.label_min_k);
 		let arrow_alpha = config.arrow.alpha_behavior.apply(k);
 		let dash_alpha = config.edge.dash_alpha_behavior.apply(k);
+		let half_fade = config.node.label_fade_width / 2.0;
+		let label_tier = |threshold: f64| {
+			AlphaBehavior::Fade {
+				zero_alpha_k: threshold - half_fade,
+				full_alpha_k: threshold + half_fade,
+			}
+			.apply(k)
+		};
 
 		Self {
 			k,
 			node_radius,
-			hit_radius,
+			min_node_radius,
+			hit_padding: config.node.hit_padding / k,
+			label_size: label_font_size,
 			label_font: format!("{}px sans-serif", label_font_size),
+			label_alpha: label_tier(config.node.label_all_k),
+			important_label_alpha: label_tier(config.node.label_important_k),
+			important_size: config.node.label_important_size,
 			edge_line_width: config.edge.line_width / k,
 			dash_pattern: config.edge.dash_pattern,
 			dash_alpha,
+			dot_pattern: config.edge.dot_pattern,
 			arrow_size: config.arrow.size_behavior.apply(config.arrow.size, k),
 			arrow_alpha,
 			cull_arrows: arrow_alpha < config.arrow.cull_alpha,
 			ring_width: config.glow.ring_width / k,
 			ring_offset: config.glow.ring_offset / k,
+			detail: DetailLevel::Full,
+			shadows: true,
+			badges: k >= config.lod.min_badge_k,
+		}
+	}
+
+	/// Drawn radius in world-space of a node of `size`, never below
+	/// `min_node_radius`.
+	pub fn node_radius_for(&self, size: f64) -> f64 {
+		(self.node_radius * size).max(self.min_node_radius)
+	}
+
+	/// Hit detection radius in world-space of a node of `size`: its drawn
+	/// radius plus the padding.
+	pub fn hit_radius(&self, size: f64) -> f64 {
+		self.node_radius_for(size) + self.hit_padding
+	}
+
+	/// Apply a detail tier; [`DetailLevel::Low`] hides all labels.
+	pub fn with_detail(mut self, detail: DetailLevel) -> Self {
+		self.detail = detail;
+		if detail == DetailLevel::Low {
+			self.shadows = false;
+			self.badges = false;
+			self.label_alpha = 0.0;
+			self.important_label_alpha = 0.0;
+		}
+		self
+	}
+
+	/// Whether glow passes and gradient fills should be drawn.
+	pub fn full_detail(&self) -> bool {
+		self.detail == DetailLevel::Full
+	}
+
+	/// Label visibility for a node of the given size multiplier.
+	pub fn label_alpha_for(&self, node_size: f64) -> f64 {
+		if node_size >= self.important_size {
+			self.important_label_alpha
+		} else {
+			self.label_alpha
 		}
 	}
 
@@ -0,0 +1,90 @@
+//! Initial node placement before the simulation starts.
+
+use std::f64::consts::PI;
+
+use super::rng::Xorshift;
+use super::types::GraphData;
+
+/// World-space spacing between neighboring seed positions.
+const SPACING: f64 = 40.0;
+/// Maximum random offset added to every seed position, in world units, so
+/// perfectly symmetric placements don't balance the forces exactly.
+const JITTER: f64 = 0.5;
+
+/// How nodes are placed before the first simulation step.
+///
+/// A placement close to the final layout converges faster and avoids the
+/// burst of nodes flying outward from a tight cluster.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SeedStrategy {
+	/// Evenly spaced on a circle whose radius grows with the node count.
+	#[default]
+	Circle,
+	/// Scattered uniformly over the canvas.
+	Random,
+	/// Row-major on a square grid, centered on the canvas.
+	Grid,
+	/// At each node's [`GraphNode::position`](super::GraphNode::position);
+	/// nodes without one fall back to [`SeedStrategy::Circle`].
+	FromData,
+}
+
+/// Starting position of each node in `data`, in node order, for a
+/// `width`×`height` canvas. All randomness comes from `rng`, so the same
+/// data and generator state always give the same positions.
+pub fn seed_positions(
+	strategy: SeedStrategy,
+	data: &GraphData,
+	width: f64,
+	height: f64,
+	rng: &mut Xorshift,
+) -> Vec<(f32, f32)> {
+	let n = data.nodes.len();
+	let (cx, cy) = (width / 2.0, height / 2.0);
+	// Grow with the area the settled layout will roughly cover, not the
+	// circumference, so large graphs don't start spread across the world
+	let radius = (SPACING * (n as f64).sqrt() / 2.0).max(100.0);
+	let circle = |i: usize| {
+		let angle = i as f64 * 2.0 * PI / n as f64;
+		(cx + radius * angle.cos(), cy + radius * angle.sin())
+	};
+
+	let positions: Vec<(f64, f64)> = match strategy {
+		SeedStrategy::Circle => (0..n).map(circle).collect(),
+		SeedStrategy::Random => (0..n)
+			.map(|_| (rng.unit() * width, rng.unit() * height))
+			.collect(),
+		SeedStrategy::Grid => {
+			let cols = (n as f64).sqrt().ceil().max(1.0) as usize;
+			let rows = n.div_ceil(cols);
+			let (x0, y0) = (
+				cx - (cols - 1) as f64 * SPACING / 2.0,
+				cy - rows.saturating_sub(1) as f64 * SPACING / 2.0,
+			);
+			(0..n)
+				.map(|i| {
+					(
+						x0 + (i % cols) as f64 * SPACING,
+						y0 + (i / cols) as f64 * SPACING,
+					)
+				})
+				.collect()
+		}
+		SeedStrategy::FromData => data
+			.nodes
+			.iter()
+			.enumerate()
+			.map(|(i, node)| node.position.unwrap_or_else(|| circle(i)))
+			.collect(),
+	};
+	positions
+		.into_iter()
+		.map(|(x, y)| {
+			let (jx, jy) = (rng.unit() - 0.5, rng.unit() - 0.5);
+			(
+				(x + 2.0 * JITTER * jx) as f32,
+				(y + 2.0 * JITTER * jy) as f32,
+			)
+		})
+		.collect()
+}
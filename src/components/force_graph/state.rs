@@ -3,34 +3,198 @@
 //! Wraps the `force_graph` physics simulation with per-node metadata, view
 //! transforms for pan/zoom, and highlight state for hover effects with smooth
 //! intensity transitions.
+//!
+//! Nothing here touches the DOM: time only advances through
+//! [`ForceGraphState::tick`] and input arrives as screen coordinates, so the
+//! whole simulation runs and is tested natively under `cargo test`.
 
 use std::collections::{HashMap, HashSet};
-use std::f64::consts::PI;
 
 use force_graph::{DefaultNodeIdx, EdgeData, ForceGraph, NodeData, SimulationParameters};
 
+use super::centrality::{self, BETWEENNESS_MAX_NODES, Betweenness, ColorBy};
+use super::collapse::{meta_group, meta_id};
+use super::community::label_propagation;
+use super::rng::{DEFAULT_SEED, Xorshift};
+use super::sampling::{Candidate, NodeSampling, SamplingInfo, UnsampledNodes, density, pick};
 use super::scale::{ScaleConfig, ScaledValues};
-use super::theme::Theme;
-use super::types::GraphData;
+use super::seed::{SeedStrategy, seed_positions};
+use super::theme::{Color, Colormap, HighlightConfig, Theme, parse_color};
+use super::types::{Corner, Filter, GraphData, LinkStyle, MouseBindings, NodeEvent, ViewportInfo};
 
 /// Per-node display metadata attached to each node in the simulation.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug)]
 pub struct NodeInfo {
+	/// Node ID from the input data
+	pub id: String,
 	pub label: Option<String>,
 	pub color: String,
+	/// `color` parsed, so frames don't reparse it
+	pub fill: Color,
+	/// CSS of the gradient fill's highlight, base and shadow stops
+	pub fill_stops: [String; 3],
+	/// Border color override from the input data, parsed
+	pub border_color: Option<Color>,
+	/// Border width override from the input data, in screen pixels
+	pub border_width: Option<f64>,
+	/// Glow color override from the input data, parsed
+	pub glow_color: Option<Color>,
+	/// Glow intensity override from the input data
+	pub glow: Option<f64>,
+	/// Donut segments from the input data, as `(value, color)`
+	pub segments: Option<Vec<(f64, String)>>,
+	/// Badge text from the input data, cut to three characters
+	pub badge: Option<String>,
+	/// Entrance progress, from 0 when added by a data update to 1 when
+	/// fully shown; counts back down to 0 once the node is removed
+	pub appear_t: f64,
+	/// Hidden by the current [`Filter`]
+	pub filtered: bool,
+	/// Time the node appears at in playback: its own, or its earliest
+	/// timed link's
+	pub time: Option<f64>,
+	/// Reached by playback, or shown because playback is off
+	pub active: bool,
+	/// Left outside the time window, along with all its links
+	pub ghost: bool,
+	/// Left out of the drawn sample of a graph over its render limit
+	pub sampled_out: bool,
+	/// Opacity under the filter, playback and time window: 1 when shown,
+	/// easing to the filter's residual alpha while filtered, to
+	/// [`GHOST_ALPHA`] while a ghost and to 0 while not yet reached
+	pub shown: f64,
 	/// Size multiplier (1.0 = normal, >1.0 = larger/more important)
 	pub size: f64,
+	/// Size `size` eases towards when a data update changes it
+	pub target_size: f64,
+	/// Group index from the input data, used for hulls and legends
+	pub group: Option<u32>,
+	/// Value of the heatmap's metric, once computed
+	pub metric: Option<f64>,
+	/// Application data from the input, returned in node events
+	pub metadata: HashMap<String, String>,
 }
 
-/// Pan and zoom transform applied to the entire graph view.
+impl NodeInfo {
+	/// Hidden by the filter, playback or sampling, so out of reach of the
+	/// pointer and keyboard.
+	pub fn is_hidden(&self) -> bool {
+		self.filtered || !self.active || self.sampled_out
+	}
+
+	/// Opacity [`shown`](Self::shown) eases towards, given the filter's
+	/// residual alpha.
+	fn shown_target(&self, residual: f64) -> f64 {
+		if !self.active {
+			0.0
+		} else if self.filtered {
+			residual
+		} else if self.ghost {
+			GHOST_ALPHA
+		} else {
+			1.0
+		}
+	}
+
+	/// Fill the node with CSS `color`, keeping its parsed fill and gradient
+	/// stops in step.
+	fn paint(&mut self, color: String) {
+		self.fill = parse_color(&color);
+		self.fill_stops = gradient_stops(self.fill);
+		self.color = color;
+	}
+}
+
+/// CSS of the highlight, base and shadow stops of a gradient node `fill`.
+fn gradient_stops(fill: Color) -> [String; 3] {
+	[fill.lighten(0.4), fill, fill.darken(0.2)].map(Color::to_css)
+}
+
+/// Per-edge display metadata attached to each edge in the simulation.
 #[derive(Clone, Debug, Default)]
+pub struct EdgeInfo {
+	/// Explicit line style; `None` uses the zoom-driven flow dash.
+	pub style: Option<LinkStyle>,
+	/// Offset in `[0, 1)` for flow particles so edges don't pulse in sync.
+	pub phase: f64,
+	/// Whether to draw direction cues (arrowheads, flow particles).
+	pub directed: bool,
+	/// The edge stands for a directed link and its reverse. It is drawn as
+	/// two curves bowing apart, one per direction.
+	pub reciprocal: bool,
+	/// The link's weight, if any; for a reciprocal edge, the first link's.
+	pub weight: Option<f64>,
+	/// The link's bend, overriding the theme's curves; for a reciprocal
+	/// edge, the first link's, applied to each direction.
+	pub curvature: Option<f64>,
+	/// Time the link appears at in playback; for a reciprocal edge, the
+	/// first link's.
+	pub time: Option<f64>,
+}
+
+/// A node removed by a data update, fading out where it last was. It's
+/// drawn but no longer part of the simulation or hit-testing.
+#[derive(Clone, Debug)]
+pub struct DepartingNode {
+	pub x: f64,
+	pub y: f64,
+	pub info: NodeInfo,
+}
+
+/// A link removed by a data update, such as a time window moving off it,
+/// fading out between its nodes. It's drawn straight and no longer part of
+/// the simulation.
+#[derive(Clone, Debug)]
+pub struct DepartingEdge {
+	/// Ids of the nodes it joined
+	pub source: String,
+	pub target: String,
+	/// Opacity, counting down to 0
+	pub fade: f64,
+}
+
+/// Pan and zoom transform applied to the entire graph view.
+///
+/// A world point `(wx, wy)` appears on screen at `(x + wx * k, y + wy * k)`.
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct ViewTransform {
+	/// Horizontal screen offset of the world origin, in pixels.
 	pub x: f64,
+	/// Vertical screen offset of the world origin, in pixels.
 	pub y: f64,
 	/// Zoom factor (1.0 = 100%, clamped to 0.1..10.0).
 	pub k: f64,
 }
 
+impl ViewTransform {
+	/// Transform that centers the world-space box `(min_x, min_y, max_x,
+	/// max_y)` in a `width`×`height` view, as large as fits with `padding`
+	/// pixels to spare on every side.
+	pub fn fit(bounds: (f64, f64, f64, f64), width: f64, height: f64, padding: f64) -> Self {
+		let (x0, y0, x1, y1) = bounds;
+		let k = ((width - 2.0 * padding) / (x1 - x0).max(1.0))
+			.min((height - 2.0 * padding) / (y1 - y0).max(1.0))
+			.clamp(MIN_ZOOM, MAX_ZOOM);
+		Self {
+			x: width / 2.0 - (x0 + x1) / 2.0 * k,
+			y: height / 2.0 - (y0 + y1) / 2.0 * k,
+			k,
+		}
+	}
+
+	/// World point under the screen point `(sx, sy)`, in pixels from the
+	/// canvas's top left corner.
+	pub fn screen_to_graph(&self, sx: f64, sy: f64) -> (f64, f64) {
+		((sx - self.x) / self.k, (sy - self.y) / self.k)
+	}
+
+	/// Screen point, in pixels from the canvas's top left corner, of the
+	/// world point `(x, y)`; the inverse of [`Self::screen_to_graph`].
+	pub fn graph_to_screen(&self, x: f64, y: f64) -> (f64, f64) {
+		(self.x + x * self.k, self.y + y * self.k)
+	}
+}
+
 /// Tracks an in-progress node drag operation.
 #[derive(Clone, Debug, Default)]
 pub struct DragState {
@@ -40,8 +204,115 @@ pub struct DragState {
 	pub start_y: f64,
 	pub node_start_x: f32,
 	pub node_start_y: f32,
+	/// Other selected nodes moving with the pressed one, with their start
+	/// positions; empty for a single-node drag
+	pub followers: Vec<(DefaultNodeIdx, f32, f32)>,
+	/// Dragged nodes that were already pinned when the drag began
+	pub pinned: HashSet<DefaultNodeIdx>,
+}
+
+/// A node's part in a [`LayoutEdit`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeMove {
+	pub id: String,
+	pub from: (f32, f32),
+	pub to: (f32, f32),
+	/// Whether the node was pinned before and after the edit
+	pub pinned: (bool, bool),
+}
+
+/// One user edit to the layout, undone and redone as a unit: the nodes a
+/// drag moved or pinned. The simulation's own movement is never recorded.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LayoutEdit {
+	pub moves: Vec<NodeMove>,
+}
+
+/// Undo and redo stacks of [`LayoutEdit`]s.
+#[derive(Clone, Debug)]
+pub struct History {
+	undo: Vec<LayoutEdit>,
+	redo: Vec<LayoutEdit>,
+	/// Most edits kept; the oldest are dropped past it
+	pub limit: usize,
+}
+
+impl Default for History {
+	fn default() -> Self {
+		Self {
+			undo: Vec::new(),
+			redo: Vec::new(),
+			limit: 100,
+		}
+	}
+}
+
+impl History {
+	/// Push a new edit, which drops everything that could be redone.
+	pub fn record(&mut self, edit: LayoutEdit) {
+		self.redo.clear();
+		self.undo.push(edit);
+		if self.undo.len() > self.limit {
+			let excess = self.undo.len() - self.limit;
+			self.undo.drain(..excess);
+		}
+	}
+
+	pub fn can_undo(&self) -> bool {
+		!self.undo.is_empty()
+	}
+
+	pub fn can_redo(&self) -> bool {
+		!self.redo.is_empty()
+	}
+}
+
+/// World-space line segment showing that a dragged node lines up with
+/// another node.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AlignGuide {
+	pub x0: f64,
+	pub y0: f64,
+	pub x1: f64,
+	pub y1: f64,
 }
 
+/// Screen distance in pixels a node must move in a tick to need a repaint.
+const MOVE_EPSILON: f64 = 0.05;
+
+/// Seconds a node added or removed by a data update takes to fade and
+/// scale in or out.
+const APPEAR_SECS: f64 = 0.3;
+
+/// Seconds the simulation takes to reach full speed after a reheat.
+const RAMP_SECS: f64 = 0.5;
+
+/// Seconds a node or link takes to fade fully out or back in when a
+/// filter hides or shows it, or playback passes its time.
+const FADE_SECS: f64 = 0.25;
+
+/// Opacity of a ghost node, left without links by the time window.
+const GHOST_ALPHA: f64 = 0.2;
+
+/// Greatest distance, in world units, a new node is placed from the existing
+/// neighbor it starts beside.
+const APPEAR_SPREAD: f64 = 20.0;
+
+/// Simulation mass of a node of size 1.
+const NODE_MASS: f32 = 10.0;
+
+/// Seconds for most of a node's change of size to play out after a data
+/// update changes its degree.
+const RESIZE_SECS: f64 = 0.3;
+
+/// Screen distance in pixels within which a dragged node is pulled toward
+/// another node's x or y coordinate, harder the closer it gets.
+const ALIGN_THRESHOLD: f64 = 6.0;
+
+/// Rate at which the spotlight closes on the pointer, per second; its lag
+/// is about the reciprocal.
+const SPOTLIGHT_FOLLOW: f64 = 10.0;
+
 /// Tracks an in-progress canvas pan operation.
 #[derive(Clone, Debug, Default)]
 pub struct PanState {
@@ -50,6 +321,435 @@ pub struct PanState {
 	pub start_y: f64,
 	pub transform_start_x: f64,
 	pub transform_start_y: f64,
+	/// Node pressed when nodes can't be dragged, clicked if the pan ends
+	/// where it began
+	pub node: Option<DefaultNodeIdx>,
+}
+
+/// Where the theme's spotlight is, trailing the pointer.
+#[derive(Clone, Debug, Default)]
+pub struct Spotlight {
+	/// Whether the theme has a spotlight to follow the pointer with
+	pub enabled: bool,
+	/// Center on screen in pixels; `None` while the pointer is off the canvas
+	pub center: Option<(f64, f64)>,
+}
+
+/// Rubber-band selection being dragged out, in screen pixels.
+#[derive(Clone, Debug, Default)]
+pub struct SelectBox {
+	pub start_x: f64,
+	pub start_y: f64,
+	pub x: f64,
+	pub y: f64,
+	/// Adds to the selection instead of replacing it
+	pub additive: bool,
+	/// Node pressed, clicked if the box ends where it began
+	pub node: Option<DefaultNodeIdx>,
+}
+
+/// Soft boundary that keeps free nodes inside the visible canvas area.
+///
+/// The walls are derived each tick from the canvas size and the current view
+/// transform, so they always match what's on screen. Nodes that enter the
+/// margin band are pushed back by a spring proportional to how far they've
+/// penetrated; only the canvas edge itself is a hard stop.
+#[derive(Clone, Debug)]
+pub struct Bounds {
+	/// Whether the boundary constraint is applied during `tick`.
+	pub bounded: bool,
+	/// Width of the spring band inside the canvas edge, in screen pixels.
+	pub margin: f64,
+	/// Fraction of the penetration depth corrected per second.
+	pub stiffness: f64,
+}
+
+impl Default for Bounds {
+	fn default() -> Self {
+		Self {
+			bounded: false,
+			margin: 40.0,
+			stiffness: 8.0,
+		}
+	}
+}
+
+/// Nodes colored by a metric, from [`ForceGraphState::set_color_by`].
+#[derive(Clone, Debug)]
+pub struct Heatmap {
+	/// Metric the nodes are colored by
+	pub color_by: ColorBy,
+	/// Lowest and highest value of the metric, once computed
+	pub range: Option<(f64, f64)>,
+	/// Betweenness still being computed, with the node each of its indices
+	/// stands for
+	pending: Option<(Betweenness, Vec<DefaultNodeIdx>)>,
+}
+
+impl Heatmap {
+	/// Share of the metric computed so far, while it's still being
+	/// computed.
+	pub fn progress(&self) -> Option<f64> {
+		self.pending.as_ref().map(|(pass, _)| pass.progress())
+	}
+}
+
+/// How node size follows degree, for
+/// [`ForceGraphState::size_by_degree`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DegreeSizing {
+	/// Curve degree is mapped through before it's scaled onto `min..=max`.
+	pub curve: DegreeCurve,
+	/// Size multiplier of the least connected nodes.
+	pub min: f64,
+	/// Size multiplier of the most connected node.
+	pub max: f64,
+}
+
+impl Default for DegreeSizing {
+	fn default() -> Self {
+		Self {
+			curve: DegreeCurve::Sqrt,
+			min: 0.7,
+			max: 2.5,
+		}
+	}
+}
+
+/// Curve of a [`DegreeSizing`], taking degree over the greatest degree to a
+/// fraction of the size range.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DegreeCurve {
+	/// Size grows in proportion to degree.
+	Linear,
+	/// Square root of degree; sets hubs apart without dwarfing the rest.
+	#[default]
+	Sqrt,
+	/// Logarithm of degree plus one, for heavy-tailed degree distributions.
+	Log,
+}
+
+/// Fisheye lens spreading out the nodes under the pointer, so dense regions
+/// can be read without zooming.
+///
+/// Only where nodes are drawn changes; the simulation keeps their true
+/// positions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fisheye {
+	/// Radius of the lens in screen pixels; nothing outside it moves.
+	pub radius: f64,
+	/// How strongly the center is magnified; 0 leaves the view as is.
+	pub magnification: f64,
+}
+
+impl Default for Fisheye {
+	fn default() -> Self {
+		Self {
+			radius: 150.0,
+			magnification: 3.0,
+		}
+	}
+}
+
+impl Fisheye {
+	/// Where the screen point `(x, y)` is shown by a lens centered on
+	/// `(fx, fy)`.
+	///
+	/// A point at distance `d` from the center, as a fraction of the
+	/// radius, moves out to `(m + 1) d / (m d + 1)` of it, which pushes
+	/// points away from the center while leaving the rim in place.
+	pub fn distort(&self, (fx, fy): (f64, f64), x: f64, y: f64) -> (f64, f64) {
+		let (dx, dy) = (x - fx, y - fy);
+		let r = dx.hypot(dy);
+		if r == 0.0 || r >= self.radius {
+			return (x, y);
+		}
+		let m = self.magnification.max(0.0);
+		let scale = (m + 1.0) / (m * r / self.radius + 1.0);
+		(fx + dx * scale, fy + dy * scale)
+	}
+}
+
+impl DegreeCurve {
+	/// Position of `degree` between no links and `max_degree`, in `[0, 1]`.
+	fn apply(self, degree: usize, max_degree: usize) -> f64 {
+		if max_degree == 0 {
+			return 0.0;
+		}
+		let (d, max) = (degree as f64, max_degree as f64);
+		match self {
+			Self::Linear => d / max,
+			Self::Sqrt => (d / max).sqrt(),
+			Self::Log => d.ln_1p() / max.ln_1p(),
+		}
+	}
+}
+
+/// Tracks layout convergence from per-tick node displacement.
+///
+/// The simulation doesn't expose node velocities, so energy is measured as
+/// the mean squared speed of all nodes between consecutive ticks. The layout
+/// is considered settled once energy has stayed below `threshold`, or every
+/// free node has been held at rest, for `settle_delay` seconds; physics then
+/// stops stepping. That transition is reported once until
+/// [`ForceGraphState::reheat`] wakes the simulation and re-arms it.
+///
+/// Forces never quite reach zero, so a node moving slower than `rest_speed`
+/// has its step undone rather than jittering forever.
+#[derive(Clone, Debug)]
+pub struct Stability {
+	/// Mean squared node speed (px²/s²) below which the layout counts as settled.
+	pub threshold: f64,
+	/// Mean squared node speed measured on the last tick.
+	pub energy: f64,
+	/// Node speed (px/s) below which a free node is held where it is.
+	pub rest_speed: f64,
+	/// Seconds energy must stay below `threshold` for the layout to settle.
+	pub settle_delay: f64,
+	settled: bool,
+	pending_event: bool,
+	/// Woken from settled since it was last reported
+	pending_wake: bool,
+	/// Seconds energy has stayed below `threshold`
+	quiet: f64,
+	/// Fraction of full speed steps run at, rising from 0 after a reheat
+	ramp: f64,
+	prev_positions: Vec<(f32, f32)>,
+	/// Largest node displacement (world units) on the last tick
+	max_step: f64,
+}
+
+impl Default for Stability {
+	fn default() -> Self {
+		Self {
+			threshold: 50.0,
+			energy: f64::INFINITY,
+			rest_speed: 5.0,
+			settle_delay: 1.0,
+			settled: false,
+			pending_event: false,
+			pending_wake: false,
+			quiet: 0.0,
+			ramp: 1.0,
+			prev_positions: Vec::new(),
+			max_step: f64::INFINITY,
+		}
+	}
+}
+
+impl Stability {
+	/// Whether the layout has settled since the last reheat.
+	pub fn is_settled(&self) -> bool {
+		self.settled
+	}
+
+	/// Measure displacement since the previous tick, hold nodes that barely
+	/// moved, and update the settled flag.
+	fn measure<N, E>(&mut self, graph: &mut ForceGraph<N, E>, dt: f64) {
+		let prev = std::mem::take(&mut self.prev_positions);
+		let mut count = 0;
+		graph.visit_nodes(|_| count += 1);
+		let comparable = dt > 0.0 && prev.len() == count;
+		let mut resting = true;
+		if comparable {
+			let rest_step = (self.rest_speed * dt) as f32;
+			let mut i = 0;
+			graph.visit_nodes_mut(|node| {
+				let (px, py) = prev[i];
+				i += 1;
+				if node.data.is_anchor {
+					return;
+				}
+				if (node.data.x - px).hypot(node.data.y - py) < rest_step {
+					(node.data.x, node.data.y) = (px, py);
+				} else {
+					resting = false;
+				}
+			});
+		}
+		graph.visit_nodes(|node| self.prev_positions.push((node.x(), node.y())));
+		if !comparable {
+			self.max_step = f64::INFINITY;
+			return;
+		}
+		self.max_step = prev
+			.iter()
+			.zip(&self.prev_positions)
+			.map(|(&(px, py), &(x, y))| (x - px).hypot(y - py) as f64)
+			.fold(0.0, f64::max);
+
+		let sum: f64 = prev
+			.iter()
+			.zip(&self.prev_positions)
+			.map(|(&(px, py), &(x, y))| {
+				let (vx, vy) = ((x - px) as f64 / dt, (y - py) as f64 / dt);
+				vx * vx + vy * vy
+			})
+			.sum();
+		self.energy = if prev.is_empty() {
+			0.0
+		} else {
+			sum / prev.len() as f64
+		};
+		if self.energy < self.threshold || resting {
+			self.quiet += dt;
+		} else {
+			self.quiet = 0.0;
+		}
+		if !self.settled && self.quiet >= self.settle_delay {
+			self.settled = true;
+			self.pending_event = true;
+		}
+	}
+}
+
+/// Simulation of just the drawn sample of a graph over its render limit,
+/// stepped in place of the whole graph's so the nodes left out hold still
+/// and cost nothing.
+///
+/// The full graph stays the source of truth: drags, pins and masses are
+/// pulled in before each step and the new positions pushed back after.
+struct SampleSim {
+	graph: ForceGraph,
+	/// Full graph index of each sampled node, in ascending order, matching
+	/// the order they were added here
+	nodes: Vec<DefaultNodeIdx>,
+}
+
+impl SampleSim {
+	fn new(full: &ForceGraph<NodeInfo, EdgeInfo>, drawn: &HashSet<DefaultNodeIdx>) -> Self {
+		let mut graph = ForceGraph::new(full.parameters.clone());
+		let mut nodes: Vec<DefaultNodeIdx> = drawn.iter().copied().collect();
+		nodes.sort_unstable();
+		let inner: HashMap<DefaultNodeIdx, DefaultNodeIdx> = nodes
+			.iter()
+			.map(|&i| {
+				let data = &full.get_graph()[i].data;
+				let idx = graph.add_node(NodeData {
+					x: data.x,
+					y: data.y,
+					mass: data.mass,
+					is_anchor: data.is_anchor,
+					user_data: (),
+				});
+				(i, idx)
+			})
+			.collect();
+		full.visit_edges(|n1, n2, _| {
+			if let (Some(&a), Some(&b)) = (inner.get(&n1.index()), inner.get(&n2.index())) {
+				graph.add_edge(a, b, EdgeData { user_data: () });
+			}
+		});
+		Self { graph, nodes }
+	}
+
+	/// Step the sample by `dt` seconds, leaving every other node where it is.
+	fn update(&mut self, full: &mut ForceGraph<NodeInfo, EdgeInfo>, dt: f32) {
+		let outer = full.get_graph();
+		let mut order = self.nodes.iter();
+		self.graph.visit_nodes_mut(|node| {
+			let data = &outer[*order.next().unwrap()].data;
+			node.data.x = data.x;
+			node.data.y = data.y;
+			node.data.mass = data.mass;
+			node.data.is_anchor = data.is_anchor;
+		});
+		self.graph.update(dt);
+
+		let mut moved = Vec::with_capacity(self.nodes.len());
+		self.graph
+			.visit_nodes(|node| moved.push((node.x(), node.y())));
+		let mut next = self.nodes.iter().zip(moved).peekable();
+		full.visit_nodes_mut(|node| {
+			if let Some((_, (x, y))) = next.next_if(|(i, _)| **i == node.index()) {
+				(node.data.x, node.data.y) = (x, y);
+			}
+		});
+	}
+}
+
+/// Debounces camera changes into one report per completed pan or zoom.
+///
+/// The viewport is sampled every tick; once it has stayed unchanged for
+/// `delay` seconds and differs from the last report, a new report is queued.
+#[derive(Clone, Debug)]
+pub struct ViewportWatch {
+	/// Seconds the viewport must stay still before it is reported.
+	pub delay: f64,
+	last_seen: Option<ViewportInfo>,
+	last_reported: Option<ViewportInfo>,
+	quiet_time: f64,
+}
+
+impl Default for ViewportWatch {
+	fn default() -> Self {
+		Self {
+			delay: 0.2,
+			last_seen: None,
+			last_reported: None,
+			quiet_time: 0.0,
+		}
+	}
+}
+
+impl ViewportWatch {
+	fn observe(&mut self, viewport: ViewportInfo, dt: f64) {
+		if self.last_seen.as_ref() == Some(&viewport) {
+			self.quiet_time += dt;
+		} else {
+			self.last_seen = Some(viewport);
+			self.quiet_time = 0.0;
+		}
+	}
+}
+
+/// Seconds a [`CameraTween`] takes.
+const CAMERA_TWEEN_SECS: f64 = 0.5;
+
+/// Zoom levels the camera stays between.
+pub(super) const MIN_ZOOM: f64 = 0.1;
+pub(super) const MAX_ZOOM: f64 = 10.0;
+
+/// Animated camera move, e.g. from [`ForceGraphState::fit_to_nodes`].
+///
+/// Zoom changes geometrically and the view center moves in a straight line,
+/// so the move feels even at any scale.
+#[derive(Clone, Debug)]
+pub struct CameraTween {
+	from: ViewTransform,
+	to: ViewTransform,
+	elapsed: f64,
+}
+
+impl CameraTween {
+	/// Advance by `dt` seconds and return the transform for a
+	/// `width`×`height` view, and whether the move is finished.
+	fn step(&mut self, dt: f64, width: f64, height: f64) -> (ViewTransform, bool) {
+		self.elapsed += dt;
+		let t = (self.elapsed / CAMERA_TWEEN_SECS).min(1.0);
+		if t >= 1.0 {
+			return (self.to.clone(), true);
+		}
+		// Ease in and out
+		let t = t * t * (3.0 - 2.0 * t);
+		let center = |v: &ViewTransform| ((width / 2.0 - v.x) / v.k, (height / 2.0 - v.y) / v.k);
+		let ((cx0, cy0), (cx1, cy1)) = (center(&self.from), center(&self.to));
+		let k = self.from.k * (self.to.k / self.from.k).powf(t);
+		let (cx, cy) = (cx0 + (cx1 - cx0) * t, cy0 + (cy1 - cy0) * t);
+		let transform = ViewTransform {
+			x: width / 2.0 - cx * k,
+			y: height / 2.0 - cy * k,
+			k,
+		};
+		(transform, false)
+	}
+}
+
+/// An edge's two ends, lower index first, so either direction finds it.
+type EdgePair = (DefaultNodeIdx, DefaultNodeIdx);
+
+fn edge_pair(a: DefaultNodeIdx, b: DefaultNodeIdx) -> EdgePair {
+	if a <= b { (a, b) } else { (b, a) }
 }
 
 /// Manages smooth highlight transitions with per-node intensity tracking.
@@ -63,11 +763,18 @@ pub struct PanState {
 ///
 /// Includes a minimum hold time to prevent flashing when the mouse briefly
 /// skirts the edge of a node's hover zone.
+///
+/// Edges are tracked the same way, so the links of a hovered node fade in
+/// and out with it rather than following whichever of their ends is lit.
 #[derive(Clone, Debug, Default)]
 pub struct HighlightState {
 	/// Currently hovered node (if any)
 	pub hovered_node: Option<DefaultNodeIdx>,
-	/// Set of nodes that should be highlighted (hovered + neighbors)
+	/// Hovered node and its neighbors
+	hover_set: HashSet<DefaultNodeIdx>,
+	/// Nodes highlighted regardless of hover (e.g. a legend group)
+	group_set: HashSet<DefaultNodeIdx>,
+	/// Set of nodes that should be highlighted (hover set + group set)
 	target_set: HashSet<DefaultNodeIdx>,
 	/// Per-node highlight intensity (0.0 = not highlighted, 1.0 = fully highlighted)
 	/// Nodes not in this map have intensity 0.
@@ -76,14 +783,22 @@ pub struct HighlightState {
 	hover_ring_intensity: HashMap<DefaultNodeIdx, f64>,
 	/// Per-node hold timer - time remaining before fade-out can begin
 	hold_timer: HashMap<DefaultNodeIdx, f64>,
+	/// Links of the hovered node
+	hover_edges: HashSet<EdgePair>,
+	/// Links between nodes of the highlighted group
+	group_edges: HashSet<EdgePair>,
+	/// Links that should be highlighted (hover edges + group edges)
+	target_edges: HashSet<EdgePair>,
+	/// Per-edge highlight intensity, animated like `node_intensity`
+	edge_intensity: HashMap<EdgePair, f64>,
+	/// Per-edge hold timer, like `hold_timer`
+	edge_hold: HashMap<EdgePair, f64>,
 	/// Cached max intensity (updated each tick)
 	cached_max: f64,
+	/// Fade timings and emphasis amounts, from the theme
+	pub config: HighlightConfig,
 }
 
-/// Minimum time (seconds) a highlight must be held before it can fade out.
-/// This prevents flashing when the mouse briefly touches a hover zone.
-const MIN_HOLD_TIME: f64 = 0.12;
-
 impl HighlightState {
 	/// Update the hovered node and recompute the target highlight set.
 	pub fn set_hover(
@@ -96,24 +811,57 @@ impl HighlightState {
 		}
 
 		self.hovered_node = node;
-		self.target_set.clear();
-
+		self.hover_set.clear();
+		self.hover_edges.clear();
 		if let Some(idx) = node {
 			// Add hovered node
-			self.target_set.insert(idx);
-			// Add neighbors
+			self.hover_set.insert(idx);
+			// Add neighbors and the links to them
 			for &(src, tgt) in edges {
 				if src == idx {
-					self.target_set.insert(tgt);
+					self.hover_set.insert(tgt);
 				} else if tgt == idx {
-					self.target_set.insert(src);
+					self.hover_set.insert(src);
+				} else {
+					continue;
 				}
+				self.hover_edges.insert(edge_pair(src, tgt));
 			}
+		}
+		self.rebuild_targets();
+	}
 
-			// Reset hold timers for newly highlighted nodes
-			for &idx in &self.target_set {
-				self.hold_timer.insert(idx, MIN_HOLD_TIME);
-			}
+	/// Replace the set of nodes highlighted independently of hover, such as
+	/// a group picked from a legend, along with the `edges` among them.
+	/// Returns whether it changed.
+	pub fn set_group(
+		&mut self,
+		nodes: HashSet<DefaultNodeIdx>,
+		edges: &[(DefaultNodeIdx, DefaultNodeIdx)],
+	) -> bool {
+		if self.group_set == nodes {
+			return false;
+		}
+		self.group_edges = edges
+			.iter()
+			.filter(|(src, tgt)| nodes.contains(src) && nodes.contains(tgt))
+			.map(|&(src, tgt)| edge_pair(src, tgt))
+			.collect();
+		self.group_set = nodes;
+		self.rebuild_targets();
+		true
+	}
+
+	/// Recompute the target set as the union of the hover and group sets.
+	fn rebuild_targets(&mut self) {
+		self.target_set = self.hover_set.union(&self.group_set).copied().collect();
+		// Reset hold timers for newly highlighted nodes
+		for &idx in &self.target_set {
+			self.hold_timer.insert(idx, self.config.hold_time);
+		}
+		self.target_edges = self.hover_edges.union(&self.group_edges).copied().collect();
+		for &pair in &self.target_edges {
+			self.edge_hold.insert(pair, self.config.hold_time);
 		}
 	}
 
@@ -123,13 +871,8 @@ impl HighlightState {
 	/// This creates natural ease-out behavior where animation slows as it approaches target.
 	pub fn tick(&mut self, dt: f64) {
 		// Smoothing factors - higher = faster response
-		// At 60fps with speed=8: reaches ~63% in ~2 frames, ~95% in ~6 frames (~100ms)
-		// At 60fps with speed=5: reaches ~63% in ~4 frames, ~95% in ~12 frames (~200ms)
-		const FADE_IN_SPEED: f64 = 6.0; // ~150ms to 95%
-		const FADE_OUT_SPEED: f64 = 4.0; // ~250ms to 95%
-
-		let fade_in_factor = 1.0 - (-FADE_IN_SPEED * dt).exp();
-		let fade_out_decay = (-FADE_OUT_SPEED * dt).exp();
+		let fade_in_factor = 1.0 - (-self.config.fade_in_speed * dt).exp();
+		let fade_out_decay = (-self.config.fade_out_speed * dt).exp();
 
 		// Animate nodes in target set (fade in)
 		for &idx in &self.target_set {
@@ -190,6 +933,29 @@ impl HighlightState {
 			}
 		});
 
+		// Edges fade in, hold and fade out just like nodes
+		for &pair in &self.target_edges {
+			let intensity = self.edge_intensity.entry(pair).or_insert(0.0);
+			*intensity += (1.0 - *intensity) * fade_in_factor;
+		}
+		self.edge_hold.retain(|pair, timer| {
+			if self.target_edges.contains(pair) {
+				true
+			} else {
+				*timer -= dt;
+				*timer > 0.0
+			}
+		});
+		self.edge_intensity.retain(|pair, intensity| {
+			if self.target_edges.contains(pair) {
+				return true;
+			}
+			if self.edge_hold.get(pair).copied().unwrap_or(0.0) <= 0.0 {
+				*intensity *= fade_out_decay;
+			}
+			*intensity > 0.005
+		});
+
 		self.cached_max = new_max;
 	}
 
@@ -203,13 +969,39 @@ impl HighlightState {
 		self.hover_ring_intensity.get(&idx).copied().unwrap_or(0.0)
 	}
 
-	/// Get the highlight intensity for an edge.
-	/// Uses geometric mean for smoother edge transitions that don't lag behind nodes.
+	/// Get the highlight intensity for an edge (already smoothed), in
+	/// either direction.
 	pub fn edge_intensity(&self, idx1: DefaultNodeIdx, idx2: DefaultNodeIdx) -> f64 {
-		let i1 = self.node_intensity(idx1);
-		let i2 = self.node_intensity(idx2);
-		// Geometric mean is smoother than min for transitions
-		(i1 * i2).sqrt()
+		self.edge_intensity
+			.get(&edge_pair(idx1, idx2))
+			.copied()
+			.unwrap_or(0.0)
+	}
+
+	/// Whether any intensity is still transitioning towards its target.
+	pub fn is_animating(&self) -> bool {
+		// Entries outside the target set are still fading out
+		let fading_nodes = self
+			.node_intensity
+			.iter()
+			.any(|(idx, &t)| !self.target_set.contains(idx) || t < 0.995);
+		let fading_rings = self
+			.hover_ring_intensity
+			.iter()
+			.any(|(idx, &t)| self.hovered_node != Some(*idx) || t < 0.995);
+		let pending_targets = self
+			.target_set
+			.iter()
+			.any(|idx| !self.node_intensity.contains_key(idx));
+		let fading_edges = self
+			.edge_intensity
+			.iter()
+			.any(|(pair, &t)| !self.target_edges.contains(pair) || t < 0.995);
+		let pending_edges = self
+			.target_edges
+			.iter()
+			.any(|pair| !self.edge_intensity.contains_key(pair));
+		fading_nodes || fading_rings || pending_targets || fading_edges || pending_edges
 	}
 
 	/// Get the maximum intensity of any node (useful for dimming non-highlighted elements).
@@ -224,20 +1016,117 @@ impl HighlightState {
 /// animation loop. The `tick` method advances the physics simulation and
 /// animates highlight intensities.
 pub struct ForceGraphState {
-	pub graph: ForceGraph<NodeInfo, ()>,
+	pub graph: ForceGraph<NodeInfo, EdgeInfo>,
 	pub transform: ViewTransform,
 	pub drag: DragState,
+	/// User edits to the layout that can be undone
+	pub history: History,
 	pub pan: PanState,
 	pub highlight: HighlightState,
+	pub bounds: Bounds,
+	pub stability: Stability,
+	pub viewport_watch: ViewportWatch,
+	/// World-space grid that dragged nodes snap to, if any
+	pub snap_grid: Option<f64>,
+	/// Nodes selected for group operations; shift-click toggles membership
+	pub selection: HashSet<DefaultNodeIdx>,
+	/// Whether nodes can be dragged; when not, pressing a node pans instead
+	pub draggable: bool,
+	/// What dragging with each mouse button does
+	pub mouse: MouseBindings,
+	/// Rubber-band selection in progress
+	pub select_box: Option<SelectBox>,
+	/// Lens magnifying the view around the pointer, if it's enabled
+	pub fisheye: Option<Fisheye>,
+	/// Whether the lens is showing; the F key toggles it
+	pub fisheye_on: bool,
+	/// Pointer position over the canvas, in screen pixels
+	pub pointer: Option<(f64, f64)>,
+	/// Spotlight trailing the pointer, if the theme has one
+	pub spotlight: Spotlight,
+	/// Whether dragged nodes align to nearby nodes' coordinates
+	pub align_guides: bool,
+	/// Alignment guides for the current drag, drawn while it lasts
+	pub guides: Vec<AlignGuide>,
+	/// Node holding keyboard focus, if any
+	pub focused: Option<DefaultNodeIdx>,
+	/// Display names for node groups, used by hull labels
+	pub group_names: HashMap<u32, String>,
+	/// Lightest and heaviest link weight in the data, if any link has one
+	pub weight_range: Option<(f64, f64)>,
+	/// Some link is styled dashed or dotted
+	pub has_dashed_edges: bool,
+	/// Some link has no style, so takes the zoom-driven flow dash
+	pub has_unstyled_edges: bool,
+	/// Camera move in progress, if any
+	pub camera_tween: Option<CameraTween>,
+	/// Some node is still fading in
+	appearing: bool,
+	/// Removed nodes still fading out
+	pub departing: Vec<DepartingNode>,
+	/// Removed links still fading out
+	pub departing_edges: Vec<DepartingEdge>,
+	/// Which nodes are hidden, if any
+	pub filter: Option<Filter>,
+	/// Playback time; nodes and links timed later are hidden
+	pub current_time: Option<f64>,
+	/// Playback opacity of each timed edge, keyed by its endpoints
+	edge_fades: HashMap<(DefaultNodeIdx, DefaultNodeIdx), f64>,
+	/// Some node or edge is still fading under the filter or playback
+	fading: bool,
+	/// Node sizes follow degree, and masses follow size
+	pub degree_sizing: Option<DegreeSizing>,
+	/// Limit on nodes drawn, and how to pick and draw them beyond it
+	sampling: Option<NodeSampling>,
+	/// How much of the graph the current sample draws, while sampling
+	pub sampled: Option<SamplingInfo>,
+	/// `sampled` changed since it was last reported
+	sampling_event: bool,
+	/// Physics of the sample alone, while sampling
+	sample_sim: Option<SampleSim>,
+	/// Some node is still easing to its target size
+	resizing: bool,
+	/// Ramp the heatmap maps its metric onto, from lowest to highest
+	pub heat_ramp: Colormap,
+	/// Corner of the heatmap's gradient legend
+	pub heat_legend: Corner,
+	heatmap: Option<Heatmap>,
+	/// Node colors from the data, kept while the heatmap repaints them
+	overlay_colors: HashMap<DefaultNodeIdx, String>,
 	pub width: f64,
 	pub height: f64,
 	pub animation_running: bool,
+	/// Physics held still by the app; unlike `animation_running`, reheating
+	/// doesn't lift it
+	pub paused: bool,
 	pub flow_time: f64,
+	/// Set when something visible changed and the next frame must repaint.
+	pub needs_redraw: bool,
 	edges: Vec<(DefaultNodeIdx, DefaultNodeIdx)>,
+	communities: HashMap<String, u32>,
+	/// Source of all layout randomness, continued from initial placement
+	rng: Xorshift,
 }
 
 impl ForceGraphState {
-	pub fn new(data: &GraphData, width: f64, height: f64, theme: &Theme) -> Self {
+	/// Build the simulation from input data.
+	///
+	/// With `color_by_community`, communities are detected once over the
+	/// links and each node without an explicit color takes its community's
+	/// palette color instead of its value or group color.
+	///
+	/// Nodes are placed by `seeding`; every random choice is drawn from a
+	/// generator seeded with `seed` (or a fixed default), so the same data,
+	/// size and seed always produce the same layout.
+	pub fn new(
+		data: &GraphData,
+		width: f64,
+		height: f64,
+		theme: &Theme,
+		color_by_community: bool,
+		seeding: SeedStrategy,
+		seed: Option<u64>,
+	) -> Self {
 		let mut graph = ForceGraph::new(SimulationParameters {
 			force_charge: 150.0,
 			force_spring: 0.05,
@@ -247,6 +1136,18 @@ impl ForceGraphState {
 		});
 		let mut id_to_idx = HashMap::new();
 		let mut edges = Vec::new();
+		let mut edge_fades = HashMap::new();
+
+		// Untimed nodes appear in playback with their earliest timed link
+		let mut link_times: HashMap<&str, f64> = HashMap::new();
+		for link in &data.links {
+			if let Some(time) = link.time {
+				for id in [&link.source, &link.target] {
+					let earliest = link_times.entry(id).or_insert(time);
+					*earliest = earliest.min(time);
+				}
+			}
+		}
 
 		// Count edges per node for importance calculation
 		let mut edge_counts: HashMap<&String, usize> = HashMap::new();
@@ -256,18 +1157,37 @@ impl ForceGraphState {
 		}
 		let max_edges = edge_counts.values().copied().max().unwrap_or(1).max(1);
 
-		for (i, node) in data.nodes.iter().enumerate() {
-			let color = node.color.clone().unwrap_or_else(|| {
-				node.group
-					.map(|g| theme.palette.get(g as usize).to_css_rgb())
-					.unwrap_or_else(|| theme.palette.get(i).to_css_rgb())
-			});
-			let angle = (i as f64) * 2.0 * PI / data.nodes.len() as f64;
-			let (x, y) = (
-				(width / 2.0 + 100.0 * angle.cos()) as f32,
-				(height / 2.0 + 100.0 * angle.sin()) as f32,
-			);
+		let communities: HashMap<String, u32> = if color_by_community {
+			let index: HashMap<&str, usize> = data
+				.nodes
+				.iter()
+				.enumerate()
+				.map(|(i, n)| (n.id.as_str(), i))
+				.collect();
+			let links: Vec<(usize, usize)> = data
+				.links
+				.iter()
+				.filter_map(|l| {
+					Some((
+						*index.get(l.source.as_str())?,
+						*index.get(l.target.as_str())?,
+					))
+				})
+				.collect();
+			let labels = label_propagation(data.nodes.len(), &links);
+			data.nodes
+				.iter()
+				.zip(labels)
+				.map(|(n, c)| (n.id.clone(), c))
+				.collect()
+		} else {
+			HashMap::new()
+		};
 
+		let colors = theme.node_colors_with_communities(data, &communities);
+		let mut rng = Xorshift::new(seed.unwrap_or(DEFAULT_SEED));
+		let positions = seed_positions(seeding, data, width, height, &mut rng);
+		for ((node, color), (x, y)) in data.nodes.iter().zip(colors).zip(positions) {
 			// Calculate node importance/size based on:
 			// - Having a label (more important)
 			// - Number of connections (more connected = larger)
@@ -281,85 +1201,2310 @@ impl ForceGraphState {
 				0.7 + 0.5 * edge_factor // unlabeled: 0.7x to 1.2x
 			};
 
+			let fill = parse_color(&color);
 			let idx = graph.add_node(NodeData {
 				x,
 				y,
-				mass: 10.0,
+				mass: NODE_MASS,
 				is_anchor: false,
 				user_data: NodeInfo {
+					id: node.id.clone(),
 					label: node.label.clone(),
 					color,
+					fill,
+					fill_stops: gradient_stops(fill),
+					border_color: node.border_color.as_deref().map(parse_color),
+					border_width: node.border_width,
+					glow_color: node.glow_color.as_deref().map(parse_color),
+					glow: node.glow,
+					segments: node.segments.clone(),
+					badge: node
+						.badge
+						.as_ref()
+						.map(|badge| badge.chars().take(3).collect()),
+					appear_t: 1.0,
+					filtered: false,
+					time: node
+						.time
+						.or_else(|| link_times.get(node.id.as_str()).copied()),
+					active: true,
+					ghost: false,
+					sampled_out: false,
+					shown: 1.0,
 					size,
+					target_size: size,
+					group: node.group,
+					metric: None,
+					metadata: node.metadata.clone(),
 				},
 			});
 			id_to_idx.insert(node.id.clone(), idx);
 		}
 
-		for link in &data.links {
+		// The simulation keeps one edge per node pair, so a directed link and
+		// its reverse share an edge that draws both directions
+		let directed: HashSet<(&str, &str)> = data
+			.links
+			.iter()
+			.filter(|l| l.directed)
+			.map(|l| (l.source.as_str(), l.target.as_str()))
+			.collect();
+		let mut reciprocal_pairs = HashSet::new();
+		for (i, link) in data.links.iter().enumerate() {
 			if let (Some(&src), Some(&tgt)) =
 				(id_to_idx.get(&link.source), id_to_idx.get(&link.target))
 			{
-				graph.add_edge(src, tgt, EdgeData::default());
+				let reciprocal = link.directed
+					&& src != tgt && directed
+					.contains(&(link.target.as_str(), link.source.as_str()));
+				if reciprocal && !reciprocal_pairs.insert((src.min(tgt), src.max(tgt))) {
+					edges.push((src, tgt));
+					continue;
+				}
+				// Golden-ratio sequence spreads phases evenly without clustering
+				let phase = (i as f64 * 0.618_033_988_75).fract();
+				graph.add_edge(
+					src,
+					tgt,
+					EdgeData {
+						user_data: EdgeInfo {
+							style: link.style,
+							phase,
+							directed: link.directed,
+							reciprocal,
+							weight: link.weight,
+							curvature: link.curvature,
+							time: link.time,
+						},
+					},
+				);
 				edges.push((src, tgt));
+				if link.time.is_some() {
+					edge_fades.insert((src, tgt), 1.0);
+				}
 			}
 		}
 
+		let (mut has_dashed_edges, mut has_unstyled_edges) = (false, false);
+		graph.visit_edges(|_, _, edge| match edge.user_data.style {
+			None => has_unstyled_edges = true,
+			Some(style) => has_dashed_edges |= style != LinkStyle::Solid,
+		});
+
+		let weight_range = data
+			.links
+			.iter()
+			.filter_map(|l| l.weight.filter(|w| w.is_finite()))
+			.fold(None, |range, w| match range {
+				None => Some((w, w)),
+				Some((lo, hi)) => Some((w.min(lo), w.max(hi))),
+			});
+
 		Self {
 			graph,
 			edges,
+			communities,
+			rng,
 			transform: ViewTransform {
 				x: width / 2.0,
 				y: height / 2.0,
 				k: 1.0,
 			},
 			drag: DragState::default(),
+			history: History::default(),
 			pan: PanState::default(),
-			highlight: HighlightState::default(),
+			highlight: HighlightState {
+				config: theme.highlight,
+				..HighlightState::default()
+			},
+			bounds: Bounds::default(),
+			stability: Stability::default(),
+			viewport_watch: ViewportWatch::default(),
+			snap_grid: None,
+			selection: HashSet::new(),
+			draggable: true,
+			mouse: MouseBindings::default(),
+			select_box: None,
+			fisheye: None,
+			fisheye_on: false,
+			pointer: None,
+			spotlight: Spotlight {
+				enabled: theme.background.spotlight.enabled,
+				center: None,
+			},
+			align_guides: false,
+			guides: Vec::new(),
+			focused: None,
+			group_names: HashMap::new(),
+			weight_range,
+			has_dashed_edges,
+			has_unstyled_edges,
+			camera_tween: None,
+			appearing: false,
+			departing: Vec::new(),
+			departing_edges: Vec::new(),
+			filter: None,
+			current_time: None,
+			edge_fades,
+			fading: false,
+			degree_sizing: None,
+			sampling: None,
+			sampled: None,
+			sampling_event: false,
+			sample_sim: None,
+			resizing: false,
+			heat_ramp: theme.colormap.clone(),
+			heat_legend: Corner::BottomLeft,
+			heatmap: None,
+			overlay_colors: HashMap::new(),
 			width,
 			height,
 			animation_running: true,
+			paused: false,
 			flow_time: 0.0,
+			needs_redraw: true,
 		}
 	}
 
-	pub fn screen_to_graph(&self, sx: f64, sy: f64) -> (f64, f64) {
-		(
-			(sx - self.transform.x) / self.transform.k,
-			(sy - self.transform.y) / self.transform.k,
-		)
+	/// Community id per node ID, empty unless coloring by community.
+	pub fn communities(&self) -> &HashMap<String, u32> {
+		&self.communities
 	}
 
-	pub fn node_at_position(
+	/// Describe a node for click and hover callbacks.
+	pub fn node_event(&self, idx: DefaultNodeIdx) -> Option<NodeEvent> {
+		let mut event = None;
+		self.graph.visit_nodes(|node| {
+			if node.index() == idx {
+				event = Some(self.describe(&node.data.user_data));
+			}
+		});
+		event
+	}
+
+	/// Describe the node with display metadata `info`.
+	fn describe(&self, info: &NodeInfo) -> NodeEvent {
+		NodeEvent {
+			id: info.id.clone(),
+			label: info.label.clone(),
+			group: info.group,
+			community: self.communities().get(&info.id).copied(),
+			metric: info.metric,
+			metadata: info.metadata.clone(),
+		}
+	}
+
+	/// World-space `(min_x, min_y, max_x, max_y)` of all node centers, or
+	/// `None` for an empty graph.
+	pub fn bounding_box(&self) -> Option<(f64, f64, f64, f64)> {
+		self.bounding_box_of(|_| true)
+	}
+
+	/// [`Self::bounding_box`] of the nodes whose index passes `include`.
+	fn bounding_box_of(
 		&self,
-		sx: f64,
-		sy: f64,
-		config: &ScaleConfig,
-	) -> Option<DefaultNodeIdx> {
-		let (gx, gy) = self.screen_to_graph(sx, sy);
-		let scale = ScaledValues::new(config, self.transform.k);
-		let mut found = None;
+		include: impl Fn(DefaultNodeIdx) -> bool,
+	) -> Option<(f64, f64, f64, f64)> {
+		let mut bounds: Option<(f64, f64, f64, f64)> = None;
 		self.graph.visit_nodes(|node| {
-			let (dx, dy) = (node.x() as f64 - gx, node.y() as f64 - gy);
-			let node_hit_radius = scale.hit_radius * node.data.user_data.size;
-			if (dx * dx + dy * dy).sqrt() < node_hit_radius {
-				found = Some(node.index());
+			if !include(node.index()) {
+				return;
 			}
+			let (x, y) = (node.x() as f64, node.y() as f64);
+			bounds = Some(match bounds {
+				Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+				None => (x, y, x, y),
+			});
 		});
-		found
+		bounds
 	}
 
-	pub fn set_hover(&mut self, node: Option<DefaultNodeIdx>) {
-		self.highlight.set_hover(node, &self.edges);
+	/// Number of nodes in the simulation.
+	pub fn node_count(&self) -> usize {
+		let mut count = 0;
+		self.graph.visit_nodes(|_| count += 1);
+		count
 	}
 
-	pub fn tick(&mut self, dt: f32) {
-		self.graph.update(dt);
-		self.flow_time += dt as f64;
-		self.highlight.tick(dt as f64);
+	/// Number of nodes drawn individually: all of them unless sampling.
+	pub fn drawn_node_count(&self) -> usize {
+		self.sampled.map_or_else(|| self.node_count(), |s| s.drawn)
 	}
 
-	pub fn resize(&mut self, width: f64, height: f64) {
-		self.width = width;
-		self.height = height;
+	/// Number of links in the simulation.
+	pub fn edge_count(&self) -> usize {
+		self.edges.len()
+	}
+
+	/// Round a world position to the snap grid, if one is set.
+	pub fn snap(&self, x: f32, y: f32) -> (f32, f32) {
+		match self.snap_grid {
+			Some(grid) if grid > 0.0 => {
+				let grid = grid as f32;
+				((x / grid).round() * grid, (y / grid).round() * grid)
+			}
+			_ => (x, y),
+		}
+	}
+
+	/// Current camera and the world-space rectangle it shows.
+	pub fn viewport(&self) -> ViewportInfo {
+		let (min_x, min_y) = self.screen_to_graph(0.0, 0.0);
+		let (max_x, max_y) = self.screen_to_graph(self.width, self.height);
+		ViewportInfo {
+			transform: self.transform.clone(),
+			min_x,
+			min_y,
+			max_x,
+			max_y,
+		}
+	}
+
+	/// [`ViewTransform::screen_to_graph`] under the current view.
+	pub fn screen_to_graph(&self, sx: f64, sy: f64) -> (f64, f64) {
+		self.transform.screen_to_graph(sx, sy)
+	}
+
+	/// [`ViewTransform::graph_to_screen`] under the current view.
+	pub fn graph_to_screen(&self, x: f64, y: f64) -> (f64, f64) {
+		self.transform.graph_to_screen(x, y)
+	}
+
+	/// Where the node with `id` is on the canvas, in pixels from its top
+	/// left corner, or `None` for an unknown or hidden node. Off-screen
+	/// nodes get positions outside the canvas.
+	pub fn node_screen_position(&self, id: &str) -> Option<(f64, f64)> {
+		let mut found = None;
+		self.graph.visit_nodes(|node| {
+			let info = &node.data.user_data;
+			if found.is_none() && !info.is_hidden() && info.id == id {
+				found = Some(self.graph_to_screen(node.x() as f64, node.y() as f64));
+			}
+		});
+		found
+	}
+
+	/// [`Self::node_screen_position`] of every visible node, by id.
+	pub fn node_screen_positions(&self) -> HashMap<String, (f64, f64)> {
+		let mut positions = HashMap::new();
+		self.graph.visit_nodes(|node| {
+			let info = &node.data.user_data;
+			if !info.is_hidden() {
+				positions.insert(
+					info.id.clone(),
+					self.graph_to_screen(node.x() as f64, node.y() as f64),
+				);
+			}
+		});
+		positions
+	}
+
+	/// The fisheye lens and the screen point it's centered on, while it
+	/// distorts the view: it's on, the pointer is over the canvas, and no
+	/// drag, pan or rubber band is in progress.
+	pub fn lens(&self) -> Option<(Fisheye, (f64, f64))> {
+		let busy = self.drag.active || self.pan.active || self.select_box.is_some();
+		if !self.fisheye_on || busy {
+			return None;
+		}
+		Some((self.fisheye?, self.pointer?))
+	}
+
+	/// Where world point `(x, y)` is drawn through `lens`, in world
+	/// coordinates.
+	fn lensed(&self, (fisheye, focus): (Fisheye, (f64, f64)), x: f64, y: f64) -> (f64, f64) {
+		let (sx, sy) = self.graph_to_screen(x, y);
+		let (sx, sy) = fisheye.distort(focus, sx, sy);
+		self.screen_to_graph(sx, sy)
+	}
+
+	/// Move nodes to where the fisheye lens shows them, returning their true
+	/// positions for [`restore_positions`](Self::restore_positions). Empty
+	/// while the lens is off.
+	///
+	/// For drawing only: the simulation must not step before the positions
+	/// are restored.
+	pub fn apply_lens(&mut self) -> HashMap<DefaultNodeIdx, (f32, f32)> {
+		let mut saved = HashMap::new();
+		let Some(lens) = self.lens() else {
+			return saved;
+		};
+		let mut shown = HashMap::new();
+		self.graph.visit_nodes(|node| {
+			let (x, y) = self.lensed(lens, node.x() as f64, node.y() as f64);
+			shown.insert(node.index(), (x as f32, y as f32));
+		});
+		self.graph.visit_nodes_mut(|node| {
+			if let Some(&(x, y)) = shown.get(&node.index()) {
+				saved.insert(node.index(), (node.data.x, node.data.y));
+				node.data.x = x;
+				node.data.y = y;
+			}
+		});
+		saved
+	}
+
+	/// Put back the positions [`apply_lens`](Self::apply_lens) moved.
+	pub fn restore_positions(&mut self, saved: HashMap<DefaultNodeIdx, (f32, f32)>) {
+		if saved.is_empty() {
+			return;
+		}
+		self.graph.visit_nodes_mut(|node| {
+			if let Some(&(x, y)) = saved.get(&node.index()) {
+				node.data.x = x;
+				node.data.y = y;
+			}
+		});
+	}
+
+	pub fn node_at_position(
+		&self,
+		sx: f64,
+		sy: f64,
+		config: &ScaleConfig,
+	) -> Option<DefaultNodeIdx> {
+		let (gx, gy) = self.screen_to_graph(sx, sy);
+		let scale = ScaledValues::new(config, self.transform.k);
+		let lens = self.lens();
+		let mut found = None;
+		self.graph.visit_nodes(|node| {
+			if node.data.user_data.is_hidden() {
+				return;
+			}
+			// Test against where the node is drawn, through the lens
+			let (x, y) = match lens {
+				Some(lens) => self.lensed(lens, node.x() as f64, node.y() as f64),
+				None => (node.x() as f64, node.y() as f64),
+			};
+			let (dx, dy) = (x - gx, y - gy);
+			if dx.hypot(dy) < scale.hit_radius(node.data.user_data.size) {
+				found = Some(node.index());
+			}
+		});
+		found
+	}
+
+	/// Move keyboard focus one step in the screen direction `(dx, dy)`.
+	///
+	/// Prefers the focused node's neighbors, scoring each by distance over
+	/// how closely it lines up with the direction; falls back to any node
+	/// that way if no neighbor lies in it. With nothing focused, focuses the
+	/// node nearest the center of the view. Returns the new focus.
+	pub fn move_focus(&mut self, dx: f64, dy: f64) -> Option<DefaultNodeIdx> {
+		let mut positions = HashMap::new();
+		self.graph.visit_nodes(|node| {
+			if !node.data.user_data.is_hidden() {
+				positions.insert(node.index(), (node.x() as f64, node.y() as f64));
+			}
+		});
+
+		let next = match self.focused.and_then(|f| Some((f, *positions.get(&f)?))) {
+			None => {
+				let (cx, cy) = self.screen_to_graph(self.width / 2.0, self.height / 2.0);
+				positions
+					.iter()
+					.map(|(&idx, &(x, y))| (idx, (x - cx).hypot(y - cy)))
+					.min_by(|a, b| a.1.total_cmp(&b.1))
+					.map(|(idx, _)| idx)
+			}
+			Some((from, (fx, fy))) => {
+				let best_in = |candidates: &mut dyn Iterator<Item = DefaultNodeIdx>| {
+					candidates
+						.filter_map(|idx| {
+							let &(x, y) = positions.get(&idx)?;
+							let (vx, vy) = (x - fx, y - fy);
+							let dist = vx.hypot(vy);
+							let cos = (vx * dx + vy * dy) / (dist * dx.hypot(dy)).max(f64::EPSILON);
+							(idx != from && cos > 0.0).then_some((idx, dist / cos))
+						})
+						.min_by(|a, b| a.1.total_cmp(&b.1))
+						.map(|(idx, _)| idx)
+				};
+				let mut neighbors = self.edges.iter().filter_map(|&(a, b)| {
+					if a == from {
+						Some(b)
+					} else if b == from {
+						Some(a)
+					} else {
+						None
+					}
+				});
+				best_in(&mut neighbors)
+					.or_else(|| best_in(&mut positions.keys().copied()))
+					.or(Some(from))
+			}
+		};
+		if next != self.focused {
+			self.focused = next;
+			self.needs_redraw = true;
+		}
+		next
+	}
+
+	/// Indices of the nodes with the given ids, skipping unknown ids.
+	pub fn indices_of<S: AsRef<str>>(&self, ids: &[S]) -> Vec<DefaultNodeIdx> {
+		let wanted: HashSet<&str> = ids.iter().map(AsRef::as_ref).collect();
+		let mut found = Vec::new();
+		self.graph.visit_nodes(|node| {
+			if wanted.contains(node.data.user_data.id.as_str()) {
+				found.push(node.index());
+			}
+		});
+		found
+	}
+
+	/// Animate the camera to frame the given nodes with `padding` pixels to
+	/// spare, as [`ViewTransform::fit`] does. Does nothing if none of them
+	/// exist.
+	pub fn fit_to_nodes(&mut self, indices: &[DefaultNodeIdx], padding: f64) {
+		let wanted: HashSet<DefaultNodeIdx> = indices.iter().copied().collect();
+		if let Some(bounds) = self.bounding_box_of(|idx| wanted.contains(&idx)) {
+			self.animate_camera(ViewTransform::fit(bounds, self.width, self.height, padding));
+		}
+	}
+
+	/// Animate the camera to zoom by `factor` around the canvas center,
+	/// within [`MIN_ZOOM`] and [`MAX_ZOOM`].
+	pub fn zoom_by(&mut self, factor: f64) {
+		// Chain onto a zoom still in flight, so repeated clicks add up
+		let from = self
+			.camera_tween
+			.as_ref()
+			.map_or(&self.transform, |tween| &tween.to);
+		let k = (from.k * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+		let ratio = k / from.k;
+		let (cx, cy) = (self.width / 2.0, self.height / 2.0);
+		let to = ViewTransform {
+			x: cx - (cx - from.x) * ratio,
+			y: cy - (cy - from.y) * ratio,
+			k,
+		};
+		self.animate_camera(to);
+	}
+
+	/// Animate the camera back to the initial view: the world origin at
+	/// the canvas center, unzoomed.
+	pub fn reset_view(&mut self) {
+		self.animate_camera(ViewTransform {
+			x: self.width / 2.0,
+			y: self.height / 2.0,
+			k: 1.0,
+		});
+	}
+
+	fn animate_camera(&mut self, to: ViewTransform) {
+		self.camera_tween = Some(CameraTween {
+			from: self.transform.clone(),
+			to,
+			elapsed: 0.0,
+		});
+		self.needs_redraw = true;
+	}
+
+	/// Pan so node `idx` sits at the center of the canvas.
+	pub fn center_on(&mut self, idx: DefaultNodeIdx) {
+		let mut target = None;
+		self.graph.visit_nodes(|node| {
+			if node.index() == idx {
+				target = Some((node.x() as f64, node.y() as f64));
+			}
+		});
+		if let Some((x, y)) = target {
+			self.transform.x = self.width / 2.0 - x * self.transform.k;
+			self.transform.y = self.height / 2.0 - y * self.transform.k;
+			self.needs_redraw = true;
+		}
+	}
+
+	/// CSS cursor reflecting the current interaction.
+	pub fn cursor(&self) -> &'static str {
+		if self.drag.active || self.pan.active {
+			"grabbing"
+		} else if self.select_box.is_some() {
+			"crosshair"
+		} else if self.highlight.hovered_node.is_some() {
+			"pointer"
+		} else {
+			"grab"
+		}
+	}
+
+	/// `weight` normalized over the data's weight range to `[0, 1]`, or 1
+	/// when every weight is the same.
+	pub fn weight_t(&self, weight: f64) -> f64 {
+		match self.weight_range {
+			Some((lo, hi)) if hi > lo => ((weight - lo) / (hi - lo)).clamp(0.0, 1.0),
+			_ => 1.0,
+		}
+	}
+
+	/// Highlight every node in `group` as if hovered, or clear the group
+	/// highlight with `None`. Hover highlighting still layers on top.
+	pub fn highlight_group(&mut self, group: Option<u32>) {
+		let mut nodes = HashSet::new();
+		if let Some(group) = group {
+			self.graph.visit_nodes(|node| {
+				if node.data.user_data.group == Some(group) {
+					nodes.insert(node.index());
+				}
+			});
+		}
+		if self.highlight.set_group(nodes, &self.edges) {
+			self.needs_redraw = true;
+		}
+	}
+
+	/// Start dragging node `idx` from the screen point `(x, y)`.
+	///
+	/// If `idx` is selected, every other selected node follows it by the
+	/// same world-space offset; otherwise the selection is cleared.
+	pub fn begin_drag(&mut self, idx: DefaultNodeIdx, x: f64, y: f64) {
+		if !self.selection.contains(&idx) {
+			self.selection.clear();
+		}
+		self.drag.active = true;
+		self.drag.node_idx = Some(idx);
+		self.drag.start_x = x;
+		self.drag.start_y = y;
+		self.drag.followers.clear();
+		self.drag.pinned.clear();
+		let (drag, selection) = (&mut self.drag, &self.selection);
+		self.graph.visit_nodes(|node| {
+			let i = node.index();
+			if i == idx {
+				drag.node_start_x = node.x();
+				drag.node_start_y = node.y();
+			} else if selection.contains(&i) {
+				drag.followers.push((i, node.x(), node.y()));
+			} else {
+				return;
+			}
+			if node.data.is_anchor {
+				drag.pinned.insert(i);
+			}
+		});
+	}
+
+	/// Finish the drag in progress: pin the pressed node where it was let
+	/// go, and record the edit if anything moved or was newly pinned.
+	pub fn end_drag(&mut self) {
+		let Some(idx) = self.drag.node_idx else {
+			return;
+		};
+		let starts: HashMap<DefaultNodeIdx, (f32, f32)> = self
+			.drag
+			.followers
+			.iter()
+			.map(|&(i, x, y)| (i, (x, y)))
+			.chain([(idx, (self.drag.node_start_x, self.drag.node_start_y))])
+			.collect();
+		let mut moves = Vec::new();
+		let pinned = &self.drag.pinned;
+		self.graph.visit_nodes_mut(|node| {
+			let i = node.index();
+			let Some(&from) = starts.get(&i) else {
+				return;
+			};
+			if i == idx {
+				node.data.is_anchor = true;
+			}
+			let step = NodeMove {
+				id: node.data.user_data.id.clone(),
+				from,
+				to: (node.x(), node.y()),
+				pinned: (pinned.contains(&i), node.data.is_anchor),
+			};
+			if step.from != step.to || step.pinned.0 != step.pinned.1 {
+				moves.push(step);
+			}
+		});
+		if !moves.is_empty() {
+			self.history.record(LayoutEdit { moves });
+		}
+	}
+
+	/// Undo the latest layout edit, returning whether there was one.
+	pub fn undo(&mut self) -> bool {
+		let Some(edit) = self.history.undo.pop() else {
+			return false;
+		};
+		self.apply_edit(&edit, false);
+		self.history.redo.push(edit);
+		true
+	}
+
+	/// Redo the latest undone layout edit, returning whether there was one.
+	pub fn redo(&mut self) -> bool {
+		let Some(edit) = self.history.redo.pop() else {
+			return false;
+		};
+		self.apply_edit(&edit, true);
+		self.history.undo.push(edit);
+		true
+	}
+
+	/// Put the nodes of `edit` where it left them, or where it found them
+	/// when undoing. Nodes since removed from the data are skipped.
+	fn apply_edit(&mut self, edit: &LayoutEdit, forward: bool) {
+		let moves: HashMap<&str, &NodeMove> =
+			edit.moves.iter().map(|m| (m.id.as_str(), m)).collect();
+		self.graph.visit_nodes_mut(|node| {
+			if let Some(step) = moves.get(node.data.user_data.id.as_str()) {
+				let ((x, y), pinned) = if forward {
+					(step.to, step.pinned.1)
+				} else {
+					(step.from, step.pinned.0)
+				};
+				node.data.x = x;
+				node.data.y = y;
+				node.data.is_anchor = pinned;
+			}
+		});
+		self.reheat();
+		self.needs_redraw = true;
+	}
+
+	/// Move the dragged node (and any followers) for a pointer at `(x, y)`,
+	/// anchoring them. Snapping applies to the pressed node; followers keep
+	/// their offsets from it.
+	pub fn drag_to(&mut self, x: f64, y: f64) {
+		let Some(idx) = self.drag.node_idx else {
+			return;
+		};
+		let (dx, dy) = (
+			(x - self.drag.start_x) / self.transform.k,
+			(y - self.drag.start_y) / self.transform.k,
+		);
+		let (nx, ny) = self.snap(
+			self.drag.node_start_x + dx as f32,
+			self.drag.node_start_y + dy as f32,
+		);
+		let (nx, ny) = self.align(idx, nx, ny);
+		let (dx, dy) = (nx - self.drag.node_start_x, ny - self.drag.node_start_y);
+		let followers: HashMap<DefaultNodeIdx, (f32, f32)> = self
+			.drag
+			.followers
+			.iter()
+			.map(|&(i, x0, y0)| (i, (x0 + dx, y0 + dy)))
+			.collect();
+		self.graph.visit_nodes_mut(|node| {
+			let i = node.index();
+			let target = if i == idx {
+				Some((nx, ny))
+			} else {
+				followers.get(&i).copied()
+			};
+			if let Some((x, y)) = target {
+				node.data.x = x;
+				node.data.y = y;
+				node.data.is_anchor = true;
+			}
+		});
+	}
+
+	/// Pull a dragged node at `(x, y)` toward the x and y coordinates of
+	/// the closest other nodes within [`ALIGN_THRESHOLD`], recording a guide
+	/// for each axis that aligned. The pull eases in with closeness, so the
+	/// node slides onto the line rather than jumping to it.
+	fn align(&mut self, idx: DefaultNodeIdx, x: f32, y: f32) -> (f32, f32) {
+		self.guides.clear();
+		if !self.align_guides {
+			return (x, y);
+		}
+		let threshold = (ALIGN_THRESHOLD / self.transform.k) as f32;
+		let moving: HashSet<DefaultNodeIdx> = self
+			.drag
+			.followers
+			.iter()
+			.map(|&(i, _, _)| i)
+			.chain([idx])
+			.collect();
+		// Closest match per axis as (distance, partner x, partner y)
+		let mut best_x: Option<(f32, f32, f32)> = None;
+		let mut best_y: Option<(f32, f32, f32)> = None;
+		self.graph.visit_nodes(|node| {
+			if moving.contains(&node.index()) {
+				return;
+			}
+			let (ox, oy) = (node.x(), node.y());
+			let dx = (ox - x).abs();
+			if dx < threshold && best_x.is_none_or(|(d, _, _)| dx < d) {
+				best_x = Some((dx, ox, oy));
+			}
+			let dy = (oy - y).abs();
+			if dy < threshold && best_y.is_none_or(|(d, _, _)| dy < d) {
+				best_y = Some((dy, ox, oy));
+			}
+		});
+
+		let pull = |d: f32| {
+			let t = 1.0 - d / threshold;
+			t * t * (3.0 - 2.0 * t)
+		};
+		let x = best_x.map_or(x, |(d, ox, _)| x + (ox - x) * pull(d));
+		let y = best_y.map_or(y, |(d, _, oy)| y + (oy - y) * pull(d));
+		if let Some((_, ox, oy)) = best_x {
+			self.guides.push(AlignGuide {
+				x0: ox as f64,
+				y0: oy as f64,
+				x1: ox as f64,
+				y1: y as f64,
+			});
+		}
+		if let Some((_, ox, oy)) = best_y {
+			self.guides.push(AlignGuide {
+				x0: ox as f64,
+				y0: oy as f64,
+				x1: x as f64,
+				y1: oy as f64,
+			});
+		}
+		(x, y)
+	}
+
+	/// Add `idx` to the selection, or remove it if already selected.
+	pub fn toggle_selected(&mut self, idx: DefaultNodeIdx) {
+		if !self.selection.remove(&idx) {
+			self.selection.insert(idx);
+		}
+		self.needs_redraw = true;
+	}
+
+	/// Select the shown nodes whose centers lie inside `select_box`, adding
+	/// to the selection if it's additive and replacing it otherwise.
+	pub fn select_in_box(&mut self, select_box: &SelectBox) {
+		let b = select_box;
+		let (x1, y1) = self.screen_to_graph(b.start_x.min(b.x), b.start_y.min(b.y));
+		let (x2, y2) = self.screen_to_graph(b.start_x.max(b.x), b.start_y.max(b.y));
+		if !b.additive {
+			self.selection.clear();
+		}
+		let selection = &mut self.selection;
+		self.graph.visit_nodes(|node| {
+			let (x, y) = (node.x() as f64, node.y() as f64);
+			if !node.data.user_data.is_hidden() && (x1..=x2).contains(&x) && (y1..=y2).contains(&y)
+			{
+				selection.insert(node.index());
+			}
+		});
+		self.needs_redraw = true;
+	}
+
+	/// Set the hovered node, returning whether it changed.
+	pub fn set_hover(&mut self, node: Option<DefaultNodeIdx>) -> bool {
+		let changed = self.highlight.hovered_node != node;
+		if changed {
+			self.needs_redraw = true;
+		}
+		self.highlight.set_hover(node, &self.edges);
+		changed
+	}
+
+	/// Hide the nodes `filter` matches, and the links touching them,
+	/// fading them to its residual alpha; `None` shows every node again.
+	/// Hidden nodes can't be hovered, clicked, dragged or focused.
+	pub fn set_filter(&mut self, filter: Option<Filter>) {
+		self.graph.visit_nodes_mut(|node| {
+			let info = &mut node.data.user_data;
+			info.filtered = filter
+				.as_ref()
+				.is_some_and(|f| f.hides(&info.id, info.group));
+		});
+		self.filter = filter;
+		self.start_fades();
+	}
+
+	/// Play a temporal graph back to `time`: nodes and links timed later
+	/// fade out, and those it reaches fade in. `None` shows every node and
+	/// link whatever its time. Hidden nodes can't be hovered, clicked,
+	/// dragged or focused.
+	pub fn set_time(&mut self, time: Option<f64>) {
+		if self.current_time == time {
+			return;
+		}
+		self.current_time = time;
+		self.graph.visit_nodes_mut(|node| {
+			let info = &mut node.data.user_data;
+			info.active = reached(info.time, time);
+		});
+		self.start_fades();
+	}
+
+	/// Draw at most `sampling.max_nodes` nodes, picked by its strategy;
+	/// `None` draws every node. Only the sample is simulated; nodes left out
+	/// hold still and can't be hovered, clicked, dragged, focused or
+	/// selected.
+	pub(super) fn set_sampling(&mut self, sampling: Option<NodeSampling>) {
+		let mut degrees: HashMap<DefaultNodeIdx, usize> = HashMap::new();
+		for &(src, tgt) in &self.edges {
+			*degrees.entry(src).or_insert(0) += 1;
+			*degrees.entry(tgt).or_insert(0) += 1;
+		}
+		let mut order = Vec::new();
+		let mut candidates = Vec::new();
+		self.graph.visit_nodes(|node| {
+			order.push(node.index());
+			candidates.push(Candidate {
+				labeled: node.data.user_data.label.is_some(),
+				degree: degrees.get(&node.index()).copied().unwrap_or(0),
+			});
+		});
+		let total = order.len();
+		let drawn: HashSet<DefaultNodeIdx> = match sampling {
+			Some(s) => pick(&candidates, s.max_nodes, s.strategy)
+				.into_iter()
+				.map(|i| order[i])
+				.collect(),
+			None => order.iter().copied().collect(),
+		};
+		self.graph.visit_nodes_mut(|node| {
+			node.data.user_data.sampled_out = !drawn.contains(&node.index());
+		});
+		self.selection.retain(|idx| drawn.contains(idx));
+		let sampled = (drawn.len() < total).then_some(SamplingInfo {
+			drawn: drawn.len(),
+			total,
+		});
+		self.sampling_event |= sampled != self.sampled;
+		self.sample_sim = sampled.map(|_| SampleSim::new(&self.graph, &drawn));
+		self.sampled = sampled;
+		self.sampling = sampling;
+		self.start_fades();
+	}
+
+	/// Returns how much of the graph is drawn once after sampling starts,
+	/// changes or stops, with `None` for a graph drawn whole.
+	pub fn take_sampling_event(&mut self) -> Option<Option<SamplingInfo>> {
+		std::mem::take(&mut self.sampling_event).then_some(self.sampled)
+	}
+
+	/// Point cloud cells of the nodes left out of the sample, as world
+	/// centers and node counts; empty unless they're drawn as a
+	/// [`UnsampledNodes::Density`] cloud.
+	pub fn unsampled_density(&self) -> Vec<(f64, f64, u32)> {
+		let cloud = self
+			.sampling
+			.is_some_and(|s| s.unsampled == UnsampledNodes::Density);
+		if !cloud || self.sampled.is_none() {
+			return Vec::new();
+		}
+		let mut points = Vec::new();
+		self.graph.visit_nodes(|node| {
+			if node.data.user_data.sampled_out {
+				points.push((node.x() as f64, node.y() as f64));
+			}
+		});
+		density(points.into_iter(), self.transform.k)
+	}
+
+	/// Begin fading to a new filter or playback time, letting go of any
+	/// node that was just hidden.
+	fn start_fades(&mut self) {
+		let mut hidden = HashSet::new();
+		self.graph.visit_nodes(|node| {
+			if node.data.user_data.is_hidden() {
+				hidden.insert(node.index());
+			}
+		});
+		if self
+			.highlight
+			.hovered_node
+			.is_some_and(|idx| hidden.contains(&idx))
+		{
+			self.set_hover(None);
+		}
+		if self.focused.is_some_and(|idx| hidden.contains(&idx)) {
+			self.focused = None;
+		}
+		self.fading = true;
+		self.needs_redraw = true;
+	}
+
+	/// Jump every filter and playback fade to its end, as for a graph that
+	/// should start out, or be frozen, in its faded state.
+	pub fn finish_fades(&mut self) {
+		let residual = self.filter_residual();
+		self.graph.visit_nodes_mut(|node| {
+			let info = &mut node.data.user_data;
+			info.shown = info.shown_target(residual);
+		});
+		let (fades, now) = (&mut self.edge_fades, self.current_time);
+		self.graph.visit_edges(|n1, n2, edge| {
+			if let Some(fade) = fades.get_mut(&(n1.index(), n2.index())) {
+				*fade = if reached(edge.user_data.time, now) {
+					1.0
+				} else {
+					0.0
+				};
+			}
+		});
+		self.fading = false;
+		self.needs_redraw = true;
+	}
+
+	/// Size nodes by their number of links through `sizing`, replacing the
+	/// default sizing by label and links, and give each a simulation mass in
+	/// proportion to its size so hubs push their surroundings apart.
+	pub fn size_by_degree(&mut self, sizing: DegreeSizing) {
+		let mut degrees: HashMap<DefaultNodeIdx, usize> = HashMap::new();
+		for &(src, tgt) in &self.edges {
+			*degrees.entry(src).or_insert(0) += 1;
+			*degrees.entry(tgt).or_insert(0) += 1;
+		}
+		let max_degree = degrees.values().copied().max().unwrap_or(0);
+		self.graph.visit_nodes_mut(|node| {
+			let degree = degrees.get(&node.index()).copied().unwrap_or(0);
+			let t = sizing.curve.apply(degree, max_degree);
+			let size = (sizing.min + (sizing.max - sizing.min) * t)
+				.clamp(sizing.min.min(sizing.max), sizing.min.max(sizing.max));
+			node.data.user_data.size = size;
+			node.data.user_data.target_size = size;
+			node.data.mass = NODE_MASS * size as f32;
+		});
+		self.degree_sizing = Some(sizing);
+	}
+
+	/// Color nodes by `color_by` through [`heat_ramp`](Self::heat_ramp),
+	/// from its low end for the lowest value to its high end for the
+	/// highest; `None` restores their own colors.
+	///
+	/// The metric is computed once per call, so call it again after the
+	/// links change. Betweenness that doesn't finish in its first slice
+	/// continues over the following ticks, and nodes keep their own colors
+	/// until it's done.
+	pub fn set_color_by(&mut self, color_by: Option<ColorBy>) {
+		let overlay_colors = std::mem::take(&mut self.overlay_colors);
+		self.graph.visit_nodes_mut(|node| {
+			let saved = overlay_colors.get(&node.index());
+			let info = &mut node.data.user_data;
+			if let Some(color) = saved {
+				info.paint(color.clone());
+			}
+			info.metric = None;
+		});
+		self.heatmap = None;
+		self.needs_redraw = true;
+		let Some(color_by) = color_by else {
+			return;
+		};
+
+		let mut order = Vec::new();
+		self.graph.visit_nodes(|node| order.push(node.index()));
+		let index: HashMap<DefaultNodeIdx, usize> =
+			order.iter().enumerate().map(|(i, &idx)| (idx, i)).collect();
+		let edges: Vec<(usize, usize)> = self
+			.edges
+			.iter()
+			.filter_map(|(a, b)| Some((*index.get(a)?, *index.get(b)?)))
+			.collect();
+		let neighbors = centrality::neighbors(order.len(), &edges);
+		let mut heatmap = Heatmap {
+			color_by,
+			range: None,
+			pending: None,
+		};
+		let values = match color_by {
+			ColorBy::Betweenness if order.len() <= BETWEENNESS_MAX_NODES => {
+				heatmap.pending = Some((Betweenness::new(neighbors), order));
+				self.heatmap = Some(heatmap);
+				self.advance_heatmap();
+				return;
+			}
+			ColorBy::Custom(value) => {
+				let mut values = Vec::with_capacity(order.len());
+				self.graph
+					.visit_nodes(|node| values.push(value(&self.describe(&node.data.user_data))));
+				values
+			}
+			_ => centrality::degree(&neighbors),
+		};
+		self.heatmap = Some(heatmap);
+		self.paint_heatmap(&order, values);
+	}
+
+	/// The heatmap coloring nodes, if any.
+	pub fn heatmap(&self) -> Option<&Heatmap> {
+		self.heatmap.as_ref()
+	}
+
+	/// Compute the rest of a betweenness heatmap at once, as for a static
+	/// render.
+	pub fn finish_heatmap(&mut self) {
+		while self.advance_heatmap() {}
+	}
+
+	/// Run the next slice of a betweenness heatmap, painting the nodes once
+	/// it's done. Returns whether there was one to run.
+	fn advance_heatmap(&mut self) -> bool {
+		let Some(heatmap) = self.heatmap.as_mut() else {
+			return false;
+		};
+		let Some((pass, _)) = heatmap.pending.as_mut() else {
+			return false;
+		};
+		if pass.step()
+			&& let Some((pass, order)) = heatmap.pending.take()
+		{
+			self.paint_heatmap(&order, pass.scores());
+		}
+		true
+	}
+
+	/// Paint each node in `order` by its value in `values`, scaled over
+	/// their range.
+	fn paint_heatmap(&mut self, order: &[DefaultNodeIdx], values: Vec<f64>) {
+		let range = values
+			.iter()
+			.copied()
+			.filter(|v| v.is_finite())
+			.fold(None, |range, v| match range {
+				None => Some((v, v)),
+				Some((lo, hi)) => Some((v.min(lo), v.max(hi))),
+			});
+		let (lo, hi) = range.unwrap_or_default();
+		let values: HashMap<DefaultNodeIdx, f64> = order.iter().copied().zip(values).collect();
+		let ramp = &self.heat_ramp;
+		let mut overlay_colors = HashMap::new();
+		self.graph.visit_nodes_mut(|node| {
+			let idx = node.index();
+			let Some(&value) = values.get(&idx) else {
+				return;
+			};
+			let t = if value.is_finite() && hi > lo {
+				(value - lo) / (hi - lo)
+			} else {
+				0.0
+			};
+			let info = &mut node.data.user_data;
+			overlay_colors.insert(idx, info.color.clone());
+			info.paint(ramp.sample(t).to_css_rgb());
+			info.metric = Some(value);
+		});
+		self.overlay_colors = overlay_colors;
+		if let Some(ref mut heatmap) = self.heatmap {
+			heatmap.range = range;
+		}
+		self.needs_redraw = true;
+	}
+
+	/// Playback opacity of the edge between `a` and `b`, either way round.
+	pub fn edge_fade(&self, a: DefaultNodeIdx, b: DefaultNodeIdx) -> f64 {
+		self.edge_fades
+			.get(&(a, b))
+			.or_else(|| self.edge_fades.get(&(b, a)))
+			.copied()
+			.unwrap_or(1.0)
+	}
+
+	/// Ease the spotlight toward the pointer. Returns whether it moved.
+	fn follow_pointer(&mut self, dt: f64) -> bool {
+		let center = &mut self.spotlight.center;
+		let (Some((px, py)), Some((cx, cy))) = (self.pointer, *center) else {
+			// Appearing or leaving with the pointer
+			let moved = *center != self.pointer;
+			*center = self.pointer;
+			return moved;
+		};
+		let (dx, dy) = (px - cx, py - cy);
+		if dx.hypot(dy) < 0.5 {
+			*center = self.pointer;
+			return dx != 0.0 || dy != 0.0;
+		}
+		let ease = 1.0 - (-SPOTLIGHT_FOLLOW * dt).exp();
+		*center = Some((cx + dx * ease, cy + dy * ease));
+		true
+	}
+
+	/// Opacity the current filter leaves hidden nodes at.
+	fn filter_residual(&self) -> f64 {
+		self.filter
+			.as_ref()
+			.map_or(1.0, |f| f.residual_alpha.clamp(0.0, 1.0))
+	}
+
+	/// Advance time-based state by `dt` seconds.
+	///
+	/// Physics only steps while `animation_running`, not `paused` and not
+	/// settled, easing up to full speed after a reheat; highlight
+	/// transitions always animate. Returns whether either changed
+	/// anything visible: a node moved more than [`MOVE_EPSILON`] screen
+	/// pixels or a highlight is mid-transition. Input handlers flag their
+	/// own redraws separately.
+	pub fn tick(&mut self, dt: f32) -> bool {
+		let mut changed = false;
+		if self.animation_running && !self.paused && !self.stability.is_settled() {
+			let ramp = (self.stability.ramp + dt as f64 / RAMP_SECS).min(1.0);
+			self.stability.ramp = ramp;
+			let step = dt * (ramp * ramp * (3.0 - 2.0 * ramp)) as f32;
+			match &mut self.sample_sim {
+				Some(sim) => sim.update(&mut self.graph, step),
+				None => self.graph.update(step),
+			}
+			if self.bounds.bounded {
+				self.apply_bounds(dt as f64);
+			}
+			// A dragged node's neighbors keep following however still it's held
+			if self.drag.active {
+				self.stability.quiet = 0.0;
+			}
+			self.stability.measure(&mut self.graph, dt as f64);
+			// A settling layout creeps by sub-pixel amounts for a long time
+			changed |= self.stability.max_step * self.transform.k > MOVE_EPSILON;
+		}
+		if let Some(ref mut tween) = self.camera_tween {
+			let (transform, done) = tween.step(dt as f64, self.width, self.height);
+			self.transform = transform;
+			if done {
+				self.camera_tween = None;
+			}
+			changed = true;
+		}
+		if self.appearing {
+			let step = dt as f64 / APPEAR_SECS;
+			let mut appearing = false;
+			self.graph.visit_nodes_mut(|node| {
+				let t = &mut node.data.user_data.appear_t;
+				*t = (*t + step).min(1.0);
+				appearing |= *t < 1.0;
+			});
+			self.appearing = appearing;
+			changed = true;
+		}
+		if self.fading {
+			let residual = self.filter_residual();
+			let step = dt as f64 / FADE_SECS;
+			let mut fading = false;
+			self.graph.visit_nodes_mut(|node| {
+				let info = &mut node.data.user_data;
+				let target = info.shown_target(residual);
+				info.shown = approach(info.shown, target, step);
+				fading |= info.shown != target;
+			});
+			let (fades, now) = (&mut self.edge_fades, self.current_time);
+			self.graph.visit_edges(|n1, n2, edge| {
+				if let Some(fade) = fades.get_mut(&(n1.index(), n2.index())) {
+					let target = if reached(edge.user_data.time, now) {
+						1.0
+					} else {
+						0.0
+					};
+					*fade = approach(*fade, target, step);
+					fading |= *fade != target;
+				}
+			});
+			self.fading = fading;
+			changed = true;
+		}
+		if self.resizing {
+			let ease = (dt as f64 / RESIZE_SECS).min(1.0);
+			let weighted = self.degree_sizing.is_some();
+			let mut resizing = false;
+			self.graph.visit_nodes_mut(|node| {
+				let info = &mut node.data.user_data;
+				let gap = info.target_size - info.size;
+				if gap.abs() < 1e-3 {
+					info.size = info.target_size;
+				} else {
+					info.size += gap * ease;
+					resizing = true;
+				}
+				if weighted {
+					node.data.mass = NODE_MASS * info.size as f32;
+				}
+			});
+			self.resizing = resizing;
+			changed = true;
+		}
+		changed |= self.advance_heatmap();
+		if self.spotlight.enabled {
+			changed |= self.follow_pointer(dt as f64);
+		}
+		if !self.departing.is_empty() {
+			let step = dt as f64 / APPEAR_SECS;
+			for node in &mut self.departing {
+				node.info.appear_t -= step;
+			}
+			self.departing.retain(|node| node.info.appear_t > 0.0);
+			changed = true;
+		}
+		if !self.departing_edges.is_empty() {
+			let step = dt as f64 / FADE_SECS;
+			for edge in &mut self.departing_edges {
+				edge.fade -= step;
+			}
+			self.departing_edges.retain(|edge| edge.fade > 0.0);
+			changed = true;
+		}
+		self.flow_time += dt as f64;
+		self.viewport_watch.observe(self.viewport(), dt as f64);
+		changed |= self.highlight.is_animating();
+		self.highlight.tick(dt as f64);
+		changed
+	}
+
+	/// Continue from `previous`, the state before a data update, so the
+	/// update doesn't restart the view: nodes that were already there keep
+	/// their positions and pins, the camera stays put, new nodes fade in
+	/// beside a neighbor that was already there, and removed nodes fade out.
+	/// A group collapsing into a meta-node gathers where its members were,
+	/// and expanding it spreads them from the meta-node.
+	pub fn carry_over(&mut self, previous: &ForceGraphState) {
+		// A rebuild that samples the same way has nothing new to report
+		self.sampling_event = self.sampled != previous.sampled;
+		let mut current = HashSet::new();
+		self.graph.visit_nodes(|node| {
+			current.insert(node.data.user_data.id.clone());
+		});
+		self.departing = previous
+			.departing
+			.iter()
+			.filter(|node| !current.contains(&node.info.id))
+			.cloned()
+			.collect();
+		previous.graph.visit_nodes(|node| {
+			if !current.contains(&node.data.user_data.id) {
+				self.departing.push(DepartingNode {
+					x: node.x() as f64,
+					y: node.y() as f64,
+					info: node.data.user_data.clone(),
+				});
+			}
+		});
+
+		let mut known = HashMap::new();
+		previous.graph.visit_nodes(|node| {
+			known.insert(
+				node.data.user_data.id.clone(),
+				(
+					node.x(),
+					node.y(),
+					node.data.is_anchor,
+					node.data.user_data.shown,
+					node.data.user_data.size,
+				),
+			);
+		});
+		if known.is_empty() {
+			return;
+		}
+		// Start up gently around the nodes already in place
+		self.stability.ramp = 0.0;
+		self.stability.pending_wake = previous.stability.settled;
+		self.transform = previous.transform.clone();
+		self.fisheye_on = previous.fisheye_on;
+		// Edits find their nodes by id, so they survive the rebuild
+		self.history = History {
+			limit: self.history.limit,
+			..previous.history.clone()
+		};
+		self.pointer = previous.pointer;
+		// Known nodes resume their filter fades where they were
+		self.fading = true;
+		self.carry_over_edges(previous, &current);
+
+		// Where each group's members were, for a meta-node collapsing them
+		let mut centers: HashMap<u32, (f32, f32, f32)> = HashMap::new();
+		previous.graph.visit_nodes(|node| {
+			let info = &node.data.user_data;
+			if let Some(group) = info.group
+				&& meta_group(&info.id).is_none()
+			{
+				let center = centers.entry(group).or_default();
+				*center = (center.0 + node.x(), center.1 + node.y(), center.2 + 1.0);
+			}
+		});
+		let mut added = HashSet::new();
+		let mut beside = HashMap::new();
+		let mut resizing = false;
+		self.graph
+			.visit_nodes_mut(|node| match known.get(&node.data.user_data.id) {
+				Some(&(x, y, anchored, shown, size)) => {
+					node.data.x = x;
+					node.data.y = y;
+					node.data.is_anchor = anchored;
+					let info = &mut node.data.user_data;
+					info.shown = shown;
+					// A node whose links changed grows or shrinks to its new size
+					resizing |= info.size != size;
+					info.size = size;
+				}
+				None => {
+					let idx = node.index();
+					let info = &mut node.data.user_data;
+					info.appear_t = 0.0;
+					added.insert(idx);
+					// A meta-node starts amid the members it replaces, and
+					// expanded members spread from their meta-node
+					let origin = match meta_group(&info.id) {
+						Some(group) => centers.get(&group).map(|&(x, y, n)| (x / n, y / n)),
+						None => info
+							.group
+							.and_then(|group| known.get(&meta_id(group)))
+							.map(|&(x, y, ..)| (x, y)),
+					};
+					if let Some(origin) = origin {
+						beside.insert(idx, origin);
+					}
+				}
+			});
+		self.resizing = resizing;
+		if added.is_empty() {
+			return;
+		}
+
+		let mut positions = HashMap::new();
+		self.graph.visit_nodes(|node| {
+			positions.insert(node.index(), (node.x(), node.y()));
+		});
+		for &(src, tgt) in &self.edges {
+			for (new, old) in [(src, tgt), (tgt, src)] {
+				if added.contains(&new) && !added.contains(&old) {
+					beside.entry(new).or_insert(positions[&old]);
+				}
+			}
+		}
+		let rng = &mut self.rng;
+		self.graph.visit_nodes_mut(|node| {
+			if let Some(&(x, y)) = beside.get(&node.index()) {
+				let mut jitter = || ((rng.unit() * 2.0 - 1.0) * APPEAR_SPREAD) as f32;
+				node.data.x = x + jitter();
+				node.data.y = y + jitter();
+			}
+		});
+		self.appearing = true;
+	}
+
+	/// Fade in the links `previous` didn't have, picking up where a link
+	/// fading out left off, and fade out those it had that are gone but
+	/// whose nodes, among `current`, remain.
+	fn carry_over_edges(&mut self, previous: &ForceGraphState, current: &HashSet<String>) {
+		let ids = |state: &ForceGraphState| {
+			let mut ids = HashMap::new();
+			state.graph.visit_nodes(|node| {
+				ids.insert(node.index(), node.data.user_data.id.clone());
+			});
+			ids
+		};
+		let (old_ids, new_ids) = (ids(previous), ids(self));
+		let mut old_edges = HashMap::new();
+		previous.graph.visit_edges(|n1, n2, _| {
+			let key = edge_key(&old_ids[&n1.index()], &old_ids[&n2.index()]);
+			old_edges.insert(key, previous.edge_fade(n1.index(), n2.index()));
+		});
+		let fading_out: HashMap<_, _> = previous
+			.departing_edges
+			.iter()
+			.map(|edge| (edge_key(&edge.source, &edge.target), edge.fade))
+			.collect();
+
+		let mut kept = HashSet::new();
+		let fades = &mut self.edge_fades;
+		self.graph.visit_edges(|n1, n2, _| {
+			let key = edge_key(&new_ids[&n1.index()], &new_ids[&n2.index()]);
+			let fade = old_edges
+				.get(&key)
+				.or_else(|| fading_out.get(&key))
+				.copied()
+				.unwrap_or(0.0);
+			let idx = (n1.index(), n2.index());
+			if fade < 1.0 || fades.contains_key(&idx) {
+				fades.insert(idx, fade);
+			}
+			kept.insert(key);
+		});
+
+		let remains = |key: &(String, String)| {
+			!kept.contains(key) && current.contains(&key.0) && current.contains(&key.1)
+		};
+		self.departing_edges = fading_out
+			.into_iter()
+			.chain(old_edges.into_iter().filter(|&(_, fade)| fade > 0.0))
+			.filter(|(key, _)| remains(key))
+			.map(|((source, target), fade)| DepartingEdge {
+				source,
+				target,
+				fade,
+			})
+			.collect();
+	}
+
+	/// World-space `(x1, y1, x2, y2, opacity)` of each departing link.
+	pub fn departing_edge_lines(&self) -> Vec<(f64, f64, f64, f64, f64)> {
+		if self.departing_edges.is_empty() {
+			return Vec::new();
+		}
+		let mut nodes = HashMap::new();
+		self.graph.visit_nodes(|node| {
+			let info = &node.data.user_data;
+			nodes.insert(
+				info.id.clone(),
+				((node.x() as f64, node.y() as f64), info.shown),
+			);
+		});
+		self.departing_edges
+			.iter()
+			.filter_map(|edge| {
+				let &((x1, y1), a_shown) = nodes.get(&edge.source)?;
+				let &((x2, y2), b_shown) = nodes.get(&edge.target)?;
+				Some((x1, y1, x2, y2, edge.fade * a_shown.min(b_shown)))
+			})
+			.collect()
+	}
+
+	/// Flag that the next frame must repaint, e.g. after user interaction.
+	pub fn mark_dirty(&mut self) {
+		self.needs_redraw = true;
+	}
+
+	/// Returns whether a repaint is pending and clears the flag.
+	pub fn take_redraw(&mut self) -> bool {
+		std::mem::take(&mut self.needs_redraw)
+	}
+
+	/// Pull free nodes back inside the world-space rectangle visible on screen.
+	///
+	/// Anchored nodes are skipped so a user can still drag a node outside.
+	fn apply_bounds(&mut self, dt: f64) {
+		let (min_x, min_y) = self.screen_to_graph(0.0, 0.0);
+		let (max_x, max_y) = self.screen_to_graph(self.width, self.height);
+		let margin = (self.bounds.margin / self.transform.k)
+			.min((max_x - min_x) / 2.0)
+			.min((max_y - min_y) / 2.0);
+		let (inner_min_x, inner_min_y) = (min_x + margin, min_y + margin);
+		let (inner_max_x, inner_max_y) = (max_x - margin, max_y - margin);
+		let pull = (self.bounds.stiffness * dt).min(1.0);
+
+		self.graph.visit_nodes_mut(|node| {
+			if node.data.is_anchor {
+				return;
+			}
+			let (mut x, mut y) = (node.data.x as f64, node.data.y as f64);
+
+			// Spring back from the margin band, proportional to penetration
+			if x < inner_min_x {
+				x += (inner_min_x - x) * pull;
+			} else if x > inner_max_x {
+				x -= (x - inner_max_x) * pull;
+			}
+			if y < inner_min_y {
+				y += (inner_min_y - y) * pull;
+			} else if y > inner_max_y {
+				y -= (y - inner_max_y) * pull;
+			}
+
+			// The canvas edge itself is a hard wall
+			node.data.x = x.clamp(min_x, max_x) as f32;
+			node.data.y = y.clamp(min_y, max_y) as f32;
+		});
+	}
+
+	/// Returns `true` exactly once after the layout first settles.
+	pub fn take_settled_event(&mut self) -> bool {
+		std::mem::take(&mut self.stability.pending_event)
+	}
+
+	/// Returns the viewport once it has settled after a change.
+	pub fn take_viewport_event(&mut self) -> Option<ViewportInfo> {
+		let watch = &mut self.viewport_watch;
+		if watch.quiet_time < watch.delay || watch.last_seen == watch.last_reported {
+			return None;
+		}
+		watch.last_reported = watch.last_seen.clone();
+		watch.last_reported.clone()
+	}
+
+	/// Returns `true` once after the simulation wakes from settled.
+	pub fn take_wake_event(&mut self) -> bool {
+		std::mem::take(&mut self.stability.pending_wake)
+	}
+
+	/// Resume the simulation, easing it back up to speed so the layout
+	/// doesn't jolt, and re-arm the settled notification.
+	pub fn reheat(&mut self) {
+		let stability = &mut self.stability;
+		stability.pending_wake |= stability.settled;
+		self.animation_running = true;
+		stability.settled = false;
+		stability.pending_event = false;
+		stability.energy = f64::INFINITY;
+		stability.quiet = 0.0;
+		stability.ramp = 0.0;
+	}
+
+	pub fn resize(&mut self, width: f64, height: f64) {
+		self.width = width;
+		self.height = height;
+		self.needs_redraw = true;
+	}
+}
+
+/// Whether playback at `now` has reached something timed at `at`; untimed
+/// things, and everything when playback is off, count as reached.
+fn reached(at: Option<f64>, now: Option<f64>) -> bool {
+	match (at, now) {
+		(Some(at), Some(now)) => at <= now,
+		_ => true,
+	}
+}
+
+/// A link's node ids, in order so either direction gives the same key.
+fn edge_key(a: &str, b: &str) -> (String, String) {
+	if a <= b {
+		(a.to_string(), b.to_string())
+	} else {
+		(b.to_string(), a.to_string())
+	}
+}
+
+/// Move `value` towards `target` by at most `step`.
+fn approach(value: f64, target: f64, step: f64) -> f64 {
+	if value < target {
+		(value + step).min(target)
+	} else {
+		(value - step).max(target)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::components::force_graph::sampling::SampleStrategy;
+	use crate::components::force_graph::scale::ScaleBehavior;
+	use crate::components::force_graph::timeline::apply_window;
+	use crate::components::force_graph::types::GraphLink;
+	use crate::components::force_graph::types::fixtures::{link, node};
+
+	const DT: f32 = 1.0 / 60.0;
+
+	/// `a`–`b` and `b`–`c` linked, `d` isolated.
+	fn sample() -> GraphData {
+		GraphData {
+			nodes: ["a", "b", "c", "d"].map(node).to_vec(),
+			links: vec![link("a", "b"), link("b", "c")],
+		}
+	}
+
+	fn state(data: &GraphData) -> ForceGraphState {
+		ForceGraphState::new(
+			data,
+			800.0,
+			600.0,
+			&Theme::default(),
+			false,
+			SeedStrategy::Circle,
+			None,
+		)
+	}
+
+	fn index_of(state: &ForceGraphState, id: &str) -> DefaultNodeIdx {
+		let mut found = None;
+		state.graph.visit_nodes(|n| {
+			if n.data.user_data.id == id {
+				found = Some(n.index());
+			}
+		});
+		found.unwrap()
+	}
+
+	fn position(state: &ForceGraphState, id: &str) -> (f64, f64) {
+		let mut found = None;
+		state.graph.visit_nodes(|n| {
+			if n.data.user_data.id == id {
+				found = Some((n.x() as f64, n.y() as f64));
+			}
+		});
+		found.unwrap()
+	}
+
+	fn distance(state: &ForceGraphState, a: &str, b: &str) -> f64 {
+		let ((ax, ay), (bx, by)) = (position(state, a), position(state, b));
+		(ax - bx).hypot(ay - by)
+	}
+
+	#[test]
+	fn linked_nodes_end_closer_than_unlinked() {
+		let mut state = state(&sample());
+		for _ in 0..600 {
+			state.tick(DT);
+		}
+		assert!(distance(&state, "a", "b") < distance(&state, "a", "d"));
+		assert!(distance(&state, "b", "c") < distance(&state, "b", "d"));
+	}
+
+	#[test]
+	fn settled_simulation_sleeps_until_reheated() {
+		let mut state = state(&sample());
+		let mut ticks = 0;
+		while !state.stability.is_settled() && ticks < 1200 {
+			state.tick(DT);
+			ticks += 1;
+		}
+		assert!(state.stability.is_settled());
+		assert!(state.take_settled_event());
+		// Quiet for the whole delay before settling
+		assert!(f64::from(ticks) * f64::from(DT) >= state.stability.settle_delay);
+		assert!(!state.take_wake_event());
+
+		state.reheat();
+		assert!(state.take_wake_event());
+		assert!(!state.stability.is_settled());
+		state.tick(DT);
+		// Easing back up to speed
+		assert!(state.stability.ramp < 1.0);
+		// Waking an already running simulation isn't reported
+		state.reheat();
+		assert!(!state.take_wake_event());
+	}
+
+	#[test]
+	fn settled_layout_holds_still() {
+		let mut state = state(&sample());
+		for _ in 0..1200 {
+			state.tick(DT);
+		}
+		assert!(state.stability.is_settled());
+		let before = ["a", "b", "c", "d"].map(|id| position(&state, id));
+		for _ in 0..60 {
+			state.tick(DT);
+		}
+		assert_eq!(["a", "b", "c", "d"].map(|id| position(&state, id)), before);
+	}
+
+	#[test]
+	fn same_seed_gives_same_layout() {
+		let data = sample();
+		let mut first = state(&data);
+		let mut second = state(&data);
+		for _ in 0..100 {
+			first.tick(DT);
+			second.tick(DT);
+		}
+		for id in ["a", "b", "c", "d"] {
+			assert_eq!(position(&first, id), position(&second, id));
+		}
+	}
+
+	#[test]
+	fn screen_to_graph_inverts_transform() {
+		let mut state = state(&sample());
+		state.transform = ViewTransform {
+			x: 120.0,
+			y: -40.0,
+			k: 2.0,
+		};
+		assert_eq!(state.screen_to_graph(120.0, -40.0), (0.0, 0.0));
+		assert_eq!(state.screen_to_graph(220.0, 60.0), (50.0, 50.0));
+		assert_eq!(state.graph_to_screen(50.0, 50.0), (220.0, 60.0));
+	}
+
+	#[test]
+	fn screen_and_graph_coordinates_round_trip() {
+		let views = [
+			ViewTransform {
+				x: 0.0,
+				y: 0.0,
+				k: 1.0,
+			},
+			ViewTransform {
+				x: 333.3,
+				y: -71.25,
+				k: MIN_ZOOM,
+			},
+			ViewTransform {
+				x: -1234.5,
+				y: 987.6,
+				k: MAX_ZOOM,
+			},
+			ViewTransform::fit((-400.0, -250.0, 900.0, 700.0), 800.0, 600.0, 40.0),
+		];
+		let points = [(0.0, 0.0), (12.5, -7.75), (-3000.0, 4500.0), (0.001, 1e6)];
+		let close = |(ax, ay): (f64, f64), (bx, by): (f64, f64)| {
+			let tolerance = 1e-9 * (1.0 + ax.abs().max(ay.abs()));
+			(ax - bx).abs() <= tolerance && (ay - by).abs() <= tolerance
+		};
+		for view in &views {
+			for &(x, y) in &points {
+				let screen = view.graph_to_screen(x, y);
+				assert!(close(view.screen_to_graph(screen.0, screen.1), (x, y)));
+				let world = view.screen_to_graph(x, y);
+				assert!(close(view.graph_to_screen(world.0, world.1), (x, y)));
+			}
+		}
+	}
+
+	#[test]
+	fn node_screen_positions_follow_the_transform() {
+		let mut state = state(&sample());
+		state.transform = ViewTransform {
+			x: 120.0,
+			y: -40.0,
+			k: 2.0,
+		};
+		let (x, y) = position(&state, "a");
+		let at = state.node_screen_position("a").unwrap();
+		assert_eq!(at, (120.0 + x * 2.0, -40.0 + y * 2.0));
+		assert_eq!(state.node_screen_positions()["a"], at);
+		assert_eq!(state.node_screen_position("missing"), None);
+	}
+
+	#[test]
+	fn node_at_position_hits_node_centers() {
+		let state = state(&sample());
+		let config = ScaleConfig::default();
+		let t = &state.transform;
+		for id in ["a", "b", "c", "d"] {
+			let (x, y) = position(&state, id);
+			let (sx, sy) = (x * t.k + t.x, y * t.k + t.y);
+			assert_eq!(
+				state.node_at_position(sx, sy, &config),
+				Some(index_of(&state, id))
+			);
+		}
+		assert_eq!(state.node_at_position(-500.0, -500.0, &config), None);
+	}
+
+	#[test]
+	fn hit_area_follows_node_size() {
+		let mut state = state(&sample());
+		let config = ScaleConfig::default();
+		let scale = ScaledValues::new(&config, state.transform.k);
+		let (x, y) = position(&state, "a");
+		let t = state.transform.clone();
+		// Just past a plain node's hit area, to the right
+		let reach = (scale.hit_radius(1.0) + 1.0) * t.k;
+		let (sx, sy) = (t.x + x * t.k + reach, t.y + y * t.k);
+		assert_ne!(
+			state.node_at_position(sx, sy, &config),
+			Some(index_of(&state, "a"))
+		);
+
+		let a = index_of(&state, "a");
+		state.graph.visit_nodes_mut(|node| {
+			if node.index() == a {
+				node.data.user_data.size = 2.0;
+			}
+		});
+		assert_eq!(state.node_at_position(sx, sy, &config), Some(a));
+	}
+
+	#[test]
+	fn screen_sized_nodes_stay_clickable_zoomed_out() {
+		let mut state = state(&sample());
+		state.transform.k = 0.05;
+		let (x, y) = position(&state, "a");
+		let t = state.transform.clone();
+		// Inside the node as drawn on screen, well outside it in world units
+		let reach = ScaleConfig::default().node.radius;
+		let (sx, sy) = (t.x + x * t.k + reach, t.y + y * t.k);
+		let config = ScaleConfig::screen_sized();
+		assert_eq!(
+			state.node_at_position(sx, sy, &config),
+			Some(index_of(&state, "a"))
+		);
+		let scale = ScaledValues::new(&config, t.k);
+		assert_eq!(scale.node_radius * t.k, config.node.radius);
+	}
+
+	#[test]
+	fn nodes_keep_a_minimum_size_zoomed_out() {
+		let mut state = state(&sample());
+		state.transform.k = 0.1;
+		let (x, y) = position(&state, "a");
+		let t = state.transform.clone();
+		let mut config = ScaleConfig::default();
+		config.node.radius_behavior = ScaleBehavior::World;
+		config.node.hit_padding = 0.0;
+		config.node.min_px = 4.0;
+		// Half a pixel as laid out, but drawn and hit at four
+		let (sx, sy) = (t.x + x * t.k + 3.0, t.y + y * t.k);
+		assert_eq!(
+			state.node_at_position(sx, sy, &config),
+			Some(index_of(&state, "a"))
+		);
+		let scale = ScaledValues::new(&config, t.k);
+		assert_eq!(scale.node_radius_for(0.5) * t.k, config.node.min_px);
+	}
+
+	#[test]
+	fn sampling_simulates_only_the_sample() {
+		let mut state = state(&sample());
+		state.set_sampling(Some(NodeSampling {
+			max_nodes: 2,
+			strategy: SampleStrategy::Important,
+			unsampled: UnsampledNodes::Skip,
+		}));
+		let before: Vec<_> = ["a", "b", "c", "d"].map(|id| position(&state, id)).into();
+		for _ in 0..30 {
+			state.tick(DT);
+		}
+		// `b` and `a` are the best linked; `c` and `d` are left out
+		assert_ne!(position(&state, "a"), before[0]);
+		assert_ne!(position(&state, "b"), before[1]);
+		assert_eq!(position(&state, "c"), before[2]);
+		assert_eq!(position(&state, "d"), before[3]);
+
+		state.set_sampling(None);
+		state.reheat();
+		for _ in 0..30 {
+			state.tick(DT);
+		}
+		assert_ne!(position(&state, "d"), before[3]);
+	}
+
+	#[test]
+	fn sampling_draws_the_best_linked_nodes() {
+		let mut state = state(&sample());
+		let config = ScaleConfig::default();
+		state.set_sampling(Some(NodeSampling {
+			max_nodes: 1,
+			strategy: SampleStrategy::Important,
+			unsampled: UnsampledNodes::Density,
+		}));
+		let info = SamplingInfo { drawn: 1, total: 4 };
+		assert_eq!(state.take_sampling_event(), Some(Some(info)));
+		assert_eq!(state.take_sampling_event(), None);
+		assert_eq!(state.drawn_node_count(), 1);
+		assert_eq!(
+			state.unsampled_density().iter().map(|c| c.2).sum::<u32>(),
+			3
+		);
+
+		let t = state.transform.clone();
+		let at = |id| {
+			let (x, y) = position(&state, id);
+			(t.x + x * t.k, t.y + y * t.k)
+		};
+		let (bx, by) = at("b");
+		let (ax, ay) = at("a");
+		assert_eq!(
+			state.node_at_position(bx, by, &config),
+			Some(index_of(&state, "b"))
+		);
+		assert_eq!(state.node_at_position(ax, ay, &config), None);
+
+		state.set_sampling(None);
+		assert_eq!(state.take_sampling_event(), Some(None));
+		assert_eq!(
+			state.node_at_position(ax, ay, &config),
+			Some(index_of(&state, "a"))
+		);
+	}
+
+	#[test]
+	fn filtered_nodes_fade_and_ignore_the_pointer() {
+		let mut state = state(&sample());
+		state.set_filter(Some(Filter {
+			hidden_nodes: HashSet::from(["b".to_string()]),
+			residual_alpha: 0.2,
+			..Filter::default()
+		}));
+		let t = state.transform.clone();
+		let (x, y) = position(&state, "b");
+		let config = ScaleConfig::default();
+		assert_eq!(
+			state.node_at_position(x * t.k + t.x, y * t.k + t.y, &config),
+			None
+		);
+
+		let shown = |state: &ForceGraphState, id| {
+			let mut shown = 0.0;
+			state.graph.visit_nodes(|n| {
+				if n.data.user_data.id == id {
+					shown = n.data.user_data.shown;
+				}
+			});
+			shown
+		};
+		state.tick(DT);
+		assert!(shown(&state, "b") < 1.0 && shown(&state, "b") > 0.2);
+		for _ in 0..30 {
+			state.tick(DT);
+		}
+		assert_eq!(shown(&state, "b"), 0.2);
+		assert_eq!(shown(&state, "a"), 1.0);
+
+		state.set_filter(None);
+		for _ in 0..30 {
+			state.tick(DT);
+		}
+		assert_eq!(shown(&state, "b"), 1.0);
+	}
+
+	#[test]
+	fn hover_highlights_node_and_neighbors() {
+		let mut state = state(&sample());
+		let b = index_of(&state, "b");
+		assert!(state.set_hover(Some(b)));
+		assert!(!state.set_hover(Some(b)));
+		for _ in 0..60 {
+			state.tick(DT);
+		}
+		for id in ["a", "b", "c"] {
+			assert!(state.highlight.node_intensity(index_of(&state, id)) > 0.9);
+		}
+		assert_eq!(state.highlight.node_intensity(index_of(&state, "d")), 0.0);
+		let (a, c) = (index_of(&state, "a"), index_of(&state, "c"));
+		assert!(state.highlight.edge_intensity(b, a) > 0.9);
+		assert!(state.highlight.edge_intensity(c, b) > 0.9);
+
+		state.set_hover(None);
+		state.tick(DT);
+		// Links fade out with their nodes rather than snapping off
+		assert!(state.highlight.edge_intensity(a, b) > 0.5);
+		for _ in 0..120 {
+			state.tick(DT);
+		}
+		assert!(state.highlight.max_intensity() < 0.01);
+		assert!(state.highlight.edge_intensity(a, b) < 0.01);
+	}
+
+	#[test]
+	fn fit_to_nodes_frames_them() {
+		let mut state = state(&sample());
+		state.animation_running = false;
+		let ids = ["a", "b"];
+		state.fit_to_nodes(&state.indices_of(&ids), 50.0);
+		for _ in 0..60 {
+			state.tick(DT);
+		}
+		assert!(state.camera_tween.is_none());
+		let t = &state.transform;
+		for id in ids {
+			let (x, y) = position(&state, id);
+			let (sx, sy) = (x * t.k + t.x, y * t.k + t.y);
+			assert!((49.9..=state.width - 49.9).contains(&sx));
+			assert!((49.9..=state.height - 49.9).contains(&sy));
+		}
+	}
+
+	#[test]
+	fn zoom_steps_chain_and_reset() {
+		let mut state = state(&sample());
+		let settle = |state: &mut ForceGraphState| {
+			for _ in 0..60 {
+				state.tick(DT);
+			}
+		};
+		let (a, b) = (position(&state, "a"), position(&state, "b"));
+		state.paused = true;
+		state.reheat();
+		// Clicked twice before the first step lands
+		state.zoom_by(2.0);
+		state.zoom_by(2.0);
+		settle(&mut state);
+		assert_eq!(state.transform.k, 4.0);
+		// Zoomed around the center, which stays put
+		let t = &state.transform;
+		assert_eq!((t.x, t.y), (state.width / 2.0, state.height / 2.0));
+		// Paused physics held the layout still, despite the reheat
+		assert_eq!((position(&state, "a"), position(&state, "b")), (a, b));
+
+		state.zoom_by(1000.0);
+		settle(&mut state);
+		assert_eq!(state.transform.k, MAX_ZOOM);
+		state.reset_view();
+		settle(&mut state);
+		assert_eq!(state.transform.k, 1.0);
+	}
+
+	#[test]
+	fn weights_normalize_over_the_data() {
+		let mut data = sample();
+		data.links[0].weight = Some(2.0);
+		data.links[1].weight = Some(6.0);
+		data.links.push(GraphLink {
+			weight: Some(f64::NAN),
+			..link("c", "d")
+		});
+		let state = state(&data);
+		assert_eq!(state.weight_range, Some((2.0, 6.0)));
+		assert_eq!(state.weight_t(4.0), 0.5);
+		assert_eq!(state.weight_t(10.0), 1.0);
+	}
+
+	#[test]
+	fn playback_shows_what_time_has_reached() {
+		let mut data = sample();
+		data.links[0].time = Some(1.0);
+		data.links[1].time = Some(2.0);
+		let mut state = state(&data);
+		state.set_time(Some(1.0));
+		state.finish_fades();
+		let shown = |state: &ForceGraphState, id| {
+			let mut shown = 0.0;
+			state.graph.visit_nodes(|n| {
+				if n.data.user_data.id == id {
+					shown = n.data.user_data.shown;
+				}
+			});
+			shown
+		};
+		// `d` has no time and no timed links, so always shows
+		assert_eq!(
+			["a", "b", "c", "d"].map(|id| shown(&state, id)),
+			[1.0, 1.0, 0.0, 1.0]
+		);
+		let (a, b, c) = (
+			index_of(&state, "a"),
+			index_of(&state, "b"),
+			index_of(&state, "c"),
+		);
+		assert_eq!(state.edge_fade(a, b), 1.0);
+		assert_eq!(state.edge_fade(c, b), 0.0);
+
+		state.set_time(Some(2.0));
+		for _ in 0..30 {
+			state.tick(DT);
+		}
+		assert_eq!(shown(&state, "c"), 1.0);
+		assert_eq!(state.edge_fade(b, c), 1.0);
+	}
+
+	#[test]
+	fn degree_sizing_grows_hubs() {
+		let mut state = state(&sample());
+		state.size_by_degree(DegreeSizing::default());
+		let mut sizes = HashMap::new();
+		state.graph.visit_nodes(|n| {
+			let info = &n.data.user_data;
+			assert_eq!(n.data.mass, NODE_MASS * info.size as f32);
+			sizes.insert(info.id.clone(), info.size);
+		});
+		assert_eq!(sizes["b"], 2.5);
+		assert!(sizes["b"] > sizes["a"] && sizes["a"] > sizes["d"]);
+		assert_eq!(sizes["d"], 0.7);
+	}
+
+	#[test]
+	fn heatmap_repaints_and_restores() {
+		let mut state = state(&sample());
+		let colors = |state: &ForceGraphState| {
+			let mut colors = HashMap::new();
+			state.graph.visit_nodes(|n| {
+				let info = &n.data.user_data;
+				colors.insert(info.id.clone(), (info.color.clone(), info.metric));
+			});
+			colors
+		};
+		let original = colors(&state);
+
+		state.set_color_by(Some(ColorBy::Betweenness));
+		let heatmap = colors(&state);
+		assert_eq!(heatmap["b"].1, Some(1.0));
+		assert_eq!(heatmap["a"].1, Some(0.0));
+		assert_eq!(heatmap["b"].0, state.heat_ramp.sample(1.0).to_css_rgb());
+		assert_eq!(state.heatmap().and_then(|h| h.range), Some((0.0, 1.0)));
+
+		state.set_color_by(Some(ColorBy::Degree));
+		assert_eq!(colors(&state)["b"].1, Some(2.0));
+		state.set_color_by(Some(ColorBy::Custom(|event| event.id.len() as f64)));
+		assert_eq!(colors(&state)["d"].1, Some(1.0));
+
+		state.set_color_by(None);
+		assert_eq!(colors(&state), original);
+	}
+
+	#[test]
+	fn moving_the_time_window_fades_links() {
+		let mut data = sample();
+		data.links[0].time = Some(1.0);
+		data.links[1].time = Some(5.0);
+		let early = apply_window(&data, (0.0, 2.0));
+		assert_eq!(early.ghosts, HashSet::from(["c".to_string()]));
+
+		let before = state(&early.data);
+		let mut after = state(&data);
+		after.carry_over(&before);
+		let (b, c) = (index_of(&after, "b"), index_of(&after, "c"));
+		assert_eq!(after.edge_fade(b, c), 0.0);
+		for _ in 0..30 {
+			after.tick(DT);
+		}
+		assert_eq!(after.edge_fade(b, c), 1.0);
+
+		let mut back = state(&early.data);
+		back.carry_over(&after);
+		assert_eq!(back.departing_edge_lines().len(), 1);
+		for _ in 0..30 {
+			back.tick(DT);
+		}
+		assert!(back.departing_edges.is_empty());
+	}
+
+	#[test]
+	fn select_box_selects_nodes_inside() {
+		let mut state = state(&sample());
+		let (ax, ay) = position(&state, "a");
+		let t = state.transform.clone();
+		let (sx, sy) = (t.x + ax * t.k, t.y + ay * t.k);
+		let around_a = |additive| SelectBox {
+			start_x: sx - 2.0,
+			start_y: sy - 2.0,
+			x: sx + 2.0,
+			y: sy + 2.0,
+			additive,
+			node: None,
+		};
+		state.selection.insert(index_of(&state, "d"));
+		state.select_in_box(&around_a(false));
+		assert_eq!(state.selection, HashSet::from([index_of(&state, "a")]));
+
+		state.selection.insert(index_of(&state, "d"));
+		state.select_in_box(&around_a(true));
+		assert_eq!(state.selection.len(), 2);
+	}
+
+	#[test]
+	fn fisheye_hits_nodes_where_they_are_drawn() {
+		let mut state = state(&sample());
+		let config = ScaleConfig::default();
+		let (ax, ay) = position(&state, "a");
+		let t = state.transform.clone();
+		let (sx, sy) = (t.x + ax * t.k, t.y + ay * t.k);
+		state.fisheye = Some(Fisheye::default());
+		state.fisheye_on = true;
+		state.pointer = Some((sx + 20.0, sy));
+
+		let saved = state.apply_lens();
+		let (lx, ly) = position(&state, "a");
+		let (dx, dy) = (t.x + lx * t.k, t.y + ly * t.k);
+		state.restore_positions(saved);
+		assert_eq!(position(&state, "a"), (ax, ay));
+		// Pushed away from the lens center, and hit where it's drawn
+		assert!(dx < sx - 20.0);
+		assert_eq!(
+			state.node_at_position(dx, dy, &config),
+			Some(index_of(&state, "a"))
+		);
+
+		state.drag.active = true;
+		assert!(state.lens().is_none());
+	}
+
+	#[test]
+	fn spotlight_trails_the_pointer() {
+		let mut state = state(&sample());
+		state.animation_running = false;
+		state.spotlight.enabled = true;
+		state.pointer = Some((100.0, 100.0));
+		assert!(state.tick(DT));
+		assert_eq!(state.spotlight.center, Some((100.0, 100.0)));
+
+		state.pointer = Some((200.0, 100.0));
+		state.tick(DT);
+		let (x, _) = state.spotlight.center.unwrap();
+		assert!(x > 100.0 && x < 200.0);
+		for _ in 0..120 {
+			state.tick(DT);
+		}
+		assert_eq!(state.spotlight.center, Some((200.0, 100.0)));
+
+		state.pointer = None;
+		state.tick(DT);
+		assert_eq!(state.spotlight.center, None);
+	}
+
+	#[test]
+	fn drags_undo_and_redo() {
+		let mut state = state(&sample());
+		state.animation_running = false;
+		let a = index_of(&state, "a");
+		let start = position(&state, "a");
+		state.begin_drag(a, 0.0, 0.0);
+		state.drag_to(50.0, 0.0);
+		state.end_drag();
+		let moved = position(&state, "a");
+		assert_ne!(moved, start);
+
+		assert!(state.undo());
+		assert_eq!(position(&state, "a"), start);
+		assert!(!state.graph.get_graph()[a].data.is_anchor);
+		assert!(!state.undo());
+
+		assert!(state.redo());
+		assert_eq!(position(&state, "a"), moved);
+		assert!(state.graph.get_graph()[a].data.is_anchor);
+
+		state.history.limit = 1;
+		state.begin_drag(a, 0.0, 0.0);
+		state.drag_to(0.0, 50.0);
+		state.end_drag();
+		assert!(!state.history.can_redo());
+		assert!(state.undo());
+		assert!(!state.undo());
+	}
+
+	#[test]
+	fn drags_ease_onto_alignment_guides() {
+		let mut state = state(&sample());
+		state.animation_running = false;
+		state.align_guides = true;
+		state.transform.k = 1.0;
+		let a = index_of(&state, "a");
+		let b = index_of(&state, "b");
+		let far = (0.0, 1000.0);
+		state.graph.visit_nodes_mut(|node| {
+			let (x, y) = if node.index() == b {
+				(100.0, 100.0)
+			} else {
+				far
+			};
+			node.data.x = x;
+			node.data.y = y;
+		});
+		let (ax, ay) = position(&state, "a");
+
+		// Drag a to 5 px left of b: pulled partway, with one guide through b
+		state.begin_drag(a, 0.0, 0.0);
+		state.drag_to(95.0 - ax, 500.0 - ay);
+		let (x, _) = position(&state, "a");
+		assert!(x > 95.0 && x < 100.0, "{x}");
+		assert_eq!(state.guides.len(), 1);
+		assert_eq!((state.guides[0].x0, state.guides[0].y0), (100.0, 100.0));
+
+		// 1 px away it's pulled most of the way, and at the threshold not at all
+		state.drag_to(99.0 - ax, 500.0 - ay);
+		let (close, _) = position(&state, "a");
+		assert!(close > 99.0 && close < 100.0, "{close}");
+		state.drag_to(94.0 - ax, 500.0 - ay);
+		assert_eq!(position(&state, "a").0, 94.0);
+		assert!(state.guides.is_empty());
+		state.end_drag();
+	}
+
+	#[test]
+	fn data_update_keeps_known_nodes() {
+		let mut before = state(&sample());
+		for _ in 0..30 {
+			before.tick(DT);
+		}
+		let mut data = sample();
+		data.nodes.retain(|n| n.id != "d");
+		data.nodes.push(node("e"));
+		data.links.push(link("c", "e"));
+		let mut after = state(&data);
+		after.carry_over(&before);
+		let departing: Vec<&str> = after.departing.iter().map(|n| n.info.id.as_str()).collect();
+		assert_eq!(departing, ["d"]);
+
+		assert_eq!(position(&after, "a"), position(&before, "a"));
+		let (cx, cy) = position(&after, "c");
+		let (ex, ey) = position(&after, "e");
+		assert!((ex - cx).hypot(ey - cy) <= APPEAR_SPREAD * 2f64.sqrt());
+		let appear = |state: &ForceGraphState, id| {
+			let mut t = 0.0;
+			state.graph.visit_nodes(|n| {
+				if n.data.user_data.id == id {
+					t = n.data.user_data.appear_t;
+				}
+			});
+			t
+		};
+		assert_eq!(appear(&after, "e"), 0.0);
+		for _ in 0..30 {
+			after.tick(DT);
+		}
+		assert_eq!(appear(&after, "e"), 1.0);
+		assert!(after.departing.is_empty());
 	}
 }
@@ -2,24 +2,36 @@
 //!
 //! Provides color palettes, gradients, and visual style configuration.
 
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
+use super::types::GraphData;
+
 /// RGBA color representation.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Color {
+	/// Red channel
 	pub r: u8,
+	/// Green channel
 	pub g: u8,
+	/// Blue channel
 	pub b: u8,
+	/// Alpha (0.0 = transparent, 1.0 = opaque)
 	pub a: f64,
 }
 
 impl Color {
+	/// Opaque color from RGB channels
 	pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
 		Self { r, g, b, a: 1.0 }
 	}
 
+	/// Color from RGB channels and alpha
 	pub const fn rgba(r: u8, g: u8, b: u8, a: f64) -> Self {
 		Self { r, g, b, a }
 	}
 
+	/// Same color with a different alpha
 	pub fn with_alpha(self, a: f64) -> Self {
 		Self { a, ..self }
 	}
@@ -57,6 +69,13 @@ impl Color {
 		}
 	}
 
+	/// Blend towards gray of equal luminance (0.0 = unchanged, 1.0 = fully gray)
+	pub fn desaturate(self, amount: f64) -> Self {
+		let luma = (0.299 * self.r as f64 + 0.587 * self.g as f64 + 0.114 * self.b as f64) as u8;
+		self.lerp(Color::rgba(luma, luma, luma, self.a), amount)
+	}
+
+	/// CSS string: `#rrggbb` when opaque, `rgba(...)` otherwise
 	pub fn to_css(self) -> String {
 		if (self.a - 1.0).abs() < 0.001 {
 			format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
@@ -65,18 +84,306 @@ impl Color {
 		}
 	}
 
+	/// CSS `#rrggbb` string, ignoring alpha
 	pub fn to_css_rgb(self) -> String {
 		format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
 	}
 }
 
+/// Parses a CSS color string into a [`Color`].
+///
+/// Supports hex (`#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`), `rgb()`/`rgba()`
+/// and `hsl()`/`hsla()` in both the comma and the space-separated syntax,
+/// and the CSS named colors. Anything else is drawn gray, with a warning
+/// logged the first time each distinct string is seen.
+pub(super) fn parse_color(color_str: &str) -> Color {
+	try_parse_color(color_str).unwrap_or_else(|| {
+		warn_invalid_color(color_str);
+		Color::rgb(128, 128, 128)
+	})
+}
+
+fn try_parse_color(color_str: &str) -> Option<Color> {
+	let css = color_str.trim().to_ascii_lowercase();
+	if let Some(hex) = css.strip_prefix('#') {
+		return parse_hex(hex);
+	}
+	if let Some((function, args)) = css.strip_suffix(')').and_then(|f| f.split_once('(')) {
+		let args: Vec<&str> = args
+			.split([',', '/', ' ', '\t'])
+			.filter(|arg| !arg.is_empty())
+			.collect();
+		if !(3..=4).contains(&args.len()) {
+			return None;
+		}
+		let a = args.get(3).map_or(Some(1.0), |a| fraction(a, 1.0))?;
+		return match function.trim() {
+			"rgb" | "rgba" => Some(Color::rgba(
+				channel(args[0])?,
+				channel(args[1])?,
+				channel(args[2])?,
+				a,
+			)),
+			"hsl" | "hsla" => {
+				let (h, s, l) = (
+					hue(args[0])?,
+					fraction(args[1], 100.0)?,
+					fraction(args[2], 100.0)?,
+				);
+				Some(hsl_to_rgb(h, s, l).with_alpha(a))
+			}
+			_ => None,
+		};
+	}
+	if css == "transparent" {
+		return Some(Color::rgba(0, 0, 0, 0.0));
+	}
+	let idx = NAMED_COLORS
+		.binary_search_by_key(&css.as_str(), |&(name, _)| name)
+		.ok()?;
+	let rgb = NAMED_COLORS[idx].1;
+	Some(Color::rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8))
+}
+
+/// Hex digits after the `#`: 3 or 4 single-digit channels, or 6 or 8
+/// two-digit ones, the last being alpha.
+fn parse_hex(hex: &str) -> Option<Color> {
+	let digits: Vec<u8> = hex
+		.chars()
+		.map(|c| c.to_digit(16).map(|d| d as u8))
+		.collect::<Option<_>>()?;
+	let channels: Vec<u8> = match digits.len() {
+		3 | 4 => digits.iter().map(|d| d * 17).collect(),
+		6 | 8 => digits
+			.chunks(2)
+			.map(|pair| pair[0] * 16 + pair[1])
+			.collect(),
+		_ => return None,
+	};
+	let a = channels.get(3).map_or(1.0, |&a| a as f64 / 255.0);
+	Some(Color::rgba(channels[0], channels[1], channels[2], a))
+}
+
+/// An `rgb()` channel: 0 to 255, or a percentage.
+fn channel(arg: &str) -> Option<u8> {
+	let value = fraction(arg, 255.0)? * 255.0;
+	Some(value.round() as u8)
+}
+
+/// A number in `[0, 1]`, written either as a fraction of `max` or as a
+/// percentage.
+fn fraction(arg: &str, max: f64) -> Option<f64> {
+	let (value, max) = match arg.strip_suffix('%') {
+		Some(percent) => (percent.parse::<f64>().ok()?, 100.0),
+		None => (arg.parse::<f64>().ok()?, max),
+	};
+	value.is_finite().then(|| (value / max).clamp(0.0, 1.0))
+}
+
+/// A hue in degrees, unitless or in `deg`, `rad` or `turn`.
+fn hue(arg: &str) -> Option<f64> {
+	let (value, unit) = if let Some(v) = arg.strip_suffix("deg") {
+		(v, 1.0)
+	} else if let Some(v) = arg.strip_suffix("rad") {
+		(v, 180.0 / std::f64::consts::PI)
+	} else if let Some(v) = arg.strip_suffix("turn") {
+		(v, 360.0)
+	} else {
+		(arg, 1.0)
+	};
+	let degrees = value.parse::<f64>().ok()? * unit;
+	degrees.is_finite().then_some(degrees)
+}
+
+/// Convert hue (degrees), saturation and lightness (both `[0, 1]`) to RGB,
+/// following the CSS Color 4 reference algorithm.
+fn hsl_to_rgb(h: f64, s: f64, l: f64) -> Color {
+	let a = s * l.min(1.0 - l);
+	let f = |n: f64| {
+		let k = (n + h.rem_euclid(360.0) / 30.0) % 12.0;
+		let v = l - a * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0);
+		(v * 255.0).round() as u8
+	};
+	Color::rgb(f(0.0), f(8.0), f(4.0))
+}
+
+/// Log an unparseable color once, not on every graph rebuild.
+fn warn_invalid_color(color_str: &str) {
+	thread_local! {
+		static WARNED: RefCell<HashSet<String>> = RefCell::new(HashSet::new());
+	}
+	WARNED.with_borrow_mut(|warned| {
+		if warned.insert(color_str.to_owned()) {
+			log::warn!("unrecognized color {color_str:?}, drawing it gray");
+		}
+	});
+}
+
+/// CSS named colors as `0xRRGGBB`, sorted by name for binary search.
+const NAMED_COLORS: [(&str, u32); 148] = [
+	("aliceblue", 0xf0f8ff),
+	("antiquewhite", 0xfaebd7),
+	("aqua", 0x00ffff),
+	("aquamarine", 0x7fffd4),
+	("azure", 0xf0ffff),
+	("beige", 0xf5f5dc),
+	("bisque", 0xffe4c4),
+	("black", 0x000000),
+	("blanchedalmond", 0xffebcd),
+	("blue", 0x0000ff),
+	("blueviolet", 0x8a2be2),
+	("brown", 0xa52a2a),
+	("burlywood", 0xdeb887),
+	("cadetblue", 0x5f9ea0),
+	("chartreuse", 0x7fff00),
+	("chocolate", 0xd2691e),
+	("coral", 0xff7f50),
+	("cornflowerblue", 0x6495ed),
+	("cornsilk", 0xfff8dc),
+	("crimson", 0xdc143c),
+	("cyan", 0x00ffff),
+	("darkblue", 0x00008b),
+	("darkcyan", 0x008b8b),
+	("darkgoldenrod", 0xb8860b),
+	("darkgray", 0xa9a9a9),
+	("darkgreen", 0x006400),
+	("darkgrey", 0xa9a9a9),
+	("darkkhaki", 0xbdb76b),
+	("darkmagenta", 0x8b008b),
+	("darkolivegreen", 0x556b2f),
+	("darkorange", 0xff8c00),
+	("darkorchid", 0x9932cc),
+	("darkred", 0x8b0000),
+	("darksalmon", 0xe9967a),
+	("darkseagreen", 0x8fbc8f),
+	("darkslateblue", 0x483d8b),
+	("darkslategray", 0x2f4f4f),
+	("darkslategrey", 0x2f4f4f),
+	("darkturquoise", 0x00ced1),
+	("darkviolet", 0x9400d3),
+	("deeppink", 0xff1493),
+	("deepskyblue", 0x00bfff),
+	("dimgray", 0x696969),
+	("dimgrey", 0x696969),
+	("dodgerblue", 0x1e90ff),
+	("firebrick", 0xb22222),
+	("floralwhite", 0xfffaf0),
+	("forestgreen", 0x228b22),
+	("fuchsia", 0xff00ff),
+	("gainsboro", 0xdcdcdc),
+	("ghostwhite", 0xf8f8ff),
+	("gold", 0xffd700),
+	("goldenrod", 0xdaa520),
+	("gray", 0x808080),
+	("green", 0x008000),
+	("greenyellow", 0xadff2f),
+	("grey", 0x808080),
+	("honeydew", 0xf0fff0),
+	("hotpink", 0xff69b4),
+	("indianred", 0xcd5c5c),
+	("indigo", 0x4b0082),
+	("ivory", 0xfffff0),
+	("khaki", 0xf0e68c),
+	("lavender", 0xe6e6fa),
+	("lavenderblush", 0xfff0f5),
+	("lawngreen", 0x7cfc00),
+	("lemonchiffon", 0xfffacd),
+	("lightblue", 0xadd8e6),
+	("lightcoral", 0xf08080),
+	("lightcyan", 0xe0ffff),
+	("lightgoldenrodyellow", 0xfafad2),
+	("lightgray", 0xd3d3d3),
+	("lightgreen", 0x90ee90),
+	("lightgrey", 0xd3d3d3),
+	("lightpink", 0xffb6c1),
+	("lightsalmon", 0xffa07a),
+	("lightseagreen", 0x20b2aa),
+	("lightskyblue", 0x87cefa),
+	("lightslategray", 0x778899),
+	("lightslategrey", 0x778899),
+	("lightsteelblue", 0xb0c4de),
+	("lightyellow", 0xffffe0),
+	("lime", 0x00ff00),
+	("limegreen", 0x32cd32),
+	("linen", 0xfaf0e6),
+	("magenta", 0xff00ff),
+	("maroon", 0x800000),
+	("mediumaquamarine", 0x66cdaa),
+	("mediumblue", 0x0000cd),
+	("mediumorchid", 0xba55d3),
+	("mediumpurple", 0x9370db),
+	("mediumseagreen", 0x3cb371),
+	("mediumslateblue", 0x7b68ee),
+	("mediumspringgreen", 0x00fa9a),
+	("mediumturquoise", 0x48d1cc),
+	("mediumvioletred", 0xc71585),
+	("midnightblue", 0x191970),
+	("mintcream", 0xf5fffa),
+	("mistyrose", 0xffe4e1),
+	("moccasin", 0xffe4b5),
+	("navajowhite", 0xffdead),
+	("navy", 0x000080),
+	("oldlace", 0xfdf5e6),
+	("olive", 0x808000),
+	("olivedrab", 0x6b8e23),
+	("orange", 0xffa500),
+	("orangered", 0xff4500),
+	("orchid", 0xda70d6),
+	("palegoldenrod", 0xeee8aa),
+	("palegreen", 0x98fb98),
+	("paleturquoise", 0xafeeee),
+	("palevioletred", 0xdb7093),
+	("papayawhip", 0xffefd5),
+	("peachpuff", 0xffdab9),
+	("peru", 0xcd853f),
+	("pink", 0xffc0cb),
+	("plum", 0xdda0dd),
+	("powderblue", 0xb0e0e6),
+	("purple", 0x800080),
+	("rebeccapurple", 0x663399),
+	("red", 0xff0000),
+	("rosybrown", 0xbc8f8f),
+	("royalblue", 0x4169e1),
+	("saddlebrown", 0x8b4513),
+	("salmon", 0xfa8072),
+	("sandybrown", 0xf4a460),
+	("seagreen", 0x2e8b57),
+	("seashell", 0xfff5ee),
+	("sienna", 0xa0522d),
+	("silver", 0xc0c0c0),
+	("skyblue", 0x87ceeb),
+	("slateblue", 0x6a5acd),
+	("slategray", 0x708090),
+	("slategrey", 0x708090),
+	("snow", 0xfffafa),
+	("springgreen", 0x00ff7f),
+	("steelblue", 0x4682b4),
+	("tan", 0xd2b48c),
+	("teal", 0x008080),
+	("thistle", 0xd8bfd8),
+	("tomato", 0xff6347),
+	("turquoise", 0x40e0d0),
+	("violet", 0xee82ee),
+	("wheat", 0xf5deb3),
+	("white", 0xffffff),
+	("whitesmoke", 0xf5f5f5),
+	("yellow", 0xffff00),
+	("yellowgreen", 0x9acd32),
+];
+
 /// A curated color palette for nodes.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct NodePalette {
+	/// Colors cycled through by group index
 	pub colors: Vec<Color>,
 }
 
 impl NodePalette {
+	/// Custom palette, e.g. to match brand colors
+	pub fn new(colors: Vec<Color>) -> Self {
+		Self { colors }
+	}
+
 	/// Muted, harmonious palette - slate blues and teals (default)
 	pub fn slate() -> Self {
 		Self {
@@ -173,13 +480,93 @@ impl NodePalette {
 		}
 	}
 
+	/// Color for `index`, cycling through the palette. Empty palettes yield gray.
 	pub fn get(&self, index: usize) -> Color {
+		if self.colors.is_empty() {
+			return Color::rgb(128, 128, 128);
+		}
 		self.colors[index % self.colors.len()]
 	}
 }
 
+/// A continuous color ramp for mapping normalized values to colors.
+///
+/// Stops are evenly spaced over `[0, 1]` and linearly interpolated.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Colormap {
+	/// Evenly spaced color stops from low to high
+	pub stops: Vec<Color>,
+}
+
+impl Colormap {
+	/// Ramp straight from `low` to `high`
+	pub fn two_stop(low: Color, high: Color) -> Self {
+		Self {
+			stops: vec![low, high],
+		}
+	}
+
+	/// Ramp from `low` through `mid` to `high`, as for a diverging scale
+	pub fn three_stop(low: Color, mid: Color, high: Color) -> Self {
+		Self {
+			stops: vec![low, mid, high],
+		}
+	}
+
+	/// Perceptually uniform blue-green-yellow ramp (default)
+	pub fn viridis() -> Self {
+		Self {
+			stops: vec![
+				Color::rgb(68, 1, 84),
+				Color::rgb(59, 82, 139),
+				Color::rgb(33, 145, 140),
+				Color::rgb(94, 201, 98),
+				Color::rgb(253, 231, 37),
+			],
+		}
+	}
+
+	/// Perceptually uniform black-purple-cream ramp
+	pub fn magma() -> Self {
+		Self {
+			stops: vec![
+				Color::rgb(0, 0, 4),
+				Color::rgb(81, 18, 124),
+				Color::rgb(183, 55, 121),
+				Color::rgb(252, 137, 97),
+				Color::rgb(252, 253, 191),
+			],
+		}
+	}
+
+	/// Muted cool-to-warm ramp that sits well with the default palettes
+	pub fn thermal() -> Self {
+		Self {
+			stops: vec![
+				Color::rgb(94, 129, 172),
+				Color::rgb(143, 163, 180),
+				Color::rgb(185, 145, 110),
+				Color::rgb(180, 100, 90),
+			],
+		}
+	}
+
+	/// Sample the ramp at `t` (clamped to `[0, 1]`).
+	pub fn sample(&self, t: f64) -> Color {
+		match self.stops.len() {
+			0 => Color::rgb(128, 128, 128),
+			1 => self.stops[0],
+			n => {
+				let pos = t.clamp(0.0, 1.0) * (n - 1) as f64;
+				let i = (pos.floor() as usize).min(n - 2);
+				self.stops[i].lerp(self.stops[i + 1], pos - i as f64)
+			}
+		}
+	}
+}
+
 /// Background style configuration.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct BackgroundStyle {
 	/// Primary background color
 	pub color: Color,
@@ -187,12 +574,134 @@ pub struct BackgroundStyle {
 	pub color_secondary: Color,
 	/// Whether to use radial gradient
 	pub use_gradient: bool,
+	/// Clear the canvas instead of filling it, so the page behind shows
+	/// through; the vignette is skipped too, as it would darken the page
+	pub transparent: bool,
 	/// Vignette intensity (0.0 = none, 1.0 = strong)
 	pub vignette: f64,
+	/// World-space reference pattern drawn beneath the graph
+	pub pattern: BackgroundPattern,
+	/// Base distance between pattern dots or lines, in world units; halved
+	/// or doubled as the view zooms so the grid stays legible
+	pub pattern_spacing: f64,
+	/// Pattern dot/line color
+	pub pattern_color: Color,
+	/// Image drawn over the background fill, beneath the pattern and the
+	/// vignette; the fill shows until it loads, or if it fails to
+	pub image: Option<BackgroundImage>,
+	/// Darkening of everything away from the pointer
+	pub spotlight: SpotlightStyle,
+}
+
+/// A pool of light following the pointer, with the rest of the scene
+/// darkened, drawn over the graph like the vignette.
+///
+/// It trails the pointer a little rather than snapping to it, and gives way
+/// to hover and group highlighting, which dim the scene themselves.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpotlightStyle {
+	/// Whether the spotlight is drawn
+	pub enabled: bool,
+	/// Radius of the lit area, in screen pixels
+	pub radius: f64,
+	/// Darkness outside the lit area (0.0 = none, 1.0 = black)
+	pub intensity: f64,
+}
+
+impl Default for SpotlightStyle {
+	fn default() -> Self {
+		Self {
+			enabled: false,
+			radius: 200.0,
+			intensity: 0.6,
+		}
+	}
+}
+
+/// An image covering the canvas behind the graph.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BackgroundImage {
+	/// Image URL, loaded once when the graph is built
+	pub url: String,
+	/// Opacity the image is drawn with, over the background fill
+	pub opacity: f64,
+	/// How far the image follows panning: 0.0 stays fixed, 1.0 moves with
+	/// the graph
+	pub parallax: f64,
+}
+
+/// Reference pattern drawn over the background fill, panning and zooming
+/// with the graph.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BackgroundPattern {
+	/// No pattern
+	None,
+	/// Dots at each grid intersection
+	Dots,
+	/// Hairline grid lines
+	Lines,
+}
+
+/// How arrowheads are drawn on directed edges.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArrowStyle {
+	/// Solid filled triangle
+	Filled,
+	/// Stroked chevron; the edge line runs through to the tip
+	Open,
+	/// Solid diamond, as in UML aggregation
+	Diamond,
+	/// No arrowheads
+	None,
+}
+
+impl ArrowStyle {
+	/// Whether the head is a solid shape the edge line should stop short of.
+	pub fn is_solid(self) -> bool {
+		matches!(self, Self::Filled | Self::Diamond)
+	}
+}
+
+/// Where along an edge arrowheads are placed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArrowPlacement {
+	/// At the target node's boundary
+	Target,
+	/// Centered on the edge midpoint
+	Midpoint,
+	/// At both the target and the midpoint
+	Both,
+}
+
+impl ArrowPlacement {
+	/// Whether an arrow is drawn at the target end.
+	pub fn at_target(self) -> bool {
+		matches!(self, Self::Target | Self::Both)
+	}
+
+	/// Whether an arrow is drawn at the edge midpoint.
+	pub fn at_midpoint(self) -> bool {
+		matches!(self, Self::Midpoint | Self::Both)
+	}
+}
+
+/// Directional particles that travel along edges from source to target.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlowParticleStyle {
+	/// Whether flow particles are drawn
+	pub enabled: bool,
+	/// Particles per edge, evenly spaced along it
+	pub count: usize,
+	/// Travel speed in edge lengths per second
+	pub speed: f64,
+	/// Particle radius in screen pixels
+	pub size: f64,
+	/// Only show particles on edges touching the hovered node
+	pub highlighted_only: bool,
 }
 
 /// Edge visual style.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct EdgeStyle {
 	/// Base edge color
 	pub color: Color,
@@ -204,10 +713,40 @@ pub struct EdgeStyle {
 	pub curved: bool,
 	/// Curve tension (0.0 = straight, 1.0 = very curved)
 	pub curve_tension: f64,
+	/// Arrowhead shape
+	pub arrow_style: ArrowStyle,
+	/// Arrowhead position along the edge
+	pub arrow_placement: ArrowPlacement,
+	/// Arrowhead size multiplier applied on top of the zoom-scaled size
+	pub arrow_scale: f64,
+	/// Arrowhead color, e.g. brighter than the line to stress direction;
+	/// `None` uses [`color`](Self::color)
+	pub arrow_color: Option<Color>,
+	/// Particles traveling along edges to show direction of flow
+	pub flow_particles: FlowParticleStyle,
+	/// Stroke edges with a gradient from the source to the target node color
+	pub use_node_gradient: bool,
+	/// Scale edges by link weight; `None` draws every edge alike
+	pub weighted: Option<WeightStyle>,
+}
+
+/// Edge emphasis by [`GraphLink::weight`](super::types::GraphLink::weight).
+///
+/// Weights are normalized over the loaded data, so the lightest link takes
+/// the start of each range and the heaviest the end. Links without a weight
+/// are drawn unscaled.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WeightStyle {
+	/// Opacity multiplier, from the lightest to the heaviest link
+	pub alpha: (f64, f64),
+	/// Width multiplier, from the lightest to the heaviest link
+	pub width: (f64, f64),
+	/// Label the hovered node's edges with their weights
+	pub labels: bool,
 }
 
 /// Node visual style.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct NodeStyle {
 	/// Whether nodes have inner gradients
 	pub use_gradient: bool,
@@ -223,10 +762,101 @@ pub struct NodeStyle {
 	pub pulse_intensity: f64,
 	/// Pulsing animation speed
 	pub pulse_speed: f64,
+	/// Color of the hover rings around the hovered node
+	pub ring_color: RingColor,
+	/// Opacity of the inner hover ring at full intensity
+	pub ring_alpha: f64,
+	/// Opacity of the outer hover ring at full intensity
+	pub ring_secondary_alpha: f64,
+	/// Inner ring width as a multiple of the scaled ring width
+	pub ring_width_mult: f64,
+	/// Outer ring width as a multiple of the scaled ring width
+	pub ring_secondary_width_mult: f64,
+	/// Node label text color; its alpha is the fully visible label opacity
+	pub label_color: Color,
+	/// Persistent look of selected nodes, distinct from hover
+	pub selection: SelectionStyle,
+	/// Whether to mark pinned (dragged, anchored) nodes with a pin glyph
+	pub pin_indicator: bool,
+	/// Pin glyph color
+	pub pin_color: Color,
+	/// Fill of node badge bubbles
+	pub badge_color: Color,
+	/// Text color of node badges
+	pub badge_text_color: Color,
+	/// Drop shadow under node fills, for depth on light backgrounds where
+	/// glows don't read; `None` for no shadow
+	pub shadow: Option<ShadowConfig>,
+}
+
+/// Drop shadow cast by node fills.
+///
+/// Sizes are in world units, so the shadow scales with the node as the view
+/// zooms. Shadows are skipped on large graphs; see
+/// [`LodConfig::max_shadow_nodes`](super::scale::LodConfig::max_shadow_nodes).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShadowConfig {
+	/// Shadow color, usually a translucent black
+	pub color: Color,
+	/// Blur radius
+	pub blur: f64,
+	/// Offset of the shadow from the node, as `(x, y)`
+	pub offset: (f64, f64),
+}
+
+/// Outline shape drawn around selected nodes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelectionRing {
+	/// One dashed ring
+	Dashed,
+	/// Two thin concentric solid rings
+	Double,
+}
+
+/// Visual style of selected nodes.
+///
+/// Selection persists until cleared, so it is drawn with its own ring
+/// outside the transient hover rings rather than replacing them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SelectionStyle {
+	/// Ring shape
+	pub ring: SelectionRing,
+	/// Ring color
+	pub color: RingColor,
+	/// Ring opacity
+	pub alpha: f64,
+	/// Ring width as a multiple of the scaled ring width
+	pub width_mult: f64,
+	/// Node radius multiplier while selected
+	pub radius_mult: f64,
+	/// Glow intensity multiplier while selected
+	pub glow_mult: f64,
+}
+
+/// Color source for the hover rings around a highlighted node.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RingColor {
+	/// Plain white, for dark backgrounds
+	White,
+	/// The node's own fill, lightened
+	NodeColor,
+	/// A fixed color; its alpha is ignored in favor of the ring alphas
+	Custom(Color),
+}
+
+impl RingColor {
+	/// Opaque ring color for a node filled with `node`.
+	pub fn resolve(self, node: Color) -> Color {
+		match self {
+			RingColor::White => Color::rgb(255, 255, 255),
+			RingColor::NodeColor => node.lighten(0.5).with_alpha(1.0),
+			RingColor::Custom(color) => color.with_alpha(1.0),
+		}
+	}
 }
 
 /// Particle effect configuration.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ParticleStyle {
 	/// Whether particles are enabled
 	pub enabled: bool,
@@ -244,15 +874,93 @@ pub struct ParticleStyle {
 	pub opacity: f64,
 }
 
+/// Translucent outlines drawn behind the members of each node group.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HullStyle {
+	/// Whether group hulls are drawn
+	pub enabled: bool,
+	/// Distance between member nodes and the hull outline, in world units
+	pub padding: f64,
+	/// Fill opacity of the hull
+	pub fill_alpha: f64,
+	/// Outline opacity of the hull
+	pub stroke_alpha: f64,
+	/// Draw the group name at the hull centroid
+	pub show_labels: bool,
+}
+
+/// How hovering a node or highlighting a group emphasizes part of the graph.
+///
+/// Dimming amounts are what the rest of the graph loses at full highlight;
+/// growth amounts are what highlighted nodes gain, as fractions of their
+/// radius.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HighlightConfig {
+	/// Opacity lost by nodes outside the highlight
+	pub dim_node_alpha: f64,
+	/// Radius lost by nodes outside the highlight
+	pub dim_node_radius: f64,
+	/// Glow lost by nodes outside the highlight
+	pub dim_node_glow: f64,
+	/// Opacity lost by edges outside the highlight
+	pub dim_edge_alpha: f64,
+	/// Width lost by edges outside the highlight
+	pub dim_edge_width: f64,
+	/// Opacity lost by group hulls while anything is highlighted
+	pub dim_hulls: f64,
+	/// Radius gained by neighbors of the hovered node and by group members
+	pub neighbor_growth: f64,
+	/// Radius gained by the hovered node itself
+	pub hovered_growth: f64,
+	/// Rate of fading in; a highlight is 95% in after about `3 / speed`
+	/// seconds
+	pub fade_in_speed: f64,
+	/// Rate of fading out once the hold time has passed
+	pub fade_out_speed: f64,
+	/// Seconds a highlight is held before it may fade out, so brushing past
+	/// a node doesn't flash
+	pub hold_time: f64,
+}
+
+impl Default for HighlightConfig {
+	fn default() -> Self {
+		Self {
+			dim_node_alpha: 0.7,
+			dim_node_radius: 0.15,
+			dim_node_glow: 0.7,
+			dim_edge_alpha: 0.5,
+			dim_edge_width: 0.3,
+			dim_hulls: 0.85,
+			neighbor_growth: 0.25,
+			hovered_growth: 0.4,
+			fade_in_speed: 6.0,
+			fade_out_speed: 4.0,
+			hold_time: 0.12,
+		}
+	}
+}
+
 /// Complete visual theme.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Theme {
+	/// Identifier for the theme preset
 	pub name: &'static str,
+	/// Canvas background fill
 	pub background: BackgroundStyle,
+	/// Edge appearance
 	pub edge: EdgeStyle,
+	/// Node appearance
 	pub node: NodeStyle,
+	/// Ambient particle effects
 	pub particles: ParticleStyle,
+	/// Colors assigned to nodes by group
 	pub palette: NodePalette,
+	/// Ramp used to color nodes that carry a numeric `value`
+	pub colormap: Colormap,
+	/// Group hull overlays
+	pub hulls: HullStyle,
+	/// Hover and group highlight emphasis
+	pub highlight: HighlightConfig,
 }
 
 impl Theme {
@@ -264,7 +972,13 @@ impl Theme {
 				color: Color::rgb(22, 27, 34),
 				color_secondary: Color::rgb(30, 35, 42),
 				use_gradient: true,
+				transparent: false,
 				vignette: 0.15,
+				pattern: BackgroundPattern::None,
+				pattern_spacing: 40.0,
+				pattern_color: Color::rgba(255, 255, 255, 0.06),
+				image: None,
+				spotlight: SpotlightStyle::default(),
 			},
 			edge: EdgeStyle {
 				color: Color::rgba(140, 160, 180, 0.5),
@@ -272,6 +986,19 @@ impl Theme {
 				glow_intensity: 0.0,
 				curved: false,
 				curve_tension: 0.0,
+				arrow_style: ArrowStyle::Filled,
+				arrow_placement: ArrowPlacement::Target,
+				arrow_scale: 1.0,
+				arrow_color: None,
+				flow_particles: FlowParticleStyle {
+					enabled: true,
+					count: 2,
+					speed: 0.5,
+					size: 2.0,
+					highlighted_only: true,
+				},
+				use_node_gradient: false,
+				weighted: None,
 			},
 			node: NodeStyle {
 				use_gradient: true,
@@ -281,6 +1008,25 @@ impl Theme {
 				border_color: Color::rgba(255, 255, 255, 0.0),
 				pulse_intensity: 0.0,
 				pulse_speed: 0.0,
+				ring_color: RingColor::NodeColor,
+				ring_alpha: 0.8,
+				ring_secondary_alpha: 0.3,
+				ring_width_mult: 1.0,
+				ring_secondary_width_mult: 0.5,
+				label_color: Color::rgba(255, 255, 255, 0.95),
+				selection: SelectionStyle {
+					ring: SelectionRing::Dashed,
+					color: RingColor::White,
+					alpha: 0.9,
+					width_mult: 1.0,
+					radius_mult: 1.15,
+					glow_mult: 1.6,
+				},
+				pin_indicator: true,
+				pin_color: Color::rgba(255, 255, 255, 0.9),
+				badge_color: Color::rgb(229, 72, 77),
+				badge_text_color: Color::rgb(255, 255, 255),
+				shadow: None,
 			},
 			particles: ParticleStyle {
 				enabled: false,
@@ -292,6 +1038,15 @@ impl Theme {
 				opacity: 0.0,
 			},
 			palette: NodePalette::slate(),
+			colormap: Colormap::viridis(),
+			hulls: HullStyle {
+				enabled: false,
+				padding: 0.0,
+				fill_alpha: 0.0,
+				stroke_alpha: 0.0,
+				show_labels: false,
+			},
+			highlight: HighlightConfig::default(),
 		}
 	}
 
@@ -303,7 +1058,13 @@ impl Theme {
 				color: Color::rgb(18, 20, 28),
 				color_secondary: Color::rgb(25, 28, 38),
 				use_gradient: true,
+				transparent: false,
 				vignette: 0.2,
+				pattern: BackgroundPattern::None,
+				pattern_spacing: 40.0,
+				pattern_color: Color::rgba(120, 140, 255, 0.08),
+				image: None,
+				spotlight: SpotlightStyle::default(),
 			},
 			edge: EdgeStyle {
 				color: Color::rgba(100, 120, 150, 0.45),
@@ -311,6 +1072,19 @@ impl Theme {
 				glow_intensity: 0.0,
 				curved: false,
 				curve_tension: 0.0,
+				arrow_style: ArrowStyle::Filled,
+				arrow_placement: ArrowPlacement::Target,
+				arrow_scale: 1.0,
+				arrow_color: None,
+				flow_particles: FlowParticleStyle {
+					enabled: false,
+					count: 0,
+					speed: 0.0,
+					size: 0.0,
+					highlighted_only: false,
+				},
+				use_node_gradient: false,
+				weighted: None,
 			},
 			node: NodeStyle {
 				use_gradient: true,
@@ -320,6 +1094,25 @@ impl Theme {
 				border_color: Color::rgba(255, 255, 255, 0.0),
 				pulse_intensity: 0.0,
 				pulse_speed: 0.0,
+				ring_color: RingColor::NodeColor,
+				ring_alpha: 0.8,
+				ring_secondary_alpha: 0.3,
+				ring_width_mult: 1.0,
+				ring_secondary_width_mult: 0.5,
+				label_color: Color::rgba(255, 255, 255, 0.95),
+				selection: SelectionStyle {
+					ring: SelectionRing::Dashed,
+					color: RingColor::White,
+					alpha: 0.9,
+					width_mult: 1.0,
+					radius_mult: 1.15,
+					glow_mult: 1.6,
+				},
+				pin_indicator: true,
+				pin_color: Color::rgba(255, 255, 255, 0.9),
+				badge_color: Color::rgb(229, 72, 77),
+				badge_text_color: Color::rgb(255, 255, 255),
+				shadow: None,
 			},
 			particles: ParticleStyle {
 				enabled: false,
@@ -331,6 +1124,15 @@ impl Theme {
 				opacity: 0.0,
 			},
 			palette: NodePalette::aurora(),
+			colormap: Colormap::viridis(),
+			hulls: HullStyle {
+				enabled: false,
+				padding: 0.0,
+				fill_alpha: 0.0,
+				stroke_alpha: 0.0,
+				show_labels: false,
+			},
+			highlight: HighlightConfig::default(),
 		}
 	}
 
@@ -342,7 +1144,13 @@ impl Theme {
 				color: Color::rgb(28, 24, 22),
 				color_secondary: Color::rgb(35, 30, 28),
 				use_gradient: true,
+				transparent: false,
 				vignette: 0.18,
+				pattern: BackgroundPattern::None,
+				pattern_spacing: 40.0,
+				pattern_color: Color::rgba(255, 180, 120, 0.06),
+				image: None,
+				spotlight: SpotlightStyle::default(),
 			},
 			edge: EdgeStyle {
 				color: Color::rgba(160, 130, 110, 0.45),
@@ -350,6 +1158,19 @@ impl Theme {
 				glow_intensity: 0.0,
 				curved: false,
 				curve_tension: 0.0,
+				arrow_style: ArrowStyle::Filled,
+				arrow_placement: ArrowPlacement::Target,
+				arrow_scale: 1.0,
+				arrow_color: None,
+				flow_particles: FlowParticleStyle {
+					enabled: false,
+					count: 0,
+					speed: 0.0,
+					size: 0.0,
+					highlighted_only: false,
+				},
+				use_node_gradient: false,
+				weighted: None,
 			},
 			node: NodeStyle {
 				use_gradient: true,
@@ -359,6 +1180,25 @@ impl Theme {
 				border_color: Color::rgba(255, 255, 255, 0.0),
 				pulse_intensity: 0.0,
 				pulse_speed: 0.0,
+				ring_color: RingColor::NodeColor,
+				ring_alpha: 0.8,
+				ring_secondary_alpha: 0.3,
+				ring_width_mult: 1.0,
+				ring_secondary_width_mult: 0.5,
+				label_color: Color::rgba(255, 255, 255, 0.95),
+				selection: SelectionStyle {
+					ring: SelectionRing::Dashed,
+					color: RingColor::White,
+					alpha: 0.9,
+					width_mult: 1.0,
+					radius_mult: 1.15,
+					glow_mult: 1.6,
+				},
+				pin_indicator: true,
+				pin_color: Color::rgba(255, 255, 255, 0.9),
+				badge_color: Color::rgb(229, 72, 77),
+				badge_text_color: Color::rgb(255, 255, 255),
+				shadow: None,
 			},
 			particles: ParticleStyle {
 				enabled: false,
@@ -370,6 +1210,15 @@ impl Theme {
 				opacity: 0.0,
 			},
 			palette: NodePalette::earth(),
+			colormap: Colormap::magma(),
+			hulls: HullStyle {
+				enabled: false,
+				padding: 0.0,
+				fill_alpha: 0.0,
+				stroke_alpha: 0.0,
+				show_labels: false,
+			},
+			highlight: HighlightConfig::default(),
 		}
 	}
 
@@ -381,7 +1230,13 @@ impl Theme {
 				color: Color::rgb(15, 25, 35),
 				color_secondary: Color::rgb(20, 32, 45),
 				use_gradient: true,
+				transparent: false,
 				vignette: 0.2,
+				pattern: BackgroundPattern::None,
+				pattern_spacing: 40.0,
+				pattern_color: Color::rgba(100, 200, 255, 0.06),
+				image: None,
+				spotlight: SpotlightStyle::default(),
 			},
 			edge: EdgeStyle {
 				color: Color::rgba(90, 130, 160, 0.45),
@@ -389,6 +1244,19 @@ impl Theme {
 				glow_intensity: 0.0,
 				curved: false,
 				curve_tension: 0.0,
+				arrow_style: ArrowStyle::Filled,
+				arrow_placement: ArrowPlacement::Target,
+				arrow_scale: 1.0,
+				arrow_color: None,
+				flow_particles: FlowParticleStyle {
+					enabled: false,
+					count: 0,
+					speed: 0.0,
+					size: 0.0,
+					highlighted_only: false,
+				},
+				use_node_gradient: false,
+				weighted: None,
 			},
 			node: NodeStyle {
 				use_gradient: true,
@@ -398,6 +1266,25 @@ impl Theme {
 				border_color: Color::rgba(255, 255, 255, 0.0),
 				pulse_intensity: 0.0,
 				pulse_speed: 0.0,
+				ring_color: RingColor::NodeColor,
+				ring_alpha: 0.8,
+				ring_secondary_alpha: 0.3,
+				ring_width_mult: 1.0,
+				ring_secondary_width_mult: 0.5,
+				label_color: Color::rgba(255, 255, 255, 0.95),
+				selection: SelectionStyle {
+					ring: SelectionRing::Dashed,
+					color: RingColor::White,
+					alpha: 0.9,
+					width_mult: 1.0,
+					radius_mult: 1.15,
+					glow_mult: 1.6,
+				},
+				pin_indicator: true,
+				pin_color: Color::rgba(255, 255, 255, 0.9),
+				badge_color: Color::rgb(229, 72, 77),
+				badge_text_color: Color::rgb(255, 255, 255),
+				shadow: None,
 			},
 			particles: ParticleStyle {
 				enabled: false,
@@ -409,6 +1296,15 @@ impl Theme {
 				opacity: 0.0,
 			},
 			palette: NodePalette::ocean(),
+			colormap: Colormap::viridis(),
+			hulls: HullStyle {
+				enabled: false,
+				padding: 0.0,
+				fill_alpha: 0.0,
+				stroke_alpha: 0.0,
+				show_labels: false,
+			},
+			highlight: HighlightConfig::default(),
 		}
 	}
 
@@ -420,7 +1316,13 @@ impl Theme {
 				color: Color::rgb(25, 28, 35),
 				color_secondary: Color::rgb(25, 28, 35),
 				use_gradient: false,
+				transparent: false,
 				vignette: 0.0,
+				pattern: BackgroundPattern::None,
+				pattern_spacing: 40.0,
+				pattern_color: Color::rgba(255, 255, 255, 0.04),
+				image: None,
+				spotlight: SpotlightStyle::default(),
 			},
 			edge: EdgeStyle {
 				color: Color::rgba(130, 145, 165, 0.4),
@@ -428,6 +1330,19 @@ impl Theme {
 				glow_intensity: 0.0,
 				curved: false,
 				curve_tension: 0.0,
+				arrow_style: ArrowStyle::Filled,
+				arrow_placement: ArrowPlacement::Target,
+				arrow_scale: 1.0,
+				arrow_color: None,
+				flow_particles: FlowParticleStyle {
+					enabled: false,
+					count: 0,
+					speed: 0.0,
+					size: 0.0,
+					highlighted_only: false,
+				},
+				use_node_gradient: false,
+				weighted: None,
 			},
 			node: NodeStyle {
 				use_gradient: false,
@@ -437,6 +1352,25 @@ impl Theme {
 				border_color: Color::rgba(255, 255, 255, 0.0),
 				pulse_intensity: 0.0,
 				pulse_speed: 0.0,
+				ring_color: RingColor::NodeColor,
+				ring_alpha: 0.8,
+				ring_secondary_alpha: 0.3,
+				ring_width_mult: 1.0,
+				ring_secondary_width_mult: 0.5,
+				label_color: Color::rgba(255, 255, 255, 0.95),
+				selection: SelectionStyle {
+					ring: SelectionRing::Dashed,
+					color: RingColor::White,
+					alpha: 0.9,
+					width_mult: 1.0,
+					radius_mult: 1.15,
+					glow_mult: 1.6,
+				},
+				pin_indicator: true,
+				pin_color: Color::rgba(255, 255, 255, 0.9),
+				badge_color: Color::rgb(229, 72, 77),
+				badge_text_color: Color::rgb(255, 255, 255),
+				shadow: None,
 			},
 			particles: ParticleStyle {
 				enabled: false,
@@ -448,12 +1382,123 @@ impl Theme {
 				opacity: 0.0,
 			},
 			palette: NodePalette::pastel(),
+			colormap: Colormap::thermal(),
+			hulls: HullStyle {
+				enabled: false,
+				padding: 0.0,
+				fill_alpha: 0.0,
+				stroke_alpha: 0.0,
+				show_labels: false,
+			},
+			highlight: HighlightConfig::default(),
 		}
 	}
 }
 
+impl Theme {
+	/// Palette color for a node group, as used for node fills and legend swatches.
+	pub fn group_color(&self, group: u32) -> Color {
+		self.palette.get(group as usize)
+	}
+
+	/// CSS fill color for each node in `data`, in node order.
+	///
+	/// An explicit [`GraphNode::color`](super::GraphNode::color) wins; then
+	/// a finite `value` is mapped through the colormap over the graph's
+	/// value range; then `group` picks a palette color; otherwise the node's
+	/// index cycles through the palette. This is exactly what the canvas
+	/// draws, so custom legends can reuse it.
+	pub fn node_colors(&self, data: &GraphData) -> Vec<String> {
+		self.node_colors_with_communities(data, &HashMap::new())
+	}
+
+	/// [`Self::node_colors`], with detected communities (keyed by node ID)
+	/// taking precedence over values and groups.
+	pub(super) fn node_colors_with_communities(
+		&self,
+		data: &GraphData,
+		communities: &HashMap<String, u32>,
+	) -> Vec<String> {
+		// Value range for colormap normalization
+		let (min_value, max_value) = data
+			.nodes
+			.iter()
+			.filter_map(|n| n.value)
+			.filter(|v| v.is_finite())
+			.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+				(lo.min(v), hi.max(v))
+			});
+		let value_range = max_value - min_value;
+
+		data.nodes
+			.iter()
+			.enumerate()
+			.map(|(i, node)| {
+				node.color.clone().unwrap_or_else(|| {
+					if let Some(&c) = communities.get(&node.id) {
+						return self.group_color(c).to_css_rgb();
+					}
+					if let Some(v) = node.value.filter(|v| v.is_finite()) {
+						let t = if value_range > 0.0 {
+							(v - min_value) / value_range
+						} else {
+							0.5
+						};
+						return self.colormap.sample(t).to_css_rgb();
+					}
+					node.group
+						.map(|g| self.group_color(g).to_css_rgb())
+						.unwrap_or_else(|| self.palette.get(i).to_css_rgb())
+				})
+			})
+			.collect()
+	}
+}
+
 impl Default for Theme {
 	fn default() -> Self {
 		Self::default_theme()
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn rgba(css: &str) -> (u8, u8, u8, f64) {
+		let c = try_parse_color(css).unwrap_or_else(|| panic!("{css:?} didn't parse"));
+		(c.r, c.g, c.b, (c.a * 100.0).round() / 100.0)
+	}
+
+	#[test]
+	fn parses_hex_forms() {
+		assert_eq!(rgba("#f00"), (255, 0, 0, 1.0));
+		assert_eq!(rgba("#f008"), (255, 0, 0, 0.53));
+		assert_eq!(rgba("#1E90FF"), (30, 144, 255, 1.0));
+		assert_eq!(rgba("#ff000080"), (255, 0, 0, 0.5));
+		assert!(try_parse_color("#ff000").is_none());
+		assert!(try_parse_color("#gg0000").is_none());
+	}
+
+	#[test]
+	fn parses_functional_forms() {
+		assert_eq!(rgba(" rgb( 255 , 0,0 ) "), (255, 0, 0, 1.0));
+		assert_eq!(rgba("rgba(0, 128, 255, 0.25)"), (0, 128, 255, 0.25));
+		assert_eq!(rgba("rgb(100% 50% 0% / 50%)"), (255, 128, 0, 0.5));
+		assert_eq!(rgba("hsl(0, 100%, 50%)"), (255, 0, 0, 1.0));
+		assert_eq!(rgba("hsl(200, 80%, 50%)"), (25, 161, 230, 1.0));
+		assert_eq!(rgba("HSLA(120deg 100% 25% / 0.5)"), (0, 128, 0, 0.5));
+		assert_eq!(rgba("hsl(-0.5turn, 100%, 50%)"), (0, 255, 255, 1.0));
+		assert!(try_parse_color("rgb(1, 2)").is_none());
+		assert!(try_parse_color("hsl(red, 1%, 1%)").is_none());
+	}
+
+	#[test]
+	fn parses_named_colors() {
+		assert_eq!(rgba("rebeccapurple"), (102, 51, 153, 1.0));
+		assert_eq!(rgba("White"), (255, 255, 255, 1.0));
+		assert_eq!(rgba("transparent").3, 0.0);
+		assert!(try_parse_color("notacolor").is_none());
+		assert!(NAMED_COLORS.windows(2).all(|pair| pair[0].0 < pair[1].0));
+	}
+}
@@ -0,0 +1,51 @@
+//! Time windows over temporal graphs, for the component's `time_window`.
+//!
+//! Like collapsing, windowing rewrites the input data rather than the
+//! simulation: links timed outside the window are dropped, so they stop
+//! pulling on the layout, and nodes left without any of their links turn
+//! to ghosts.
+
+use std::collections::HashSet;
+
+use super::types::GraphData;
+
+/// Data inside a time window.
+pub(super) struct Windowed {
+	pub(super) data: GraphData,
+	/// Nodes timed outside the window, or whose links all are
+	pub(super) ghosts: HashSet<String>,
+}
+
+/// Whether something timed at `time` falls inside `window`; untimed things
+/// always do.
+fn in_window(time: Option<f64>, (start, end): (f64, f64)) -> bool {
+	time.is_none_or(|t| start <= t && t <= end)
+}
+
+/// Keep the links of `data` timed inside `window`, and mark the nodes it
+/// leaves out as ghosts.
+pub(super) fn apply_window(data: &GraphData, window: (f64, f64)) -> Windowed {
+	let mut linked = HashSet::new();
+	let mut kept = HashSet::new();
+	let mut links = Vec::new();
+	for link in &data.links {
+		linked.extend([&link.source, &link.target]);
+		if in_window(link.time, window) {
+			kept.extend([&link.source, &link.target]);
+			links.push(link.clone());
+		}
+	}
+	let ghosts = data
+		.nodes
+		.iter()
+		.filter(|n| !in_window(n.time, window) || (linked.contains(&n.id) && !kept.contains(&n.id)))
+		.map(|n| n.id.clone())
+		.collect();
+	Windowed {
+		data: GraphData {
+			nodes: data.nodes.clone(),
+			links,
+		},
+		ghosts,
+	}
+}
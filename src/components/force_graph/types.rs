@@ -1,7 +1,11 @@
 //! Graph data structures for input to the force graph component.
 
+use std::collections::{HashMap, HashSet};
+
+use super::state::ViewTransform;
+
 /// A node in the graph.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct GraphNode {
 	/// Unique identifier for this node. Used to reference nodes in links.
 	pub id: String,
@@ -10,22 +14,324 @@ pub struct GraphNode {
 	/// Optional CSS color override (e.g., "#ff0000" or "rgb(255, 0, 0)").
 	/// If not set, color is derived from the theme palette based on `group`.
 	pub color: Option<String>,
+	/// Optional CSS border color, overriding the theme's. Also tints the
+	/// node's hover rings. Drawn at the theme's border width, or
+	/// 1.5 pixels when the theme has no border, unless `border_width` is set.
+	pub border_color: Option<String>,
+	/// Optional border width in screen pixels, overriding the theme's.
+	pub border_width: Option<f64>,
+	/// Optional CSS glow color, used at full strength in place of the
+	/// theme's tint of the node color.
+	pub glow_color: Option<String>,
+	/// Optional glow intensity overriding the theme's `glow_intensity`;
+	/// `Some(0.0)` turns the node's glow off. Hover and selection still
+	/// scale it.
+	pub glow: Option<f64>,
 	/// Optional group index for palette-based coloring.
 	pub group: Option<u32>,
+	/// Optional numeric metric. Nodes with a value are colored by sampling the
+	/// theme colormap at the value's position within the range of all values.
+	/// Takes precedence over `group`, but not over `color`.
+	pub value: Option<f64>,
+	/// Optional starting position in world coordinates, used when the
+	/// component's `seed_strategy` is [`SeedStrategy::FromData`](super::SeedStrategy::FromData).
+	pub position: Option<(f64, f64)>,
+	/// Optional composition as `(value, CSS color)` pairs. The node is drawn
+	/// as a donut with one arc per segment, sized by its share of the total,
+	/// around a hole in the node's own color. Ignored when empty or summing
+	/// to zero.
+	pub segments: Option<Vec<(f64, String)>>,
+	/// Optional short marker, such as an unread count, drawn in a bubble at
+	/// the node's upper right. Only the first three characters are shown.
+	pub badge: Option<String>,
+	/// Optional time the node appears at, for playing back a temporal graph
+	/// with the component's `current_time`. Without one, the node appears
+	/// with its earliest timed link, or is always shown if it has none.
+	/// Outside the component's `time_window`, the node turns to a ghost.
+	pub time: Option<f64>,
+	/// Arbitrary application data (URLs, types, timestamps), passed back
+	/// untouched in [`NodeEvent`]s. Not used for rendering.
+	pub metadata: HashMap<String, String>,
+}
+
+/// Line style for a link, used to convey link semantics.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkStyle {
+	/// Continuous line.
+	Solid,
+	/// Long dashes that animate along the flow direction.
+	Dashed,
+	/// Short dots that hold still.
+	Dotted,
 }
 
-/// A directed edge between two nodes.
+/// An edge between two nodes.
 #[derive(Clone, Debug)]
 pub struct GraphLink {
 	/// Source node ID.
 	pub source: String,
 	/// Target node ID.
 	pub target: String,
+	/// Optional line style. If not set, edges use the zoom-driven flow dash.
+	pub style: Option<LinkStyle>,
+	/// Optional numeric weight, e.g. from an edge-list column.
+	pub weight: Option<f64>,
+	/// Bend of this link, overriding the theme's curves: 0 is straight, and
+	/// positive and negative values bow to either side of the direction
+	/// from source to target, with the theme's `curve_tension` scale.
+	pub curvature: Option<f64>,
+	/// Optional time the link appears at, for playing back a temporal graph
+	/// with the component's `current_time`. Untimed links show whenever
+	/// both their nodes do. Outside the component's `time_window`, the link
+	/// leaves the graph.
+	pub time: Option<f64>,
+	/// Whether the link has a direction. Undirected links are drawn without
+	/// arrowheads or flow particles. A directed link and its reverse are
+	/// drawn as two curves bowing apart.
+	pub directed: bool,
+}
+
+impl Default for GraphLink {
+	/// An unstyled, untimed directed link with empty endpoints.
+	fn default() -> Self {
+		Self {
+			source: String::new(),
+			target: String::new(),
+			style: None,
+			weight: None,
+			curvature: None,
+			time: None,
+			directed: true,
+		}
+	}
 }
 
 /// Complete graph data: nodes and links.
 #[derive(Clone, Debug, Default)]
 pub struct GraphData {
+	/// All nodes in the graph.
 	pub nodes: Vec<GraphNode>,
+	/// Links between nodes, referencing [`GraphNode::id`].
 	pub links: Vec<GraphLink>,
 }
+
+impl GraphData {
+	/// Earliest and latest `time` of any node or link, or `None` if nothing
+	/// is timed.
+	pub fn time_range(&self) -> Option<(f64, f64)> {
+		let nodes = self.nodes.iter().filter_map(|n| n.time);
+		let links = self.links.iter().filter_map(|l| l.time);
+		nodes
+			.chain(links)
+			.filter(|t| t.is_finite())
+			.fold(None, |range, t| match range {
+				None => Some((t, t)),
+				Some((lo, hi)) => Some((t.min(lo), t.max(hi))),
+			})
+	}
+}
+
+/// Nodes to hide from view, matched by id or by group.
+///
+/// Hidden nodes and the links touching them fade to `residual_alpha` and
+/// stop responding to the pointer. They stay in the simulation, so the
+/// layout holds its shape, unless `remove_from_simulation` is set.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Filter {
+	/// [`GraphNode::id`]s of nodes to hide.
+	pub hidden_nodes: HashSet<String>,
+	/// Groups whose nodes are all hidden.
+	pub hidden_groups: HashSet<u32>,
+	/// Opacity hidden nodes and links fade to; 0.0 hides them entirely.
+	pub residual_alpha: f64,
+	/// Take hidden nodes out of the layout too, letting the rest close up
+	/// around the gap. They fade out fully, whatever `residual_alpha` says.
+	pub remove_from_simulation: bool,
+}
+
+impl Filter {
+	/// Whether a node with this id and group is hidden.
+	pub fn hides(&self, id: &str, group: Option<u32>) -> bool {
+		self.hidden_nodes.contains(id) || group.is_some_and(|g| self.hidden_groups.contains(&g))
+	}
+
+	/// `data` without the hidden nodes and the links touching them.
+	pub fn apply(&self, data: &GraphData) -> GraphData {
+		let nodes: Vec<GraphNode> = data
+			.nodes
+			.iter()
+			.filter(|n| !self.hides(&n.id, n.group))
+			.cloned()
+			.collect();
+		let kept: HashSet<&str> = nodes.iter().map(|n| n.id.as_str()).collect();
+		let links = data
+			.links
+			.iter()
+			.filter(|l| kept.contains(l.source.as_str()) && kept.contains(l.target.as_str()))
+			.cloned()
+			.collect();
+		GraphData { nodes, links }
+	}
+}
+
+/// Details of a node passed to click and hover callbacks.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeEvent {
+	/// The node's [`GraphNode::id`].
+	pub id: String,
+	/// The node's display label, if any.
+	pub label: Option<String>,
+	/// The node's group index from the input data, if any.
+	pub group: Option<u32>,
+	/// Detected community id, when community coloring is enabled.
+	pub community: Option<u32>,
+	/// The node's value of the [`ColorBy`](super::ColorBy) metric coloring
+	/// the graph, once computed.
+	pub metric: Option<f64>,
+	/// The node's [`GraphNode::metadata`].
+	pub metadata: HashMap<String, String>,
+}
+
+/// Camera state passed to viewport change callbacks.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ViewportInfo {
+	/// Current pan and zoom.
+	pub transform: ViewTransform,
+	/// Left edge of the visible area, in world units.
+	pub min_x: f64,
+	/// Top edge of the visible area, in world units.
+	pub min_y: f64,
+	/// Right edge of the visible area, in world units.
+	pub max_x: f64,
+	/// Bottom edge of the visible area, in world units.
+	pub max_y: f64,
+}
+
+/// What dragging with a mouse button does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PointerAction {
+	/// Pan the view.
+	Pan,
+	/// Move the node pressed, or pan the view when nodes aren't
+	/// draggable. Does nothing on empty canvas.
+	MoveNode,
+	/// Select the nodes inside a dragged-out rectangle, adding to the
+	/// selection with shift held.
+	Select,
+	/// Nothing.
+	Ignore,
+}
+
+/// What dragging with one mouse button does on empty canvas and on a node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ButtonBinding {
+	/// Drag started on empty canvas.
+	pub background: PointerAction,
+	/// Drag started on a node.
+	pub node: PointerAction,
+}
+
+impl ButtonBinding {
+	/// The same action wherever the drag starts.
+	pub fn always(action: PointerAction) -> Self {
+		Self {
+			background: action,
+			node: action,
+		}
+	}
+}
+
+/// What dragging with each mouse button does.
+///
+/// By default the left button pans on empty canvas and moves nodes, the
+/// middle button always pans, and the right button does nothing, leaving
+/// the browser's context menu. With any action but
+/// [`Ignore`](PointerAction::Ignore), pressing without moving clicks the
+/// node under the pointer, or clears the selection on empty canvas.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MouseBindings {
+	/// Primary button.
+	pub left: ButtonBinding,
+	/// Wheel button.
+	pub middle: ButtonBinding,
+	/// Secondary button.
+	pub right: ButtonBinding,
+}
+
+impl Default for MouseBindings {
+	fn default() -> Self {
+		Self {
+			left: ButtonBinding {
+				background: PointerAction::Pan,
+				node: PointerAction::MoveNode,
+			},
+			middle: ButtonBinding::always(PointerAction::Pan),
+			right: ButtonBinding::always(PointerAction::Ignore),
+		}
+	}
+}
+
+impl MouseBindings {
+	/// The binding of `MouseEvent.button`, if it's the left, middle or
+	/// right button.
+	pub fn for_button(&self, button: i16) -> Option<ButtonBinding> {
+		match button {
+			0 => Some(self.left),
+			1 => Some(self.middle),
+			2 => Some(self.right),
+			_ => None,
+		}
+	}
+}
+
+/// A corner of the canvas, used to position screen-space overlays.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Corner {
+	/// Top-left corner.
+	TopLeft,
+	/// Top-right corner.
+	#[default]
+	TopRight,
+	/// Bottom-left corner.
+	BottomLeft,
+	/// Bottom-right corner.
+	BottomRight,
+}
+
+impl Corner {
+	/// Top-left origin for a `w`×`h` box inset by `margin` within a canvas.
+	pub fn origin(self, canvas_w: f64, canvas_h: f64, w: f64, h: f64, margin: f64) -> (f64, f64) {
+		let x = match self {
+			Corner::TopLeft | Corner::BottomLeft => margin,
+			Corner::TopRight | Corner::BottomRight => canvas_w - w - margin,
+		};
+		let y = match self {
+			Corner::TopLeft | Corner::TopRight => margin,
+			Corner::BottomLeft | Corner::BottomRight => canvas_h - h - margin,
+		};
+		(x, y)
+	}
+}
+
+/// Node and link builders shared by the tests.
+#[cfg(test)]
+pub(super) mod fixtures {
+	use super::{GraphLink, GraphNode};
+
+	/// A node with only an id.
+	pub(in super::super) fn node(id: &str) -> GraphNode {
+		GraphNode {
+			id: id.into(),
+			..Default::default()
+		}
+	}
+
+	/// A directed link from `source` to `target`.
+	pub(in super::super) fn link(source: &str, target: &str) -> GraphLink {
+		GraphLink {
+			source: source.into(),
+			target: target.into(),
+			..Default::default()
+		}
+	}
+}
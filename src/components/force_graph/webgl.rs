@@ -0,0 +1,612 @@
+//! WebGL2 renderer for large graphs.
+//!
+//! Nodes are instanced quads shaded into discs (with their border or hover
+//! ring) in the fragment shader, and edges and arrowheads are one triangle
+//! batch rebuilt each frame. Labels, the legend and the stats panel go on a
+//! transparent 2D canvas stacked over the GL canvas.
+//!
+//! Compared to the 2D renderer this skips glows, gradients, curved edges,
+//! dashes, flow particles, hulls, group labels, background patterns, the
+//! vignette and spotlight, badges and selection and focus rings; open arrows
+//! are drawn filled.
+
+use js_sys::Float32Array;
+use wasm_bindgen::prelude::*;
+use web_sys::{
+	CanvasRenderingContext2d, HtmlCanvasElement, WebGl2RenderingContext as Gl, WebGlBuffer,
+	WebGlProgram, WebGlShader, WebGlUniformLocation, WebGlVertexArrayObject,
+};
+
+use super::draw::DrawContext;
+use super::render::{
+	FrameStats, Legend, appear_scale, density_alpha, draw_heat_legend, draw_legend,
+	draw_select_box, draw_stats, edge_emphasis, edge_shown, hover_ring_color, node_border_width,
+	node_emphasis, node_opacity, node_pulse, smooth_step,
+};
+use super::sampling::density_cell;
+use super::scale::{ScaleConfig, ScaledValues};
+use super::state::ForceGraphState;
+use super::theme::{ArrowStyle, Color, Theme};
+
+/// Which backend draws the graph.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RendererKind {
+	/// Canvas 2D, with every visual effect.
+	#[default]
+	Canvas2d,
+	/// WebGL2, for graphs too large to draw at 60fps with Canvas 2D. Falls
+	/// back to Canvas 2D when a WebGL2 context can't be created.
+	WebGl2,
+}
+
+const NODE_VERTEX: &str = r#"#version 300 es
+layout(location = 0) in vec2 a_corner;
+layout(location = 1) in vec3 a_node;
+layout(location = 2) in vec4 a_color;
+layout(location = 3) in vec4 a_ring;
+layout(location = 4) in vec2 a_ring_geom;
+uniform vec3 u_view;
+uniform vec2 u_resolution;
+out vec2 v_local;
+out float v_radius;
+out vec4 v_color;
+out vec4 v_ring;
+out vec2 v_ring_geom;
+
+void main() {
+	float extent = a_node.z + a_ring_geom.x + a_ring_geom.y;
+	v_local = a_corner * extent;
+	v_radius = a_node.z;
+	v_color = a_color;
+	v_ring = a_ring;
+	v_ring_geom = a_ring_geom;
+	vec2 screen = (a_node.xy + v_local) * u_view.z + u_view.xy;
+	vec2 clip = screen / u_resolution * 2.0 - 1.0;
+	gl_Position = vec4(clip.x, -clip.y, 0.0, 1.0);
+}
+"#;
+
+const NODE_FRAGMENT: &str = r#"#version 300 es
+precision mediump float;
+in vec2 v_local;
+in float v_radius;
+in vec4 v_color;
+in vec4 v_ring;
+in vec2 v_ring_geom;
+out vec4 out_color;
+
+void main() {
+	float d = length(v_local);
+	float aa = fwidth(d);
+	float fill = 1.0 - smoothstep(v_radius - aa, v_radius, d);
+	float ring_d = abs(d - v_radius - v_ring_geom.x);
+	float ring = 1.0 - smoothstep(v_ring_geom.y * 0.5, v_ring_geom.y * 0.5 + aa, ring_d);
+	float fill_a = v_color.a * fill;
+	float ring_a = v_ring.a * ring * (1.0 - fill_a);
+	float a = fill_a + ring_a;
+	if (a <= 0.0) {
+		discard;
+	}
+	out_color = vec4(v_color.rgb * fill_a + v_ring.rgb * ring_a, a);
+}
+"#;
+
+const EDGE_VERTEX: &str = r#"#version 300 es
+layout(location = 0) in vec2 a_pos;
+layout(location = 1) in vec4 a_color;
+uniform vec3 u_view;
+uniform vec2 u_resolution;
+out vec4 v_color;
+
+void main() {
+	v_color = vec4(a_color.rgb * a_color.a, a_color.a);
+	vec2 clip = (a_pos * u_view.z + u_view.xy) / u_resolution * 2.0 - 1.0;
+	gl_Position = vec4(clip.x, -clip.y, 0.0, 1.0);
+}
+"#;
+
+const EDGE_FRAGMENT: &str = r#"#version 300 es
+precision mediump float;
+in vec4 v_color;
+out vec4 out_color;
+
+void main() {
+	out_color = v_color;
+}
+"#;
+
+/// Floats per node instance: center and radius, fill, ring color, ring
+/// offset and width.
+const NODE_STRIDE: i32 = 3 + 4 + 4 + 2;
+/// Floats per edge vertex: position and color.
+const EDGE_STRIDE: i32 = 2 + 4;
+
+/// A linked shader program with its view uniforms and vertex state.
+struct Pass {
+	program: WebGlProgram,
+	vao: WebGlVertexArrayObject,
+	buffer: WebGlBuffer,
+	view: Option<WebGlUniformLocation>,
+	resolution: Option<WebGlUniformLocation>,
+}
+
+/// Draws the graph with WebGL2 onto a canvas, with a 2D overlay for text.
+pub(super) struct GlRenderer {
+	gl: Gl,
+	canvas: HtmlCanvasElement,
+	nodes: Pass,
+	edges: Pass,
+	overlay: HtmlCanvasElement,
+	overlay_ctx: CanvasRenderingContext2d,
+	node_data: Vec<f32>,
+	edge_data: Vec<f32>,
+}
+
+impl GlRenderer {
+	/// Set up WebGL2 on `canvas` and insert the text overlay after it, or
+	/// `None` if WebGL2 is unavailable.
+	pub(super) fn new(canvas: &HtmlCanvasElement) -> Option<Self> {
+		let gl: Gl = canvas.get_context("webgl2").ok()??.dyn_into().ok()?;
+		let nodes = node_pass(&gl)
+			.inspect_err(|err| log::warn!("WebGL2 node shader: {err}"))
+			.ok()?;
+		let edges = edge_pass(&gl)
+			.inspect_err(|err| log::warn!("WebGL2 edge shader: {err}"))
+			.ok()?;
+
+		let overlay: HtmlCanvasElement = canvas
+			.owner_document()?
+			.create_element("canvas")
+			.ok()?
+			.dyn_into()
+			.ok()?;
+		let _ = overlay.set_attribute(
+			"style",
+			"position: absolute; pointer-events: none; display: block;",
+		);
+		canvas.after_with_node_1(&overlay).ok()?;
+		let overlay_ctx = overlay.get_context("2d").ok()??.dyn_into().ok()?;
+
+		gl.enable(Gl::BLEND);
+		// Shaders output premultiplied alpha
+		gl.blend_func(Gl::ONE, Gl::ONE_MINUS_SRC_ALPHA);
+
+		Some(Self {
+			gl,
+			canvas: canvas.clone(),
+			nodes,
+			edges,
+			overlay,
+			overlay_ctx,
+			node_data: Vec::new(),
+			edge_data: Vec::new(),
+		})
+	}
+
+	/// The 2D context drawn over the graph, for screen-space overlays.
+	pub(super) fn overlay(&self) -> &CanvasRenderingContext2d {
+		&self.overlay_ctx
+	}
+
+	/// Draw one frame of `state`.
+	pub(super) fn render(
+		&mut self,
+		state: &ForceGraphState,
+		config: &ScaleConfig,
+		theme: &Theme,
+		legend: Option<&Legend>,
+		stats: Option<&FrameStats>,
+	) {
+		let scale = ScaledValues::new(config, state.transform.k);
+		self.sync_overlay();
+		let (w, h) = (self.canvas.width(), self.canvas.height());
+		let gl = &self.gl;
+		gl.viewport(0, 0, w as i32, h as i32);
+		let bg = theme.background.color;
+		let alpha = if theme.background.transparent {
+			0.0
+		} else {
+			bg.a as f32
+		};
+		gl.clear_color(
+			bg.r as f32 / 255.0,
+			bg.g as f32 / 255.0,
+			bg.b as f32 / 255.0,
+			alpha,
+		);
+		gl.clear(Gl::COLOR_BUFFER_BIT);
+
+		let t = &state.transform;
+		let view = [t.x as f32, t.y as f32, t.k as f32];
+		let resolution = [w as f32, h as f32];
+
+		self.edge_data.clear();
+		build_edges(state, &scale, theme, &mut self.edge_data);
+		let edges = &self.edges;
+		gl.use_program(Some(&edges.program));
+		gl.uniform3fv_with_f32_array(edges.view.as_ref(), &view);
+		gl.uniform2fv_with_f32_array(edges.resolution.as_ref(), &resolution);
+		gl.bind_vertex_array(Some(&edges.vao));
+		upload(gl, &edges.buffer, &self.edge_data);
+		gl.draw_arrays(Gl::TRIANGLES, 0, self.edge_data.len() as i32 / EDGE_STRIDE);
+
+		self.node_data.clear();
+		build_nodes(state, &scale, theme, &mut self.node_data);
+		let nodes = &self.nodes;
+		gl.use_program(Some(&nodes.program));
+		gl.uniform3fv_with_f32_array(nodes.view.as_ref(), &view);
+		gl.uniform2fv_with_f32_array(nodes.resolution.as_ref(), &resolution);
+		gl.bind_vertex_array(Some(&nodes.vao));
+		upload(gl, &nodes.buffer, &self.node_data);
+		gl.draw_arrays_instanced(
+			Gl::TRIANGLE_STRIP,
+			0,
+			4,
+			self.node_data.len() as i32 / NODE_STRIDE,
+		);
+		gl.bind_vertex_array(None);
+
+		let ctx = &self.overlay_ctx;
+		ctx.clear_rect(0.0, 0.0, w as f64, h as f64);
+		ctx.save();
+		let _ = ctx.set_transform(t.k, 0.0, 0.0, t.k, t.x, t.y);
+		draw_labels(state, ctx, &scale, theme);
+		ctx.restore();
+		if let Some(ref select_box) = state.select_box {
+			draw_select_box(ctx, theme, select_box);
+		}
+		if let Some(legend) = legend.filter(|l| !l.entries.is_empty()) {
+			draw_legend(state, ctx, theme, legend);
+		}
+		if let Some(heatmap) = state.heatmap() {
+			draw_heat_legend(state, ctx, theme, heatmap);
+		}
+		if let Some(stats) = stats {
+			draw_stats(state, ctx, stats);
+		}
+	}
+
+	/// Keep the overlay the same size as, and on top of, the GL canvas.
+	fn sync_overlay(&self) {
+		let (w, h) = (self.canvas.width(), self.canvas.height());
+		if self.overlay.width() != w || self.overlay.height() != h {
+			self.overlay.set_width(w);
+			self.overlay.set_height(h);
+		}
+		let _ = self.overlay.set_attribute(
+			"style",
+			&format!(
+				"position: absolute; pointer-events: none; display: block; left: {}px; top: {}px;",
+				self.canvas.offset_left(),
+				self.canvas.offset_top()
+			),
+		);
+	}
+}
+
+impl Drop for GlRenderer {
+	fn drop(&mut self) {
+		self.overlay.remove();
+	}
+}
+
+/// Append two triangles per edge and one per arrowhead, in world space.
+fn build_edges(state: &ForceGraphState, scale: &ScaledValues, theme: &Theme, out: &mut Vec<f32>) {
+	let color = theme.edge.color;
+	let arrow_size = scale.arrow_size * theme.edge.arrow_scale;
+	let placement = theme.edge.arrow_placement;
+	state.graph.visit_edges(|n1, n2, edge| {
+		let (x1, y1, x2, y2) = (n1.x() as f64, n1.y() as f64, n2.x() as f64, n2.y() as f64);
+		let (dx, dy) = (x2 - x1, y2 - y1);
+		let dist = dx.hypot(dy);
+		if dist < 0.001 {
+			return;
+		}
+		let shown = edge_shown(state, n1, n2);
+		if shown < 0.01 {
+			return;
+		}
+		let edge_t = smooth_step(state.highlight.edge_intensity(n1.index(), n2.index()));
+		let (edge_alpha, arrow_alpha, width) =
+			edge_emphasis(state, scale, theme, &edge.user_data, edge_t);
+		let edge_alpha = edge_alpha * shown;
+		let arrow_alpha = arrow_alpha * scale.arrow_alpha * shown;
+		let arrows = edge.user_data.directed
+			&& theme.edge.arrow_style != ArrowStyle::None
+			&& !scale.cull_arrows
+			&& arrow_alpha > 0.0;
+
+		let (ux, uy) = (dx / dist, dy / dist);
+		let end_offset = if arrows && placement.at_target() {
+			scale.node_radius + arrow_size
+		} else {
+			scale.node_radius
+		};
+		let (sx, sy) = (x1 + ux * scale.node_radius, y1 + uy * scale.node_radius);
+		let (ex, ey) = (x2 - ux * end_offset, y2 - uy * end_offset);
+		let (px, py) = (-uy * width / 2.0, ux * width / 2.0);
+		let line = color.with_alpha(edge_alpha * color.a);
+		for (x, y) in [
+			(sx + px, sy + py),
+			(sx - px, sy - py),
+			(ex + px, ey + py),
+			(ex + px, ey + py),
+			(sx - px, sy - py),
+			(ex - px, ey - py),
+		] {
+			push_vertex(out, x, y, line);
+		}
+
+		if arrows {
+			let arrow_color = theme.edge.arrow_color.unwrap_or(color);
+			let fill = arrow_color.with_alpha(arrow_alpha * arrow_color.a);
+			let diamond = theme.edge.arrow_style == ArrowStyle::Diamond;
+			let mut arrow = |tip_x: f64, tip_y: f64| {
+				let (bx, by) = (tip_x - ux * arrow_size, tip_y - uy * arrow_size);
+				let (ax, ay) = (-uy * arrow_size * 0.5, ux * arrow_size * 0.5);
+				if diamond {
+					let (mx, my) = ((tip_x + bx) / 2.0, (tip_y + by) / 2.0);
+					for side in [0.8, -0.8] {
+						push_vertex(out, tip_x, tip_y, fill);
+						push_vertex(out, mx + ax * side, my + ay * side, fill);
+						push_vertex(out, bx, by, fill);
+					}
+				} else {
+					push_vertex(out, tip_x, tip_y, fill);
+					push_vertex(out, bx + ax, by + ay, fill);
+					push_vertex(out, bx - ax, by - ay, fill);
+				}
+			};
+			if placement.at_target() {
+				arrow(x2 - ux * scale.node_radius, y2 - uy * scale.node_radius);
+			}
+			if placement.at_midpoint() {
+				let (mx, my) = ((x1 + x2) / 2.0, (y1 + y2) / 2.0);
+				arrow(mx + ux * arrow_size / 2.0, my + uy * arrow_size / 2.0);
+			}
+		}
+	});
+
+	for (x1, y1, x2, y2, alpha) in state.departing_edge_lines() {
+		let (dx, dy) = (x2 - x1, y2 - y1);
+		let dist = dx.hypot(dy);
+		if dist < 0.001 {
+			continue;
+		}
+		let (px, py) = (
+			-dy / dist * scale.edge_line_width / 2.0,
+			dx / dist * scale.edge_line_width / 2.0,
+		);
+		let line = color.with_alpha(0.7 * alpha * color.a);
+		for (x, y) in [
+			(x1 + px, y1 + py),
+			(x1 - px, y1 - py),
+			(x2 + px, y2 + py),
+			(x2 + px, y2 + py),
+			(x1 - px, y1 - py),
+			(x2 - px, y2 - py),
+		] {
+			push_vertex(out, x, y, line);
+		}
+	}
+}
+
+/// Append one instance per node, highlighted nodes last so they draw on top.
+fn build_nodes(state: &ForceGraphState, scale: &ScaledValues, theme: &Theme, out: &mut Vec<f32>) {
+	let pulse = node_pulse(state, theme);
+	let selection = &theme.node.selection;
+	// Removed nodes fade out beneath the rest
+	for node in &state.departing {
+		let info = &node.info;
+		let radius = scale.node_radius_for(info.size) * (1.0 + pulse) * appear_scale(info);
+		out.extend([node.x as f32, node.y as f32, radius as f32]);
+		push_color(out, info.fill.with_alpha(info.fill.a * info.appear_t));
+		push_color(out, Color::rgba(0, 0, 0, 0.0));
+		out.extend([0.0, 0.0]);
+	}
+	// Nodes left out of the sample, as a point cloud of borderless discs
+	let side = density_cell(state.transform.k);
+	let cloud = theme.node.label_color;
+	for (x, y, count) in state.unsampled_density() {
+		out.extend([x as f32, y as f32, (side / 2.0) as f32]);
+		push_color(out, cloud.with_alpha(cloud.a * density_alpha(count)));
+		push_color(out, Color::rgba(0, 0, 0, 0.0));
+		out.extend([0.0, 0.0]);
+	}
+	for highlighted in [false, true] {
+		state.graph.visit_nodes(|node| {
+			let idx = node.index();
+			if node.data.user_data.sampled_out
+				|| (state.highlight.node_intensity(idx) > 0.001) != highlighted
+			{
+				return;
+			}
+			let (alpha, mut radius_mult) = node_emphasis(state, idx);
+			if state.selection.contains(&idx) {
+				radius_mult *= selection.radius_mult;
+			}
+			let radius = scale.node_radius_for(node.data.user_data.size)
+				* radius_mult
+				* (1.0 + pulse)
+				* appear_scale(&node.data.user_data);
+			let color = node.data.user_data.fill;
+			let info = &node.data.user_data;
+			let ring_t = smooth_step(state.highlight.hover_ring_intensity(idx));
+			let border_width = node_border_width(theme, info) / scale.k;
+			// The ring slot outlines the node with its border until hovered
+			let (ring, ring_offset, ring_width) = if ring_t <= 0.01 && border_width > 0.0 {
+				let border = info.border_color.unwrap_or(theme.node.border_color);
+				(
+					border.with_alpha(border.a * alpha * node_opacity(info)),
+					border_width / 2.0,
+					border_width,
+				)
+			} else {
+				(
+					hover_ring_color(theme, info).with_alpha(theme.node.ring_alpha * ring_t),
+					scale.ring_offset,
+					scale.ring_width * theme.node.ring_width_mult,
+				)
+			};
+
+			out.extend([node.x(), node.y(), radius as f32]);
+			push_color(out, color.with_alpha(color.a * alpha * node_opacity(info)));
+			push_color(out, ring);
+			out.extend([ring_offset as f32, ring_width as f32]);
+		});
+	}
+}
+
+/// Draw node labels in world space, matching the 2D renderer's fading.
+fn draw_labels(
+	state: &ForceGraphState,
+	ctx: &CanvasRenderingContext2d,
+	scale: &ScaledValues,
+	theme: &Theme,
+) {
+	let pulse = node_pulse(state, theme);
+	let label_color = theme.node.label_color;
+	DrawContext::set_font(ctx, &scale.label_font);
+	state.graph.visit_nodes(|node| {
+		let Some(label) = &node.data.user_data.label else {
+			return;
+		};
+		let idx = node.index();
+		let (alpha, radius_mult) = node_emphasis(state, idx);
+		let alpha = alpha * node_opacity(&node.data.user_data);
+		let node_size = node.data.user_data.size;
+		let tier_alpha = scale.label_alpha_for(node_size);
+		// Highlighted labels fade in with hover; resting ones sit dimmer
+		let label_alpha = if state.highlight.node_intensity(idx) > 0.001 {
+			let hover_t = smooth_step(state.highlight.hover_ring_intensity(idx));
+			alpha * (tier_alpha + (1.0 - tier_alpha) * hover_t)
+		} else if alpha > 0.5 {
+			alpha * 0.8 * 0.9 * tier_alpha
+		} else {
+			return;
+		};
+		if label_alpha < 0.01 {
+			return;
+		}
+		let radius = scale.node_radius_for(node_size) * radius_mult * (1.0 + pulse);
+		let css = label_color.with_alpha(label_color.a * label_alpha).to_css();
+		DrawContext::set_fill_style_str(ctx, &css);
+		DrawContext::fill_text(
+			ctx,
+			label,
+			node.x() as f64 + radius + 4.0,
+			node.y() as f64 + 3.0,
+		);
+	});
+}
+
+fn push_vertex(out: &mut Vec<f32>, x: f64, y: f64, color: Color) {
+	out.extend([x as f32, y as f32]);
+	push_color(out, color);
+}
+
+fn push_color(out: &mut Vec<f32>, color: Color) {
+	out.extend([
+		color.r as f32 / 255.0,
+		color.g as f32 / 255.0,
+		color.b as f32 / 255.0,
+		color.a as f32,
+	]);
+}
+
+fn upload(gl: &Gl, buffer: &WebGlBuffer, data: &[f32]) {
+	gl.bind_buffer(Gl::ARRAY_BUFFER, Some(buffer));
+	gl.buffer_data_with_array_buffer_view(
+		Gl::ARRAY_BUFFER,
+		&Float32Array::from(data),
+		Gl::DYNAMIC_DRAW,
+	);
+}
+
+fn node_pass(gl: &Gl) -> Result<Pass, String> {
+	let pass = pass(gl, NODE_VERTEX, NODE_FRAGMENT)?;
+	gl.bind_vertex_array(Some(&pass.vao));
+
+	// Unit quad corners, shared by every instance
+	let corners = gl.create_buffer().ok_or("no buffer")?;
+	gl.bind_buffer(Gl::ARRAY_BUFFER, Some(&corners));
+	gl.buffer_data_with_array_buffer_view(
+		Gl::ARRAY_BUFFER,
+		&Float32Array::from(&[-1.0f32, -1.0, 1.0, -1.0, -1.0, 1.0, 1.0, 1.0][..]),
+		Gl::STATIC_DRAW,
+	);
+	gl.enable_vertex_attrib_array(0);
+	gl.vertex_attrib_pointer_with_i32(0, 2, Gl::FLOAT, false, 0, 0);
+
+	gl.bind_buffer(Gl::ARRAY_BUFFER, Some(&pass.buffer));
+	let mut offset = 0;
+	for (location, size) in [(1, 3), (2, 4), (3, 4), (4, 2)] {
+		gl.enable_vertex_attrib_array(location);
+		gl.vertex_attrib_pointer_with_i32(
+			location,
+			size,
+			Gl::FLOAT,
+			false,
+			NODE_STRIDE * 4,
+			offset * 4,
+		);
+		gl.vertex_attrib_divisor(location, 1);
+		offset += size;
+	}
+	gl.bind_vertex_array(None);
+	Ok(pass)
+}
+
+fn edge_pass(gl: &Gl) -> Result<Pass, String> {
+	let pass = pass(gl, EDGE_VERTEX, EDGE_FRAGMENT)?;
+	gl.bind_vertex_array(Some(&pass.vao));
+	gl.bind_buffer(Gl::ARRAY_BUFFER, Some(&pass.buffer));
+	gl.enable_vertex_attrib_array(0);
+	gl.vertex_attrib_pointer_with_i32(0, 2, Gl::FLOAT, false, EDGE_STRIDE * 4, 0);
+	gl.enable_vertex_attrib_array(1);
+	gl.vertex_attrib_pointer_with_i32(1, 4, Gl::FLOAT, false, EDGE_STRIDE * 4, 2 * 4);
+	gl.bind_vertex_array(None);
+	Ok(pass)
+}
+
+/// Link a program from vertex and fragment sources, with an empty vertex
+/// array and buffer for its pass to configure.
+fn pass(gl: &Gl, vertex: &str, fragment: &str) -> Result<Pass, String> {
+	let program = gl.create_program().ok_or("no program")?;
+	for shader in [
+		compile(gl, Gl::VERTEX_SHADER, vertex)?,
+		compile(gl, Gl::FRAGMENT_SHADER, fragment)?,
+	] {
+		gl.attach_shader(&program, &shader);
+	}
+	gl.link_program(&program);
+	if !gl
+		.get_program_parameter(&program, Gl::LINK_STATUS)
+		.as_bool()
+		.unwrap_or(false)
+	{
+		return Err(gl.get_program_info_log(&program).unwrap_or_default());
+	}
+	Ok(Pass {
+		view: gl.get_uniform_location(&program, "u_view"),
+		resolution: gl.get_uniform_location(&program, "u_resolution"),
+		vao: gl.create_vertex_array().ok_or("no vertex array")?,
+		buffer: gl.create_buffer().ok_or("no buffer")?,
+		program,
+	})
+}
+
+fn compile(gl: &Gl, kind: u32, source: &str) -> Result<WebGlShader, String> {
+	let shader = gl.create_shader(kind).ok_or("no shader")?;
+	gl.shader_source(&shader, source);
+	gl.compile_shader(&shader);
+	if gl
+		.get_shader_parameter(&shader, Gl::COMPILE_STATUS)
+		.as_bool()
+		.unwrap_or(false)
+	{
+		Ok(shader)
+	} else {
+		Err(gl.get_shader_info_log(&shader).unwrap_or_default())
+	}
+}
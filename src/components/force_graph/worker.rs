@@ -0,0 +1,1737 @@
+//! Worker rendering mode: the simulation and renderer run in a web worker
+//! that owns the canvas through an `OffscreenCanvas`.
+//!
+//! The main thread ([`WorkerHost`]) forwards [`Input`]s, data updates and
+//! resizes as [`Command`]s; the worker ([`run_graph_worker`]) answers with
+//! [`Reply`]s carrying the same outcomes the in-thread path reports. Messages
+//! are plain JS objects, so they survive `postMessage`'s structured clone.
+
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::prelude::*;
+use web_sys::{
+	DedicatedWorkerGlobalScope, HtmlCanvasElement, MessageEvent, OffscreenCanvas, Worker,
+	WorkerOptions, WorkerType,
+};
+
+use super::annotations::{OffscreenAnnotations, Placement};
+use super::centrality::ColorBy;
+use super::component::{ClosureSlot, FrameOutcome, GraphContext, GraphOptions, Surface, frame_dt};
+use super::input::{Input, InputOutcome};
+use super::sampling::{NodeSampling, SampleStrategy, SamplingInfo, UnsampledNodes};
+use super::scale::{
+	AlphaBehavior, ArrowScaleConfig, EdgeScaleConfig, GlowScaleConfig, LodConfig, NodeScaleConfig,
+	ScaleBehavior, ScaleConfig,
+};
+use super::seed::SeedStrategy;
+use super::state::{DegreeCurve, DegreeSizing, Fisheye, ViewTransform};
+use super::theme::{Colormap, Theme, parse_color};
+use super::types::{
+	ButtonBinding, Corner, Filter, GraphData, GraphLink, GraphNode, LinkStyle, MouseBindings,
+	NodeEvent, PointerAction, ViewportInfo,
+};
+
+/// A message from the main thread to the worker.
+#[derive(Debug)]
+pub(super) enum Command {
+	/// Take over `canvas` and lay out `data` on it.
+	Init {
+		canvas: OffscreenCanvas,
+		data: GraphData,
+		width: f64,
+		height: f64,
+		opts: Box<GraphOptions>,
+	},
+	/// Apply a user interaction.
+	Input(Input),
+	/// Replace the graph.
+	Data(GraphData),
+	/// Resize the canvas.
+	Resize { width: f64, height: f64 },
+	/// Move the camera, from a controlled `transform` signal.
+	Camera(ViewTransform),
+	/// Highlight a group, or clear the highlight.
+	Group(Option<u32>),
+	/// Hide the nodes a filter matches, or show every node again.
+	Filter(Option<Filter>),
+	/// Play a temporal graph back to a time, or show all of it.
+	Time(Option<f64>),
+	/// Collapse these groups into meta-nodes from the next [`Command::Data`].
+	Collapse(HashSet<u32>),
+	/// Keep only links inside a time range from the next [`Command::Data`].
+	TimeWindow(Option<(f64, f64)>),
+	/// Color nodes by a metric, or by their own colors again.
+	ColorBy(Option<ColorBy>),
+	/// Show or hide the fisheye lens.
+	Fisheye(bool),
+	/// Place annotations for the nodes with these ids.
+	Annotate(Vec<String>),
+	/// Size visuals against zoom by a new configuration.
+	Scale(Box<ScaleConfig>),
+	/// Hold the simulation still, or let it run again.
+	Pause(bool),
+}
+
+/// A message from the worker back to the main thread.
+pub(super) enum Reply {
+	/// The worker script loaded and is waiting for [`Command::Init`].
+	Ready,
+	/// What an input changed.
+	Input(Box<InputOutcome>),
+	/// What a frame changed.
+	Frame(FrameOutcome),
+}
+
+/// Whether the browser can render the graph in a worker.
+pub(super) fn supported() -> bool {
+	let global = js_sys::global();
+	["Worker", "OffscreenCanvas"]
+		.iter()
+		.all(|name| Reflect::has(&global, &JsValue::from_str(name)).unwrap_or(false))
+}
+
+/// Whether `opts` arrive in the worker as given. Themes travel by preset
+/// name and functions can't travel at all, so a customized theme or a
+/// [`ColorBy::Custom`] metric would be lost on the way.
+pub(super) fn can_send(opts: &GraphOptions) -> bool {
+	opts.theme == theme_preset(opts.theme.name)
+		&& !matches!(opts.color_by, Some(ColorBy::Custom(_)))
+}
+
+/// What the worker is started with once it reports [`Reply::Ready`].
+struct PendingInit {
+	canvas: HtmlCanvasElement,
+	data: GraphData,
+	size: (f64, f64),
+	opts: GraphOptions,
+}
+
+/// Main-thread handle to a graph worker. The worker is terminated on drop.
+pub(super) struct WorkerHost {
+	worker: Worker,
+	/// Set until the worker loads and takes over the canvas
+	pending: Rc<RefCell<Option<PendingInit>>>,
+	_on_message: Closure<dyn FnMut(MessageEvent)>,
+	_on_error: Closure<dyn FnMut(web_sys::Event)>,
+}
+
+impl WorkerHost {
+	/// Start the module worker at `url` to render `data` on `canvas`.
+	/// `on_reply` receives the worker's replies.
+	///
+	/// The canvas is only transferred once the worker script has loaded. If
+	/// it fails before then (a missing script, say) `on_failed` is called and
+	/// the canvas is untouched, so the caller can still render on the main
+	/// thread.
+	pub(super) fn spawn(
+		url: &str,
+		canvas: &HtmlCanvasElement,
+		data: GraphData,
+		size: (f64, f64),
+		opts: GraphOptions,
+		mut on_reply: impl FnMut(Reply) + 'static,
+		on_failed: impl Fn() + 'static,
+	) -> Result<Self, JsValue> {
+		let options = WorkerOptions::new();
+		options.set_type(WorkerType::Module);
+		let worker = Worker::new_with_options(url, &options)?;
+		let pending = Rc::new(RefCell::new(Some(PendingInit {
+			canvas: canvas.clone(),
+			data,
+			size,
+			opts,
+		})));
+		let on_failed = Rc::new(on_failed);
+
+		let (worker_msg, pending_msg, failed_msg) =
+			(worker.clone(), pending.clone(), on_failed.clone());
+		let on_message = Closure::<dyn FnMut(MessageEvent)>::new(move |ev: MessageEvent| {
+			match Reply::decode(&ev.data()) {
+				Some(Reply::Ready) => {
+					let Some(init) = pending_msg.borrow_mut().take() else {
+						return;
+					};
+					if let Err(err) = start(&worker_msg, init) {
+						log::warn!("graph worker could not take the canvas: {err:?}");
+						failed_msg();
+					}
+				}
+				Some(reply) => on_reply(reply),
+				None => {}
+			}
+		});
+		worker.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+
+		let pending_err = pending.clone();
+		let on_error = Closure::<dyn FnMut(web_sys::Event)>::new(move |_: web_sys::Event| {
+			// Past startup the worker owns the canvas and there is nothing to
+			// fall back to
+			if pending_err.borrow().is_some() {
+				log::warn!("graph worker failed to load, rendering on the main thread");
+				on_failed();
+			} else {
+				log::error!("graph worker raised an error");
+			}
+		});
+		worker.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+
+		Ok(Self {
+			worker,
+			pending,
+			_on_message: on_message,
+			_on_error: on_error,
+		})
+	}
+
+	/// Post `command` to the worker. Until the worker has started, data,
+	/// size, camera and group changes update what it will start with, and
+	/// input is dropped.
+	pub(super) fn send(&self, command: &Command) {
+		if let Some(ref mut init) = *self.pending.borrow_mut() {
+			match command {
+				Command::Data(data) => init.data = data.clone(),
+				Command::Resize { width, height } => {
+					init.canvas.set_width(*width as u32);
+					init.canvas.set_height(*height as u32);
+					init.size = (*width, *height);
+				}
+				Command::Camera(transform) => init.opts.transform = Some(transform.clone()),
+				Command::Group(group) => init.opts.highlighted_group = *group,
+				Command::Filter(filter) => init.opts.filter = filter.clone(),
+				Command::Time(time) => init.opts.current_time = *time,
+				Command::Collapse(groups) => init.opts.collapsed = groups.clone(),
+				Command::TimeWindow(window) => init.opts.time_window = *window,
+				Command::ColorBy(color_by) => init.opts.color_by = *color_by,
+				Command::Fisheye(on) => init.opts.fisheye_on = *on,
+				Command::Annotate(ids) => init.opts.annotated = ids.clone(),
+				Command::Scale(config) => init.opts.scale = (**config).clone(),
+				Command::Pause(paused) => init.opts.paused = *paused,
+				Command::Init { .. } | Command::Input(_) => {}
+			}
+			return;
+		}
+		if let Err(err) = self.worker.post_message(&command.encode()) {
+			log::error!("failed to message graph worker: {err:?}");
+		}
+	}
+}
+
+/// Transfer the canvas to `worker` and start it.
+fn start(worker: &Worker, init: PendingInit) -> Result<(), JsValue> {
+	let offscreen = init.canvas.transfer_control_to_offscreen()?;
+	let (width, height) = init.size;
+	let command = Command::Init {
+		canvas: offscreen.clone(),
+		data: init.data,
+		width,
+		height,
+		opts: Box::new(init.opts),
+	};
+	worker.post_message_with_transfer(&command.encode(), &Array::of1(&offscreen))
+}
+
+impl Drop for WorkerHost {
+	fn drop(&mut self) {
+		self.worker.terminate();
+	}
+}
+
+/// Worker-side graph: the context plus the canvas it draws on.
+struct WorkerGraph {
+	context: GraphContext,
+	canvas: OffscreenCanvas,
+	surface: Surface,
+	opts: GraphOptions,
+	last_frame: Option<f64>,
+	/// Set while the canvas's rendering context is lost
+	lost: bool,
+}
+
+/// Entry point for a graph worker: serves the main thread's
+/// [`ForceGraphCanvas`](super::ForceGraphCanvas) `worker_url` mode.
+///
+/// Call it once from the `main` of a wasm binary built for the worker, and
+/// load that binary from the module script passed as `worker_url`. The
+/// worker runs until the component unmounts and terminates it.
+///
+/// # Building the worker
+///
+/// Add a binary whose `main` does nothing else, e.g. `src/bin/graph_worker.rs`:
+///
+/// ```ignore
+/// fn main() {
+///     force_graph_canvas::run_graph_worker();
+/// }
+/// ```
+///
+/// Build it for the web and generate its module bindings next to the app:
+///
+/// ```text
+/// cargo build --release --target wasm32-unknown-unknown --bin graph_worker
+/// wasm-bindgen --target web --out-dir dist/worker \
+///     target/wasm32-unknown-unknown/release/graph_worker.wasm
+/// ```
+///
+/// Then serve a module script beside the output that loads it, which runs
+/// `main`, and pass that script's URL as `worker_url`:
+///
+/// ```text
+/// // dist/worker/start.js
+/// import init from "./graph_worker.js";
+/// await init();
+/// ```
+pub fn run_graph_worker() {
+	let scope: DedicatedWorkerGlobalScope = js_sys::global().unchecked_into();
+	let graph: Rc<RefCell<Option<WorkerGraph>>> = Rc::new(RefCell::new(None));
+	let animate: ClosureSlot<dyn FnMut(f64)> = Rc::new(RefCell::new(None));
+
+	let (graph_anim, scope_anim, animate_inner) = (graph.clone(), scope.clone(), animate.clone());
+	*animate.borrow_mut() = Some(Closure::new(move |timestamp: f64| {
+		let outcome = graph_anim.borrow_mut().as_mut().and_then(|g| {
+			// Hold the graph as it was until the context comes back
+			if g.lost {
+				g.last_frame = None;
+				return None;
+			}
+			let dt = frame_dt(g.last_frame.replace(timestamp), timestamp);
+			// Node positions can't be read across the worker boundary, so
+			// repaints go unreported
+			let outcome = g.context.frame(&mut g.surface, dt);
+			Some(FrameOutcome {
+				repainted: false,
+				..outcome
+			})
+		});
+		if let Some(outcome) = outcome
+			&& outcome != FrameOutcome::default()
+		{
+			reply(&scope_anim, &Reply::Frame(outcome));
+		}
+		// Workers get no `visibilitychange`; the browser throttles their
+		// animation frames in background tabs instead
+		if let Some(ref cb) = *animate_inner.borrow() {
+			let _ = scope_anim.request_animation_frame(cb.as_ref().unchecked_ref());
+		}
+	}));
+
+	let scope_msg = scope.clone();
+	let graph_health = graph.clone();
+	let on_message = Closure::<dyn FnMut(MessageEvent)>::new(move |ev: MessageEvent| {
+		let Some(command) = Command::decode(&ev.data()) else {
+			log::warn!("graph worker ignored a malformed message");
+			return;
+		};
+		let mut slot = graph.borrow_mut();
+		if let Command::Init {
+			canvas,
+			data,
+			width,
+			height,
+			opts,
+		} = command
+		{
+			let canvas_events = canvas.clone();
+			let Some(ctx) = canvas.get_context("2d").ok().flatten() else {
+				log::error!("graph worker could not get a 2d context");
+				return;
+			};
+			let context = GraphContext::new(&data, width, height, &opts);
+			// Let a controlled `transform` signal pick up the initial view
+			let camera = InputOutcome {
+				moved: Some(context.state.transform.clone()),
+				..InputOutcome::default()
+			};
+			reply(&scope_msg, &Reply::Input(Box::new(camera)));
+			*slot = Some(WorkerGraph {
+				context,
+				canvas,
+				// The 2d context methods are bound structurally, so the
+				// `OffscreenCanvasRenderingContext2D` answers them all
+				surface: Surface::Canvas2d(ctx.unchecked_into()),
+				opts: *opts,
+				last_frame: None,
+				lost: false,
+			});
+			watch_context(&canvas_events, &graph_health);
+			if let Some(ref cb) = *animate.borrow() {
+				let _ = scope_msg.request_animation_frame(cb.as_ref().unchecked_ref());
+			}
+			return;
+		}
+		let Some(g) = slot.as_mut() else {
+			return;
+		};
+		match command {
+			Command::Init { .. } => {}
+			Command::Input(input) => {
+				let outcome = g.context.handle(input);
+				if outcome != InputOutcome::default() {
+					drop(slot);
+					reply(&scope_msg, &Reply::Input(Box::new(outcome)));
+				}
+			}
+			Command::Data(data) => {
+				let (width, height) = (g.canvas.width() as f64, g.canvas.height() as f64);
+				let previous = std::mem::replace(
+					&mut g.context,
+					GraphContext::new(&data, width, height, &g.opts),
+				);
+				g.context.state.carry_over(&previous.state);
+			}
+			Command::Resize { width, height } => {
+				g.canvas.set_width(width as u32);
+				g.canvas.set_height(height as u32);
+				g.context.resize(width, height);
+			}
+			Command::Camera(transform) => {
+				if transform.k > 0.0 && g.context.state.transform != transform {
+					g.context.state.transform = transform;
+					g.context.state.mark_dirty();
+				}
+			}
+			Command::Group(group) => {
+				g.opts.highlighted_group = group;
+				g.context.state.highlight_group(group);
+			}
+			Command::Filter(filter) => {
+				g.opts.filter = filter.clone();
+				g.context.state.set_filter(filter);
+			}
+			Command::Time(time) => {
+				g.opts.current_time = time;
+				g.context.state.set_time(time);
+			}
+			Command::Collapse(groups) => g.opts.collapsed = groups,
+			Command::TimeWindow(window) => g.opts.time_window = window,
+			Command::ColorBy(color_by) => {
+				g.opts.color_by = color_by;
+				g.context.state.set_color_by(color_by);
+			}
+			Command::Annotate(ids) => {
+				g.opts.annotated = ids.clone();
+				g.context.annotated.ids = ids;
+			}
+			Command::Fisheye(on) => {
+				g.opts.fisheye_on = on;
+				g.context.state.fisheye_on = on;
+				g.context.state.mark_dirty();
+			}
+			Command::Scale(config) => {
+				g.opts.scale = (*config).clone();
+				g.context.scale = *config;
+				g.context.state.mark_dirty();
+			}
+			Command::Pause(paused) => {
+				g.opts.paused = paused;
+				g.context.state.paused = paused;
+			}
+		}
+	});
+	scope.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+	// The handler lives as long as the worker itself
+	on_message.forget();
+	// Ask the main thread for the canvas
+	reply(&scope, &Reply::Ready);
+}
+
+/// Pause drawing on `canvas` while its rendering context is lost, and pick
+/// up on the restored one.
+fn watch_context(canvas: &OffscreenCanvas, graph: &Rc<RefCell<Option<WorkerGraph>>>) {
+	let (graph_lost, losses) = (graph.clone(), Cell::new(0u32));
+	let on_lost = Closure::<dyn FnMut(web_sys::Event)>::new(move |ev: web_sys::Event| {
+		ev.prevent_default();
+		losses.set(losses.get() + 1);
+		log::warn!(
+			"graph worker canvas lost its rendering context (loss {} on this canvas)",
+			losses.get()
+		);
+		if let Some(ref mut g) = *graph_lost.borrow_mut() {
+			g.lost = true;
+		}
+	});
+	let graph_restored = graph.clone();
+	let on_restored = Closure::<dyn FnMut()>::new(move || {
+		if let Some(ref mut g) = *graph_restored.borrow_mut() {
+			g.lost = false;
+			g.context.restore_context();
+		}
+	});
+	let _ =
+		canvas.add_event_listener_with_callback("contextlost", on_lost.as_ref().unchecked_ref());
+	let _ = canvas
+		.add_event_listener_with_callback("contextrestored", on_restored.as_ref().unchecked_ref());
+	// The canvas lives as long as the worker itself
+	on_lost.forget();
+	on_restored.forget();
+}
+
+/// Post `message` to the main thread.
+fn reply(scope: &DedicatedWorkerGlobalScope, message: &Reply) {
+	if let Err(err) = scope.post_message(&message.encode()) {
+		log::error!("graph worker failed to reply: {err:?}");
+	}
+}
+
+impl Command {
+	fn encode(&self) -> JsValue {
+		let msg = Object::new();
+		match self {
+			Self::Init {
+				canvas,
+				data,
+				width,
+				height,
+				opts,
+			} => {
+				set(&msg, "type", "init");
+				set(&msg, "canvas", canvas.clone());
+				set(&msg, "data", encode_data(data));
+				set(&msg, "width", *width);
+				set(&msg, "height", *height);
+				set(&msg, "opts", encode_options(opts));
+			}
+			Self::Input(input) => {
+				set(&msg, "type", "input");
+				set(&msg, "input", encode_input(input));
+			}
+			Self::Data(data) => {
+				set(&msg, "type", "data");
+				set(&msg, "data", encode_data(data));
+			}
+			Self::Resize { width, height } => {
+				set(&msg, "type", "resize");
+				set(&msg, "width", *width);
+				set(&msg, "height", *height);
+			}
+			Self::Camera(transform) => {
+				set(&msg, "type", "camera");
+				set(&msg, "transform", encode_transform(transform));
+			}
+			Self::Group(group) => {
+				set(&msg, "type", "group");
+				set_opt(&msg, "group", group.map(f64::from));
+			}
+			Self::Filter(filter) => {
+				set(&msg, "type", "filter");
+				set_opt(&msg, "filter", filter.as_ref().map(encode_filter));
+			}
+			Self::Time(time) => {
+				set(&msg, "type", "time");
+				set_opt(&msg, "time", *time);
+			}
+			Self::Collapse(groups) => {
+				set(&msg, "type", "collapse");
+				set(&msg, "groups", encode_groups(groups));
+			}
+			Self::TimeWindow(window) => {
+				set(&msg, "type", "time-window");
+				set_opt(&msg, "window", window.map(encode_window));
+			}
+			Self::ColorBy(color_by) => {
+				set(&msg, "type", "color-by");
+				set_opt(&msg, "colorBy", color_by.and_then(color_by_name));
+			}
+			Self::Fisheye(on) => {
+				set(&msg, "type", "fisheye");
+				set(&msg, "on", *on);
+			}
+			Self::Annotate(ids) => {
+				set(&msg, "type", "annotate");
+				set(&msg, "ids", encode_ids(ids));
+			}
+			Self::Scale(config) => {
+				set(&msg, "type", "scale");
+				set(&msg, "config", encode_scale(config));
+			}
+			Self::Pause(paused) => {
+				set(&msg, "type", "pause");
+				set(&msg, "paused", *paused);
+			}
+		}
+		msg.into()
+	}
+
+	fn decode(msg: &JsValue) -> Option<Self> {
+		Some(match get_str(msg, "type")?.as_str() {
+			"init" => Self::Init {
+				canvas: get(msg, "canvas").dyn_into().ok()?,
+				data: decode_data(&get(msg, "data"))?,
+				width: get_f64(msg, "width")?,
+				height: get_f64(msg, "height")?,
+				opts: Box::new(decode_options(&get(msg, "opts"))?),
+			},
+			"input" => Self::Input(decode_input(&get(msg, "input"))?),
+			"data" => Self::Data(decode_data(&get(msg, "data"))?),
+			"resize" => Self::Resize {
+				width: get_f64(msg, "width")?,
+				height: get_f64(msg, "height")?,
+			},
+			"camera" => Self::Camera(decode_transform(&get(msg, "transform"))?),
+			"group" => Self::Group(get_f64(msg, "group").map(|g| g as u32)),
+			"filter" => Self::Filter(decode_filter(&get(msg, "filter"))),
+			"time" => Self::Time(get_f64(msg, "time")),
+			"collapse" => Self::Collapse(decode_groups(&get(msg, "groups"))),
+			"time-window" => Self::TimeWindow(decode_window(&get(msg, "window"))),
+			"color-by" => Self::ColorBy(color_by_from(get_str(msg, "colorBy"))),
+			"fisheye" => Self::Fisheye(get(msg, "on").is_truthy()),
+			"annotate" => Self::Annotate(decode_ids(&get(msg, "ids"))),
+			"scale" => Self::Scale(Box::new(decode_scale(&get(msg, "config"))?)),
+			"pause" => Self::Pause(get(msg, "paused").is_truthy()),
+			_ => return None,
+		})
+	}
+}
+
+impl Reply {
+	fn encode(&self) -> JsValue {
+		let msg = Object::new();
+		match self {
+			Self::Ready => set(&msg, "type", "ready"),
+			Self::Input(out) => {
+				set(&msg, "type", "input");
+				if let Some(ref t) = out.moved {
+					set(&msg, "moved", encode_transform(t));
+				}
+				if let Some(ref hovered) = out.hovered {
+					let node = hovered.as_ref().map_or(JsValue::NULL, encode_event);
+					set(&msg, "hovered", node);
+				}
+				if let Some(ref event) = out.clicked {
+					set(&msg, "clicked", encode_event(event));
+				}
+				set_opt(&msg, "announce", out.announce.as_deref());
+				set_opt(&msg, "cursor", out.cursor);
+				set_opt(&msg, "expand", out.expand.map(f64::from));
+			}
+			Self::Frame(out) => {
+				set(&msg, "type", "frame");
+				set(&msg, "settled", out.settled);
+				set(&msg, "woke", out.woke);
+				if let Some(ref info) = out.viewport {
+					let viewport = Object::new();
+					set(&viewport, "transform", encode_transform(&info.transform));
+					set(&viewport, "minX", info.min_x);
+					set(&viewport, "minY", info.min_y);
+					set(&viewport, "maxX", info.max_x);
+					set(&viewport, "maxY", info.max_y);
+					set(&msg, "viewport", viewport);
+				}
+				if let Some(ref placed) = out.annotations {
+					set(&msg, "annotations", encode_placements(placed));
+				}
+				// `null` once sampling stops, so it's told apart from no change
+				if let Some(sampled) = out.sampling {
+					let info = sampled
+						.as_ref()
+						.map_or(JsValue::NULL, |s| encode_sampling_info(s).into());
+					set(&msg, "sampling", info);
+				}
+			}
+		}
+		msg.into()
+	}
+
+	fn decode(msg: &JsValue) -> Option<Self> {
+		Some(match get_str(msg, "type")?.as_str() {
+			"ready" => Self::Ready,
+			"input" => Self::Input(Box::new(InputOutcome {
+				moved: decode_transform(&get(msg, "moved")),
+				hovered: has(msg, "hovered").then(|| decode_event(&get(msg, "hovered"))),
+				clicked: decode_event(&get(msg, "clicked")),
+				announce: get_str(msg, "announce"),
+				cursor: get_str(msg, "cursor").and_then(|c| {
+					["grab", "grabbing", "pointer"]
+						.into_iter()
+						.find(|known| *known == c)
+				}),
+				expand: get_f64(msg, "expand").map(|g| g as u32),
+			})),
+			"frame" => {
+				let viewport = get(msg, "viewport");
+				Self::Frame(FrameOutcome {
+					settled: get(msg, "settled").is_truthy(),
+					woke: get(msg, "woke").is_truthy(),
+					viewport: decode_transform(&get(&viewport, "transform")).and_then(
+						|transform| {
+							Some(ViewportInfo {
+								transform,
+								min_x: get_f64(&viewport, "minX")?,
+								min_y: get_f64(&viewport, "minY")?,
+								max_x: get_f64(&viewport, "maxX")?,
+								max_y: get_f64(&viewport, "maxY")?,
+							})
+						},
+					),
+					annotations: has(msg, "annotations")
+						.then(|| decode_placements(&get(msg, "annotations"))),
+					repainted: false,
+					sampling: has(msg, "sampling")
+						.then(|| decode_sampling_info(&get(msg, "sampling"))),
+				})
+			}
+			_ => return None,
+		})
+	}
+}
+
+fn encode_input(input: &Input) -> Object {
+	let obj = Object::new();
+	let (kind, x, y) = match *input {
+		Input::Down {
+			x,
+			y,
+			shift,
+			button,
+		} => {
+			set(&obj, "shift", shift);
+			set(&obj, "button", button);
+			("down", x, y)
+		}
+		Input::Move { x, y } => ("move", x, y),
+		Input::Up { x, y } => ("up", x, y),
+		Input::Leave => ("leave", 0.0, 0.0),
+		Input::DoubleClick { x, y } => ("dblclick", x, y),
+		Input::Wheel {
+			x,
+			y,
+			delta_x,
+			delta_y,
+			zoom,
+		} => {
+			set(&obj, "deltaX", delta_x);
+			set(&obj, "deltaY", delta_y);
+			set(&obj, "zoom", zoom);
+			("wheel", x, y)
+		}
+		Input::Focus { dx, dy } => ("focus", dx, dy),
+		Input::Activate => ("activate", 0.0, 0.0),
+		Input::Blur => ("blur", 0.0, 0.0),
+		Input::ToggleFisheye => ("toggle-fisheye", 0.0, 0.0),
+		Input::Undo => ("undo", 0.0, 0.0),
+		Input::Redo => ("redo", 0.0, 0.0),
+	};
+	set(&obj, "kind", kind);
+	set(&obj, "x", x);
+	set(&obj, "y", y);
+	obj
+}
+
+fn decode_input(obj: &JsValue) -> Option<Input> {
+	let (x, y) = (get_f64(obj, "x")?, get_f64(obj, "y")?);
+	Some(match get_str(obj, "kind")?.as_str() {
+		"down" => Input::Down {
+			x,
+			y,
+			shift: get(obj, "shift").is_truthy(),
+			button: get_f64(obj, "button").unwrap_or(0.0) as i16,
+		},
+		"move" => Input::Move { x, y },
+		"up" => Input::Up { x, y },
+		"leave" => Input::Leave,
+		"dblclick" => Input::DoubleClick { x, y },
+		"wheel" => Input::Wheel {
+			x,
+			y,
+			delta_x: get_f64(obj, "deltaX")?,
+			delta_y: get_f64(obj, "deltaY")?,
+			zoom: get(obj, "zoom").is_truthy(),
+		},
+		"focus" => Input::Focus { dx: x, dy: y },
+		"activate" => Input::Activate,
+		"blur" => Input::Blur,
+		"toggle-fisheye" => Input::ToggleFisheye,
+		"undo" => Input::Undo,
+		"redo" => Input::Redo,
+		_ => return None,
+	})
+}
+
+fn encode_data(data: &GraphData) -> Object {
+	let nodes: Array = data
+		.nodes
+		.iter()
+		.map(|n| {
+			let obj = Object::new();
+			set(&obj, "id", n.id.as_str());
+			set_opt(&obj, "label", n.label.as_deref());
+			set_opt(&obj, "color", n.color.as_deref());
+			set_opt(&obj, "borderColor", n.border_color.as_deref());
+			set_opt(&obj, "borderWidth", n.border_width);
+			set_opt(&obj, "glowColor", n.glow_color.as_deref());
+			set_opt(&obj, "glow", n.glow);
+			set_opt(&obj, "group", n.group.map(f64::from));
+			set_opt(&obj, "value", n.value);
+			if let Some((x, y)) = n.position {
+				set(&obj, "x", x);
+				set(&obj, "y", y);
+			}
+			if let Some(ref segments) = n.segments {
+				set(&obj, "segments", encode_segments(segments));
+			}
+			set_opt(&obj, "badge", n.badge.as_deref());
+			set_opt(&obj, "time", n.time);
+			set(&obj, "metadata", encode_metadata(&n.metadata));
+			JsValue::from(obj)
+		})
+		.collect();
+	let links: Array = data
+		.links
+		.iter()
+		.map(|l| {
+			let obj = Object::new();
+			set(&obj, "source", l.source.as_str());
+			set(&obj, "target", l.target.as_str());
+			set_opt(
+				&obj,
+				"style",
+				l.style.map(|s| match s {
+					LinkStyle::Solid => "solid",
+					LinkStyle::Dashed => "dashed",
+					LinkStyle::Dotted => "dotted",
+				}),
+			);
+			set_opt(&obj, "weight", l.weight);
+			set_opt(&obj, "curvature", l.curvature);
+			set_opt(&obj, "time", l.time);
+			set(&obj, "directed", l.directed);
+			JsValue::from(obj)
+		})
+		.collect();
+	let obj = Object::new();
+	set(&obj, "nodes", nodes);
+	set(&obj, "links", links);
+	obj
+}
+
+fn decode_data(obj: &JsValue) -> Option<GraphData> {
+	let nodes = Array::from(&get(obj, "nodes"))
+		.iter()
+		.map(|n| {
+			Some(GraphNode {
+				id: get_str(&n, "id")?,
+				label: get_str(&n, "label"),
+				color: get_str(&n, "color"),
+				border_color: get_str(&n, "borderColor"),
+				border_width: get_f64(&n, "borderWidth"),
+				glow_color: get_str(&n, "glowColor"),
+				glow: get_f64(&n, "glow"),
+				group: get_f64(&n, "group").map(|g| g as u32),
+				value: get_f64(&n, "value"),
+				position: get_f64(&n, "x").zip(get_f64(&n, "y")),
+				segments: has(&n, "segments").then(|| decode_segments(&get(&n, "segments"))),
+				badge: get_str(&n, "badge"),
+				time: get_f64(&n, "time"),
+				metadata: decode_metadata(&get(&n, "metadata")),
+			})
+		})
+		.collect::<Option<_>>()?;
+	let links = Array::from(&get(obj, "links"))
+		.iter()
+		.map(|l| {
+			Some(GraphLink {
+				source: get_str(&l, "source")?,
+				target: get_str(&l, "target")?,
+				style: get_str(&l, "style").and_then(|s| match s.as_str() {
+					"solid" => Some(LinkStyle::Solid),
+					"dashed" => Some(LinkStyle::Dashed),
+					"dotted" => Some(LinkStyle::Dotted),
+					_ => None,
+				}),
+				weight: get_f64(&l, "weight"),
+				curvature: get_f64(&l, "curvature"),
+				time: get_f64(&l, "time"),
+				directed: get(&l, "directed").is_truthy(),
+			})
+		})
+		.collect::<Option<_>>()?;
+	Some(GraphData { nodes, links })
+}
+
+fn encode_event(event: &NodeEvent) -> JsValue {
+	let obj = Object::new();
+	set(&obj, "id", event.id.as_str());
+	set_opt(&obj, "label", event.label.as_deref());
+	set_opt(&obj, "group", event.group.map(f64::from));
+	set_opt(&obj, "community", event.community.map(f64::from));
+	set_opt(&obj, "metric", event.metric);
+	set(&obj, "metadata", encode_metadata(&event.metadata));
+	obj.into()
+}
+
+fn decode_event(obj: &JsValue) -> Option<NodeEvent> {
+	Some(NodeEvent {
+		id: get_str(obj, "id")?,
+		label: get_str(obj, "label"),
+		group: get_f64(obj, "group").map(|g| g as u32),
+		community: get_f64(obj, "community").map(|c| c as u32),
+		metric: get_f64(obj, "metric"),
+		metadata: decode_metadata(&get(obj, "metadata")),
+	})
+}
+
+fn encode_metadata(metadata: &HashMap<String, String>) -> Object {
+	let obj = Object::new();
+	for (key, value) in metadata {
+		set(&obj, key, value.as_str());
+	}
+	obj
+}
+
+fn decode_metadata(obj: &JsValue) -> HashMap<String, String> {
+	if !obj.is_object() {
+		return HashMap::new();
+	}
+	Object::entries(obj.unchecked_ref())
+		.iter()
+		.filter_map(|entry| {
+			let pair = Array::from(&entry);
+			Some((pair.get(0).as_string()?, pair.get(1).as_string()?))
+		})
+		.collect()
+}
+
+fn encode_segments(segments: &[(f64, String)]) -> Array {
+	segments
+		.iter()
+		.map(|(value, color)| JsValue::from(Array::of2(&(*value).into(), &color.into())))
+		.collect()
+}
+
+fn decode_segments(array: &JsValue) -> Vec<(f64, String)> {
+	Array::from(array)
+		.iter()
+		.filter_map(|entry| {
+			let pair = Array::from(&entry);
+			Some((pair.get(0).as_f64()?, pair.get(1).as_string()?))
+		})
+		.collect()
+}
+
+fn encode_transform(t: &ViewTransform) -> Object {
+	let obj = Object::new();
+	set(&obj, "x", t.x);
+	set(&obj, "y", t.y);
+	set(&obj, "k", t.k);
+	obj
+}
+
+fn decode_transform(obj: &JsValue) -> Option<ViewTransform> {
+	Some(ViewTransform {
+		x: get_f64(obj, "x")?,
+		y: get_f64(obj, "y")?,
+		k: get_f64(obj, "k")?,
+	})
+}
+
+fn encode_groups(groups: &HashSet<u32>) -> Array {
+	groups
+		.iter()
+		.map(|&g| JsValue::from(f64::from(g)))
+		.collect()
+}
+
+fn decode_groups(array: &JsValue) -> HashSet<u32> {
+	if !array.is_object() {
+		return HashSet::new();
+	}
+	Array::from(array)
+		.iter()
+		.filter_map(|g| g.as_f64().map(|g| g as u32))
+		.collect()
+}
+
+fn encode_mouse_bindings(bindings: &MouseBindings) -> Object {
+	let name = |action| match action {
+		PointerAction::Pan => "pan",
+		PointerAction::MoveNode => "move-node",
+		PointerAction::Select => "select",
+		PointerAction::Ignore => "ignore",
+	};
+	let obj = Object::new();
+	for (button, binding) in [
+		("left", bindings.left),
+		("middle", bindings.middle),
+		("right", bindings.right),
+	] {
+		let entry = Object::new();
+		set(&entry, "background", name(binding.background));
+		set(&entry, "node", name(binding.node));
+		set(&obj, button, entry);
+	}
+	obj
+}
+
+fn decode_mouse_bindings(obj: &JsValue) -> MouseBindings {
+	let action = |entry: &JsValue, key| {
+		Some(match get_str(entry, key)?.as_str() {
+			"pan" => PointerAction::Pan,
+			"move-node" => PointerAction::MoveNode,
+			"select" => PointerAction::Select,
+			_ => PointerAction::Ignore,
+		})
+	};
+	let binding = |button, default: ButtonBinding| {
+		let entry = get(obj, button);
+		ButtonBinding {
+			background: action(&entry, "background").unwrap_or(default.background),
+			node: action(&entry, "node").unwrap_or(default.node),
+		}
+	};
+	let defaults = MouseBindings::default();
+	MouseBindings {
+		left: binding("left", defaults.left),
+		middle: binding("middle", defaults.middle),
+		right: binding("right", defaults.right),
+	}
+}
+
+fn encode_window((start, end): (f64, f64)) -> Array {
+	Array::of2(&start.into(), &end.into())
+}
+
+fn decode_window(value: &JsValue) -> Option<(f64, f64)> {
+	let pair = value.dyn_ref::<Array>()?;
+	Some((pair.get(0).as_f64()?, pair.get(1).as_f64()?))
+}
+
+fn encode_filter(filter: &Filter) -> Object {
+	let obj = Object::new();
+	let nodes: Array = filter
+		.hidden_nodes
+		.iter()
+		.map(|id| JsValue::from(id.as_str()))
+		.collect();
+	set(&obj, "hiddenNodes", nodes);
+	set(&obj, "hiddenGroups", encode_groups(&filter.hidden_groups));
+	set(&obj, "residualAlpha", filter.residual_alpha);
+	set(&obj, "removeFromSimulation", filter.remove_from_simulation);
+	obj
+}
+
+fn decode_filter(obj: &JsValue) -> Option<Filter> {
+	if !obj.is_object() {
+		return None;
+	}
+	Some(Filter {
+		hidden_nodes: Array::from(&get(obj, "hiddenNodes"))
+			.iter()
+			.filter_map(|id| id.as_string())
+			.collect(),
+		hidden_groups: decode_groups(&get(obj, "hiddenGroups")),
+		residual_alpha: get_f64(obj, "residualAlpha").unwrap_or(0.0),
+		remove_from_simulation: get(obj, "removeFromSimulation").is_truthy(),
+	})
+}
+
+fn encode_options(opts: &GraphOptions) -> Object {
+	let obj = Object::new();
+	// Themes travel by preset name; see `can_send`
+	set(&obj, "theme", opts.theme.name);
+	set(&obj, "bounded", opts.bounded);
+	set(&obj, "boundMargin", opts.bound_margin);
+	set(&obj, "colorByCommunity", opts.color_by_community);
+	set(
+		&obj,
+		"seedStrategy",
+		match opts.seed_strategy {
+			SeedStrategy::Circle => "circle",
+			SeedStrategy::Random => "random",
+			SeedStrategy::Grid => "grid",
+			SeedStrategy::FromData => "from-data",
+		},
+	);
+	// As a string: a u64 doesn't fit in a JS number
+	set_opt(
+		&obj,
+		"layoutSeed",
+		opts.layout_seed.map(|s| s.to_string()).as_deref(),
+	);
+	set_opt(&obj, "snapGrid", opts.snap_grid);
+	set(&obj, "draggable", opts.draggable);
+	set(
+		&obj,
+		"mouseBindings",
+		encode_mouse_bindings(&opts.mouse_bindings),
+	);
+	set(&obj, "restSpeed", opts.rest_speed);
+	set(&obj, "settleThreshold", opts.settle_threshold);
+	set(&obj, "settleDelay", opts.settle_delay);
+	set(&obj, "alignGuides", opts.align_guides);
+	set(&obj, "undoLimit", opts.undo_limit as f64);
+	set(&obj, "annotated", encode_ids(&opts.annotated));
+	set(
+		&obj,
+		"offscreenAnnotations",
+		match opts.offscreen_annotations {
+			OffscreenAnnotations::Clamp => "clamp",
+			OffscreenAnnotations::Hide => "hide",
+		},
+	);
+	set_opt(
+		&obj,
+		"highlightedGroup",
+		opts.highlighted_group.map(f64::from),
+	);
+	let groups: Array = opts
+		.groups
+		.iter()
+		.map(|(group, name)| {
+			JsValue::from(Array::of2(&f64::from(*group).into(), &name.as_str().into()))
+		})
+		.collect();
+	set(&obj, "groups", groups);
+	set(&obj, "legend", opts.legend);
+	set(&obj, "legendCorner", corner_name(opts.legend_corner));
+	set(&obj, "minimap", opts.minimap);
+	set(&obj, "minimapCorner", corner_name(opts.minimap_corner));
+	set(&obj, "minimapSize", opts.minimap_size);
+	set(&obj, "showStats", opts.show_stats);
+	set(&obj, "edgeBundling", opts.edge_bundling);
+	set(&obj, "groupLabels", opts.group_labels);
+	if let Some(ref t) = opts.transform {
+		set(&obj, "transform", encode_transform(t));
+	}
+	set_opt(&obj, "filter", opts.filter.as_ref().map(encode_filter));
+	set_opt(&obj, "currentTime", opts.current_time);
+	set(&obj, "collapsed", encode_groups(&opts.collapsed));
+	set_opt(&obj, "timeWindow", opts.time_window.map(encode_window));
+	set_opt(
+		&obj,
+		"sizeByDegree",
+		opts.size_by_degree.as_ref().map(encode_degree_sizing),
+	);
+	set_opt(&obj, "colorBy", opts.color_by.and_then(color_by_name));
+	if let Some(ref ramp) = opts.color_ramp {
+		let stops: Array = ramp
+			.stops
+			.iter()
+			.map(|stop| JsValue::from(stop.to_css()))
+			.collect();
+		set(&obj, "colorRamp", stops);
+	}
+	set(
+		&obj,
+		"heatLegendCorner",
+		corner_name(opts.heat_legend_corner),
+	);
+	set_opt(&obj, "fisheye", opts.fisheye.as_ref().map(encode_fisheye));
+	set(&obj, "fisheyeOn", opts.fisheye_on);
+	set(&obj, "scale", encode_scale(&opts.scale));
+	set(&obj, "paused", opts.paused);
+	set_opt(
+		&obj,
+		"sampling",
+		opts.sampling.as_ref().map(encode_sampling),
+	);
+	obj
+}
+
+fn decode_options(obj: &JsValue) -> Option<GraphOptions> {
+	Some(GraphOptions {
+		theme: theme_preset(&get_str(obj, "theme")?),
+		bounded: get(obj, "bounded").is_truthy(),
+		bound_margin: get_f64(obj, "boundMargin")?,
+		color_by_community: get(obj, "colorByCommunity").is_truthy(),
+		seed_strategy: match get_str(obj, "seedStrategy")?.as_str() {
+			"random" => SeedStrategy::Random,
+			"grid" => SeedStrategy::Grid,
+			"from-data" => SeedStrategy::FromData,
+			_ => SeedStrategy::Circle,
+		},
+		layout_seed: get_str(obj, "layoutSeed").and_then(|s| s.parse().ok()),
+		snap_grid: get_f64(obj, "snapGrid"),
+		draggable: get(obj, "draggable").is_truthy(),
+		mouse_bindings: decode_mouse_bindings(&get(obj, "mouseBindings")),
+		rest_speed: get_f64(obj, "restSpeed")?,
+		settle_threshold: get_f64(obj, "settleThreshold")?,
+		settle_delay: get_f64(obj, "settleDelay")?,
+		align_guides: get(obj, "alignGuides").is_truthy(),
+		undo_limit: get_f64(obj, "undoLimit")? as usize,
+		annotated: decode_ids(&get(obj, "annotated")),
+		offscreen_annotations: match get_str(obj, "offscreenAnnotations")?.as_str() {
+			"hide" => OffscreenAnnotations::Hide,
+			_ => OffscreenAnnotations::Clamp,
+		},
+		highlighted_group: get_f64(obj, "highlightedGroup").map(|g| g as u32),
+		groups: Array::from(&get(obj, "groups"))
+			.iter()
+			.filter_map(|pair| {
+				let pair = Array::from(&pair);
+				Some((pair.get(0).as_f64()? as u32, pair.get(1).as_string()?))
+			})
+			.collect(),
+		legend: get(obj, "legend").is_truthy(),
+		legend_corner: corner_from(&get_str(obj, "legendCorner")?),
+		minimap: get(obj, "minimap").is_truthy(),
+		minimap_corner: corner_from(&get_str(obj, "minimapCorner")?),
+		minimap_size: get_f64(obj, "minimapSize")?,
+		show_stats: get(obj, "showStats").is_truthy(),
+		edge_bundling: get(obj, "edgeBundling").is_truthy(),
+		group_labels: get(obj, "groupLabels").is_truthy(),
+		transform: decode_transform(&get(obj, "transform")),
+		filter: decode_filter(&get(obj, "filter")),
+		current_time: get_f64(obj, "currentTime"),
+		collapsed: decode_groups(&get(obj, "collapsed")),
+		time_window: decode_window(&get(obj, "timeWindow")),
+		size_by_degree: decode_degree_sizing(&get(obj, "sizeByDegree")),
+		color_by: color_by_from(get_str(obj, "colorBy")),
+		color_ramp: get(obj, "colorRamp").is_object().then(|| Colormap {
+			stops: Array::from(&get(obj, "colorRamp"))
+				.iter()
+				.filter_map(|stop| stop.as_string())
+				.map(|stop| parse_color(&stop))
+				.collect(),
+		}),
+		heat_legend_corner: corner_from(&get_str(obj, "heatLegendCorner")?),
+		fisheye: decode_fisheye(&get(obj, "fisheye")),
+		fisheye_on: get(obj, "fisheyeOn").is_truthy(),
+		scale: decode_scale(&get(obj, "scale")).unwrap_or_default(),
+		paused: get(obj, "paused").is_truthy(),
+		sampling: decode_sampling(&get(obj, "sampling")),
+	})
+}
+
+fn encode_sampling(sampling: &NodeSampling) -> Object {
+	let obj = Object::new();
+	set(&obj, "maxNodes", sampling.max_nodes as f64);
+	let strategy = match sampling.strategy {
+		SampleStrategy::Important => "important",
+		SampleStrategy::Stride => "stride",
+	};
+	set(&obj, "strategy", strategy);
+	let unsampled = match sampling.unsampled {
+		UnsampledNodes::Density => "density",
+		UnsampledNodes::Skip => "skip",
+	};
+	set(&obj, "unsampled", unsampled);
+	obj
+}
+
+fn decode_sampling(obj: &JsValue) -> Option<NodeSampling> {
+	Some(NodeSampling {
+		max_nodes: get_f64(obj, "maxNodes")? as usize,
+		strategy: match get_str(obj, "strategy")?.as_str() {
+			"stride" => SampleStrategy::Stride,
+			_ => SampleStrategy::Important,
+		},
+		unsampled: match get_str(obj, "unsampled")?.as_str() {
+			"skip" => UnsampledNodes::Skip,
+			_ => UnsampledNodes::Density,
+		},
+	})
+}
+
+fn encode_sampling_info(info: &SamplingInfo) -> Object {
+	let obj = Object::new();
+	set(&obj, "drawn", info.drawn as f64);
+	set(&obj, "total", info.total as f64);
+	obj
+}
+
+fn decode_sampling_info(obj: &JsValue) -> Option<SamplingInfo> {
+	Some(SamplingInfo {
+		drawn: get_f64(obj, "drawn")? as usize,
+		total: get_f64(obj, "total")? as usize,
+	})
+}
+
+fn encode_degree_sizing(sizing: &DegreeSizing) -> Object {
+	let obj = Object::new();
+	let curve = match sizing.curve {
+		DegreeCurve::Linear => "linear",
+		DegreeCurve::Sqrt => "sqrt",
+		DegreeCurve::Log => "log",
+	};
+	set(&obj, "curve", curve);
+	set(&obj, "min", sizing.min);
+	set(&obj, "max", sizing.max);
+	obj
+}
+
+fn decode_degree_sizing(obj: &JsValue) -> Option<DegreeSizing> {
+	Some(DegreeSizing {
+		curve: match get_str(obj, "curve")?.as_str() {
+			"linear" => DegreeCurve::Linear,
+			"log" => DegreeCurve::Log,
+			_ => DegreeCurve::Sqrt,
+		},
+		min: get_f64(obj, "min")?,
+		max: get_f64(obj, "max")?,
+	})
+}
+
+fn encode_ids(ids: &[String]) -> Array {
+	ids.iter().map(|id| JsValue::from(id.as_str())).collect()
+}
+
+fn decode_ids(value: &JsValue) -> Vec<String> {
+	if !value.is_object() {
+		return Vec::new();
+	}
+	Array::from(value)
+		.iter()
+		.filter_map(|id| id.as_string())
+		.collect()
+}
+
+fn encode_placements(placed: &HashMap<String, Placement>) -> Array {
+	placed
+		.iter()
+		.map(|(id, p)| {
+			let obj = Object::new();
+			set(&obj, "id", id.as_str());
+			set(&obj, "x", p.x);
+			set(&obj, "y", p.y);
+			set_opt(&obj, "pointing", p.pointing);
+			JsValue::from(obj)
+		})
+		.collect()
+}
+
+fn decode_placements(value: &JsValue) -> HashMap<String, Placement> {
+	Array::from(value)
+		.iter()
+		.filter_map(|obj| {
+			let placement = Placement {
+				x: get_f64(&obj, "x")?,
+				y: get_f64(&obj, "y")?,
+				pointing: get_f64(&obj, "pointing"),
+			};
+			Some((get_str(&obj, "id")?, placement))
+		})
+		.collect()
+}
+
+fn encode_fisheye(fisheye: &Fisheye) -> Object {
+	let obj = Object::new();
+	set(&obj, "radius", fisheye.radius);
+	set(&obj, "magnification", fisheye.magnification);
+	obj
+}
+
+fn decode_fisheye(obj: &JsValue) -> Option<Fisheye> {
+	Some(Fisheye {
+		radius: get_f64(obj, "radius")?,
+		magnification: get_f64(obj, "magnification")?,
+	})
+}
+
+fn encode_scale(config: &ScaleConfig) -> Object {
+	let node = Object::new();
+	let n = &config.node;
+	set(&node, "radius", n.radius);
+	set(
+		&node,
+		"radiusBehavior",
+		encode_scale_behavior(&n.radius_behavior),
+	);
+	set(&node, "minPx", n.min_px);
+	set(&node, "hitPadding", n.hit_padding);
+	set(&node, "labelSize", n.label_size);
+	set(&node, "labelMinK", n.label_min_k);
+	set(&node, "labelImportantK", n.label_important_k);
+	set(&node, "labelAllK", n.label_all_k);
+	set(&node, "labelFadeWidth", n.label_fade_width);
+	set(&node, "labelImportantSize", n.label_important_size);
+
+	let edge = Object::new();
+	let e = &config.edge;
+	set(&edge, "lineWidth", e.line_width);
+	set(&edge, "dashPattern", encode_window(e.dash_pattern));
+	set(&edge, "dotPattern", encode_window(e.dot_pattern));
+	set(&edge, "flowSpeed", e.flow_speed);
+	set(
+		&edge,
+		"dashAlphaBehavior",
+		encode_alpha_behavior(&e.dash_alpha_behavior),
+	);
+
+	let arrow = Object::new();
+	let a = &config.arrow;
+	set(&arrow, "size", a.size);
+	set(
+		&arrow,
+		"sizeBehavior",
+		encode_scale_behavior(&a.size_behavior),
+	);
+	set(
+		&arrow,
+		"alphaBehavior",
+		encode_alpha_behavior(&a.alpha_behavior),
+	);
+	set(&arrow, "cullAlpha", a.cull_alpha);
+
+	let glow = Object::new();
+	let g = &config.glow;
+	set(&glow, "hoveredRadius", g.hovered_radius);
+	set(&glow, "neighborRadius", g.neighbor_radius);
+	set(&glow, "ringWidth", g.ring_width);
+	set(&glow, "ringOffset", g.ring_offset);
+
+	let lod = Object::new();
+	let l = &config.lod;
+	set(&lod, "maxFullNodes", l.max_full_nodes as f64);
+	set(&lod, "minFullK", l.min_full_k);
+	set(&lod, "hysteresis", l.hysteresis);
+	set(&lod, "maxShadowNodes", l.max_shadow_nodes as f64);
+	set(&lod, "minBadgeK", l.min_badge_k);
+
+	let obj = Object::new();
+	set(&obj, "node", node);
+	set(&obj, "edge", edge);
+	set(&obj, "arrow", arrow);
+	set(&obj, "glow", glow);
+	set(&obj, "lod", lod);
+	obj
+}
+
+fn decode_scale(obj: &JsValue) -> Option<ScaleConfig> {
+	let (node, edge, arrow, glow, lod) = (
+		get(obj, "node"),
+		get(obj, "edge"),
+		get(obj, "arrow"),
+		get(obj, "glow"),
+		get(obj, "lod"),
+	);
+	Some(ScaleConfig {
+		node: NodeScaleConfig {
+			radius: get_f64(&node, "radius")?,
+			radius_behavior: decode_scale_behavior(&get(&node, "radiusBehavior"))?,
+			min_px: get_f64(&node, "minPx")?,
+			hit_padding: get_f64(&node, "hitPadding")?,
+			label_size: get_f64(&node, "labelSize")?,
+			label_min_k: get_f64(&node, "labelMinK")?,
+			label_important_k: get_f64(&node, "labelImportantK")?,
+			label_all_k: get_f64(&node, "labelAllK")?,
+			label_fade_width: get_f64(&node, "labelFadeWidth")?,
+			label_important_size: get_f64(&node, "labelImportantSize")?,
+		},
+		edge: EdgeScaleConfig {
+			line_width: get_f64(&edge, "lineWidth")?,
+			dash_pattern: decode_window(&get(&edge, "dashPattern"))?,
+			dot_pattern: decode_window(&get(&edge, "dotPattern"))?,
+			flow_speed: get_f64(&edge, "flowSpeed")?,
+			dash_alpha_behavior: decode_alpha_behavior(&get(&edge, "dashAlphaBehavior"))?,
+		},
+		arrow: ArrowScaleConfig {
+			size: get_f64(&arrow, "size")?,
+			size_behavior: decode_scale_behavior(&get(&arrow, "sizeBehavior"))?,
+			alpha_behavior: decode_alpha_behavior(&get(&arrow, "alphaBehavior"))?,
+			cull_alpha: get_f64(&arrow, "cullAlpha")?,
+		},
+		glow: GlowScaleConfig {
+			hovered_radius: get_f64(&glow, "hoveredRadius")?,
+			neighbor_radius: get_f64(&glow, "neighborRadius")?,
+			ring_width: get_f64(&glow, "ringWidth")?,
+			ring_offset: get_f64(&glow, "ringOffset")?,
+		},
+		lod: LodConfig {
+			max_full_nodes: get_f64(&lod, "maxFullNodes")? as usize,
+			min_full_k: get_f64(&lod, "minFullK")?,
+			hysteresis: get_f64(&lod, "hysteresis")?,
+			max_shadow_nodes: get_f64(&lod, "maxShadowNodes")? as usize,
+			min_badge_k: get_f64(&lod, "minBadgeK")?,
+		},
+	})
+}
+
+fn encode_scale_behavior(behavior: &ScaleBehavior) -> Object {
+	let obj = Object::new();
+	match behavior {
+		ScaleBehavior::World => set(&obj, "kind", "world"),
+		ScaleBehavior::Screen => set(&obj, "kind", "screen"),
+		ScaleBehavior::Clamped {
+			min_screen,
+			max_screen,
+		} => {
+			set(&obj, "kind", "clamped");
+			set(&obj, "minScreen", *min_screen);
+			set(&obj, "maxScreen", *max_screen);
+		}
+	}
+	obj
+}
+
+fn decode_scale_behavior(obj: &JsValue) -> Option<ScaleBehavior> {
+	Some(match get_str(obj, "kind")?.as_str() {
+		"world" => ScaleBehavior::World,
+		"screen" => ScaleBehavior::Screen,
+		"clamped" => ScaleBehavior::Clamped {
+			min_screen: get_f64(obj, "minScreen")?,
+			max_screen: get_f64(obj, "maxScreen")?,
+		},
+		_ => return None,
+	})
+}
+
+fn encode_alpha_behavior(behavior: &AlphaBehavior) -> Object {
+	let obj = Object::new();
+	match behavior {
+		AlphaBehavior::Constant => set(&obj, "kind", "constant"),
+		AlphaBehavior::ScaleWithZoom => set(&obj, "kind", "scale-with-zoom"),
+		AlphaBehavior::Fade {
+			zero_alpha_k,
+			full_alpha_k,
+		} => {
+			set(&obj, "kind", "fade");
+			set(&obj, "zeroAlphaK", *zero_alpha_k);
+			set(&obj, "fullAlphaK", *full_alpha_k);
+		}
+	}
+	obj
+}
+
+fn decode_alpha_behavior(obj: &JsValue) -> Option<AlphaBehavior> {
+	Some(match get_str(obj, "kind")?.as_str() {
+		"constant" => AlphaBehavior::Constant,
+		"scale-with-zoom" => AlphaBehavior::ScaleWithZoom,
+		"fade" => AlphaBehavior::Fade {
+			zero_alpha_k: get_f64(obj, "zeroAlphaK")?,
+			full_alpha_k: get_f64(obj, "fullAlphaK")?,
+		},
+		_ => return None,
+	})
+}
+
+fn corner_name(corner: Corner) -> &'static str {
+	match corner {
+		Corner::TopLeft => "top-left",
+		Corner::TopRight => "top-right",
+		Corner::BottomLeft => "bottom-left",
+		Corner::BottomRight => "bottom-right",
+	}
+}
+
+fn corner_from(name: &str) -> Corner {
+	match name {
+		"top-left" => Corner::TopLeft,
+		"bottom-left" => Corner::BottomLeft,
+		"bottom-right" => Corner::BottomRight,
+		_ => Corner::TopRight,
+	}
+}
+
+/// A custom metric is a function pointer, so it can't cross to the worker.
+/// The built-in theme called `name`, or the default one.
+fn theme_preset(name: &str) -> Theme {
+	match name {
+		"midnight" => Theme::midnight(),
+		"ember" => Theme::ember(),
+		"deep_sea" => Theme::deep_sea(),
+		"minimal" => Theme::minimal(),
+		_ => Theme::default(),
+	}
+}
+
+fn color_by_name(color_by: ColorBy) -> Option<&'static str> {
+	match color_by {
+		ColorBy::Degree => Some("degree"),
+		ColorBy::Betweenness => Some("betweenness"),
+		ColorBy::Custom(_) => None,
+	}
+}
+
+fn color_by_from(name: Option<String>) -> Option<ColorBy> {
+	match name?.as_str() {
+		"degree" => Some(ColorBy::Degree),
+		"betweenness" => Some(ColorBy::Betweenness),
+		_ => None,
+	}
+}
+
+fn set(obj: &Object, key: &str, value: impl Into<JsValue>) {
+	let _ = Reflect::set(obj, &JsValue::from_str(key), &value.into());
+}
+
+/// Set `key` only when `value` is present, so absent fields stay absent.
+fn set_opt<T: Into<JsValue>>(obj: &Object, key: &str, value: Option<T>) {
+	if let Some(value) = value {
+		set(obj, key, value);
+	}
+}
+
+fn has(obj: &JsValue, key: &str) -> bool {
+	obj.is_object() && Reflect::has(obj, &JsValue::from_str(key)).unwrap_or(false)
+}
+
+fn get(obj: &JsValue, key: &str) -> JsValue {
+	if !obj.is_object() {
+		return JsValue::UNDEFINED;
+	}
+	Reflect::get(obj, &JsValue::from_str(key)).unwrap_or(JsValue::UNDEFINED)
+}
+
+fn get_f64(obj: &JsValue, key: &str) -> Option<f64> {
+	get(obj, key).as_f64()
+}
+
+fn get_str(obj: &JsValue, key: &str) -> Option<String> {
+	get(obj, key).as_string()
+}
+
+#[cfg(test)]
+mod tests {
+	// The codecs go through `js_sys`, so their tests only run on wasm, e.g.
+	// with `cargo test --target wasm32-unknown-unknown` and
+	// `wasm-bindgen-test-runner` as the target's runner
+	#[cfg(target_arch = "wasm32")]
+	use wasm_bindgen_test::wasm_bindgen_test;
+
+	use super::*;
+	#[cfg(target_arch = "wasm32")]
+	use crate::components::force_graph::types::fixtures::{link, node};
+
+	fn options() -> GraphOptions {
+		let theme = Theme::ember();
+		GraphOptions {
+			color_ramp: Some(theme.colormap.clone()),
+			theme,
+			bounded: true,
+			bound_margin: 12.0,
+			color_by_community: true,
+			seed_strategy: SeedStrategy::Grid,
+			layout_seed: Some(7),
+			snap_grid: Some(20.0),
+			draggable: false,
+			mouse_bindings: MouseBindings::default(),
+			rest_speed: 3.0,
+			settle_threshold: 0.5,
+			settle_delay: 1.5,
+			align_guides: true,
+			undo_limit: 40,
+			annotated: vec!["a".into()],
+			offscreen_annotations: OffscreenAnnotations::Hide,
+			highlighted_group: Some(2),
+			groups: vec![(2, "Two".into())],
+			legend: true,
+			legend_corner: Corner::BottomLeft,
+			minimap: true,
+			minimap_corner: Corner::TopLeft,
+			minimap_size: 160.0,
+			show_stats: true,
+			edge_bundling: true,
+			group_labels: true,
+			transform: Some(ViewTransform {
+				x: 10.0,
+				y: -4.0,
+				k: 2.0,
+			}),
+			filter: Some(Filter {
+				hidden_nodes: HashSet::from(["b".into()]),
+				hidden_groups: HashSet::from([3]),
+				residual_alpha: 0.25,
+				remove_from_simulation: true,
+			}),
+			current_time: Some(4.0),
+			collapsed: HashSet::from([5]),
+			time_window: Some((1.0, 9.0)),
+			size_by_degree: Some(DegreeSizing {
+				curve: DegreeCurve::Log,
+				min: 2.0,
+				max: 9.0,
+			}),
+			color_by: Some(ColorBy::Betweenness),
+			heat_legend_corner: Corner::TopRight,
+			fisheye: Some(Fisheye::default()),
+			fisheye_on: true,
+			scale: ScaleConfig::screen_sized(),
+			paused: true,
+			sampling: Some(NodeSampling {
+				max_nodes: 500,
+				strategy: SampleStrategy::Stride,
+				unsampled: UnsampledNodes::Skip,
+			}),
+		}
+	}
+
+	#[cfg(target_arch = "wasm32")]
+	#[wasm_bindgen_test]
+	fn data_round_trips() {
+		let data = GraphData {
+			nodes: vec![
+				GraphNode {
+					label: Some("A".into()),
+					color: Some("#f00".into()),
+					border_color: Some("#0f0".into()),
+					border_width: Some(2.0),
+					glow_color: Some("#00f".into()),
+					glow: Some(0.5),
+					group: Some(1),
+					value: Some(3.5),
+					position: Some((1.0, -2.0)),
+					segments: Some(vec![(1.0, "red".into()), (2.0, "blue".into())]),
+					badge: Some("9".into()),
+					time: Some(4.0),
+					metadata: HashMap::from([("url".into(), "/a".into())]),
+					..node("a")
+				},
+				node("b"),
+			],
+			links: vec![
+				GraphLink {
+					style: Some(LinkStyle::Dashed),
+					weight: Some(2.0),
+					curvature: Some(-0.3),
+					time: Some(1.0),
+					..link("a", "b")
+				},
+				GraphLink {
+					directed: false,
+					..link("b", "a")
+				},
+			],
+		};
+		let decoded = decode_data(&encode_data(&data).into()).unwrap();
+		assert_eq!(format!("{decoded:?}"), format!("{data:?}"));
+	}
+
+	#[cfg(target_arch = "wasm32")]
+	#[wasm_bindgen_test]
+	fn options_round_trip() {
+		let opts = options();
+		let decoded = decode_options(&encode_options(&opts).into()).unwrap();
+		assert_eq!(format!("{decoded:?}"), format!("{opts:?}"));
+	}
+
+	#[cfg(target_arch = "wasm32")]
+	#[wasm_bindgen_test]
+	fn commands_round_trip() {
+		let commands = [
+			Command::Input(Input::Down {
+				x: 1.0,
+				y: 2.0,
+				shift: true,
+				button: 2,
+			}),
+			Command::Resize {
+				width: 640.0,
+				height: 480.0,
+			},
+			Command::Group(Some(4)),
+			Command::Time(None),
+			Command::TimeWindow(Some((0.0, 3.0))),
+			Command::ColorBy(Some(ColorBy::Degree)),
+			Command::Annotate(vec!["a".into(), "b".into()]),
+			Command::Scale(Box::new(ScaleConfig::screen_sized())),
+			Command::Pause(true),
+		];
+		for command in commands {
+			let decoded = Command::decode(&command.encode()).unwrap();
+			assert_eq!(format!("{decoded:?}"), format!("{command:?}"));
+		}
+	}
+
+	#[test]
+	fn custom_settings_stay_on_the_main_thread() {
+		assert!(can_send(&options()));
+		let mut custom = options();
+		custom.theme.background = Theme::midnight().background;
+		assert!(!can_send(&custom));
+		let mut metric = options();
+		metric.color_by = Some(ColorBy::Custom(|_| 1.0));
+		assert!(!can_send(&metric));
+	}
+}
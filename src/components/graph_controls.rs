@@ -0,0 +1,98 @@
+//! HTML zoom and simulation buttons laid over a graph canvas.
+
+use leptos::prelude::*;
+
+use super::force_graph::{Corner, GraphController};
+
+/// Distance of the buttons from the container's edges, in pixels.
+const INSET: f64 = 8.0;
+
+/// Zoom in, zoom out, fit and reset buttons driving a graph through its
+/// [`GraphController`], with an optional pause/play toggle for the
+/// simulation when `pause_button` is set.
+///
+/// Pass the same controller to
+/// [`ForceGraphCanvas`](super::force_graph::ForceGraphCanvas)'s
+/// `controller` prop. The buttons are absolutely positioned in `corner`,
+/// so place them in the same positioned container as the canvas. They're
+/// ordinary buttons, reachable with Tab and pressed with Enter or Space;
+/// the pause toggle reports its state through `aria-pressed` and its
+/// label.
+#[component]
+pub fn GraphControls(
+	controller: GraphController,
+	#[prop(default = Corner::TopLeft)] corner: Corner,
+	#[prop(default = false)] pause_button: bool,
+) -> impl IntoView {
+	let (vertical, horizontal) = match corner {
+		Corner::TopLeft => ("top", "left"),
+		Corner::TopRight => ("top", "right"),
+		Corner::BottomLeft => ("bottom", "left"),
+		Corner::BottomRight => ("bottom", "right"),
+	};
+	let style = format!("position: absolute; {vertical}: {INSET}px; {horizontal}: {INSET}px;");
+	let paused = move || controller.is_paused();
+	let pause_label = move || {
+		if paused() {
+			"Resume simulation"
+		} else {
+			"Pause simulation"
+		}
+	};
+	let pause = pause_button.then(|| {
+		view! {
+			<button
+				type="button"
+				class="graph-controls-button"
+				aria-label=pause_label
+				aria-pressed=move || paused().to_string()
+				title=pause_label
+				on:click=move |_| controller.toggle_paused()
+			>
+				{move || if paused() { "▶" } else { "❚❚" }}
+			</button>
+		}
+	});
+
+	view! {
+		<div class="graph-controls" role="toolbar" aria-label="Graph view" style=style>
+			<button
+				type="button"
+				class="graph-controls-button"
+				aria-label="Zoom in"
+				title="Zoom in"
+				on:click=move |_| controller.zoom_in()
+			>
+				"+"
+			</button>
+			<button
+				type="button"
+				class="graph-controls-button"
+				aria-label="Zoom out"
+				title="Zoom out"
+				on:click=move |_| controller.zoom_out()
+			>
+				"−"
+			</button>
+			<button
+				type="button"
+				class="graph-controls-button"
+				aria-label="Fit graph to view"
+				title="Fit graph to view"
+				on:click=move |_| controller.fit()
+			>
+				"⤢"
+			</button>
+			<button
+				type="button"
+				class="graph-controls-button"
+				aria-label="Reset view"
+				title="Reset view"
+				on:click=move |_| controller.reset_view()
+			>
+				"⟲"
+			</button>
+			{pause}
+		</div>
+	}
+}
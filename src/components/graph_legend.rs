@@ -0,0 +1,63 @@
+//! HTML legend listing a graph's groups next to their palette colors.
+
+use std::collections::{BTreeSet, HashMap};
+
+use leptos::prelude::*;
+
+use super::force_graph::{GraphData, Theme};
+
+/// Clickable key mapping each node group in `data` to its color and name.
+///
+/// Swatches use [`Theme::group_color`], the same palette the canvas fills
+/// grouped nodes with, so pass the same `theme` as the canvas. Groups are
+/// listed in ascending order and named from `group_names`, falling back to
+/// `Group <n>`.
+///
+/// Clicking an entry toggles it in `highlighted_group`; pass the same signal
+/// to [`ForceGraphCanvas`](super::force_graph::ForceGraphCanvas)'s
+/// `highlighted_group` prop to highlight that group's nodes on the canvas.
+#[component]
+pub fn GraphLegend(
+	#[prop(into)] data: Signal<GraphData>,
+	#[prop(optional)] group_names: HashMap<u32, String>,
+	#[prop(optional)] theme: Option<Theme>,
+	highlighted_group: RwSignal<Option<u32>>,
+) -> impl IntoView {
+	let theme = theme.unwrap_or_default();
+	let groups = move || {
+		data.with(|d| {
+			d.nodes
+				.iter()
+				.filter_map(|n| n.group)
+				.collect::<BTreeSet<u32>>()
+		})
+	};
+
+	view! {
+		<ul class="graph-legend">
+			<For each=groups key=|g| *g let:group>
+				{
+					let name = group_names
+						.get(&group)
+						.cloned()
+						.unwrap_or_else(|| format!("Group {group}"));
+					let swatch = format!("background-color: {}", theme.group_color(group).to_css_rgb());
+					let active = move || highlighted_group.get() == Some(group);
+					view! {
+						<li
+							class="graph-legend-entry"
+							class:active=active
+							on:click=move |_| {
+								highlighted_group
+									.update(|g| *g = if *g == Some(group) { None } else { Some(group) })
+							}
+						>
+							<span class="graph-legend-swatch" style=swatch></span>
+							<span class="graph-legend-name">{name}</span>
+						</li>
+					}
+				}
+			</For>
+		</ul>
+	}
+}
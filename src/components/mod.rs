@@ -1 +1,4 @@
 pub mod force_graph;
+pub mod graph_controls;
+pub mod graph_legend;
+pub mod time_slider;
@@ -0,0 +1,81 @@
+//! Range sliders scrubbing a temporal graph's time window.
+
+use leptos::prelude::*;
+
+use super::force_graph::GraphData;
+
+/// Two sliders setting the start and end of `time_window`, spanning the
+/// earliest to the latest `time` of any node or link in `data`.
+///
+/// Pass the same signal to
+/// [`ForceGraphCanvas`](super::force_graph::ForceGraphCanvas)'s
+/// `time_window` prop. While the window is `None`, the sliders sit at the
+/// ends of the range; moving either sets it, and the start never passes
+/// the end. Nothing is shown while no node or link has a time.
+#[component]
+pub fn TimeSlider(
+	#[prop(into)] data: Signal<GraphData>,
+	time_window: RwSignal<Option<(f64, f64)>>,
+) -> impl IntoView {
+	let range = Memo::new(move |_| data.with(GraphData::time_range));
+	let window = move || {
+		let (lo, hi) = range.get().unwrap_or_default();
+		time_window.get().unwrap_or((lo, hi))
+	};
+	let lo = move || range.get().map_or(0.0, |(lo, _)| lo);
+	let hi = move || range.get().map_or(0.0, |(_, hi)| hi);
+	let set_start = move |ev| {
+		if let Ok(value) = event_target_value(&ev).parse::<f64>() {
+			let (_, end) = window();
+			time_window.set(Some((value.min(end), end)));
+		}
+	};
+	let set_end = move |ev| {
+		if let Ok(value) = event_target_value(&ev).parse::<f64>() {
+			let (start, _) = window();
+			time_window.set(Some((start, value.max(start))));
+		}
+	};
+	let label = move || {
+		let (start, end) = window();
+		format!("{} – {}", format_time(start), format_time(end))
+	};
+
+	view! {
+		<Show when=move || range.get().is_some()>
+			<div class="time-slider">
+				<input
+					type="range"
+					class="time-slider-start"
+					aria-label="Window start"
+					min=lo
+					max=hi
+					step="any"
+					prop:value=move || window().0
+					on:input=set_start
+				/>
+				<input
+					type="range"
+					class="time-slider-end"
+					aria-label="Window end"
+					min=lo
+					max=hi
+					step="any"
+					prop:value=move || window().1
+					on:input=set_end
+				/>
+				<span class="time-slider-label">{label}</span>
+			</div>
+		</Show>
+	}
+}
+
+/// A time for the label: whole numbers as such, others to two decimal
+/// places.
+fn format_time(time: f64) -> String {
+	if time.fract() == 0.0 {
+		format!("{time:.0}")
+	} else {
+		format!("{time:.2}")
+	}
+}
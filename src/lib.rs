@@ -10,6 +10,22 @@ use log::{Level, info};
 mod components;
 mod pages;
 
+// Public component API
+pub use crate::components::force_graph::{
+	AlphaBehavior, Annotation, ArrowScaleConfig, BETWEENNESS_MAX_NODES, BackgroundImage,
+	BackgroundPattern, BackgroundRenderer, ButtonBinding, Centrality, Color, ColorBy, Colormap,
+	Corner, DegreeCurve, DegreeSizing, DetailLevel, DrawContext, DrawGradient, EdgeScaleConfig,
+	ExportOptions, ExportRegion, Filter, Fisheye, ForceGraphCanvas, GlowScaleConfig,
+	GraphController, GraphData, GraphLink, GraphNode, HighlightConfig, LinkStyle, LodConfig,
+	MAX_RENDER_NODES, MouseBindings, NodeEvent, NodePalette, NodeScaleConfig, OffscreenAnnotations,
+	PointerAction, RecordedGradient, RecordingContext, RendererKind, RingColor, SampleStrategy,
+	SamplingInfo, ScaleBehavior, ScaleConfig, SeedStrategy, SelectionRing, SelectionStyle,
+	ShadowConfig, SpotlightStyle, Theme, UnsampledNodes, ViewTransform, ViewportInfo, WeightStyle,
+	render_headless, run_graph_worker,
+};
+pub use crate::components::graph_controls::GraphControls;
+pub use crate::components::graph_legend::GraphLegend;
+pub use crate::components::time_slider::TimeSlider;
 // Top-Level pages
 use crate::pages::home::Home;
 use crate::pages::not_found::NotFound;
@@ -1,6 +1,8 @@
+use std::collections::HashSet;
+
 use leptos::prelude::*;
 
-use crate::components::force_graph::{ForceGraphCanvas, GraphData, GraphLink, GraphNode};
+use crate::components::force_graph::{Filter, ForceGraphCanvas, GraphData, GraphLink, GraphNode};
 
 /// Generate sample graph data (random tree similar to the JS example).
 fn generate_sample_data(n: usize) -> GraphData {
@@ -12,8 +14,8 @@ fn generate_sample_data(n: usize) -> GraphData {
 			} else {
 				None
 			},
-			color: None,
 			group: Some((i % 10) as u32),
+			..Default::default()
 		})
 		.collect();
 
@@ -23,6 +25,7 @@ fn generate_sample_data(n: usize) -> GraphData {
 			GraphLink {
 				source: i.to_string(),
 				target: target.to_string(),
+				..Default::default()
 			}
 		})
 		.collect();
@@ -41,6 +44,16 @@ fn rand_simple(seed: usize) -> f64 {
 pub fn Home() -> impl IntoView {
 	// Create graph data signal
 	let graph_data = Signal::derive(move || generate_sample_data(100));
+	// Groups unchecked in the overlay, faded out of the graph
+	let hidden_groups = RwSignal::new(HashSet::<u32>::new());
+	let filter = Signal::derive(move || {
+		let hidden_groups = hidden_groups.get();
+		(!hidden_groups.is_empty()).then(|| Filter {
+			hidden_groups,
+			residual_alpha: 0.08,
+			..Filter::default()
+		})
+	});
 
 	view! {
 		<ErrorBoundary fallback=|errors| {
@@ -61,10 +74,33 @@ pub fn Home() -> impl IntoView {
 		}>
 
 			<div class="fullscreen-graph">
-				<ForceGraphCanvas data=graph_data fullscreen=true />
+				<ForceGraphCanvas data=graph_data filter=filter fullscreen=true />
 				<div class="graph-overlay">
 					<h1>"Force-Directed Graph"</h1>
-					<p class="subtitle">"Drag nodes to reposition. Scroll to zoom. Drag background to pan."</p>
+					<p class="subtitle">"Drag nodes to reposition. Scroll or drag the background to pan; pinch or Ctrl+scroll to zoom."</p>
+					<div class="group-filter">
+						{(0..10u32)
+							.map(|group| {
+								view! {
+									<label>
+										<input
+											type="checkbox"
+											prop:checked=move || !hidden_groups.get().contains(&group)
+											on:change=move |_| {
+												hidden_groups
+													.update(|hidden| {
+														if !hidden.remove(&group) {
+															hidden.insert(group);
+														}
+													})
+											}
+										/>
+										{format!("Group {group}")}
+									</label>
+								}
+							})
+							.collect_view()}
+					</div>
 				</div>
 			</div>
 		</ErrorBoundary>